@@ -2,7 +2,7 @@ use std::convert::Infallible;
 
 use axum::{
     extract::{FromRequest, RequestParts},
-    http::header::ACCEPT_LANGUAGE,
+    http::{header::ACCEPT_LANGUAGE, HeaderMap},
 };
 use unic_langid::{parser::parse_language_identifier, LanguageIdentifier};
 
@@ -11,6 +11,24 @@ pub struct AcceptLanguage {
     pub languages: Vec<LanguageIdentifier>,
 }
 
+impl AcceptLanguage {
+    /// Parses the `Accept-Language` header, in order of preference.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let languages = headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|header| header.to_str().ok())
+            .map(|header| {
+                accept_language::parse(header)
+                    .iter()
+                    .filter_map(|al| parse_language_identifier(al.as_bytes()).ok())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        AcceptLanguage { languages }
+    }
+}
+
 #[async_trait::async_trait]
 impl<B> FromRequest<B> for AcceptLanguage
 where
@@ -23,19 +41,9 @@ where
             return Ok(this);
         }
 
-        if let Some(header) = req.headers().get(ACCEPT_LANGUAGE) {
-            if let Ok(header_value) = header.to_str() {
-                let languages = accept_language::parse(header_value)
-                    .iter()
-                    .filter_map(|al| parse_language_identifier(al.as_bytes()).ok())
-                    .collect::<Vec<_>>();
-
-                let this = AcceptLanguage { languages };
-                req.extensions_mut().insert(this.clone());
-                return Ok(this);
-            }
-        }
+        let this = Self::from_headers(req.headers());
+        req.extensions_mut().insert(this.clone());
 
-        return Ok(AcceptLanguage { languages: vec![] });
+        Ok(this)
     }
 }
@@ -1,4 +1,4 @@
-use stry_evermore::{Evermore, Worker};
+use evermore::{Evermore, Worker};
 
 #[derive(Debug, Default, Clone)]
 struct Data {}
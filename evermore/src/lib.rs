@@ -114,7 +114,7 @@ where
     /// This function panics if the `working_count` is `0` .
     // TODO: remove the need for a factory
     pub fn new(signal: S, worker_count: usize, data: D, factory: F) -> Self {
-        assert!(worker_count == 0, "Worker count cannot be 0");
+        assert!(worker_count != 0, "Worker count cannot be 0");
 
         let worker_data = Worker {
             data,
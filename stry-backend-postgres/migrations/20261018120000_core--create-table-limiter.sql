@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS core_limiter_attempt (
+    key         TEXT    NOT NULL,
+
+    attempted   TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS core_limiter_attempt_key_index ON core_limiter_attempt ( key, attempted );
+
+CREATE TABLE IF NOT EXISTS core_limiter_lock (
+    key         TEXT    UNIQUE  NOT NULL    PRIMARY KEY,
+
+    until       TIMESTAMP WITH TIME ZONE        NOT NULL
+);
@@ -0,0 +1,41 @@
+-- Times are decoded as `OffsetDateTime` which needs a time zone, every stored time is UTC
+
+ALTER TABLE core_settings
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE core_tag
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE core_user
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_origin
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_story
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_story_origin
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_story_tag
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_story_user
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_story_warning
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
+
+ALTER TABLE story_warning
+    ALTER COLUMN created TYPE TIMESTAMP WITH TIME ZONE USING created AT TIME ZONE 'utc',
+    ALTER COLUMN updated TYPE TIMESTAMP WITH TIME ZONE USING updated AT TIME ZONE 'utc';
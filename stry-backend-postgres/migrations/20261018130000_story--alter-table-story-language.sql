@@ -0,0 +1,7 @@
+ALTER TABLE story_story ADD COLUMN IF NOT EXISTS language TEXT NOT NULL DEFAULT 'en';
+
+ALTER TABLE story_story ADD COLUMN IF NOT EXISTS original VARCHAR(8) REFERENCES story_story ( id ) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS story_story_language_index ON story_story ( language );
+
+CREATE INDEX IF NOT EXISTS story_story_original_index ON story_story ( original );
@@ -0,0 +1,4 @@
+ALTER TYPE story_user_relationship ADD VALUE IF NOT EXISTS 'translator';
+
+-- The original enum misspells it as 'comissioner', which the story queries don't ask for
+ALTER TYPE story_user_relationship ADD VALUE IF NOT EXISTS 'commissioner';
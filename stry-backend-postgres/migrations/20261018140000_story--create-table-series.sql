@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS story_series (
+    id          VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+    name        TEXT                NOT NULL,
+    summary     TEXT                NOT NULL,
+    state       story_state         NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS story_series_story (
+    series_id   VARCHAR(8)          NOT NULL    REFERENCES story_series ( id ) ON DELETE CASCADE,
+    story_id    VARCHAR(8)  UNIQUE  NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+    position    INTEGER             NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL,
+
+    PRIMARY KEY (series_id, story_id)
+);
+
+CREATE INDEX IF NOT EXISTS story_series_story_series_index ON story_series_story ( series_id, position );
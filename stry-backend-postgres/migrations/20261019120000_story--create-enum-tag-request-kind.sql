@@ -0,0 +1,6 @@
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'story_tag_request_kind') THEN
+        CREATE TYPE story_tag_request_kind AS ENUM ('origin', 'warning', 'pairing', 'character', 'general');
+    END IF;
+END $$;
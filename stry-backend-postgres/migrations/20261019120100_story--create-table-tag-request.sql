@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS story_tag_request (
+    story_id    VARCHAR(8)                  NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+
+    kind        story_tag_request_kind      NOT NULL,
+    content     TEXT                        NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (story_id, kind, content)
+);
+
+CREATE INDEX IF NOT EXISTS story_tag_request_content_index ON story_tag_request ( kind, lower(content) );
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS core_media (
+    id              VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    uploader_id     VARCHAR(8)          NOT NULL    REFERENCES core_user ( id ) ON DELETE CASCADE,
+
+    hash            VARCHAR(64) UNIQUE  NOT NULL,
+    extension       TEXT                NOT NULL,
+
+    width           INTEGER             NOT NULL,
+    height          INTEGER             NOT NULL,
+
+    thumbnail       VARCHAR(64)         NOT NULL,
+
+    created         TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated         TIMESTAMP WITH TIME ZONE        NOT NULL
+);
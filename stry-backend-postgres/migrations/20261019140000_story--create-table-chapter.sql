@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS story_chapter (
+    id          VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    story_id    VARCHAR(8)          NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+    position    INTEGER             NOT NULL,
+
+    name        TEXT,
+
+    published   BOOLEAN             NOT NULL,
+    scheduled   TIMESTAMP WITH TIME ZONE,
+
+    content     JSONB               NOT NULL,
+    draft       JSONB,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS story_chapter_story_index ON story_chapter ( story_id, position );
+
+CREATE INDEX IF NOT EXISTS story_chapter_scheduled_index ON story_chapter ( scheduled ) WHERE scheduled IS NOT NULL;
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS core_notification (
+    id          VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    user_id     VARCHAR(8)          NOT NULL    REFERENCES core_user ( id ) ON DELETE CASCADE,
+    story_id    VARCHAR(8)                      REFERENCES story_story ( id ) ON DELETE CASCADE,
+
+    content     JSONB               NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS core_notification_user_index ON core_notification ( user_id, created );
+
+CREATE INDEX IF NOT EXISTS core_notification_story_index ON core_notification ( story_id, created ) WHERE story_id IS NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE core_user ADD COLUMN IF NOT EXISTS admin BOOLEAN NOT NULL DEFAULT FALSE;
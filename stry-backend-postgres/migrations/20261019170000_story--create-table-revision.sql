@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS story_revision (
+    id          VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    story_id    VARCHAR(8)          NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+    chapter_id  VARCHAR(8)                      REFERENCES story_chapter ( id ) ON DELETE CASCADE,
+    user_id     VARCHAR(8)                      REFERENCES core_user ( id ) ON DELETE SET NULL,
+
+    content     JSONB               NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS story_revision_story_index ON story_revision ( story_id, chapter_id, created );
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS blog_post (
+    id          VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    user_id     VARCHAR(8)          NOT NULL    REFERENCES core_user ( id ) ON DELETE CASCADE,
+
+    content     JSONB               NOT NULL,
+
+    created     TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated     TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS blog_post_user_index ON blog_post ( user_id, created );
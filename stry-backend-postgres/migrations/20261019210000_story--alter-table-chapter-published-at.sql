@@ -0,0 +1,6 @@
+ALTER TABLE story_chapter ADD COLUMN IF NOT EXISTS published_at TIMESTAMP WITH TIME ZONE;
+
+-- The first publication of existing chapters wasn't recorded, their creation is the closest
+UPDATE story_chapter SET published_at = created WHERE published AND published_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS story_chapter_published_index ON story_chapter ( published_at ) WHERE published_at IS NOT NULL;
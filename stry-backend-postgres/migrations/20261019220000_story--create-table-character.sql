@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS story_character (
+    id              VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    content         TEXT                NOT NULL,
+    description     TEXT                NOT NULL,
+
+    created         TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated         TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS story_character_content_index ON story_character ( lower(content) );
+
+CREATE TABLE IF NOT EXISTS story_story_character (
+    story_id        VARCHAR(8)          NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+    character_id    VARCHAR(8)          NOT NULL    REFERENCES story_character ( id ) ON DELETE CASCADE,
+
+    level           story_tag_level     NOT NULL,
+
+    created         TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated         TIMESTAMP WITH TIME ZONE        NOT NULL,
+
+    PRIMARY KEY (story_id, character_id)
+);
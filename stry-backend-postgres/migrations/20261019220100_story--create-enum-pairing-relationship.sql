@@ -0,0 +1,6 @@
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_type WHERE typname = 'story_pairing_relationship') THEN
+        CREATE TYPE story_pairing_relationship AS ENUM ('family', 'friends', 'romantic');
+    END IF;
+END $$;
@@ -0,0 +1,33 @@
+CREATE TABLE IF NOT EXISTS story_pairing (
+    id              VARCHAR(8)  UNIQUE  NOT NULL    PRIMARY KEY,
+
+    -- The relationship and the sorted ids of the characters, so a group of
+    -- characters has one pairing per relationship
+    hash            TEXT        UNIQUE  NOT NULL,
+
+    relationship    story_pairing_relationship      NOT NULL,
+
+    created         TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated         TIMESTAMP WITH TIME ZONE        NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS story_pairing_character (
+    pairing_id      VARCHAR(8)          NOT NULL    REFERENCES story_pairing ( id ) ON DELETE CASCADE,
+    character_id    VARCHAR(8)          NOT NULL    REFERENCES story_character ( id ) ON DELETE CASCADE,
+
+    position        INTEGER             NOT NULL,
+
+    PRIMARY KEY (pairing_id, character_id)
+);
+
+CREATE TABLE IF NOT EXISTS story_story_pairing (
+    story_id        VARCHAR(8)          NOT NULL    REFERENCES story_story ( id ) ON DELETE CASCADE,
+    pairing_id      VARCHAR(8)          NOT NULL    REFERENCES story_pairing ( id ) ON DELETE CASCADE,
+
+    level           story_tag_level     NOT NULL,
+
+    created         TIMESTAMP WITH TIME ZONE        NOT NULL,
+    updated         TIMESTAMP WITH TIME ZONE        NOT NULL,
+
+    PRIMARY KEY (story_id, pairing_id)
+);
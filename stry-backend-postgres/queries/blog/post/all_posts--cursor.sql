@@ -0,0 +1,15 @@
+SELECT
+    p.id,
+    p.content::text as "content!",
+    p.created as "created: _",
+    p.updated as "updated: _"
+FROM
+    blog_post p
+WHERE
+    p.user_id = $1
+    AND
+    p.created < (SELECT c.created FROM blog_post c WHERE c.id = $2)
+ORDER BY
+    p.created DESC
+LIMIT
+    $3;
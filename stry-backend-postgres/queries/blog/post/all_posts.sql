@@ -0,0 +1,13 @@
+SELECT
+    p.id,
+    p.content::text as "content!",
+    p.created as "created: _",
+    p.updated as "updated: _"
+FROM
+    blog_post p
+WHERE
+    p.user_id = $1
+ORDER BY
+    p.created DESC
+LIMIT
+    $2;
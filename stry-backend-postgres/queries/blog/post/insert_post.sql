@@ -0,0 +1,13 @@
+INSERT INTO blog_post (
+    id,
+    user_id,
+    content,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3::text::jsonb,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
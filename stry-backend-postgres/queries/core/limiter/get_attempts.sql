@@ -0,0 +1,8 @@
+SELECT
+    attempted as "attempted: _"
+FROM
+    core_limiter_attempt
+WHERE
+    key = $1
+ORDER BY
+    attempted ASC;
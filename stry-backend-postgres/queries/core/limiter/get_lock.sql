@@ -0,0 +1,8 @@
+SELECT
+    until as "until: _"
+FROM
+    core_limiter_lock
+WHERE
+    key = $1
+    AND
+    until > timezone('utc', now());
@@ -0,0 +1,7 @@
+INSERT INTO core_limiter_attempt (
+    key,
+    attempted
+) VALUES (
+    $1,
+    timezone('utc', now())
+);
@@ -0,0 +1,6 @@
+-- Serializes concurrent attempts for the same key until the transaction ends,
+-- so each one sees the attempts recorded before it
+SELECT
+    1 AS "locked!"
+FROM
+    (SELECT pg_advisory_xact_lock(hashtext($1))) AS lock;
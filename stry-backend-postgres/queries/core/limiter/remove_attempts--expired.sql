@@ -0,0 +1,6 @@
+DELETE FROM
+    core_limiter_attempt
+WHERE
+    key = $1
+    AND
+    attempted <= timezone('utc', now()) - make_interval(secs => $2);
@@ -0,0 +1,4 @@
+DELETE FROM
+    core_limiter_attempt
+WHERE
+    key = $1;
@@ -0,0 +1,9 @@
+INSERT INTO core_limiter_lock (
+    key,
+    until
+) VALUES (
+    $1,
+    timezone('utc', now()) + make_interval(secs => $2)
+)
+ON CONFLICT (key) DO UPDATE SET
+    until = EXCLUDED.until;
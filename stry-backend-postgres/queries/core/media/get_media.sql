@@ -0,0 +1,14 @@
+SELECT
+    m.id,
+    m.uploader_id,
+    m.hash,
+    m.extension,
+    m.width,
+    m.height,
+    m.thumbnail,
+    m.created as "created: _",
+    m.updated as "updated: _"
+FROM
+    core_media m
+WHERE
+    m.id = $1;
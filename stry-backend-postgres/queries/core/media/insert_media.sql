@@ -0,0 +1,24 @@
+INSERT INTO core_media (
+    id,
+    uploader_id,
+    hash,
+    extension,
+    width,
+    height,
+    thumbnail,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3,
+    $4,
+    $5,
+    $6,
+    $7,
+    timezone('utc', now()),
+    timezone('utc', now())
+)
+-- The same image uploaded at the same time, the first one is kept
+ON CONFLICT ( hash ) DO UPDATE SET hash = EXCLUDED.hash
+RETURNING id;
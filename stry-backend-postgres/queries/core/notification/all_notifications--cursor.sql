@@ -0,0 +1,15 @@
+SELECT
+    n.id,
+    n.content::text as "content!",
+    n.created as "created: _",
+    n.updated as "updated: _"
+FROM
+    core_notification n
+WHERE
+    n.user_id = $1
+    AND
+    n.created < (SELECT c.created FROM core_notification c WHERE c.id = $2)
+ORDER BY
+    n.created DESC
+LIMIT
+    $3;
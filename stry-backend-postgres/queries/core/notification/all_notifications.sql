@@ -0,0 +1,13 @@
+SELECT
+    n.id,
+    n.content::text as "content!",
+    n.created as "created: _",
+    n.updated as "updated: _"
+FROM
+    core_notification n
+WHERE
+    n.user_id = $1
+ORDER BY
+    n.created DESC
+LIMIT
+    $2;
@@ -0,0 +1,15 @@
+INSERT INTO core_notification (
+    id,
+    user_id,
+    story_id,
+    content,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3,
+    $4::text::jsonb,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
@@ -0,0 +1,6 @@
+SELECT
+    value
+FROM
+    core_settings
+WHERE
+    key = $1;
@@ -0,0 +1,14 @@
+INSERT INTO core_settings (
+    key,
+    value,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    timezone('utc', now()),
+    timezone('utc', now())
+)
+ON CONFLICT (key) DO UPDATE SET
+    value = EXCLUDED.value,
+    updated = EXCLUDED.updated;
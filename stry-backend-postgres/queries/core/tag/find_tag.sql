@@ -0,0 +1,13 @@
+SELECT
+    t.id,
+    t.content,
+    t.description,
+    t.created as "created: _",
+    t.updated as "updated: _"
+FROM
+    core_tag t
+WHERE
+    lower(t.content) = lower($1)
+ORDER BY
+    t.created
+LIMIT 1;
@@ -0,0 +1,10 @@
+SELECT
+    t.id,
+    t.content,
+    t.description,
+    t.created as "created: _",
+    t.updated as "updated: _"
+FROM
+    core_tag t
+WHERE
+    t.id = ANY($1);
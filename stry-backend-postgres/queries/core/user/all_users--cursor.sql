@@ -0,0 +1,15 @@
+SELECT
+    u.id,
+    u.name,
+    u.biography,
+    u.settings::text as "settings!",
+    u.created as "created: _",
+    u.updated as "updated: _"
+FROM
+    core_user u
+WHERE
+    u.created < (SELECT c.created FROM core_user c WHERE c.id = $1)
+ORDER BY
+    u.created DESC
+LIMIT
+    $2;
@@ -0,0 +1,13 @@
+SELECT
+    u.id,
+    u.name,
+    u.biography,
+    u.settings::text as "settings!",
+    u.created as "created: _",
+    u.updated as "updated: _"
+FROM
+    core_user u
+ORDER BY
+    u.created DESC
+LIMIT
+    $1;
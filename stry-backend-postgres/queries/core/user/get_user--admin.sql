@@ -0,0 +1,6 @@
+SELECT
+    u.admin
+FROM
+    core_user u
+WHERE
+    u.id = $1;
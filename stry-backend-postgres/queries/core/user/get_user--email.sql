@@ -0,0 +1,11 @@
+SELECT
+    u.id,
+    u.name,
+    u.email,
+    u.hash,
+    u.created as "created: _",
+    u.updated as "updated: _"
+FROM
+    core_user u
+WHERE
+    u.email = $1;
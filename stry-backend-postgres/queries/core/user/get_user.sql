@@ -0,0 +1,11 @@
+SELECT
+    u.id,
+    u.name,
+    u.biography,
+    u.settings::text as "settings!",
+    u.created as "created: _",
+    u.updated as "updated: _"
+FROM
+    core_user u
+WHERE
+    u.id = $1;
@@ -0,0 +1,19 @@
+INSERT INTO core_user (
+    id,
+    email,
+    name,
+    biography,
+    hash,
+    settings,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3,
+    '',
+    $4,
+    $5::text::jsonb,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
@@ -0,0 +1,7 @@
+UPDATE
+    core_user
+SET
+    admin = $2,
+    updated = timezone('utc', now())
+WHERE
+    id = $1;
@@ -0,0 +1,7 @@
+UPDATE
+    core_user
+SET
+    settings = $2::text::jsonb,
+    updated = timezone('utc', now())
+WHERE
+    id = $1;
@@ -0,0 +1,31 @@
+SELECT
+    s.id as "id: _",
+    s.name,
+    s.summary,
+    s.language,
+    s.original,
+    s.rating as "rating: _",
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_story s
+WHERE
+    s.state = 'in-progress'
+    AND
+    s.updated < $1
+    AND
+    NOT EXISTS (
+        SELECT
+            1
+        FROM
+            core_notification n
+        WHERE
+            n.story_id = s.id
+            AND
+            n.content ? 'StoryAbandoning'
+            AND
+            n.created >= s.updated
+    )
+ORDER BY
+    s.updated;
@@ -2,6 +2,8 @@ SELECT
     s.id as "id: _",
     s.name,
     s.summary,
+    s.language,
+    s.original,
     s.rating as "rating: _",
     s.state as "state: _",
     s.created as "created: _",
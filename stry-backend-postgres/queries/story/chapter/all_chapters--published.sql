@@ -0,0 +1,19 @@
+SELECT
+    c.id,
+    c.story_id,
+    c.name,
+    c.published,
+    c.scheduled as "scheduled: _",
+    c.published_at as "published_at: _",
+    c.content::text as "content!",
+    c.draft::text as draft,
+    c.created as "created: _",
+    c.updated as "updated: _"
+FROM
+    story_chapter c
+WHERE
+    c.published_at IS NOT NULL
+ORDER BY
+    c.published_at DESC
+LIMIT
+    $1;
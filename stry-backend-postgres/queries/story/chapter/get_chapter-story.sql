@@ -0,0 +1,6 @@
+SELECT
+    story_id as id
+FROM
+    story_chapter
+WHERE
+    id = $1;
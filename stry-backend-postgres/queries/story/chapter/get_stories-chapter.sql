@@ -0,0 +1,10 @@
+SELECT
+    story_id,
+    id
+FROM
+    story_chapter
+WHERE
+    story_id = ANY($1)
+ORDER BY
+    story_id,
+    position;
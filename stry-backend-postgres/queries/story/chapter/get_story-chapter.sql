@@ -0,0 +1,8 @@
+SELECT
+    id
+FROM
+    story_chapter
+WHERE
+    story_id = $1
+ORDER BY
+    position;
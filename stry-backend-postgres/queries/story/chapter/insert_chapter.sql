@@ -0,0 +1,25 @@
+INSERT INTO story_chapter (
+    id,
+    story_id,
+    position,
+    name,
+    published,
+    scheduled,
+    published_at,
+    content,
+    draft,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2::varchar,
+    ( SELECT COALESCE(MAX(position) + 1, 0) FROM story_chapter WHERE story_id = $2::varchar ),
+    $3,
+    $4,
+    $5,
+    CASE WHEN $4 THEN timezone('utc', now()) END,
+    $6::text::jsonb,
+    $7::text::jsonb,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
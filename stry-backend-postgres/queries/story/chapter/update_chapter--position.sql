@@ -0,0 +1,8 @@
+UPDATE
+    story_chapter
+SET
+    position = $3
+WHERE
+    id = $1
+    AND
+    story_id = $2;
@@ -0,0 +1,12 @@
+UPDATE
+    story_chapter
+SET
+    name = $2,
+    published = TRUE,
+    scheduled = NULL,
+    published_at = COALESCE(published_at, timezone('utc', now())),
+    content = $3::text::jsonb,
+    draft = NULL,
+    updated = timezone('utc', now())
+WHERE
+    id = $1;
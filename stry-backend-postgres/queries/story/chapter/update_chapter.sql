@@ -0,0 +1,16 @@
+UPDATE
+    story_chapter
+SET
+    name = $2,
+    published = $3,
+    scheduled = $4,
+    published_at = CASE WHEN $3 THEN COALESCE(published_at, timezone('utc', now())) END,
+    content = $5::text::jsonb,
+    draft = $6::text::jsonb,
+    updated = timezone('utc', now())
+WHERE
+    id = $1
+    AND
+    updated = $7
+RETURNING
+    story_id;
@@ -0,0 +1,15 @@
+SELECT
+    t.id,
+    t.content,
+    t.description,
+    t.created as "created: _",
+    t.updated as "updated: _"
+FROM
+    story_character t
+WHERE
+    (t.content, t.id) > (SELECT c.content, c.id FROM story_character c WHERE c.id = $1)
+ORDER BY
+    t.content,
+    t.id
+LIMIT
+    $2;
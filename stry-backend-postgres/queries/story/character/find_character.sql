@@ -0,0 +1,13 @@
+SELECT
+    c.id,
+    c.content,
+    c.description,
+    c.created as "created: _",
+    c.updated as "updated: _"
+FROM
+    story_character c
+WHERE
+    lower(c.content) = lower($1)
+ORDER BY
+    c.created
+LIMIT 1;
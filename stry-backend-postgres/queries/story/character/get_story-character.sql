@@ -0,0 +1,7 @@
+SELECT
+    character_id as id,
+    level as "level: String"
+FROM
+    story_story_character
+WHERE
+    story_id = $1;
\ No newline at end of file
@@ -0,0 +1,66 @@
+SELECT
+    s.id as "id: _",
+    s.name,
+    s.summary,
+    s.language,
+    s.original,
+    s.rating as "rating: _",
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_story s
+WHERE
+    ($1::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_user su
+        WHERE su.story_id = s.id AND su.user_id = $1 AND su.relationship = 'author'
+    ))
+    AND
+    ($2::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_origin so
+        WHERE so.story_id = s.id AND so.origin_id = $2
+    ))
+    AND
+    ($3::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_warning sw
+        WHERE sw.story_id = s.id AND sw.warning_id = $3
+    ))
+    AND
+    ($4::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_tag st
+        WHERE st.story_id = s.id AND st.tag_id = $4
+    ))
+    AND
+    ($5::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_pairing sp
+        WHERE sp.story_id = s.id AND sp.pairing_id = $5
+    ))
+    AND
+    ($6::text IS NULL OR EXISTS (
+        SELECT 1 FROM story_story_character sc
+        WHERE sc.story_id = s.id AND sc.character_id = $6
+    ))
+    AND
+    ($7::story_rating IS NULL OR s.rating = $7)
+    AND
+    ($8::story_state IS NULL OR s.state = $8)
+    AND
+    ($9::text IS NULL OR lower(s.language) = lower($9) OR starts_with(lower(s.language), lower($9) || '-'))
+    AND
+    ($10::story_rating IS NULL OR s.rating >= $10)
+    AND
+    NOT EXISTS (
+        SELECT 1 FROM story_story_warning sw
+        WHERE sw.story_id = s.id AND sw.warning_id = ANY($11::text[])
+    )
+    AND
+    NOT EXISTS (
+        SELECT 1 FROM story_story_tag st
+        WHERE st.story_id = s.id AND st.tag_id = ANY($12::text[])
+    )
+    AND
+    ($13::text IS NULL OR s.created < (SELECT c.created FROM story_story c WHERE c.id = $13))
+ORDER BY
+    s.created DESC
+LIMIT
+    $14;
@@ -0,0 +1,13 @@
+SELECT
+    o.id,
+    o.content,
+    o.description,
+    o.created as "created: _",
+    o.updated as "updated: _"
+FROM
+    story_origin o
+WHERE
+    lower(o.content) = lower($1)
+ORDER BY
+    o.created
+LIMIT 1;
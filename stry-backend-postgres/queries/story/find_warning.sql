@@ -0,0 +1,13 @@
+SELECT
+    w.id,
+    w.content,
+    w.description,
+    w.created as "created: _",
+    w.updated as "updated: _"
+FROM
+    story_warning w
+WHERE
+    lower(w.content) = lower($1)
+ORDER BY
+    w.created
+LIMIT 1;
@@ -0,0 +1,10 @@
+SELECT
+    story_id as id
+FROM
+    story_story_user
+WHERE
+    story_id = ANY($1)
+    AND
+    user_id = $2
+    AND
+    relationship = 'author';
@@ -0,0 +1,12 @@
+SELECT
+    c.story_id,
+    COALESCE(SUM((p.part -> 'Text' ->> 'words')::bigint), 0)::bigint as "words!"
+FROM
+    story_chapter c,
+    jsonb_array_elements(c.content -> 'main') p(part)
+WHERE
+    c.story_id = ANY($1)
+    AND
+    c.published
+GROUP BY
+    c.story_id;
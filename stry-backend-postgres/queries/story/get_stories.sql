@@ -0,0 +1,14 @@
+SELECT
+    s.id as "id: _",
+    s.name,
+    s.summary,
+    s.language,
+    s.original,
+    s.rating as "rating: _",
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_story s
+WHERE
+    s.id = ANY($1);
@@ -1,6 +1,8 @@
 SELECT
     s.name,
     s.summary,
+    s.language,
+    s.original,
     s.rating as "rating: _",
     s.state as "state: _",
     s.created as "created: _",
@@ -0,0 +1,14 @@
+INSERT INTO story_story_origin (
+    story_id,
+    origin_id,
+    level,
+    created,
+    updated
+) SELECT
+    $1,
+    t.id,
+    t.level::story_tag_level,
+    timezone('utc', now()),
+    timezone('utc', now())
+FROM
+    unnest($2::text[], $3::text[]) t(id, level);
@@ -0,0 +1,12 @@
+INSERT INTO story_story_tag (
+    story_id,
+    tag_id,
+    created,
+    updated
+) SELECT
+    $1,
+    t.id,
+    timezone('utc', now()),
+    timezone('utc', now())
+FROM
+    unnest($2::text[]) t(id);
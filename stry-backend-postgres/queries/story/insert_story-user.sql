@@ -0,0 +1,14 @@
+INSERT INTO story_story_user (
+    story_id,
+    user_id,
+    relationship,
+    created,
+    updated
+) SELECT
+    $1,
+    u.id,
+    $3::text::story_user_relationship,
+    timezone('utc', now()),
+    timezone('utc', now())
+FROM
+    unnest($2::text[]) u(id);
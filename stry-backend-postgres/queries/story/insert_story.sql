@@ -0,0 +1,21 @@
+INSERT INTO story_story (
+    id,
+    name,
+    summary,
+    rating,
+    state,
+    language,
+    original,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3,
+    $4,
+    $5,
+    $6,
+    $7,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
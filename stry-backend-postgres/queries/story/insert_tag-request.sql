@@ -0,0 +1,11 @@
+INSERT INTO story_tag_request (
+    story_id,
+    kind,
+    content,
+    created
+) VALUES (
+    $1,
+    $2,
+    $3,
+    timezone('utc', now())
+) ON CONFLICT DO NOTHING;
@@ -0,0 +1,15 @@
+SELECT
+    p.id,
+    p.hash,
+    p.relationship as "relationship: _",
+    p.created as "created: _",
+    p.updated as "updated: _"
+FROM
+    story_pairing p
+WHERE
+    (p.created, p.id) > (SELECT c.created, c.id FROM story_pairing c WHERE c.id = $1)
+ORDER BY
+    p.created,
+    p.id
+LIMIT
+    $2;
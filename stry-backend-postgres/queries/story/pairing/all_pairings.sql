@@ -0,0 +1,13 @@
+SELECT
+    p.id,
+    p.hash,
+    p.relationship as "relationship: _",
+    p.created as "created: _",
+    p.updated as "updated: _"
+FROM
+    story_pairing p
+ORDER BY
+    p.created,
+    p.id
+LIMIT
+    $1;
@@ -0,0 +1,6 @@
+SELECT
+    p.id
+FROM
+    story_pairing p
+WHERE
+    p.hash = $1;
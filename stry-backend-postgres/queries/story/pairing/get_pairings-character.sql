@@ -0,0 +1,10 @@
+SELECT
+    pairing_id,
+    character_id
+FROM
+    story_pairing_character
+WHERE
+    pairing_id = ANY($1)
+ORDER BY
+    pairing_id,
+    position;
@@ -0,0 +1,10 @@
+SELECT
+    p.id,
+    p.hash,
+    p.relationship as "relationship: _",
+    p.created as "created: _",
+    p.updated as "updated: _"
+FROM
+    story_pairing p
+WHERE
+    p.id = ANY($1);
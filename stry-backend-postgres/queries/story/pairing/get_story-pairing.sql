@@ -0,0 +1,7 @@
+SELECT
+    pairing_id as id,
+    level as "level: String"
+FROM
+    story_story_pairing
+WHERE
+    story_id = $1;
\ No newline at end of file
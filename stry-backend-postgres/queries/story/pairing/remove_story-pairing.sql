@@ -0,0 +1,4 @@
+DELETE FROM
+    story_story_pairing
+WHERE
+    story_id = $1;
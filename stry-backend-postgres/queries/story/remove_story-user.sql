@@ -0,0 +1,6 @@
+DELETE FROM
+    story_story_user
+WHERE
+    story_id = $1
+    AND
+    relationship IN ('author', 'commissioner', 'dedicated', 'translator');
@@ -0,0 +1,19 @@
+SELECT
+    r.id,
+    u.id as "editor_id?",
+    u.name as "editor_name?",
+    u.created as "editor_created?: _",
+    u.updated as "editor_updated?: _",
+    r.created as "created: _",
+    r.updated as "updated: _"
+FROM
+    story_revision r
+    LEFT JOIN core_user u ON u.id = r.user_id
+WHERE
+    r.story_id = $1
+    AND
+    r.chapter_id IS NOT DISTINCT FROM $2
+ORDER BY
+    r.created DESC
+LIMIT
+    $3;
@@ -0,0 +1,12 @@
+SELECT
+    r.content::text as "content!"
+FROM
+    story_revision r
+WHERE
+    r.story_id = $1
+    AND
+    r.chapter_id IS NOT DISTINCT FROM $2
+ORDER BY
+    r.created DESC
+LIMIT
+    1;
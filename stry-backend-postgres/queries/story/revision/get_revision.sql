@@ -0,0 +1,16 @@
+SELECT
+    r.id,
+    r.story_id,
+    r.chapter_id,
+    r.content::text as "content!",
+    u.id as "editor_id?",
+    u.name as "editor_name?",
+    u.created as "editor_created?: _",
+    u.updated as "editor_updated?: _",
+    r.created as "created: _",
+    r.updated as "updated: _"
+FROM
+    story_revision r
+    LEFT JOIN core_user u ON u.id = r.user_id
+WHERE
+    r.id = $1;
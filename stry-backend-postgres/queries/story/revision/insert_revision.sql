@@ -0,0 +1,17 @@
+INSERT INTO story_revision (
+    id,
+    story_id,
+    chapter_id,
+    user_id,
+    content,
+    created,
+    updated
+) VALUES (
+    $1,
+    $2,
+    $3,
+    $4,
+    $5::text::jsonb,
+    timezone('utc', now()),
+    timezone('utc', now())
+);
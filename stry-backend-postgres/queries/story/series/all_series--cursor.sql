@@ -0,0 +1,15 @@
+SELECT
+    s.id,
+    s.name,
+    s.summary,
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_series s
+WHERE
+    s.created < (SELECT c.created FROM story_series c WHERE c.id = $1)
+ORDER BY
+    s.created DESC
+LIMIT
+    $2;
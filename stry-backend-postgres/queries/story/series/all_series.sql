@@ -0,0 +1,13 @@
+SELECT
+    s.id,
+    s.name,
+    s.summary,
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_series s
+ORDER BY
+    s.created DESC
+LIMIT
+    $1;
@@ -0,0 +1,10 @@
+SELECT
+    series_id,
+    story_id
+FROM
+    story_series_story
+WHERE
+    series_id = ANY($1)
+ORDER BY
+    series_id,
+    position;
@@ -0,0 +1,6 @@
+SELECT
+    series_id as id
+FROM
+    story_series_story
+WHERE
+    story_id = $1;
@@ -0,0 +1,13 @@
+INSERT INTO story_series_story (
+    series_id,
+    story_id,
+    position,
+    created,
+    updated
+) VALUES (
+    $2::varchar,
+    $1,
+    ( SELECT COALESCE(MAX(position) + 1, 0) FROM story_series_story WHERE series_id = $2::varchar ),
+    timezone('utc', now()),
+    timezone('utc', now())
+) ON CONFLICT DO NOTHING;
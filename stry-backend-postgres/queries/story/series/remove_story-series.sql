@@ -0,0 +1,6 @@
+DELETE FROM
+    story_series_story
+WHERE
+    story_id = $1
+    AND
+    series_id IS DISTINCT FROM $2;
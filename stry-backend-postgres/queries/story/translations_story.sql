@@ -0,0 +1,21 @@
+WITH root AS (
+    SELECT COALESCE(o.original, o.id) AS id FROM story_story o WHERE o.id = $1
+)
+SELECT
+    s.id as "id: _",
+    s.name,
+    s.summary,
+    s.language,
+    s.original,
+    s.rating as "rating: _",
+    s.state as "state: _",
+    s.created as "created: _",
+    s.updated as "updated: _"
+FROM
+    story_story s, root
+WHERE
+    s.id <> $1
+    AND
+    (s.id = root.id OR s.original = root.id)
+ORDER BY
+    s.language;
@@ -0,0 +1,9 @@
+UPDATE
+    story_story
+SET
+    state = 'abandoned',
+    updated = timezone('utc', now())
+WHERE
+    id = $1
+    AND
+    updated = $2;
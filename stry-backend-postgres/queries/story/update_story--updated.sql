@@ -0,0 +1,6 @@
+UPDATE
+    story_story
+SET
+    updated = timezone('utc', now())
+WHERE
+    id = $1;
@@ -0,0 +1,14 @@
+UPDATE
+    story_story
+SET
+    name = $2,
+    summary = $3,
+    rating = $4,
+    state = $5,
+    language = $6,
+    original = $7,
+    updated = timezone('utc', now())
+WHERE
+    id = $1
+    AND
+    updated = $8;
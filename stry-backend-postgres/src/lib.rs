@@ -1,26 +1,43 @@
 #![allow(unused_variables)]
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
+use std::collections::HashMap;
+
 use stry_common::{
     backend::{
-        Backend, ChapterEntity, CharacterEntity, CommentEntity, OriginEntity, PairingEntity,
-        PartEntity, SeriesEntity, StoryEntity, TagEntity, UserEntity, WarningEntity,
+        Backend, ChapterEntity, CharacterEntity, CommentEntity, JobLock, MediaEntity,
+        NotificationEntity, OriginEntity, PairingEntity, PartEntity, PostEntity, SeriesEntity,
+        SettingEntity, StoryEntity, TagEntity, UserEntity, WarningEntity,
     },
-    error::NotFound,
+    error::{Conflict, Invalid, NotFound, PreconditionFailed},
     futures::utils::TryStreamExt as _,
+    html,
+    limiter::{AttemptRecord, Duration, LimiterStore, LockRecord},
     loader::story::StoryLoaders,
     models::{
-        core::{Tag, User},
+        blog::{Post, PostRecordId},
+        core::{
+            Account, Appearance, Comment, Content, Media, MediaRecordId, Notification,
+            NotificationRecordId, Notifications, Part, PartText, Tag, TagRecordId, User,
+            UserRecordId, UserRecordProfile,
+        },
         story::{
-            Chapter, Character, Origin, Pairing, Series, Story, StoryRecord, StoryRecordId,
-            TagLevel, Warning,
+            Chapter, ChapterDraft, ChapterRecordId, ChapterRevision, Character, Origin, Pairing,
+            PairingRecordId, Relationship, Revision, RevisionRecordContent, RevisionRecordId,
+            Series, SeriesRecordId, State, Story, StoryFilter, StoryRecord, StoryRecordId,
+            StoryRevision, TagLevel, TagRequest, Warning,
         },
-        Existing, Id, New,
+        Either, Existing, Id, IdRecord, New,
     },
     prelude::*,
     uri::Uri,
+    utils::nanoid,
+    words,
 };
 
-use sqlx::{migrate::Migrator, postgres::PgConnectOptions, Pool, Postgres};
+use sqlx::{migrate::Migrator, postgres::PgConnectOptions, Pool, Postgres, Transaction};
 
 static MIGRATOR: Migrator = sqlx::migrate!();
 
@@ -74,6 +91,62 @@ macro_rules! id_level_loader {
     }};
 }
 
+/// Unique constraints that a request can break, with the field it's shown on.
+const UNIQUE: &[(&str, &str)] = &[
+    ("core_user_email_key", "email"),
+    ("story_story_user_pkey", "authors"),
+    ("story_story_tag_pkey", "tags"),
+    ("story_story_origin_pkey", "origins"),
+    ("story_story_warning_pkey", "warnings"),
+    ("story_story_pairing_pkey", "pairings"),
+    ("story_story_character_pkey", "characters"),
+    ("story_tag_request_pkey", "requests"),
+];
+
+/// Foreign keys to ids given in a request, with the field they came from.
+const REFERENCES: &[(&str, &str)] = &[
+    ("story_story_original_fkey", "original"),
+    ("story_series_story_series_id_fkey", "series"),
+    ("story_story_pairing_pairing_id_fkey", "pairings"),
+    ("story_story_character_character_id_fkey", "characters"),
+];
+
+/// Turns constraint violations into errors that can be shown to the user, a
+/// [`Conflict`] for unique constraints and an [`Invalid`] for ids that don't
+/// exist.
+fn constraint(err: sqlx::Error) -> Error {
+    if let sqlx::Error::Database(db) = &err {
+        let field = |fields: &[(&str, &'static str)]| {
+            db.constraint().and_then(|constraint| {
+                fields
+                    .iter()
+                    .find(|(name, _)| *name == constraint)
+                    .map(|(_, field)| *field)
+            })
+        };
+
+        match db.code().as_deref() {
+            Some("23505") => {
+                if let Some(field) = field(UNIQUE) {
+                    return Conflict::field(field, "unique").into();
+                }
+            }
+            Some("23503") => {
+                if let Some(field) = field(REFERENCES) {
+                    return Invalid::field(field, "exists").into();
+                }
+
+                // Anything else references the entity the request is for, ie
+                // a chapter added to a story that doesn't exist
+                return NotFound.into();
+            }
+            _ => {}
+        }
+    }
+
+    err.into()
+}
+
 #[derive(Clone)]
 pub struct PostgresBackend {
     pool: Pool<Postgres>,
@@ -119,214 +192,2361 @@ impl PostgresBackend {
 
         Ok(Self { pool })
     }
-}
 
-#[stry_common::prelude::async_trait]
-impl Backend for PostgresBackend {
-    async fn migrate(&self) -> Result<(), Error> {
-        MIGRATOR.run(&self.pool).await?;
+    /// Loads the entities of every story record, keeping their order.
+    async fn stories(&self, records: Vec<StoryRecordId>) -> Result<Vec<Existing<Story>>, Error> {
+        let loaders = StoryLoaders::new(Clone::clone(self));
 
-        Ok(())
+        let ids = records
+            .iter()
+            .map(|record| record.id.as_str())
+            .collect::<Vec<_>>();
+
+        let words = self.words(&ids).await?;
+
+        let mut stories = Vec::with_capacity(records.len());
+
+        for record in records {
+            let mut story = Story::new(
+                record.name,
+                record.summary,
+                record.language,
+                record.rating,
+                record.state,
+            );
+
+            story.original = record.original.map(Id::try_from).transpose()?;
+            story.words = words.get(&record.id).copied().unwrap_or_default();
+
+            let id = record.id.as_str();
+
+            async {
+                #[rustfmt::skip]
+                id_loader![
+                    [&self.pool, loaders.user, id, story.authors, "queries/story/get_story-user.sql", id, "author"],
+                    [&self.pool, loaders.user, id, story.commissioners, "queries/story/get_story-user.sql", id, "commissioner"],
+                    [&self.pool, loaders.user, id, story.dedicatees, "queries/story/get_story-user.sql", id, "dedicated"],
+                    [&self.pool, loaders.user, id, story.translators, "queries/story/get_story-user.sql", id, "translator"],
+                    [&self.pool, loaders.tag, id, story.tags, "queries/story/get_story-tag.sql", id],
+                ];
+
+                #[rustfmt::skip]
+                id_level_loader![
+                    [&self.pool, loaders.origin, id, story.origins, "queries/story/get_story-origin.sql", id],
+                    [&self.pool, loaders.warning, id, story.warnings, "queries/story/get_story-warning.sql", id],
+                    [&self.pool, loaders.pairing, id, story.pairings, "queries/story/pairing/get_story-pairing.sql", id],
+                    [&self.pool, loaders.character, id, story.characters, "queries/story/character/get_story-character.sql", id],
+                ];
+
+                Ok::<(), Error>(())
+            }.instrument(trace_span!("story entities", id = ?record.id)).await?;
+
+            story.series = self.story_series(&loaders, id).await?;
+
+            stories.push(Existing::new(
+                Id::try_from(record.id.as_str())?,
+                story,
+                record.created,
+                record.updated,
+            ));
+        }
+
+        Ok(stories)
     }
-}
 
-#[async_trait]
-impl UserEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<User>, Error> {
-        todo!()
+    /// Counts the words of the stories' published chapters, stories without
+    /// any are left out.
+    async fn words(&self, stories: &[&str]) -> Result<HashMap<String, i32>, Error> {
+        let records = sqlx::query_file!("queries/story/get_stories-words.sql", stories as _)
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch stories word counts"))
+            .await?;
+
+        Ok(records
+            .into_iter()
+            .map(|record| {
+                let words = i32::try_from(record.words).unwrap_or(i32::MAX);
+
+                (record.story_id, words)
+            })
+            .collect())
     }
 
-    #[instrument(skip(self, data), err)]
-    async fn create(&self, data: New<User>) -> Result<Id, Error> {
-        todo!()
+    /// Loads the series the story is part of, if it is in one.
+    async fn story_series(
+        &self,
+        loaders: &StoryLoaders<Self>,
+        story: &str,
+    ) -> Result<Option<Existing<Series>>, Error> {
+        let record =
+            sqlx::query_file_as!(IdRecord, "queries/story/series/get_story-series.sql", story)
+                .fetch_optional(&self.pool)
+                .instrument(trace_span!("fetch story series", id = ?story))
+                .await?;
+
+        match record {
+            Some(record) => Ok(Some(loaders.series.load(Id::try_from(record.id)?).await?)),
+            None => Ok(None),
+        }
     }
-}
 
-#[async_trait]
-impl CommentEntity for PostgresBackend {}
+    /// Turns pairing records into their entities, with their characters in
+    /// order.
+    async fn pairings(
+        &self,
+        records: Vec<PairingRecordId>,
+    ) -> Result<Vec<Existing<Pairing>>, Error> {
+        let ids = records
+            .iter()
+            .map(|record| record.id.as_str())
+            .collect::<Vec<_>>();
+
+        let rows = sqlx::query_file!(
+            "queries/story/pairing/get_pairings-character.sql",
+            &ids as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch pairing characters"))
+        .await?;
+
+        let characters = CharacterEntity::many(
+            self,
+            rows.iter()
+                .map(|row| Id::try_from(row.character_id.as_str()))
+                .collect::<Result<_, _>>()?,
+        )
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| {
+                let characters = rows
+                    .iter()
+                    .filter(|row| row.pairing_id == record.id)
+                    .filter_map(|row| {
+                        characters
+                            .iter()
+                            .find(|character| character.id.as_str() == row.character_id)
+                            .cloned()
+                    })
+                    .collect();
+
+                let pairing = Pairing {
+                    hash: record.hash,
+                    relationship: record.relationship,
+                    characters,
+                    level: TagLevel::Major,
+                };
+
+                Ok(Existing::new(
+                    Id::try_from(record.id)?,
+                    pairing,
+                    record.created,
+                    record.updated,
+                ))
+            })
+            .collect()
+    }
 
-#[async_trait]
-impl PartEntity for PostgresBackend {}
+    /// Turns series records into their entities, with the ids of their
+    /// stories in order.
+    async fn series(&self, records: Vec<SeriesRecordId>) -> Result<Vec<Existing<Series>>, Error> {
+        let ids = records
+            .iter()
+            .map(|record| record.id.as_str())
+            .collect::<Vec<_>>();
 
-#[async_trait]
-impl TagEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Tag>, Error> {
-        todo!()
+        let stories = sqlx::query_file!("queries/story/series/get_series-story.sql", &ids as _)
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch series stories"))
+            .await?;
+
+        records
+            .into_iter()
+            .map(|record| {
+                let ids = stories
+                    .iter()
+                    .filter(|story| story.series_id == record.id)
+                    .map(|story| Id::try_from(story.story_id.as_str()))
+                    .collect::<Result<_, _>>()?;
+
+                let series = Series {
+                    name: record.name,
+                    summary: record.summary,
+                    state: record.state,
+                    stories: Either::Right(ids),
+                };
+
+                Ok(Existing::new(
+                    Id::try_from(record.id)?,
+                    series,
+                    record.created,
+                    record.updated,
+                ))
+            })
+            .collect()
     }
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Tag>>, Error> {
-        todo!()
+    /// Checks if the user is one of the story's authors.
+    async fn is_author(&self, story: &str, user: &Id) -> Result<bool, Error> {
+        let authors = sqlx::query_file_as!(
+            IdRecord,
+            "queries/story/get_story-user.sql",
+            story,
+            "author"
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch story authors"))
+        .await?;
+
+        Ok(authors.iter().any(|author| author.id == user.as_str()))
     }
-}
 
-#[async_trait]
-impl ChapterEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Chapter>, Error> {
-        todo!()
+    /// Get the story's current metadata as a revision, with when it was last
+    /// updated.
+    async fn story_revision(
+        &self,
+        transaction: &mut Transaction<'_, Postgres>,
+        id: &Id,
+    ) -> Result<(StoryRevision, OffsetDateTime), Error> {
+        let record = sqlx::query_file_as!(StoryRecord, "queries/story/get_story.sql", id.as_str())
+            .fetch_optional(&mut *transaction)
+            .await?;
+
+        let record = match record {
+            Some(record) => record,
+            None => return Err(NotFound.into()),
+        };
+
+        let revision = StoryRevision {
+            editor: None,
+            name: record.name,
+            summary: record.summary,
+            language: record.language,
+            original: record.original.map(Id::try_from).transpose()?,
+            rating: record.rating,
+            state: record.state,
+        };
+
+        Ok((revision, record.updated))
     }
 
-    #[instrument(skip(self, data), err)]
-    async fn create(&self, data: New<Chapter>) -> Result<Id, Error> {
-        todo!()
+    /// Get the revisions of a story's metadata (`chapter` is `None`) or one
+    /// of its chapters.
+    async fn revisions(
+        &self,
+        story: &str,
+        chapter: Option<&str>,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Revision>>, Error> {
+        let records = match cursor {
+            Some(cursor) => {
+                sqlx::query_file_as!(
+                    RevisionRecordId,
+                    "queries/story/revision/all_revisions--cursor.sql",
+                    story,
+                    chapter,
+                    cursor.as_str(),
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch revisions after cursor"))
+                .await?
+            }
+            None => {
+                sqlx::query_file_as!(
+                    RevisionRecordId,
+                    "queries/story/revision/all_revisions.sql",
+                    story,
+                    chapter,
+                    limit
+                )
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch revisions"))
+                .await?
+            }
+        };
+
+        records
+            .into_iter()
+            .map(|record| {
+                let revision = Revision {
+                    editor: editor(
+                        record.editor_id,
+                        record.editor_name,
+                        record.editor_created,
+                        record.editor_updated,
+                    )?,
+                };
+
+                Ok(Existing::new(
+                    Id::try_from(record.id)?,
+                    revision,
+                    record.created,
+                    record.updated,
+                ))
+            })
+            .collect()
+    }
+
+    /// Get a revision, failing with [`NotFound`] unless it's of the story's
+    /// metadata (`chapter` is `None`) or the chapter.
+    async fn revision<T>(
+        &self,
+        story: &str,
+        chapter: Option<&str>,
+        revision: Id,
+    ) -> Result<(Existing<T>, Option<Existing<User>>), Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let record = sqlx::query_file_as!(
+            RevisionRecordContent,
+            "queries/story/revision/get_revision.sql",
+            revision.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch revision with id"))
+        .await?;
+
+        let record = match record {
+            Some(record) if record.story_id == story && record.chapter_id.as_deref() == chapter => {
+                record
+            }
+            _ => return Err(NotFound.into()),
+        };
+
+        let editor = editor(
+            record.editor_id,
+            record.editor_name,
+            record.editor_created,
+            record.editor_updated,
+        )?;
+
+        Ok((
+            Existing::new(
+                Id::try_from(record.id)?,
+                serde_json::from_str(&record.content)?,
+                record.created,
+                record.updated,
+            ),
+            editor,
+        ))
     }
 }
 
-#[async_trait]
-impl OriginEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Origin>, Error> {
-        todo!()
+/// Turns a users table row into a user profile.
+///
+/// The biography is stored as plain text, so its given as a single text part
+/// sharing the user's id, its paragraphs turned into HTML.
+fn profile(record: UserRecordProfile) -> Result<Existing<User>, Error> {
+    let id = Id::try_from(record.id.as_str())?;
+
+    let biography = if record.biography.is_empty() {
+        Vec::new()
+    } else {
+        let content = html::from_text(&record.biography);
+
+        vec![Existing::new(
+            id,
+            Part::Text(PartText {
+                words: html::words(&content),
+                content,
+                comments: Vec::new(),
+            }),
+            record.created,
+            record.updated,
+        )]
+    };
+
+    let settings: Settings = serde_json::from_str(&record.settings)?;
+
+    let mut user = User::new(Account {
+        name: record.name,
+        email: None,
+        hash: None,
+        biography: Some(biography),
+    });
+
+    user.appearance = settings.appearance;
+    user.notifications = settings.notifications;
+    user.content = settings.content;
+
+    Ok(Existing::new(id, user, record.created, record.updated))
+}
+
+/// Turns a tags table row (or one of the story tag tables) into its entity,
+/// story tags get the [`TagLevel`] of the story they're used in.
+fn tag<T>(record: TagRecordId, tag: fn(String, String) -> T) -> Result<Existing<T>, Error> {
+    Ok(Existing::new(
+        Id::try_from(record.id)?,
+        tag(record.content, record.description),
+        record.created,
+        record.updated,
+    ))
+}
+
+fn core_tag(content: String, description: String) -> Tag {
+    Tag {
+        content,
+        description,
     }
+}
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Origin>>, Error> {
-        todo!()
+fn story_origin(content: String, description: String) -> Origin {
+    Origin {
+        content,
+        description,
+        level: TagLevel::Major,
     }
 }
 
-#[async_trait]
-impl WarningEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Warning>, Error> {
-        todo!()
+fn story_warning(content: String, description: String) -> Warning {
+    Warning {
+        content,
+        description,
+        level: TagLevel::Major,
     }
+}
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Warning>>, Error> {
-        todo!()
+fn story_character(content: String, description: String) -> Character {
+    Character {
+        content,
+        description,
+        level: TagLevel::Major,
     }
 }
 
-#[async_trait]
-impl PairingEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Pairing>, Error> {
-        todo!()
+/// The hash a pairing is stored under, see [`Pairing::hash`].
+fn pairing_hash(relationship: Relationship, characters: &[Id]) -> String {
+    let relationship = match relationship {
+        Relationship::Family => "family",
+        Relationship::Friends => "friends",
+        Relationship::Romantic => "romantic",
+    };
+
+    let mut characters = as_strs(characters);
+
+    characters.sort_unstable();
+    characters.dedup();
+
+    format!("{}:{}", relationship, characters.join(","))
+}
+
+/// The ids as they're given to `ANY`.
+fn as_strs(ids: &[Id]) -> Vec<&str> {
+    ids.iter().map(Id::as_str).collect()
+}
+
+/// The ids of the entities as they're given to `unnest`.
+fn entity_ids<T>(entities: &[Existing<T>]) -> Vec<&str> {
+    entities.iter().map(|entity| entity.id.as_str()).collect()
+}
+
+/// Replaces the users credited on a story, its tags and the series its part
+/// of with the ones in `data`.
+async fn relations(
+    transaction: &mut Transaction<'_, Postgres>,
+    story: &str,
+    data: &Story,
+) -> Result<(), Error> {
+    sqlx::query_file!("queries/story/remove_story-user.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    for (relationship, users) in [
+        ("author", &data.authors),
+        ("commissioner", &data.commissioners),
+        ("dedicated", &data.dedicatees),
+        ("translator", &data.translators),
+    ] {
+        sqlx::query_file!(
+            "queries/story/insert_story-user.sql",
+            story,
+            &entity_ids(users) as _,
+            relationship
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(constraint)?;
     }
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Pairing>>, Error> {
-        todo!()
+    sqlx::query_file!("queries/story/remove_story-tag.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query_file!(
+        "queries/story/insert_story-tag.sql",
+        story,
+        &entity_ids(&data.tags) as _
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    sqlx::query_file!("queries/story/remove_story-origin.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query_file!(
+        "queries/story/insert_story-origin.sql",
+        story,
+        &entity_ids(&data.origins) as _,
+        &data
+            .origins
+            .iter()
+            .map(|origin| origin.level.as_str())
+            .collect::<Vec<_>>() as _
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    sqlx::query_file!("queries/story/remove_story-warning.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query_file!(
+        "queries/story/insert_story-warning.sql",
+        story,
+        &entity_ids(&data.warnings) as _,
+        &data
+            .warnings
+            .iter()
+            .map(|warning| warning.level.as_str())
+            .collect::<Vec<_>>() as _
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    sqlx::query_file!("queries/story/pairing/remove_story-pairing.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query_file!(
+        "queries/story/pairing/insert_story-pairing.sql",
+        story,
+        &entity_ids(&data.pairings) as _,
+        &data
+            .pairings
+            .iter()
+            .map(|pairing| pairing.level.as_str())
+            .collect::<Vec<_>>() as _
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    sqlx::query_file!("queries/story/character/remove_story-character.sql", story)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query_file!(
+        "queries/story/character/insert_story-character.sql",
+        story,
+        &entity_ids(&data.characters) as _,
+        &data
+            .characters
+            .iter()
+            .map(|character| character.level.as_str())
+            .collect::<Vec<_>>() as _
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    // Staying in the same series keeps the story's place in it
+    let series = data.series.as_ref().map(|series| series.id.as_str());
+
+    sqlx::query_file!(
+        "queries/story/series/remove_story-series.sql",
+        story,
+        series
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    if let Some(series) = series {
+        sqlx::query_file!(
+            "queries/story/series/insert_story-series.sql",
+            story,
+            series
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(constraint)?;
     }
+
+    Ok(())
 }
 
-#[async_trait]
-impl CharacterEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Character>, Error> {
-        todo!()
-    }
+/// Inserts the story and the rows relating it to its users, tags and series.
+async fn create_story(
+    transaction: &mut Transaction<'_, Postgres>,
+    data: &Story,
+) -> Result<Id, Error> {
+    let id = nanoid::new_id().ok_or_else(|| err!("unable to generate story id"))?;
+
+    sqlx::query_file!(
+        "queries/story/insert_story.sql",
+        id.as_str(),
+        data.name,
+        data.summary,
+        data.rating as _,
+        data.state as _,
+        data.language,
+        data.original.as_ref().map(Id::as_str)
+    )
+    .execute(&mut *transaction)
+    .instrument(trace_span!("insert story"))
+    .await
+    .map_err(constraint)?;
+
+    relations(transaction, id.as_str(), data).await?;
+
+    Ok(id)
+}
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Character>>, Error> {
-        todo!()
+/// Adds the chapter to the end of the story, along with its first revision.
+async fn create_chapter(
+    transaction: &mut Transaction<'_, Postgres>,
+    story: &str,
+    data: &Chapter,
+    editor: &str,
+) -> Result<Id, Error> {
+    let id = nanoid::new_id().ok_or_else(|| err!("unable to generate chapter id"))?;
+    let revision = nanoid::new_id().ok_or_else(|| err!("unable to generate revision id"))?;
+
+    let draft = data.draft.as_ref().map(serde_json::to_string).transpose()?;
+
+    sqlx::query_file!(
+        "queries/story/chapter/insert_chapter.sql",
+        id.as_str(),
+        story,
+        data.name,
+        data.published,
+        data.scheduled as _,
+        ChapterContent::to_json(data)?,
+        draft
+    )
+    .execute(&mut *transaction)
+    .instrument(trace_span!("insert chapter"))
+    .await
+    .map_err(constraint)?;
+
+    sqlx::query_file!(
+        "queries/story/revision/insert_revision.sql",
+        revision.as_str(),
+        story,
+        id.as_str(),
+        editor,
+        serde_json::to_string(&ChapterRevision::new(data))?
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    Ok(id)
+}
+
+/// The layout of the users table's settings column.
+#[derive(Deserialize, Serialize)]
+struct Settings {
+    #[serde(default)]
+    appearance: Appearance,
+    #[serde(default)]
+    notifications: Notifications,
+    #[serde(default)]
+    content: Content,
+}
+
+/// The layout of the chapters table's content column.
+#[derive(Deserialize, Serialize)]
+struct ChapterContent {
+    prefix: Vec<Existing<Part>>,
+    main: Vec<Existing<Part>>,
+    suffix: Vec<Existing<Part>>,
+    #[serde(default)]
+    comments: Vec<Existing<Comment>>,
+}
+
+impl ChapterContent {
+    fn to_json(chapter: &Chapter) -> Result<String, Error> {
+        Ok(serde_json::to_string(&ChapterContent {
+            prefix: chapter.prefix.clone(),
+            main: chapter.main.clone(),
+            suffix: chapter.suffix.clone(),
+            comments: chapter.comments.clone(),
+        })?)
     }
 }
 
-#[async_trait]
-impl StoryEntity for PostgresBackend {
-    #[instrument(skip(self, id), err)]
-    async fn get(&self, id: Id) -> Result<Existing<Story>, Error> {
-        let loaders = StoryLoaders::new(Clone::clone(self));
+/// Turns a chapters table row into a chapter, counting the words of it and
+/// its draft.
+fn chapter(record: ChapterRecordId) -> Result<Existing<Chapter>, Error> {
+    let content: ChapterContent = serde_json::from_str(&record.content)?;
+
+    let draft = record
+        .draft
+        .as_deref()
+        .map(serde_json::from_str::<ChapterDraft>)
+        .transpose()?
+        .map(|mut draft| {
+            draft.words = words::parts(&draft.main);
+
+            draft
+        });
+
+    let mut chapter = Chapter {
+        name: record.name,
+        published: record.published,
+        scheduled: record.scheduled,
+        published_at: record.published_at,
+        prefix: content.prefix,
+        main: content.main,
+        suffix: content.suffix,
+        comments: content.comments,
+        words: 0,
+        draft,
+    };
+
+    chapter.words = words::chapter(&chapter);
+
+    Ok(Existing::new(
+        Id::try_from(record.id)?,
+        chapter,
+        record.created,
+        record.updated,
+    ))
+}
 
-        let record_id = id.as_str();
+/// Records a revision of a story's metadata (`chapter` is `None`) or one of
+/// its chapters, unless nothing changed since the last one.
+///
+/// Anything that existed before revisions were kept gets its previous content
+/// recorded first, made by no one when it was last updated.
+async fn revise<T>(
+    transaction: &mut Transaction<'_, Postgres>,
+    story: &str,
+    chapter: Option<&str>,
+    editor: Option<&str>,
+    previous: (&T, OffsetDateTime),
+    current: &T,
+) -> Result<(), Error>
+where
+    T: Serialize,
+{
+    let latest = sqlx::query_file!(
+        "queries/story/revision/get_revision--latest.sql",
+        story,
+        chapter
+    )
+    .fetch_optional(&mut *transaction)
+    .await?;
+
+    let current = serde_json::to_value(current)?;
+
+    let latest = match latest {
+        Some(record) => serde_json::from_str::<serde_json::Value>(&record.content)?,
+        None => {
+            let (previous, updated) = previous;
+            let previous = serde_json::to_value(previous)?;
+
+            if previous == current {
+                return Ok(());
+            }
 
-        let record = sqlx::query_file_as!(StoryRecord, "queries/story/get_story.sql", id.as_str())
-            .fetch_optional(&self.pool)
-            .instrument(trace_span!("fetch story with id", id = ?record_id))
+            let id = nanoid::new_id().ok_or_else(|| err!("unable to generate revision id"))?;
+
+            sqlx::query_file!(
+                "queries/story/revision/insert_revision--baseline.sql",
+                id.as_str(),
+                story,
+                chapter,
+                previous.to_string(),
+                updated as _
+            )
+            .execute(&mut *transaction)
             .await?;
 
-        if let Some(record) = record {
-            let mut story = Story::new(record.name, record.summary, record.rating, record.state);
+            previous
+        }
+    };
 
-            async {
-                #[rustfmt::skip]
-                id_loader![
-                    [&self.pool, loaders.user, record_id, story.authors, "queries/story/get_story-user.sql", record_id, "author"],
-                    [&self.pool, loaders.user, record_id, story.commissioners, "queries/story/get_story-user.sql", record_id, "commissioner"],
-                    [&self.pool, loaders.user, record_id, story.dedicatees, "queries/story/get_story-user.sql", record_id, "dedicated"],
-                    [&self.pool, loaders.tag, record_id, story.tags, "queries/story/get_story-tag.sql", record_id],
-                ];
+    if latest == current {
+        return Ok(());
+    }
 
-                #[rustfmt::skip]
-                id_level_loader![
-                    [&self.pool, loaders.origin, record_id, story.origins, "queries/story/get_story-origin.sql", record_id],
-                    [&self.pool, loaders.warning, record_id, story.warnings, "queries/story/get_story-warning.sql", record_id],
-                ];
+    let id = nanoid::new_id().ok_or_else(|| err!("unable to generate revision id"))?;
+
+    sqlx::query_file!(
+        "queries/story/revision/insert_revision.sql",
+        id.as_str(),
+        story,
+        chapter,
+        editor,
+        current.to_string()
+    )
+    .execute(&mut *transaction)
+    .await
+    .map_err(constraint)?;
+
+    Ok(())
+}
 
-                Ok::<(), Error>(())
-            }.instrument(trace_span!("story load all entities", id = ?record_id)).await?;
+/// Turns the editor columns of a revision into a user, without any of their
+/// settings.
+fn editor(
+    id: Option<String>,
+    name: Option<String>,
+    created: Option<OffsetDateTime>,
+    updated: Option<OffsetDateTime>,
+) -> Result<Option<Existing<User>>, Error> {
+    match (id, name, created, updated) {
+        (Some(id), Some(name), Some(created), Some(updated)) => {
+            let user = User::new(Account {
+                name,
+                email: None,
+                hash: None,
+                biography: None,
+            });
+
+            Ok(Some(Existing::new(
+                Id::try_from(id)?,
+                user,
+                created,
+                updated,
+            )))
+        }
+        _ => Ok(None),
+    }
+}
 
-            Ok(Existing::new(id, story, record.created, record.updated))
-        } else {
-            Err(NotFound.into())
+#[stry_common::prelude::async_trait]
+impl Backend for PostgresBackend {
+    async fn migrate(&self) -> Result<(), Error> {
+        MIGRATOR.run(&self.pool).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn lock_job(&self, name: &str) -> Result<Option<JobLock>, Error> {
+        // Advisory locks belong to the connection, so it's taken out of the
+        // pool and closing it when the lock is dropped releases the lock
+        let mut connection = self.pool.acquire().await?.detach();
+
+        let record = sqlx::query_file!("queries/core/job/lock_job.sql", name)
+            .fetch_one(&mut connection)
+            .instrument(trace_span!("take job lock"))
+            .await?;
+
+        Ok(record.locked.then(|| JobLock::new(connection)))
+    }
+}
+
+#[async_trait]
+impl UserEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<User>, Error> {
+        let record = sqlx::query_file_as!(
+            UserRecordProfile,
+            "queries/core/user/get_user.sql",
+            id.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch user with id"))
+        .await?;
+
+        match record {
+            Some(record) => profile(record),
+            None => Err(NotFound.into()),
         }
     }
 
-    #[instrument(skip(self, cursor, limit), err)]
-    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Story>>, Error> {
-        let loaders = StoryLoaders::new(Clone::clone(self));
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<User>>, Error> {
+        let records = sqlx::query_file_as!(
+            UserRecordProfile,
+            "queries/core/user/get_users.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch users with ids"))
+        .await?;
+
+        records.into_iter().map(profile).collect()
+    }
 
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<User>>, Error> {
         let records = if let Some(cursor) = cursor {
             sqlx::query_file_as!(
-                StoryRecordId,
-                "queries/story/all_stories--cursor.sql",
+                UserRecordProfile,
+                "queries/core/user/all_users--cursor.sql",
                 cursor.as_str(),
                 limit
             )
             .fetch_all(&self.pool)
-            .instrument(trace_span!("fetch stories with cursor"))
+            .instrument(trace_span!("fetch users with cursor"))
             .await?
         } else {
-            sqlx::query_file_as!(StoryRecordId, "queries/story/all_stories.sql", limit)
+            sqlx::query_file_as!(UserRecordProfile, "queries/core/user/all_users.sql", limit)
                 .fetch_all(&self.pool)
-                .instrument(trace_span!("fetch stories without cursor"))
+                .instrument(trace_span!("fetch users without cursor"))
                 .await?
         };
 
-        let mut stories = Vec::with_capacity(records.len());
-
-        for record in records {
-            let mut story = Story::new(record.name, record.summary, record.rating, record.state);
-
-            let id = record.id.as_str();
-
-            async {
-                #[rustfmt::skip]
-                id_loader![
-                    [&self.pool, loaders.user, id, story.authors, "queries/story/get_story-user.sql", id, "author"],
-                    [&self.pool, loaders.user, id, story.commissioners, "queries/story/get_story-user.sql", id, "commissioner"],
-                    [&self.pool, loaders.user, id, story.dedicatees, "queries/story/get_story-user.sql", id, "dedicated"],
-                    [&self.pool, loaders.tag, id, story.tags, "queries/story/get_story-tag.sql", id],
-                ];
-
-                #[rustfmt::skip]
-                id_level_loader![
-                    [&self.pool, loaders.origin, id, story.origins, "queries/story/get_story-origin.sql", id],
-                    [&self.pool, loaders.warning, id, story.warnings, "queries/story/get_story-warning.sql", id],
-                ];
+        records
+            .into_iter()
+            .map(|record| {
+                // Lists only show the name, the biography is for the profile
+                profile(record).map(|mut user| {
+                    user.account.biography = None;
+
+                    user
+                })
+            })
+            .collect()
+    }
 
-                Ok::<(), Error>(())
-            }.instrument(trace_span!("story entities", id = ?record.id)).await?;
+    #[instrument(skip(self, email), err)]
+    async fn get_by_email(&self, email: String) -> Result<Existing<User>, Error> {
+        let record =
+            sqlx::query_file_as!(UserRecordId, "queries/core/user/get_user--email.sql", email)
+                .fetch_optional(&self.pool)
+                .instrument(trace_span!("fetch user with email"))
+                .await?;
 
-            stories.push(Existing::new(
+        if let Some(record) = record {
+            let user = User::new(Account {
+                name: record.name,
+                email: Some(record.email),
+                hash: Some(record.hash.into_bytes()),
+                biography: None,
+            });
+
+            Ok(Existing::new(
                 Id::try_from(record.id.as_str())?,
-                story,
+                user,
                 record.created,
                 record.updated,
-            ));
-        }
+            ))
+        } else {
+            Err(NotFound.into())
+        }
+    }
+
+    #[instrument(skip(self, data), err)]
+    async fn create(&self, data: New<User>) -> Result<Id, Error> {
+        let id = nanoid::new_id().ok_or_else(|| err!("unable to generate user id"))?;
+
+        let email = data
+            .account
+            .email
+            .as_deref()
+            .ok_or_else(|| err!("a new user requires an email"))?;
+
+        // The hash is a null padded string, postgres text does not allow nulls
+        let hash = data
+            .account
+            .hash
+            .as_deref()
+            .ok_or_else(|| err!("a new user requires a password hash"))?;
+        let hash = std::str::from_utf8(hash)?.trim_end_matches('\0');
+
+        let settings = serde_json::to_string(&Settings {
+            appearance: data.appearance.clone(),
+            notifications: data.notifications.clone(),
+            content: data.content.clone(),
+        })?;
+
+        sqlx::query_file!(
+            "queries/core/user/insert_user.sql",
+            id.as_str(),
+            email,
+            data.account.name,
+            hash,
+            settings
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(constraint)?;
+
+        Ok(id)
+    }
+
+    #[instrument(skip(self, appearance, notifications, content), err)]
+    async fn update_settings(
+        &self,
+        id: Id,
+        appearance: Appearance,
+        notifications: Notifications,
+        content: Content,
+    ) -> Result<(), Error> {
+        let settings = serde_json::to_string(&Settings {
+            appearance,
+            notifications,
+            content,
+        })?;
+
+        let result = sqlx::query_file!(
+            "queries/core/user/update_user--settings.sql",
+            id.as_str(),
+            settings
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(NotFound.into());
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, id), err)]
+    async fn is_admin(&self, id: Id) -> Result<bool, Error> {
+        let record = sqlx::query_file!("queries/core/user/get_user--admin.sql", id.as_str())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match record {
+            Some(record) => Ok(record.admin),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, id), err)]
+    async fn set_admin(&self, id: Id, admin: bool) -> Result<(), Error> {
+        let result = sqlx::query_file!(
+            "queries/core/user/update_user--admin.sql",
+            id.as_str(),
+            admin
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(NotFound.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CommentEntity for PostgresBackend {}
+
+fn media(record: MediaRecordId) -> Result<Existing<Media>, Error> {
+    Ok(Existing::new(
+        Id::try_from(record.id)?,
+        Media {
+            uploader: Id::try_from(record.uploader_id)?,
+            hash: record.hash,
+            extension: record.extension,
+            width: record.width,
+            height: record.height,
+            thumbnail: record.thumbnail,
+        },
+        record.created,
+        record.updated,
+    ))
+}
+
+#[async_trait]
+impl MediaEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Media>, Error> {
+        let record = sqlx::query_file_as!(
+            MediaRecordId,
+            "queries/core/media/get_media.sql",
+            id.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch media with id"))
+        .await?;
+
+        match record {
+            Some(record) => media(record),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self), err)]
+    async fn find(&self, hash: String) -> Result<Option<Existing<Media>>, Error> {
+        let record = sqlx::query_file_as!(
+            MediaRecordId,
+            "queries/core/media/find_media--hash.sql",
+            hash
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch media with hash"))
+        .await?;
+
+        record.map(media).transpose()
+    }
+
+    #[instrument(skip(self, data), err)]
+    async fn create(&self, data: New<Media>) -> Result<Id, Error> {
+        let id = nanoid::new_id().ok_or_else(|| err!("unable to generate media id"))?;
+
+        let record = sqlx::query_file!(
+            "queries/core/media/insert_media.sql",
+            id.as_str(),
+            data.uploader.as_str(),
+            data.hash,
+            data.extension,
+            data.width,
+            data.height,
+            data.thumbnail
+        )
+        .fetch_one(&self.pool)
+        .instrument(trace_span!("insert media"))
+        .await?;
+
+        Id::try_from(record.id)
+    }
+}
+
+fn notification(record: NotificationRecordId) -> Result<Existing<Notification>, Error> {
+    Ok(Existing::new(
+        Id::try_from(record.id)?,
+        serde_json::from_str(&record.content)?,
+        record.created,
+        record.updated,
+    ))
+}
+
+#[async_trait]
+impl NotificationEntity for PostgresBackend {
+    #[instrument(skip(self, user, cursor, limit), err)]
+    async fn all(
+        &self,
+        user: Id,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Notification>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                NotificationRecordId,
+                "queries/core/notification/all_notifications--cursor.sql",
+                user.as_str(),
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch notifications with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(
+                NotificationRecordId,
+                "queries/core/notification/all_notifications.sql",
+                user.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch notifications without cursor"))
+            .await?
+        };
+
+        records.into_iter().map(notification).collect()
+    }
+
+    #[instrument(skip(self, user, data), err)]
+    async fn create(&self, user: Id, data: New<Notification>) -> Result<Id, Error> {
+        let id = nanoid::new_id().ok_or_else(|| err!("unable to generate notification id"))?;
+
+        let story = data.story();
+
+        sqlx::query_file!(
+            "queries/core/notification/insert_notification.sql",
+            id.as_str(),
+            user.as_str(),
+            story.as_ref().map(Id::as_str),
+            serde_json::to_string(&*data)?
+        )
+        .execute(&self.pool)
+        .instrument(trace_span!("insert notification"))
+        .await?;
+
+        Ok(id)
+    }
+}
+
+#[async_trait]
+impl PartEntity for PostgresBackend {}
+
+fn post(record: PostRecordId) -> Result<Existing<Post>, Error> {
+    Ok(Existing::new(
+        Id::try_from(record.id)?,
+        serde_json::from_str(&record.content)?,
+        record.created,
+        record.updated,
+    ))
+}
+
+#[async_trait]
+impl PostEntity for PostgresBackend {
+    #[instrument(skip(self, user, cursor, limit), err)]
+    async fn all(
+        &self,
+        user: Id,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Post>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                PostRecordId,
+                "queries/blog/post/all_posts--cursor.sql",
+                user.as_str(),
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch posts with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(
+                PostRecordId,
+                "queries/blog/post/all_posts.sql",
+                user.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch posts without cursor"))
+            .await?
+        };
+
+        records.into_iter().map(post).collect()
+    }
+
+    #[instrument(skip(self, user, data), err)]
+    async fn create(&self, user: Id, data: New<Post>) -> Result<Id, Error> {
+        let id = nanoid::new_id().ok_or_else(|| err!("unable to generate post id"))?;
+
+        sqlx::query_file!(
+            "queries/blog/post/insert_post.sql",
+            id.as_str(),
+            user.as_str(),
+            serde_json::to_string(&*data)?
+        )
+        .execute(&self.pool)
+        .instrument(trace_span!("insert post"))
+        .await?;
+
+        Ok(id)
+    }
+}
+
+#[async_trait]
+impl SettingEntity for PostgresBackend {
+    #[instrument(skip(self), err)]
+    async fn get(&self, key: String) -> Result<Option<String>, Error> {
+        let record = sqlx::query_file!("queries/core/settings/get_setting.sql", key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(record.map(|record| record.value))
+    }
+
+    #[instrument(skip(self), err)]
+    async fn set(&self, key: String, value: String) -> Result<(), Error> {
+        sqlx::query_file!("queries/core/settings/upsert_setting.sql", key, value)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TagEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Tag>, Error> {
+        let record = sqlx::query_file_as!(TagRecordId, "queries/core/tag/get_tag.sql", id.as_str())
+            .fetch_optional(&self.pool)
+            .instrument(trace_span!("fetch tag with id"))
+            .await?;
+
+        match record {
+            Some(record) => tag(record, core_tag),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Tag>>, Error> {
+        let records = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/core/tag/get_tags.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch tags with ids"))
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| tag(record, core_tag))
+            .collect()
+    }
+
+    /// Tags are listed alphabetically, they're browsed to find one.
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Tag>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                TagRecordId,
+                "queries/core/tag/all_tags--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch tags with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(TagRecordId, "queries/core/tag/all_tags.sql", limit)
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch tags without cursor"))
+                .await?
+        };
+
+        records
+            .into_iter()
+            .map(|record| tag(record, core_tag))
+            .collect()
+    }
+
+    #[instrument(skip(self), err)]
+    async fn find(&self, content: String) -> Result<Option<Existing<Tag>>, Error> {
+        let record = sqlx::query_file_as!(TagRecordId, "queries/core/tag/find_tag.sql", content)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        record.map(|record| tag(record, core_tag)).transpose()
+    }
+
+    #[instrument(skip(self, request), err)]
+    async fn request(&self, story: Id, request: TagRequest) -> Result<(), Error> {
+        sqlx::query_file!(
+            "queries/story/insert_tag-request.sql",
+            story.as_str(),
+            request.kind as _,
+            request.content
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChapterEntity for PostgresBackend {
+    #[instrument(skip(self, id, viewer), err)]
+    async fn get(&self, id: Id, viewer: Option<Id>) -> Result<Existing<Chapter>, Error> {
+        let record = sqlx::query_file_as!(
+            ChapterRecordId,
+            "queries/story/chapter/get_chapter.sql",
+            id.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch chapter with id"))
+        .await?;
+
+        let record = match record {
+            Some(record) => record,
+            None => return Err(NotFound.into()),
+        };
+
+        let author = match viewer {
+            Some(viewer) => self.is_author(&record.story_id, &viewer).await?,
+            None => false,
+        };
+
+        let mut chapter = chapter(record)?;
+
+        if !author {
+            if !chapter.published {
+                return Err(NotFound.into());
+            }
+
+            chapter.for_readers();
+        }
+
+        Ok(chapter)
+    }
+
+    #[instrument(skip(self, ids, viewer), err)]
+    async fn many(
+        &self,
+        ids: Vec<Id>,
+        viewer: Option<Id>,
+    ) -> Result<Vec<Existing<Chapter>>, Error> {
+        let records = sqlx::query_file_as!(
+            ChapterRecordId,
+            "queries/story/chapter/get_chapters.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch chapters with ids"))
+        .await?;
+
+        // The stories of these chapters that the viewer is an author of
+        let authored = match viewer {
+            Some(viewer) => {
+                let stories = records
+                    .iter()
+                    .map(|record| record.story_id.as_str())
+                    .collect::<Vec<_>>();
+
+                sqlx::query_file_as!(
+                    IdRecord,
+                    "queries/story/get_stories-author.sql",
+                    &stories as _,
+                    viewer.as_str()
+                )
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch authored stories"))
+                .await?
+            }
+            None => Vec::new(),
+        };
+
+        let mut chapters = Vec::with_capacity(records.len());
+
+        for record in records {
+            let author = authored.iter().any(|story| story.id == record.story_id);
+
+            let mut chapter = chapter(record)?;
+
+            if !author {
+                if !chapter.published {
+                    continue;
+                }
+
+                chapter.for_readers();
+            }
+
+            chapters.push(chapter);
+        }
+
+        Ok(chapters)
+    }
+
+    #[instrument(skip(self, id), err)]
+    async fn story(&self, id: Id) -> Result<Id, Error> {
+        let record = sqlx::query_file_as!(
+            IdRecord,
+            "queries/story/chapter/get_chapter-story.sql",
+            id.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch chapter story"))
+        .await?;
+
+        match record {
+            Some(record) => Id::try_from(record.id),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, data, editor), err)]
+    async fn create(&self, story: Id, data: New<Chapter>, editor: Id) -> Result<Id, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let id = create_chapter(&mut transaction, story.as_str(), &data, editor.as_str()).await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    #[instrument(skip(self, data, updated, editor), err)]
+    async fn update(
+        &self,
+        id: Id,
+        data: New<Chapter>,
+        updated: OffsetDateTime,
+        editor: Id,
+    ) -> Result<(), Error> {
+        let draft = data.draft.as_ref().map(serde_json::to_string).transpose()?;
+
+        let mut transaction = self.pool.begin().await?;
+
+        let previous = sqlx::query_file_as!(
+            ChapterRecordId,
+            "queries/story/chapter/get_chapter.sql",
+            id.as_str()
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+
+        let previous = match previous {
+            Some(record) => chapter(record)?,
+            None => return Err(NotFound.into()),
+        };
+
+        // Like stories, only matching the last known update time stops
+        // concurrent edits from overwriting each other
+        let record = sqlx::query_file!(
+            "queries/story/chapter/update_chapter.sql",
+            id.as_str(),
+            data.name,
+            data.published,
+            data.scheduled as _,
+            ChapterContent::to_json(&data)?,
+            draft,
+            updated as _
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+
+        let record = match record {
+            Some(record) => record,
+            None => return Err(PreconditionFailed.into()),
+        };
+
+        if data.published {
+            sqlx::query_file!("queries/story/update_story--updated.sql", record.story_id)
+                .execute(&mut transaction)
+                .await?;
+        }
+
+        revise(
+            &mut transaction,
+            &record.story_id,
+            Some(id.as_str()),
+            Some(editor.as_str()),
+            (&ChapterRevision::new(&previous), previous.updated),
+            &ChapterRevision::new(&data),
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, chapters), err)]
+    async fn reorder(&self, story: Id, chapters: Vec<Id>) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let mut current = sqlx::query_file_as!(
+            IdRecord,
+            "queries/story/chapter/get_story-chapter.sql",
+            story.as_str()
+        )
+        .fetch_all(&mut transaction)
+        .await?
+        .into_iter()
+        .map(|record| record.id)
+        .collect::<Vec<_>>();
+
+        let mut order = chapters.iter().map(|id| id.as_str()).collect::<Vec<_>>();
+
+        current.sort_unstable();
+        order.sort_unstable();
+
+        if current != order {
+            return Err(Invalid::field("chapters", "chapters").into());
+        }
+
+        for (position, id) in chapters.iter().enumerate() {
+            sqlx::query_file!(
+                "queries/story/chapter/update_chapter--position.sql",
+                id.as_str(),
+                story.as_str(),
+                i32::try_from(position)?
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        sqlx::query_file!("queries/story/update_story--updated.sql", story.as_str())
+            .execute(&mut transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, now), err)]
+    async fn publish_scheduled(&self, now: OffsetDateTime) -> Result<Vec<Id>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let records = sqlx::query_file_as!(
+            ChapterRecordId,
+            "queries/story/chapter/get_chapters--scheduled.sql",
+            now as _
+        )
+        .fetch_all(&mut transaction)
+        .await?;
+
+        let mut published = Vec::with_capacity(records.len());
+
+        for record in records {
+            let story = record.story_id.clone();
+
+            let mut chapter = chapter(record)?;
+
+            let previous = (ChapterRevision::new(&chapter), chapter.updated);
+
+            chapter.publish();
+
+            sqlx::query_file!(
+                "queries/story/chapter/update_chapter--publish.sql",
+                chapter.id.as_str(),
+                chapter.name,
+                ChapterContent::to_json(&chapter)?
+            )
+            .execute(&mut transaction)
+            .await?;
+
+            sqlx::query_file!("queries/story/update_story--updated.sql", story)
+                .execute(&mut transaction)
+                .await?;
+
+            revise(
+                &mut transaction,
+                &story,
+                Some(chapter.id.as_str()),
+                None,
+                (&previous.0, previous.1),
+                &ChapterRevision::new(&chapter),
+            )
+            .await?;
+
+            published.push(chapter.id);
+        }
+
+        transaction.commit().await?;
+
+        Ok(published)
+    }
+
+    #[instrument(skip(self, limit), err)]
+    async fn recent(&self, limit: i64) -> Result<Vec<(Id, Existing<Chapter>)>, Error> {
+        let records = sqlx::query_file_as!(
+            ChapterRecordId,
+            "queries/story/chapter/all_chapters--published.sql",
+            limit
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch published chapters"))
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| {
+                let story = Id::try_from(record.story_id.as_str())?;
+
+                let mut chapter = chapter(record)?;
+
+                chapter.for_readers();
+
+                Ok((story, chapter))
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self, id, cursor, limit), err)]
+    async fn revisions(
+        &self,
+        id: Id,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Revision>>, Error> {
+        let story = ChapterEntity::story(self, id).await?;
+
+        PostgresBackend::revisions(self, story.as_str(), Some(id.as_str()), cursor, limit).await
+    }
+
+    #[instrument(skip(self, id, revision), err)]
+    async fn revision(&self, id: Id, revision: Id) -> Result<Existing<ChapterRevision>, Error> {
+        let story = ChapterEntity::story(self, id).await?;
+
+        let (mut revision, editor) = PostgresBackend::revision::<ChapterRevision>(
+            self,
+            story.as_str(),
+            Some(id.as_str()),
+            revision,
+        )
+        .await?;
+
+        revision.editor = editor;
+
+        Ok(revision)
+    }
+}
+
+#[async_trait]
+impl OriginEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Origin>, Error> {
+        let record = sqlx::query_file_as!(TagRecordId, "queries/story/get_origin.sql", id.as_str())
+            .fetch_optional(&self.pool)
+            .instrument(trace_span!("fetch origin with id"))
+            .await?;
+
+        match record {
+            Some(record) => tag(record, story_origin),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Origin>>, Error> {
+        let records = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/story/get_origins.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch origins with ids"))
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_origin))
+            .collect()
+    }
+
+    /// Origins are listed alphabetically, the same as tags.
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Origin>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                TagRecordId,
+                "queries/story/all_origins--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch origins with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(TagRecordId, "queries/story/all_origins.sql", limit)
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch origins without cursor"))
+                .await?
+        };
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_origin))
+            .collect()
+    }
+
+    #[instrument(skip(self), err)]
+    async fn find(&self, content: String) -> Result<Option<Existing<Origin>>, Error> {
+        let record = sqlx::query_file_as!(TagRecordId, "queries/story/find_origin.sql", content)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        record.map(|record| tag(record, story_origin)).transpose()
+    }
+}
+
+#[async_trait]
+impl WarningEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Warning>, Error> {
+        let record =
+            sqlx::query_file_as!(TagRecordId, "queries/story/get_warning.sql", id.as_str())
+                .fetch_optional(&self.pool)
+                .instrument(trace_span!("fetch warning with id"))
+                .await?;
+
+        match record {
+            Some(record) => tag(record, story_warning),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Warning>>, Error> {
+        let records = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/story/get_warnings.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch warnings with ids"))
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_warning))
+            .collect()
+    }
+
+    /// Warnings are listed alphabetically, the same as tags.
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Warning>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                TagRecordId,
+                "queries/story/all_warnings--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch warnings with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(TagRecordId, "queries/story/all_warnings.sql", limit)
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch warnings without cursor"))
+                .await?
+        };
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_warning))
+            .collect()
+    }
+
+    #[instrument(skip(self), err)]
+    async fn find(&self, content: String) -> Result<Option<Existing<Warning>>, Error> {
+        let record = sqlx::query_file_as!(TagRecordId, "queries/story/find_warning.sql", content)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        record.map(|record| tag(record, story_warning)).transpose()
+    }
+}
+
+#[async_trait]
+impl PairingEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Pairing>, Error> {
+        match PairingEntity::many(self, vec![id]).await?.pop() {
+            Some(pairing) => Ok(pairing),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Pairing>>, Error> {
+        let records = sqlx::query_file_as!(
+            PairingRecordId,
+            "queries/story/pairing/get_pairings.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch pairings with ids"))
+        .await?;
+
+        self.pairings(records).await
+    }
+
+    /// Pairings are listed oldest first, they have no name of their own to
+    /// sort by.
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Pairing>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                PairingRecordId,
+                "queries/story/pairing/all_pairings--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch pairings with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(
+                PairingRecordId,
+                "queries/story/pairing/all_pairings.sql",
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch pairings without cursor"))
+            .await?
+        };
+
+        self.pairings(records).await
+    }
+
+    #[instrument(skip(self, characters, relationship), err)]
+    async fn find(
+        &self,
+        characters: Vec<Id>,
+        relationship: Relationship,
+    ) -> Result<Option<Existing<Pairing>>, Error> {
+        let hash = pairing_hash(relationship, &characters);
+
+        let record = sqlx::query_file_as!(IdRecord, "queries/story/pairing/find_pairing.sql", hash)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match record {
+            Some(record) => Ok(PairingEntity::many(self, vec![Id::try_from(record.id)?])
+                .await?
+                .pop()),
+            None => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl CharacterEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Character>, Error> {
+        let record = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/story/character/get_character.sql",
+            id.as_str()
+        )
+        .fetch_optional(&self.pool)
+        .instrument(trace_span!("fetch character with id"))
+        .await?;
+
+        match record {
+            Some(record) => tag(record, story_character),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Character>>, Error> {
+        let records = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/story/character/get_characters.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch characters with ids"))
+        .await?;
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_character))
+            .collect()
+    }
+
+    /// Characters are listed alphabetically, the same as tags.
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Character>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                TagRecordId,
+                "queries/story/character/all_characters--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch characters with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(
+                TagRecordId,
+                "queries/story/character/all_characters.sql",
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch characters without cursor"))
+            .await?
+        };
+
+        records
+            .into_iter()
+            .map(|record| tag(record, story_character))
+            .collect()
+    }
+
+    #[instrument(skip(self), err)]
+    async fn find(&self, content: String) -> Result<Option<Existing<Character>>, Error> {
+        let record = sqlx::query_file_as!(
+            TagRecordId,
+            "queries/story/character/find_character.sql",
+            content
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        record
+            .map(|record| tag(record, story_character))
+            .transpose()
+    }
+}
+
+#[async_trait]
+impl StoryEntity for PostgresBackend {
+    #[instrument(skip(self, id), err)]
+    async fn get(&self, id: Id) -> Result<Existing<Story>, Error> {
+        let loaders = StoryLoaders::new(Clone::clone(self));
+
+        let record_id = id.as_str();
+
+        let record = sqlx::query_file_as!(StoryRecord, "queries/story/get_story.sql", id.as_str())
+            .fetch_optional(&self.pool)
+            .instrument(trace_span!("fetch story with id", id = ?record_id))
+            .await?;
+
+        if let Some(record) = record {
+            let mut story = Story::new(
+                record.name,
+                record.summary,
+                record.language,
+                record.rating,
+                record.state,
+            );
+
+            story.original = record.original.map(Id::try_from).transpose()?;
+            story.words = self
+                .words(&[record_id])
+                .await?
+                .remove(record_id)
+                .unwrap_or_default();
+
+            // Every chapter is listed, unpublished ones can only be fetched by
+            // the story's authors
+            let chapters = sqlx::query_file_as!(
+                IdRecord,
+                "queries/story/chapter/get_story-chapter.sql",
+                record_id
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch story chapters", id = ?record_id))
+            .await?;
+
+            story.chapters = Some(Either::Right(
+                chapters
+                    .into_iter()
+                    .map(|record| Id::try_from(record.id))
+                    .collect::<Result<_, _>>()?,
+            ));
+
+            async {
+                #[rustfmt::skip]
+                id_loader![
+                    [&self.pool, loaders.user, record_id, story.authors, "queries/story/get_story-user.sql", record_id, "author"],
+                    [&self.pool, loaders.user, record_id, story.commissioners, "queries/story/get_story-user.sql", record_id, "commissioner"],
+                    [&self.pool, loaders.user, record_id, story.dedicatees, "queries/story/get_story-user.sql", record_id, "dedicated"],
+                    [&self.pool, loaders.user, record_id, story.translators, "queries/story/get_story-user.sql", record_id, "translator"],
+                    [&self.pool, loaders.tag, record_id, story.tags, "queries/story/get_story-tag.sql", record_id],
+                ];
+
+                #[rustfmt::skip]
+                id_level_loader![
+                    [&self.pool, loaders.origin, record_id, story.origins, "queries/story/get_story-origin.sql", record_id],
+                    [&self.pool, loaders.warning, record_id, story.warnings, "queries/story/get_story-warning.sql", record_id],
+                    [&self.pool, loaders.pairing, record_id, story.pairings, "queries/story/pairing/get_story-pairing.sql", record_id],
+                    [&self.pool, loaders.character, record_id, story.characters, "queries/story/character/get_story-character.sql", record_id],
+                ];
+
+                Ok::<(), Error>(())
+            }.instrument(trace_span!("story load all entities", id = ?record_id)).await?;
+
+            story.series = self.story_series(&loaders, record_id).await?;
+
+            Ok(Existing::new(id, story, record.created, record.updated))
+        } else {
+            Err(NotFound.into())
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Story>>, Error> {
+        let records = sqlx::query_file_as!(
+            StoryRecordId,
+            "queries/story/get_stories.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch stories with ids"))
+        .await?;
+
+        let mut stories = self.stories(records).await?;
+
+        // Like a single story, every chapter is listed
+        let chapters = sqlx::query_file!(
+            "queries/story/chapter/get_stories-chapter.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch stories chapters"))
+        .await?;
+
+        for story in &mut stories {
+            story.chapters = Some(Either::Right(
+                chapters
+                    .iter()
+                    .filter(|chapter| chapter.story_id == story.id.as_str())
+                    .map(|chapter| Id::try_from(chapter.id.as_str()))
+                    .collect::<Result<_, _>>()?,
+            ));
+        }
 
         Ok(stories)
     }
 
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Story>>, Error> {
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                StoryRecordId,
+                "queries/story/all_stories--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch stories with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(StoryRecordId, "queries/story/all_stories.sql", limit)
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch stories without cursor"))
+                .await?
+        };
+
+        self.stories(records).await
+    }
+
+    #[instrument(skip(self, cursor, limit), err)]
+    async fn filter(
+        &self,
+        filter: StoryFilter,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Story>>, Error> {
+        // The enum is declared from explicit to general, so a rating is
+        // allowed if it's at or after the limit
+        let records = sqlx::query_file_as!(
+            StoryRecordId,
+            "queries/story/filter_stories.sql",
+            filter.author.as_ref().map(Id::as_str),
+            filter.origin.as_ref().map(Id::as_str),
+            filter.warning.as_ref().map(Id::as_str),
+            filter.tag.as_ref().map(Id::as_str),
+            filter.pairing.as_ref().map(Id::as_str),
+            filter.character.as_ref().map(Id::as_str),
+            filter.rating as _,
+            filter.state as _,
+            filter.language,
+            filter.max_rating as _,
+            &filter
+                .without_warnings
+                .iter()
+                .map(Id::as_str)
+                .collect::<Vec<_>>() as _,
+            &filter
+                .without_tags
+                .iter()
+                .map(Id::as_str)
+                .collect::<Vec<_>>() as _,
+            cursor.as_ref().map(Id::as_str),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch filtered stories"))
+        .await?;
+
+        self.stories(records).await
+    }
+
+    #[instrument(skip(self, id), err)]
+    async fn translations(&self, id: Id) -> Result<Vec<Existing<Story>>, Error> {
+        let records = sqlx::query_file_as!(
+            StoryRecordId,
+            "queries/story/translations_story.sql",
+            id.as_str()
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch story translations"))
+        .await?;
+
+        self.stories(records).await
+    }
+
     #[instrument(skip(self, data), err)]
     async fn create(&self, data: New<Story>) -> Result<Id, Error> {
-        todo!()
+        let mut transaction = self.pool.begin().await?;
+
+        let id = create_story(&mut transaction, &data).await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    #[instrument(skip(self, data, chapters, requests, editor), err)]
+    async fn import(
+        &self,
+        data: New<Story>,
+        chapters: Vec<New<Chapter>>,
+        requests: Vec<TagRequest>,
+        editor: Id,
+    ) -> Result<Id, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let id = create_story(&mut transaction, &data).await?;
+
+        for chapter in &chapters {
+            create_chapter(&mut transaction, id.as_str(), chapter, editor.as_str()).await?;
+        }
+
+        for request in requests {
+            sqlx::query_file!(
+                "queries/story/insert_tag-request.sql",
+                id.as_str(),
+                request.kind as _,
+                request.content
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    #[instrument(skip(self, data, updated, editor), err)]
+    async fn update(
+        &self,
+        id: Id,
+        data: New<Story>,
+        updated: OffsetDateTime,
+        editor: Id,
+    ) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let previous = self.story_revision(&mut transaction, &id).await?;
+
+        // Only matching the last known update time stops concurrent edits from
+        // silently overwriting each other
+        let result = sqlx::query_file!(
+            "queries/story/update_story.sql",
+            id.as_str(),
+            data.name,
+            data.summary,
+            data.rating as _,
+            data.state as _,
+            data.language,
+            data.original.as_ref().map(Id::as_str),
+            updated as _
+        )
+        .execute(&mut transaction)
+        .await
+        .map_err(constraint)?;
+
+        if result.rows_affected() == 0 {
+            return Err(PreconditionFailed.into());
+        }
+
+        relations(&mut transaction, id.as_str(), &data).await?;
+
+        revise(
+            &mut transaction,
+            id.as_str(),
+            None,
+            Some(editor.as_str()),
+            (&previous.0, previous.1),
+            &StoryRevision::new(&data),
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, updated), err)]
+    async fn unwarned(&self, updated: OffsetDateTime) -> Result<Vec<Existing<Story>>, Error> {
+        let records = sqlx::query_file_as!(
+            StoryRecordId,
+            "queries/story/all_stories--unwarned.sql",
+            updated as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch unwarned stale stories"))
+        .await?;
+
+        self.stories(records).await
+    }
+
+    #[instrument(skip(self, updated, warned), err)]
+    async fn abandonable(
+        &self,
+        updated: OffsetDateTime,
+        warned: OffsetDateTime,
+    ) -> Result<Vec<Existing<Story>>, Error> {
+        let records = sqlx::query_file_as!(
+            StoryRecordId,
+            "queries/story/all_stories--abandonable.sql",
+            updated as _,
+            warned as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch warned stale stories"))
+        .await?;
+
+        self.stories(records).await
+    }
+
+    #[instrument(skip(self, updated), err)]
+    async fn abandon(&self, id: Id, updated: OffsetDateTime) -> Result<(), Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let (previous, previous_updated) = self.story_revision(&mut transaction, &id).await?;
+
+        let result = sqlx::query_file!(
+            "queries/story/update_story--abandon.sql",
+            id.as_str(),
+            updated as _
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(PreconditionFailed.into());
+        }
+
+        let current = StoryRevision {
+            state: State::Abandoned,
+            ..previous.clone()
+        };
+
+        revise(
+            &mut transaction,
+            id.as_str(),
+            None,
+            None,
+            (&previous, previous_updated),
+            &current,
+        )
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, id, cursor, limit), err)]
+    async fn revisions(
+        &self,
+        id: Id,
+        cursor: Option<Id>,
+        limit: i64,
+    ) -> Result<Vec<Existing<Revision>>, Error> {
+        PostgresBackend::revisions(self, id.as_str(), None, cursor, limit).await
+    }
+
+    #[instrument(skip(self, id, revision), err)]
+    async fn revision(&self, id: Id, revision: Id) -> Result<Existing<StoryRevision>, Error> {
+        let (mut revision, editor) =
+            PostgresBackend::revision::<StoryRevision>(self, id.as_str(), None, revision).await?;
+
+        revision.editor = editor;
+
+        Ok(revision)
     }
 }
 
@@ -334,11 +2554,504 @@ impl StoryEntity for PostgresBackend {
 impl SeriesEntity for PostgresBackend {
     #[instrument(skip(self, id), err)]
     async fn get(&self, id: Id) -> Result<Existing<Series>, Error> {
-        todo!()
+        match SeriesEntity::many(self, vec![id]).await?.pop() {
+            Some(series) => Ok(series),
+            None => Err(NotFound.into()),
+        }
+    }
+
+    #[instrument(skip(self, ids), err)]
+    async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Series>>, Error> {
+        let records = sqlx::query_file_as!(
+            SeriesRecordId,
+            "queries/story/series/get_series.sql",
+            &as_strs(&ids) as _
+        )
+        .fetch_all(&self.pool)
+        .instrument(trace_span!("fetch series with ids"))
+        .await?;
+
+        self.series(records).await
     }
 
     #[instrument(skip(self, cursor, limit), err)]
     async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Series>>, Error> {
-        todo!()
+        let records = if let Some(cursor) = cursor {
+            sqlx::query_file_as!(
+                SeriesRecordId,
+                "queries/story/series/all_series--cursor.sql",
+                cursor.as_str(),
+                limit
+            )
+            .fetch_all(&self.pool)
+            .instrument(trace_span!("fetch series with cursor"))
+            .await?
+        } else {
+            sqlx::query_file_as!(SeriesRecordId, "queries/story/series/all_series.sql", limit)
+                .fetch_all(&self.pool)
+                .instrument(trace_span!("fetch series without cursor"))
+                .await?
+        };
+
+        self.series(records).await
+    }
+}
+
+#[async_trait]
+impl LimiterStore for PostgresBackend {
+    #[instrument(skip(self, window), err)]
+    async fn record(&self, key: &str, window: Duration) -> Result<Vec<OffsetDateTime>, Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        // Without this two concurrent attempts can both miss each other's
+        // insert, and neither of them go over the limit
+        sqlx::query_file!("queries/core/limiter/lock_attempts.sql", key)
+            .fetch_one(&mut transaction)
+            .await?;
+
+        sqlx::query_file!(
+            "queries/core/limiter/remove_attempts--expired.sql",
+            key,
+            window.as_seconds_f64()
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        sqlx::query_file!("queries/core/limiter/insert_attempt.sql", key)
+            .execute(&mut transaction)
+            .await?;
+
+        let records =
+            sqlx::query_file_as!(AttemptRecord, "queries/core/limiter/get_attempts.sql", key)
+                .fetch_all(&mut transaction)
+                .await?;
+
+        transaction.commit().await?;
+
+        Ok(records.into_iter().map(|record| record.attempted).collect())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn clear(&self, key: &str) -> Result<(), Error> {
+        sqlx::query_file!("queries/core/limiter/remove_attempts.sql", key)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self, duration), err)]
+    async fn lock(&self, key: &str, duration: Duration) -> Result<(), Error> {
+        sqlx::query_file!(
+            "queries/core/limiter/upsert_lock.sql",
+            key,
+            duration.as_seconds_f64()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), err)]
+    async fn locked(&self, key: &str) -> Result<Option<OffsetDateTime>, Error> {
+        let record = sqlx::query_file_as!(LockRecord, "queries/core/limiter/get_lock.sql", key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(record.map(|record| record.until))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Connects to the database the queries are checked against.
+    ///
+    /// # Note
+    ///
+    /// Like building this crate, these tests need `DATABASE_URL` to point to
+    /// a database with every migration applied.
+    async fn backend() -> PostgresBackend {
+        let url = std::env::var("DATABASE_URL").expect("`DATABASE_URL` has to be set");
+
+        PostgresBackend::new(Uri::parse(url).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn new_id() -> Id {
+        nanoid::new_id().unwrap()
+    }
+
+    /// Adds a user with nothing but a name.
+    async fn user(backend: &PostgresBackend) -> Id {
+        let id = new_id();
+
+        sqlx::query(
+            "INSERT INTO core_user (id, email, name, biography, hash, settings, created, updated)
+             VALUES ($1, $1 || '@example.com', 'User', '', 'hash', '{}', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(id.as_str())
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        id
+    }
+
+    /// Adds a bare story in the `language` by the `author`.
+    async fn story(backend: &PostgresBackend, author: &Id, language: &str) -> Id {
+        let id = new_id();
+
+        sqlx::query(
+            "INSERT INTO story_story (id, name, summary, language, rating, state, created, updated)
+             VALUES ($1, 'Story', '', $2, 'general', 'completed', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(id.as_str())
+        .bind(language)
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO story_story_user (story_id, user_id, relationship, created, updated)
+             VALUES ($1, $2, 'author', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(id.as_str())
+        .bind(author.as_str())
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        id
+    }
+
+    async fn remove(backend: &PostgresBackend, users: &[Id], stories: &[Id]) {
+        sqlx::query("DELETE FROM story_story_user WHERE story_id = ANY($1)")
+            .bind(as_strs(stories))
+            .execute(&backend.pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM story_story WHERE id = ANY($1)")
+            .bind(as_strs(stories))
+            .execute(&backend.pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM core_user WHERE id = ANY($1)")
+            .bind(as_strs(users))
+            .execute(&backend.pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_filter_language() {
+        let backend = backend().await;
+        let author = user(&backend).await;
+
+        let english = story(&backend, &author, "en").await;
+        let british = story(&backend, &author, "en-GB").await;
+        let other = story(&backend, &author, "eng").await;
+
+        let filter = |language: &str| StoryFilter {
+            author: Some(author),
+            language: Some(language.to_string()),
+            ..StoryFilter::default()
+        };
+
+        let found = |stories: Vec<Existing<Story>>| {
+            let mut ids = stories
+                .into_iter()
+                .map(|story| story.id.as_str().to_string())
+                .collect::<Vec<_>>();
+
+            ids.sort();
+
+            ids
+        };
+
+        let mut expected = vec![english.as_str().to_string(), british.as_str().to_string()];
+        expected.sort();
+
+        // A language matches its regional variants, but not a language that
+        // only starts with the same letters
+        let en = StoryEntity::filter(&backend, filter("en"), None, 10).await;
+        let gb = StoryEntity::filter(&backend, filter("EN-gb"), None, 10).await;
+
+        remove(&backend, &[author], &[english, british, other]).await;
+
+        assert_eq!(found(en.unwrap()), expected);
+        assert_eq!(found(gb.unwrap()), [british.as_str()]);
+    }
+
+    /// Adds a character named `content`.
+    async fn character(backend: &PostgresBackend, content: &str) -> Id {
+        let id = new_id();
+
+        sqlx::query(
+            "INSERT INTO story_character (id, content, description, created, updated)
+             VALUES ($1, $2, '', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(id.as_str())
+        .bind(content)
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        id
+    }
+
+    #[tokio::test]
+    async fn test_pairing() {
+        let backend = backend().await;
+        let author = user(&backend).await;
+        let story = story(&backend, &author, "en").await;
+
+        // Unique names, so finding them by name doesn't match another test's
+        let first = character(&backend, new_id().as_str()).await;
+        let second = character(&backend, new_id().as_str()).await;
+
+        let pairing = new_id();
+
+        sqlx::query(
+            "INSERT INTO story_pairing (id, hash, relationship, created, updated)
+             VALUES ($1, $2, 'romantic', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(pairing.as_str())
+        .bind(pairing_hash(Relationship::Romantic, &[first, second]))
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO story_pairing_character (pairing_id, character_id, position)
+             VALUES ($1, $2, 0), ($1, $3, 1)",
+        )
+        .bind(pairing.as_str())
+        .bind(second.as_str())
+        .bind(first.as_str())
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "INSERT INTO story_story_pairing (story_id, pairing_id, level, created, updated)
+             VALUES ($1, $2, 'minor', timezone('utc', now()), timezone('utc', now()))",
+        )
+        .bind(story.as_str())
+        .bind(pairing.as_str())
+        .execute(&backend.pool)
+        .await
+        .unwrap();
+
+        // The characters can be given in any order, but not another relationship
+        let found =
+            PairingEntity::find(&backend, vec![first, second], Relationship::Romantic).await;
+        let friends =
+            PairingEntity::find(&backend, vec![first, second], Relationship::Friends).await;
+
+        let loaded = StoryEntity::get(&backend, story).await;
+        let filtered = StoryEntity::filter(
+            &backend,
+            StoryFilter {
+                pairing: Some(pairing),
+                ..StoryFilter::default()
+            },
+            None,
+            10,
+        )
+        .await;
+
+        sqlx::query("DELETE FROM story_pairing WHERE id = $1")
+            .bind(pairing.as_str())
+            .execute(&backend.pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM story_character WHERE id = ANY($1)")
+            .bind(as_strs(&[first, second]))
+            .execute(&backend.pool)
+            .await
+            .unwrap();
+
+        remove(&backend, &[author], &[story]).await;
+
+        let found = found.unwrap().unwrap();
+
+        assert_eq!(found.id, pairing);
+        assert_eq!(
+            found
+                .characters
+                .iter()
+                .map(|character| character.id)
+                .collect::<Vec<_>>(),
+            [second, first]
+        );
+        assert!(friends.unwrap().is_none());
+
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.pairings.len(), 1);
+        assert_eq!(loaded.pairings[0].level, TagLevel::Minor);
+
+        let filtered = filtered.unwrap();
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, story);
+    }
+
+    #[tokio::test]
+    async fn test_lock_job() {
+        let backend = backend().await;
+
+        // Unique, so other tests running at the same time can't hold it
+        let name = format!("test-{}", new_id().as_str());
+
+        let lock = backend.lock_job(&name).await.unwrap();
+
+        assert!(lock.is_some());
+        assert!(backend.lock_job(&name).await.unwrap().is_none());
+
+        drop(lock);
+
+        // The server releases it once it notices the connection closed
+        let mut released = false;
+
+        for _ in 0..250 {
+            if backend.lock_job(&name).await.unwrap().is_some() {
+                released = true;
+
+                break;
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        assert!(released);
+    }
+
+    #[tokio::test]
+    async fn test_abandon() {
+        use stry_common::{abandon, backend::ArcBackend, settings::AUTO_ABANDON};
+
+        let backend = backend().await;
+        let author = user(&backend).await;
+        let stale = story(&backend, &author, "en").await;
+        let locked = story(&backend, &author, "en").await;
+
+        // Settings are cached, so each change is read with a new one
+        let settings = |days: &str| {
+            let backend = backend.clone();
+            let days = days.to_string();
+
+            async move {
+                SettingEntity::set(&backend, AUTO_ABANDON.to_string(), days)
+                    .await
+                    .unwrap();
+
+                stry_common::settings::Settings::new(ArcBackend::new(backend))
+            }
+        };
+
+        // Runs a check, waiting for the lock of a previous one to be released
+        let check = |settings: stry_common::settings::Settings, now: OffsetDateTime| async move {
+            for _ in 0..250 {
+                if abandon::check(&settings, now).await.unwrap() {
+                    return;
+                }
+
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+
+            panic!("the abandon lock was never released");
+        };
+
+        let warnings = |story: Id| {
+            let pool = backend.pool.clone();
+
+            async move {
+                sqlx::query_scalar::<_, i64>(
+                    "SELECT count(*) FROM core_notification WHERE story_id = $1",
+                )
+                .bind(story.as_str())
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+            }
+        };
+
+        let state = |story: Id| {
+            let backend = backend.clone();
+
+            async move { StoryEntity::get(&backend, story).await.unwrap().state }
+        };
+
+        // Last updated a month ago
+        let age = |story: Id| {
+            let pool = backend.pool.clone();
+
+            async move {
+                sqlx::query(
+                    "UPDATE story_story
+                     SET state = 'in-progress', updated = timezone('utc', now()) - INTERVAL '30 days'
+                     WHERE id = $1",
+                )
+                .bind(story.as_str())
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        };
+
+        age(stale).await;
+
+        let now = OffsetDateTime::now_utc();
+
+        // Nothing happens while it's turned off
+        check(settings("0").await, now).await;
+
+        let off = (warnings(stale).await, state(stale).await);
+
+        // Warned once, with a week's notice as that's shorter than half of
+        // the period, however often it's checked
+        check(settings("20").await, now).await;
+        check(settings("20").await, now).await;
+
+        let warned = (warnings(stale).await, state(stale).await);
+
+        check(settings("20").await, now + Duration::days(6)).await;
+
+        let noticed = state(stale).await;
+
+        check(settings("20").await, now + Duration::days(8)).await;
+
+        let abandoned = (warnings(stale).await, state(stale).await);
+
+        // Another server is already checking
+        age(locked).await;
+
+        let lock = backend.lock_job("abandon").await.unwrap();
+        let skipped = abandon::check(&settings("20").await, now).await;
+        let skipped_warnings = warnings(locked).await;
+
+        drop(lock);
+
+        SettingEntity::set(&backend, AUTO_ABANDON.to_string(), String::from("0"))
+            .await
+            .unwrap();
+
+        remove(&backend, &[author], &[stale, locked]).await;
+
+        assert_eq!(off, (0, State::InProgress));
+        assert_eq!(warned, (1, State::InProgress));
+        assert_eq!(noticed, State::InProgress);
+        assert_eq!(abandoned, (1, State::Abandoned));
+
+        assert!(!skipped.unwrap());
+        assert_eq!(skipped_warnings, 0);
     }
 }
@@ -0,0 +1,133 @@
+//! Fixtures for the services' tests that need a database.
+//!
+//! # Note
+//!
+//! Like the backend's tests, these need `DATABASE_URL` to point to a database
+//! with every migration applied.
+
+use stry_common::{
+    backend::{ArcBackend, ChapterEntity, StoryEntity, UserEntity},
+    limiter::Duration,
+    models::{
+        core::{Account, User},
+        story::{Chapter, ChapterDraft, Rating, State, Story},
+        Existing, Id, New,
+    },
+    prelude::*,
+    uri::Uri,
+    utils::nanoid,
+};
+
+use sqlx::{Pool, Postgres};
+
+use crate::PostgresBackend;
+
+/// A story with a published chapter, which has a draft and a schedule,
+/// followed by an unpublished one.
+pub struct Drafts {
+    pub data: ArcBackend,
+    pub author: Existing<User>,
+    pub story: Id,
+    pub published: Id,
+    pub unpublished: Id,
+    pool: Pool<Postgres>,
+}
+
+impl Drafts {
+    pub async fn new() -> Self {
+        let url = std::env::var("DATABASE_URL").expect("`DATABASE_URL` has to be set");
+        let backend = PostgresBackend::new(Uri::parse(url).unwrap())
+            .await
+            .unwrap();
+
+        let pool = backend.pool.clone();
+        let data = ArcBackend::new(backend);
+
+        let name = nanoid::new_id().unwrap();
+        let account = Account {
+            name: String::from("Author"),
+            email: Some(format!("{}@example.com", name.as_str())),
+            hash: Some(b"hash".to_vec()),
+            biography: None,
+        };
+
+        let author = UserEntity::create(&data, New::from(User::new(account)))
+            .await
+            .unwrap();
+        let author = UserEntity::get(&data, author).await.unwrap();
+
+        let mut story = Story::new(
+            String::from("Drafts"),
+            String::new(),
+            String::from("en"),
+            Rating::General,
+            State::InProgress,
+        );
+
+        story.authors.push(author.clone());
+
+        let story = StoryEntity::create(&data, New::from(story)).await.unwrap();
+
+        let mut published = chapter("Published", true);
+
+        published.scheduled = Some(OffsetDateTime::now_utc() + Duration::days(1));
+        published.draft = Some(ChapterDraft::from(chapter("Draft", false)));
+
+        let published = ChapterEntity::create(&data, story, New::from(published), author.id)
+            .await
+            .unwrap();
+        let unpublished = ChapterEntity::create(
+            &data,
+            story,
+            New::from(chapter("Unpublished", false)),
+            author.id,
+        )
+        .await
+        .unwrap();
+
+        Self {
+            data,
+            author,
+            story,
+            published,
+            unpublished,
+            pool,
+        }
+    }
+
+    /// Deletes the story, its chapters and its author.
+    pub async fn remove(self) {
+        sqlx::query("DELETE FROM story_story_user WHERE story_id = $1")
+            .bind(self.story.as_str())
+            .execute(&self.pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM story_story WHERE id = $1")
+            .bind(self.story.as_str())
+            .execute(&self.pool)
+            .await
+            .unwrap();
+
+        sqlx::query("DELETE FROM core_user WHERE id = $1")
+            .bind(self.author.id.as_str())
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+}
+
+fn chapter(name: &str, published: bool) -> Chapter {
+    Chapter {
+        name: Some(name.to_string()),
+        published,
+        scheduled: None,
+        published_at: None,
+        prefix: Vec::new(),
+        main: Vec::new(),
+        suffix: Vec::new(),
+        comments: Vec::new(),
+        words: 0,
+        draft: None,
+    }
+}
@@ -0,0 +1,99 @@
+//! Marks in progress stories as [`Abandoned`] once they've gone without an
+//! update for the number of days in the [`AUTO_ABANDON`] setting, warning
+//! their authors beforehand.
+//!
+//! Warnings and abandoned stories are logged as audit events, with the
+//! `audit` target. Only one server checks stories at a time, any others
+//! skip the check while it's running.
+//!
+//! [`Abandoned`]: crate::models::story::State::Abandoned
+//! [`AUTO_ABANDON`]: crate::settings::AUTO_ABANDON
+
+use std::convert::Infallible;
+
+use evermore::Worker;
+use time::Duration;
+
+use crate::{
+    backend::{Backend as _, NotificationEntity, StoryEntity},
+    error::PreconditionFailed,
+    jobs,
+    models::{
+        core::{Notification, NotificationStoryAbandoning},
+        New,
+    },
+    prelude::*,
+    settings::Settings,
+};
+
+/// How long before a story is abandoned its authors are warned, or half of
+/// the period if that's shorter.
+pub const NOTICE: Duration = Duration::days(7);
+
+/// How often stories are checked.
+pub const INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Checks stories every [`INTERVAL`], see [`jobs::run`].
+pub async fn task(worker: Worker<Settings>) -> Result<(), Infallible> {
+    jobs::repeat(&worker, "abandon", INTERVAL, |settings| async move {
+        check(&settings, OffsetDateTime::now_utc())
+            .await
+            .map(|_| ())
+    })
+    .await
+}
+
+/// Warns the authors of stories that will soon be abandoned, and abandons
+/// those whose authors were warned long enough ago.
+///
+/// Returns `false` if another server was already checking them.
+pub async fn check(settings: &Settings, now: OffsetDateTime) -> Result<bool, Error> {
+    let period = match settings.auto_abandon().await? {
+        Some(period) => period,
+        None => return Ok(true),
+    };
+
+    let data = settings.data();
+
+    // Otherwise every server would warn the same authors
+    let _lock = match data.lock_job("abandon").await? {
+        Some(lock) => lock,
+        None => return Ok(false),
+    };
+
+    let notice = NOTICE.min(period / 2);
+
+    for story in StoryEntity::unwarned(data, now - (period - notice)).await? {
+        // Stories that were already overdue (ie the period was shortened) are
+        // still given the full notice
+        let at = (story.updated + period).max(now + notice);
+
+        for author in &story.authors {
+            let notification = Notification::StoryAbandoning(NotificationStoryAbandoning {
+                story: story.id,
+                at,
+            });
+
+            NotificationEntity::create(data, author.id, New::from(notification)).await?;
+        }
+
+        info!(
+            target: "audit",
+            story = story.id.as_str(),
+            authors = ?story.authors.iter().map(|author| author.id).collect::<Vec<_>>(),
+            %at,
+            "warned authors that their story will be abandoned"
+        );
+    }
+
+    for story in StoryEntity::abandonable(data, now - period, now - notice).await? {
+        match StoryEntity::abandon(data, story.id, story.updated).await {
+            Ok(()) => info!(target: "audit", story = story.id.as_str(), "abandoned story"),
+            // Updated since it was fetched, so it isn't stale anymore
+            Err(err) if err.is::<PreconditionFailed>() => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(true)
+}
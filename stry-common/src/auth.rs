@@ -0,0 +1,195 @@
+//! Account registration and sign ins, shared by every service so they all
+//! apply the same rate limits and issue the same session tokens.
+
+use std::net::IpAddr;
+
+use biscuit::{
+    jwa::SignatureAlgorithm,
+    jws::{RegisteredHeader, Secret},
+    ClaimsSet, RegisteredClaims, ValidationOptions, JWT,
+};
+
+use crate::{
+    backend::{ArcBackend, UserEntity},
+    error::{Invalid, NotFound, RegistrationClosed, Unauthenticated},
+    limiter::{Duration, Limiter, Lockout, Rule},
+    models::{
+        core::{Account, User, UserRegisterForm, UserSessionForm},
+        Existing, Id, New,
+    },
+    prelude::*,
+    settings::Settings,
+};
+
+/// Registrations allowed from a single ip.
+pub const REGISTER_IP: Rule = Rule::new(5, Duration::hours(1));
+
+/// Sign in attempts allowed from a single ip, across every account.
+pub const SESSION_IP: Rule = Rule::new(30, Duration::minutes(15));
+
+/// Sign in attempts allowed for a single account, across every ip.
+pub const SESSION_ACCOUNT: Rule = Rule::new(10, Duration::minutes(15));
+
+/// Failed sign ins allowed for a single account before it is locked.
+pub const SESSION_LOCKOUT: Lockout =
+    Lockout::new(Rule::new(5, Duration::minutes(15)), Duration::minutes(15));
+
+/// How long a session token is valid for.
+pub const SESSION_EXPIRY: Duration = Duration::days(7);
+
+/// The hash of a password no one uses, hashed like an account's would be. It's
+/// checked when there's no account with an email so that those sign ins take
+/// as long as any other.
+const DUMMY_HASH: &str = "$argon2id$v=19$m=65536,t=2,p=1$TA+FdzZ7/aS95zzC7jov2g$7IU+ciS9jPdipewtRdYlmNA3WAueEka1BOkExcTTbKk";
+
+/// Validates the form and creates a new user from it, errors with
+/// [`RegistrationClosed`] if registrations are closed.
+pub async fn register(
+    data: &ArcBackend,
+    settings: &Settings,
+    limiter: &Limiter,
+    ip: IpAddr,
+    form: UserRegisterForm,
+) -> Result<Id, Error> {
+    if !settings.registration_open().await? {
+        return Err(RegistrationClosed.into());
+    }
+
+    form.validate().map_err(Invalid::from)?;
+
+    limiter
+        .check(&format!("register:ip:{}", ip), REGISTER_IP)
+        .await?;
+
+    let account =
+        tokio::task::spawn_blocking(move || Account::new(form.username, form.email, form.password))
+            .await
+            .map_err(|err| err!(err))??;
+
+    UserEntity::create(data, New::from(User::new(account))).await
+}
+
+/// Validates the form and checks the user's password.
+///
+/// Returns `None` if the email or password is incorrect, which counts
+/// towards locking the account.
+pub async fn sign_in(
+    data: &ArcBackend,
+    limiter: &Limiter,
+    ip: IpAddr,
+    form: UserSessionForm,
+) -> Result<Option<Existing<User>>, Error> {
+    form.validate().map_err(Invalid::from)?;
+
+    let account_key = format!("session:account:{}", form.email.to_lowercase());
+
+    limiter
+        .check(&format!("session:ip:{}", ip), SESSION_IP)
+        .await?;
+    limiter.ensure_unlocked(&account_key).await?;
+    limiter.check(&account_key, SESSION_ACCOUNT).await?;
+
+    let user = match UserEntity::get_by_email(data, form.email).await {
+        Ok(user) => Some(user),
+        Err(err) if err.is::<NotFound>() => None,
+        Err(err) => return Err(err),
+    };
+
+    let password = form.password;
+    let user = tokio::task::spawn_blocking(move || match user {
+        Some(user) => Ok(user.account.verify(&password)?.then_some(user)),
+        None => {
+            let dummy = Account {
+                name: String::new(),
+                email: None,
+                hash: Some(DUMMY_HASH.as_bytes().to_vec()),
+                biography: None,
+            };
+
+            dummy.verify(&password)?;
+
+            Ok::<_, Error>(None)
+        }
+    })
+    .await
+    .map_err(|err| err!(err))??;
+
+    match user {
+        Some(user) => {
+            limiter.success(&account_key).await?;
+
+            Ok(Some(user))
+        }
+        None => {
+            limiter.failure(&account_key, SESSION_LOCKOUT).await?;
+
+            Ok(None)
+        }
+    }
+}
+
+/// Creates a signed session token with the user's id as its subject.
+pub fn issue_token(secret: &str, user: &Existing<User>) -> Result<String, Error> {
+    let now = OffsetDateTime::now_utc();
+
+    let claims = ClaimsSet::<biscuit::Empty> {
+        registered: RegisteredClaims {
+            subject: Some(user.id.as_str().to_string()),
+            issued_at: Some(now.unix_timestamp().into()),
+            expiry: Some((now + SESSION_EXPIRY).unix_timestamp().into()),
+            ..Default::default()
+        },
+        private: biscuit::Empty {},
+    };
+
+    let header = RegisteredHeader {
+        algorithm: SignatureAlgorithm::HS256,
+        ..Default::default()
+    };
+
+    let token =
+        JWT::new_decoded(header.into(), claims).into_encoded(&Secret::bytes_from_str(secret))?;
+
+    Ok(token.unwrap_encoded().to_string())
+}
+
+/// Checks a session token's signature and expiry, returning the id of the
+/// user it was issued to.
+///
+/// Fails with [`Unauthenticated`] for any token that isn't valid.
+pub fn verify_token(secret: &str, token: &str) -> Result<Id, Error> {
+    let token = JWT::<biscuit::Empty, biscuit::Empty>::new_encoded(token)
+        .into_decoded(&Secret::bytes_from_str(secret), SignatureAlgorithm::HS256)
+        .map_err(|_| Unauthenticated)?;
+
+    token
+        .validate(ValidationOptions::default())
+        .map_err(|_| Unauthenticated)?;
+
+    let subject = token
+        .payload()
+        .ok()
+        .and_then(|claims| claims.registered.subject.as_deref())
+        .ok_or(Unauthenticated)?;
+
+    Ok(Id::try_from(subject).map_err(|_| Unauthenticated)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dummy_hash() {
+        // Only a well formed hash is actually checked, others fail straight away
+        let dummy = Account {
+            name: String::new(),
+            email: None,
+            hash: Some(DUMMY_HASH.as_bytes().to_vec()),
+            biography: None,
+        };
+
+        assert!(dummy.verify("stry-dummy-password").unwrap());
+        assert!(!dummy.verify("password").unwrap());
+    }
+}
@@ -3,8 +3,11 @@ use std::sync::Arc;
 use crate::{
     models::{
         blog::Post,
-        core::{Comment, Part, Tag, User},
-        story::{Chapter, Character, Origin, Pairing, Series, Story, Warning},
+        core::{Appearance, Comment, Content, Media, Notification, Notifications, Part, Tag, User},
+        story::{
+            Chapter, ChapterRevision, Character, Origin, Pairing, Relationship, Revision, Series,
+            Story, StoryFilter, StoryRevision, TagRequest, Warning,
+        },
         wiki::Page,
         Existing, Id, New,
     },
@@ -18,7 +21,10 @@ use crate::{
 ///
 ///   - Core Types
 ///     - [`Comment`]
+///     - [`Media`]
+///     - [`Notification`]
 ///     - [`Part`]
+///     - Settings
 ///     - [`Tag`]
 ///     - [`User`]
 ///   - Blog Types
@@ -39,8 +45,13 @@ pub trait Backend:
     // Core
     UserEntity
     + CommentEntity
+    + MediaEntity
+    + NotificationEntity
     + PartEntity
+    + SettingEntity
     + TagEntity
+    // Blog
+    + PostEntity
     // Story
     + ChapterEntity
     + OriginEntity
@@ -52,6 +63,30 @@ pub trait Backend:
 {
     /// Run any missing migration on the database backend.
     async fn migrate(&self) -> Result<(), Error>;
+
+    /// Takes the job's lock, shared by every server using the database,
+    /// `None` if another server is holding it.
+    ///
+    /// The lock is held until the returned [`JobLock`] is dropped.
+    async fn lock_job(&self, name: &str) -> Result<Option<JobLock>, Error>;
+}
+
+/// Held while a job runs so that only one server runs it at a time, see
+/// [`Backend::lock_job`].
+pub struct JobLock {
+    _inner: Box<dyn Send + 'static>,
+}
+
+impl JobLock {
+    /// Wraps whatever keeps the lock taken, it's released when it's dropped.
+    pub fn new<L>(inner: L) -> Self
+    where
+        L: Send + 'static,
+    {
+        Self {
+            _inner: Box::new(inner),
+        }
+    }
 }
 
 pub struct ArcBackend {
@@ -77,6 +112,17 @@ impl Clone for ArcBackend {
     }
 }
 
+#[crate::prelude::async_trait]
+impl Backend for ArcBackend {
+    async fn migrate(&self) -> Result<(), Error> {
+        self.inner.migrate().await
+    }
+
+    async fn lock_job(&self, name: &str) -> Result<Option<JobLock>, Error> {
+        self.inner.lock_job(name).await
+    }
+}
+
 impl std::ops::Deref for ArcBackend {
     type Target = Arc<dyn Backend + Send + Sync + 'static>;
 
@@ -126,7 +172,17 @@ macro_rules! def {
 def! {
     pub trait UserEntity {
         async fn get(&self, id: Id) -> Result<Existing<User>, Error>;
+        /// Get every user with one of the `ids`, in no particular order, leaving
+        /// out the ones that don't exist.
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<User>>, Error>;
+        /// Get a user by their email, including their password hash.
+        async fn get_by_email(&self, email: String) -> Result<Existing<User>, Error>;
+        async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<User>>, Error>;
         async fn create(&self, data: New<User>) -> Result<Id, Error>;
+        async fn update_settings(&self, id: Id, appearance: Appearance, notifications: Notifications, content: Content) -> Result<(), Error>;
+        /// Whether the user can change the site's settings.
+        async fn is_admin(&self, id: Id) -> Result<bool, Error>;
+        async fn set_admin(&self, id: Id, admin: bool) -> Result<(), Error>;
     }
 }
 
@@ -134,63 +190,199 @@ def! {
     pub trait CommentEntity {}
 }
 
+def! {
+    pub trait MediaEntity {
+        async fn get(&self, id: Id) -> Result<Existing<Media>, Error>;
+        /// Get uploaded media by the hash of its contents.
+        async fn find(&self, hash: String) -> Result<Option<Existing<Media>>, Error>;
+        /// Returns the id of the existing media instead if the same image has
+        /// already been uploaded.
+        async fn create(&self, data: New<Media>) -> Result<Id, Error>;
+    }
+}
+
+def! {
+    pub trait NotificationEntity {
+        /// Get the user's notifications, newest first.
+        async fn all(&self, user: Id, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Notification>>, Error>;
+        async fn create(&self, user: Id, data: New<Notification>) -> Result<Id, Error>;
+    }
+}
+
 def! {
     pub trait PartEntity {}
 }
 
+def! {
+    pub trait PostEntity {
+        /// Get the user's blog posts, newest first.
+        async fn all(&self, user: Id, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Post>>, Error>;
+        async fn create(&self, user: Id, data: New<Post>) -> Result<Id, Error>;
+    }
+}
+
+def! {
+    /// The site wide settings, stored as text under their key (ie
+    /// `story-auto-abandon`), see [`crate::settings`] for reading them.
+    pub trait SettingEntity {
+        async fn get(&self, key: String) -> Result<Option<String>, Error>;
+        /// Stores the value, replacing the key's current value if it has one.
+        async fn set(&self, key: String, value: String) -> Result<(), Error>;
+    }
+}
+
 def! {
     pub trait TagEntity {
         async fn get(&self, id: Id) -> Result<Existing<Tag>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Tag>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Tag>>, Error>;
+        /// Get the tag with the same content, ignoring case.
+        async fn find(&self, content: String) -> Result<Option<Existing<Tag>>, Error>;
+        /// Queue a tag a story used that didn't match an existing one, see
+        /// [`TagRequest`].
+        async fn request(&self, story: Id, request: TagRequest) -> Result<(), Error>;
     }
 }
 
 def! {
     pub trait ChapterEntity {
-        async fn get(&self, id: Id) -> Result<Existing<Chapter>, Error>;
-        async fn create(&self, data: New<Chapter>) -> Result<Id, Error>;
+        /// Get a chapter as the `viewer` is allowed to see it.
+        ///
+        /// Only the story's authors can see unpublished chapters (anyone else
+        /// gets [`NotFound`]), drafts and schedules, see [`Chapter::for_readers`].
+        ///
+        /// [`NotFound`]: crate::error::NotFound
+        async fn get(&self, id: Id, viewer: Option<Id>) -> Result<Existing<Chapter>, Error>;
+        /// Get every chapter with one of the `ids` the `viewer` is allowed to see,
+        /// see [`get`](ChapterEntity::get).
+        async fn many(&self, ids: Vec<Id>, viewer: Option<Id>) -> Result<Vec<Existing<Chapter>>, Error>;
+        /// The story the chapter is part of.
+        async fn story(&self, id: Id) -> Result<Id, Error>;
+        /// Adds a chapter to the end of the story, recording its first
+        /// revision as made by `editor`.
+        async fn create(&self, story: Id, data: New<Chapter>, editor: Id) -> Result<Id, Error>;
+        /// Replace a chapter's data, failing with [`PreconditionFailed`] if it
+        /// has been updated since `updated`.
+        ///
+        /// Changes to a published chapter also update its story, changes to
+        /// its name or parts are recorded as a revision made by `editor`.
+        ///
+        /// [`PreconditionFailed`]: crate::error::PreconditionFailed
+        async fn update(&self, id: Id, data: New<Chapter>, updated: OffsetDateTime, editor: Id) -> Result<(), Error>;
+        /// Puts the story's chapters in the given order, failing with
+        /// [`Invalid`] unless `chapters` has every one of them exactly once.
+        ///
+        /// [`Invalid`]: crate::error::Invalid
+        async fn reorder(&self, story: Id, chapters: Vec<Id>) -> Result<(), Error>;
+        /// Publishes every chapter scheduled at or before `now` and updates
+        /// their stories, returning the published chapters.
+        async fn publish_scheduled(&self, now: OffsetDateTime) -> Result<Vec<Id>, Error>;
+        /// Get the most recently published chapters as readers see them,
+        /// newest first, along with the id of their story.
+        async fn recent(&self, limit: i64) -> Result<Vec<(Id, Existing<Chapter>)>, Error>;
+        /// Get the chapter's revisions, newest first.
+        async fn revisions(&self, id: Id, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Revision>>, Error>;
+        /// Get one of the chapter's revisions, failing with [`NotFound`] if
+        /// it belongs to something else.
+        ///
+        /// [`NotFound`]: crate::error::NotFound
+        async fn revision(&self, id: Id, revision: Id) -> Result<Existing<ChapterRevision>, Error>;
     }
 }
 
 def! {
     pub trait OriginEntity {
         async fn get(&self, id: Id) -> Result<Existing<Origin>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Origin>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Origin>>, Error>;
+        /// Get the origin with the same content, ignoring case.
+        async fn find(&self, content: String) -> Result<Option<Existing<Origin>>, Error>;
     }
 }
 
 def! {
     pub trait WarningEntity {
         async fn get(&self, id: Id) -> Result<Existing<Warning>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Warning>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Warning>>, Error>;
+        /// Get the warning with the same content, ignoring case.
+        async fn find(&self, content: String) -> Result<Option<Existing<Warning>>, Error>;
     }
 }
 
 def! {
     pub trait PairingEntity {
         async fn get(&self, id: Id) -> Result<Existing<Pairing>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Pairing>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Pairing>>, Error>;
+        /// Get the pairing of exactly these characters, in any order.
+        async fn find(&self, characters: Vec<Id>, relationship: Relationship) -> Result<Option<Existing<Pairing>>, Error>;
     }
 }
 
 def! {
     pub trait CharacterEntity {
         async fn get(&self, id: Id) -> Result<Existing<Character>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Character>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Character>>, Error>;
+        /// Get the character with the same content, ignoring case.
+        async fn find(&self, content: String) -> Result<Option<Existing<Character>>, Error>;
     }
 }
 
 def! {
     pub trait StoryEntity {
         async fn get(&self, id: Id) -> Result<Existing<Story>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Story>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Story>>, Error>;
+        /// Get the stories matching the `filter`, ie every story by an author
+        /// or with a tag, newest first.
+        async fn filter(&self, filter: StoryFilter, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Story>>, Error>;
+        /// Get every other language version of a story, its original and all
+        /// of the original's translations, ordered by language.
+        async fn translations(&self, id: Id) -> Result<Vec<Existing<Story>>, Error>;
         async fn create(&self, data: New<Story>) -> Result<Id, Error>;
+        /// Creates the story along with its chapters, in order, and queues
+        /// its tag requests, either all of them are saved or none are.
+        ///
+        /// The chapters' first revisions are made by `editor`.
+        async fn import(&self, data: New<Story>, chapters: Vec<New<Chapter>>, requests: Vec<TagRequest>, editor: Id) -> Result<Id, Error>;
+        /// Replace a story's data, failing with [`PreconditionFailed`] if it
+        /// has been updated since `updated`.
+        ///
+        /// Its credited users, tags and series are replaced too, its chapters
+        /// are left as they are. Changes to its metadata are recorded as a
+        /// revision made by `editor`.
+        ///
+        /// [`PreconditionFailed`]: crate::error::PreconditionFailed
+        async fn update(&self, id: Id, data: New<Story>, updated: OffsetDateTime, editor: Id) -> Result<(), Error>;
+        /// Get the in progress stories last updated before `updated` whose
+        /// authors haven't been warned about them being abandoned since.
+        async fn unwarned(&self, updated: OffsetDateTime) -> Result<Vec<Existing<Story>>, Error>;
+        /// Get the in progress stories last updated before `updated` whose
+        /// authors have since been warned, but only if that was before `warned`.
+        async fn abandonable(&self, updated: OffsetDateTime, warned: OffsetDateTime) -> Result<Vec<Existing<Story>>, Error>;
+        /// Marks a story as [`Abandoned`], failing with [`PreconditionFailed`]
+        /// if it has been updated since `updated`.
+        ///
+        /// [`Abandoned`]: crate::models::story::State::Abandoned
+        /// [`PreconditionFailed`]: crate::error::PreconditionFailed
+        async fn abandon(&self, id: Id, updated: OffsetDateTime) -> Result<(), Error>;
+        /// Get the story's revisions, newest first, its chapters' revisions
+        /// aren't included.
+        async fn revisions(&self, id: Id, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Revision>>, Error>;
+        /// Get one of the story's revisions, failing with [`NotFound`] if it
+        /// belongs to something else.
+        ///
+        /// [`NotFound`]: crate::error::NotFound
+        async fn revision(&self, id: Id, revision: Id) -> Result<Existing<StoryRevision>, Error>;
     }
 }
 
 def! {
     pub trait SeriesEntity {
         async fn get(&self, id: Id) -> Result<Existing<Series>, Error>;
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Series>>, Error>;
         async fn all(&self, cursor: Option<Id>, limit: i64) -> Result<Vec<Existing<Series>>, Error>;
     }
 }
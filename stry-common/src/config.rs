@@ -1,4 +1,5 @@
-//! Init config information, everything else is handled though the frontend.
+//! Init config information, site-wide settings that can change while the
+//! site is running are in [`crate::settings`].
 
 use std::sync::Arc;
 
@@ -67,6 +68,59 @@ pub struct Config {
     /// The secret key used for JWT creation and verification.
     #[serde(default = "default_secret")]
     pub secret: String,
+
+    /// Where login and registration attempts are tracked for rate limiting.
+    ///
+    /// Either `memory`, which keeps them in-process, or `database`, which
+    /// stores them in the database backend so the limits survive restarts and
+    /// are shared between instances.
+    ///
+    /// # Default
+    ///
+    /// If constructed with [`Default::default`] this value is set to `memory`.
+    #[serde(default = "default_limiter")]
+    pub limiter: String,
+
+    /// The directory holding the HTML frontend's translations, with a
+    /// sub-directory of `.ftl` files for each language (ie `en-US/core.ftl`).
+    ///
+    /// # Default
+    ///
+    /// If constructed with [`Default::default`] this value is set to `stry-service-html/localization`.
+    #[serde(default = "default_localization")]
+    pub localization: String,
+
+    /// The address the site is publicly reachable at, without a trailing
+    /// slash, used for links that leave the site (ie feed entries).
+    ///
+    /// # Default
+    ///
+    /// If constructed with [`Default::default`] this value is set to `http://localhost:8901`.
+    #[serde(default = "default_url")]
+    pub url: String,
+
+    /// The directory images and other uploads are stored in when the
+    /// `media_store` is `local`, they're served from `/media/`.
+    ///
+    /// # Default
+    ///
+    /// If constructed with [`Default::default`] this value is set to `media`.
+    #[serde(default = "default_media")]
+    pub media: String,
+
+    /// Where uploaded media is kept, either `local` for the `media` directory
+    /// or an S3 compatible bucket as
+    /// `s3://<access key>:<secret key>@<host>:<port>/<bucket>?region=<region>&scheme=<scheme>`.
+    ///
+    /// # Note
+    ///
+    /// Buckets are reached over HTTPS unless the `scheme` is `http`.
+    ///
+    /// # Default
+    ///
+    /// If constructed with [`Default::default`] this value is set to `local`.
+    #[serde(default = "default_media_store")]
+    pub media_store: String,
 }
 
 impl Config {
@@ -97,3 +151,23 @@ fn default_database() -> String {
 fn default_secret() -> String {
     String::from(DEFAULT_SECRET)
 }
+
+fn default_limiter() -> String {
+    String::from("memory")
+}
+
+fn default_localization() -> String {
+    String::from("stry-service-html/localization")
+}
+
+fn default_url() -> String {
+    String::from("http://localhost:8901")
+}
+
+fn default_media() -> String {
+    String::from("media")
+}
+
+fn default_media_store() -> String {
+    String::from("local")
+}
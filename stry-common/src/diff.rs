@@ -0,0 +1,185 @@
+//! Word-level differences between two revisions of a chapter or story.
+//!
+//! Text is split with [`crate::words::tokens`] and compared with Myers'
+//! algorithm, a chapter's parts are compared as the Markdown they export to
+//! (see [`crate::markdown::from_parts`]) so formatting changes show up too.
+
+use std::time::Duration;
+
+use similar::{Algorithm, ChangeTag, TextDiff};
+
+use crate::{
+    markdown,
+    models::{
+        story::{ChapterRevision, Revision, StoryRevision},
+        Existing,
+    },
+    words,
+};
+
+/// How long a diff can take before it gives up on finding the smallest
+/// difference, and returns a larger one instead.
+pub const DEADLINE: Duration = Duration::from_secs(1);
+
+/// A run of text that is in both revisions, or only in one of them.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", content = "text", rename_all = "lowercase")]
+pub enum Change {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+/// The changes made to a single field, only fields that changed are given.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FieldDiff {
+    pub field: String,
+    pub changes: Vec<Change>,
+}
+
+/// The changes between two revisions, along with who made each of them.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RevisionDiff {
+    pub from: Existing<Revision>,
+    pub to: Existing<Revision>,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// The word-level changes from `old` to `new`, adjacent changes of the same
+/// kind are joined.
+pub fn words(old: &str, new: &str) -> Vec<Change> {
+    let old = words::tokens(old);
+    let new = words::tokens(new);
+
+    let diff = TextDiff::configure()
+        .algorithm(Algorithm::Myers)
+        .timeout(DEADLINE)
+        .diff_slices(&old, &new);
+
+    let mut changes: Vec<Change> = Vec::new();
+
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+
+        match (changes.last_mut(), change.tag()) {
+            (Some(Change::Equal(text)), ChangeTag::Equal)
+            | (Some(Change::Insert(text)), ChangeTag::Insert)
+            | (Some(Change::Delete(text)), ChangeTag::Delete) => text.push_str(value),
+            (_, ChangeTag::Equal) => changes.push(Change::Equal(value.to_string())),
+            (_, ChangeTag::Insert) => changes.push(Change::Insert(value.to_string())),
+            (_, ChangeTag::Delete) => changes.push(Change::Delete(value.to_string())),
+        }
+    }
+
+    changes
+}
+
+/// The changes to a chapter's name and parts.
+pub fn chapter(old: &ChapterRevision, new: &ChapterRevision) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    field(
+        &mut diffs,
+        "name",
+        old.name.as_deref().unwrap_or_default(),
+        new.name.as_deref().unwrap_or_default(),
+    );
+
+    for (name, old, new) in [
+        ("prefix", &old.prefix, &new.prefix),
+        ("main", &old.main, &new.main),
+        ("suffix", &old.suffix, &new.suffix),
+    ] {
+        field(
+            &mut diffs,
+            name,
+            &markdown::from_parts(old),
+            &markdown::from_parts(new),
+        );
+    }
+
+    diffs
+}
+
+/// The changes to a story's metadata, the rating and state are compared by
+/// their names.
+pub fn story(old: &StoryRevision, new: &StoryRevision) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    field(&mut diffs, "name", &old.name, &new.name);
+    field(&mut diffs, "summary", &old.summary, &new.summary);
+    field(&mut diffs, "language", &old.language, &new.language);
+    field(
+        &mut diffs,
+        "original",
+        old.original
+            .as_ref()
+            .map(|id| id.as_str())
+            .unwrap_or_default(),
+        new.original
+            .as_ref()
+            .map(|id| id.as_str())
+            .unwrap_or_default(),
+    );
+    field(&mut diffs, "rating", &name(&old.rating), &name(&new.rating));
+    field(&mut diffs, "state", &name(&old.state), &name(&new.state));
+
+    diffs
+}
+
+fn field(diffs: &mut Vec<FieldDiff>, field: &str, old: &str, new: &str) {
+    if old != new {
+        diffs.push(FieldDiff {
+            field: field.to_string(),
+            changes: words(old, new),
+        });
+    }
+}
+
+/// The name a value is serialized as.
+fn name<T: serde::Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("The quick brown fox", "The slow brown fox jumps"),
+            vec![
+                Change::Equal(String::from("The ")),
+                Change::Delete(String::from("quick")),
+                Change::Insert(String::from("slow")),
+                Change::Equal(String::from(" brown fox")),
+                Change::Insert(String::from(" jumps")),
+            ]
+        );
+
+        assert_eq!(
+            words("我爱你", "我想你"),
+            vec![
+                Change::Equal(String::from("我")),
+                Change::Delete(String::from("爱")),
+                Change::Insert(String::from("想")),
+                Change::Equal(String::from("你")),
+            ]
+        );
+
+        assert!(words("", "").is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 //! Custom errors used for HTTP responses.
 
-use std::{error::Error, fmt};
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use time::OffsetDateTime;
 
 pub use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
 
@@ -31,16 +33,324 @@ http_error! {
     NotFound: "not found",
     Unauthenticated: "unauthenticated",
     Unauthorized: "unauthorized",
+    /// The entity was changed since the client last fetched it.
+    PreconditionFailed: "precondition failed",
+    /// Registrations have been closed by an admin.
+    RegistrationClosed: "registration closed",
+}
+
+/// Too many attempts have been made, or the target has been locked out.
+#[derive(Debug)]
+pub struct TooManyRequests {
+    /// How many seconds the client should wait before trying again.
+    pub retry_after: u64,
+}
+
+impl TooManyRequests {
+    /// Creates the error with the time left until `until`, rounded up to the
+    /// next whole second.
+    pub fn until(until: OffsetDateTime) -> Self {
+        let left = until - OffsetDateTime::now_utc();
+
+        Self {
+            retry_after: left.whole_seconds().max(0) as u64 + 1,
+        }
+    }
+}
+
+impl fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "too many requests, retry after {} seconds",
+            self.retry_after
+        )
+    }
+}
+
+impl Error for TooManyRequests {}
+
+/// The request contained one or more invalid fields.
+#[derive(Debug)]
+pub struct Invalid {
+    pub fields: Vec<FieldError>,
+}
+
+impl Invalid {
+    /// Creates the error for a single field, using the default message for the code.
+    pub fn field<P>(path: P, code: &'static str) -> Self
+    where
+        P: Into<String>,
+    {
+        Self {
+            fields: vec![FieldError::new(path, code, BTreeMap::new())],
+        }
+    }
+}
+
+impl From<ValidationErrors> for Invalid {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = Vec::new();
+
+        flatten(&mut fields, None, errors);
+
+        fields.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Self { fields }
+    }
 }
 
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid fields:")?;
+
+        for field in &self.fields {
+            write!(f, " `{}` ({})", field.path, field.code)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for Invalid {}
+
+/// The request conflicts with existing data, ie an email that is already in use.
+#[derive(Debug)]
+pub struct Conflict {
+    pub fields: Vec<FieldError>,
+}
+
+impl Conflict {
+    /// Creates the error for a single field, using the default message for the code.
+    pub fn field<P>(path: P, code: &'static str) -> Self
+    where
+        P: Into<String>,
+    {
+        Self {
+            fields: vec![FieldError::new(path, code, BTreeMap::new())],
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting fields:")?;
+
+        for field in &self.fields {
+            write!(f, " `{}` ({})", field.path, field.code)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for Conflict {}
+
+/// A problem with a single field of a request.
+#[rustfmt::skip]
+#[derive(Clone, Debug, PartialEq)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FieldError {
+    /// The path to the field, ie `email` or `main[2].content`.
+    pub path: String,
+
+    /// A machine readable error code, ie `length` or `unique`.
+    ///
+    /// # Note
+    ///
+    /// Clients should use this and the `params` to display their own
+    /// translated messages.
+    pub code: String,
+
+    /// A human readable message, in the language negotiated from the
+    /// request's `Accept-Language` (the site's default if there is no match).
+    pub message: String,
+
+    /// Any extra information needed to display the error, ie `min` and `max`
+    /// for `length`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+impl FieldError {
+    pub fn new<P, C>(path: P, code: C, params: BTreeMap<String, serde_json::Value>) -> Self
+    where
+        P: Into<String>,
+        C: Into<String>,
+    {
+        let code = code.into();
+        let message = message(&code, &params);
+
+        Self {
+            path: path.into(),
+            code,
+            message,
+            params,
+        }
+    }
+}
+
+fn flatten(fields: &mut Vec<FieldError>, prefix: Option<&str>, errors: ValidationErrors) {
+    for (name, kind) in errors.into_errors() {
+        let path = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, name),
+            None => name.to_string(),
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    let params = error
+                        .params
+                        .into_iter()
+                        // The submitted value is never sent back as it could be a password
+                        .filter(|(key, _)| key != "value")
+                        .map(|(key, value)| (key.into_owned(), value))
+                        .collect();
+
+                    let mut field = FieldError::new(path.clone(), error.code, params);
+
+                    if let Some(message) = error.message {
+                        field.message = message.into_owned();
+                    }
+
+                    fields.push(field);
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => flatten(fields, Some(&path), *errors),
+            ValidationErrorsKind::List(list) => {
+                for (index, errors) in list {
+                    flatten(fields, Some(&format!("{}[{}]", path, index)), *errors);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the default message for an error code.
+fn message(code: &str, params: &BTreeMap<String, serde_json::Value>) -> String {
+    let param = |key: &str| params.get(key).map(|value| value.to_string());
+
+    match code {
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("must be exactly {} characters long", equal),
+            (Some(min), Some(max), _) => {
+                format!("must be between {} and {} characters long", min, max)
+            }
+            (Some(min), None, _) => format!("must be at least {} characters long", min),
+            (None, Some(max), _) => format!("must be at most {} characters long", max),
+            (None, None, _) => String::from("has an invalid length"),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+            (Some(min), None) => format!("must be at least {}", min),
+            (None, Some(max)) => format!("must be at most {}", max),
+            (None, None) => String::from("is out of range"),
+        },
+        "email" => String::from("must be a valid email address"),
+        "url" => String::from("must be a valid url"),
+        "required" => String::from("is required"),
+        "type" => String::from("has the wrong type"),
+        "unique" => String::from("is already in use"),
+        "language" => String::from("must be a language tag, ie `en` or `pt-BR`"),
+        "ids" => String::from("must be ids separated by spaces"),
+        "media" => String::from("must be the id of uploaded media"),
+        "chapters" => String::from("must list each of the story's chapters once"),
+        "boolean" => String::from("must be `true` or `false`"),
+        "number" => String::from("must be a whole number"),
+        "setting" => String::from("is not a setting"),
+        "exists" => String::from("does not exist"),
+        _ => String::from("is invalid"),
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ErrorResponse<Err> {
     pub error: Err,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct StatusCodeErrorResponse {
     pub code: u16,
     pub status: Option<&'static str>,
     pub message: &'static str,
+
+    /// The fields that caused the error, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::models::{core::UserRegisterForm, story::ChapterDraft};
+
+    use validator::Validate as _;
+
+    #[test]
+    fn test_invalid_from_validation_errors() {
+        let form = UserRegisterForm {
+            username: String::from("abc"),
+            email: String::from("not an email"),
+            password: String::from("short"),
+        };
+
+        let invalid = Invalid::from(form.validate().unwrap_err());
+
+        let fields = invalid
+            .fields
+            .iter()
+            .map(|field| (field.path.as_str(), field.code.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("email", "email"),
+                ("password", "length"),
+                ("username", "length")
+            ],
+            fields
+        );
+    }
+
+    #[test]
+    fn test_invalid_never_returns_value() {
+        let form = UserRegisterForm {
+            username: String::from("username"),
+            email: String::from("user@example.com"),
+            password: String::from("short"),
+        };
+
+        let invalid = Invalid::from(form.validate().unwrap_err());
+
+        assert_eq!(1, invalid.fields.len());
+        assert!(!invalid.fields[0].params.contains_key("value"));
+        assert_eq!(
+            "must be between 8 and 512 characters long",
+            invalid.fields[0].message
+        );
+    }
+
+    #[test]
+    fn test_invalid_nested_part_path() {
+        let text = format!("one\n\ntwo\n\n{}", "a".repeat(70_000));
+
+        let draft = ChapterDraft {
+            name: None,
+            prefix: Vec::new(),
+            main: crate::markdown::to_parts(&text, &[]).unwrap(),
+            suffix: Vec::new(),
+            words: 0,
+        };
+
+        let invalid = Invalid::from(draft.validate().unwrap_err());
+
+        assert_eq!(1, invalid.fields.len());
+        assert_eq!("main[2].content", invalid.fields[0].path);
+        assert_eq!("length", invalid.fields[0].code);
+    }
 }
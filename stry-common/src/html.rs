@@ -0,0 +1,599 @@
+//! The rich text of [`PartText`], which is stored as sanitized HTML.
+//!
+//! Everything an author writes is passed through [`sanitize`] before it is
+//! stored, so it can be rendered as is. Only formatting is allowed, there are
+//! no scripts, styles, event handlers or `javascript:` urls left that a
+//! content security policy would need to block.
+//!
+//! [`PartText`]: crate::models::core::PartText
+
+use std::collections::HashMap;
+
+use ammonia::Builder;
+
+use crate::{
+    models::{
+        core::{Comment, Part},
+        story::Chapter,
+        Existing,
+    },
+    words,
+};
+
+/// The elements that are kept, any others are removed leaving their text.
+const TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "cite",
+    "code",
+    "del",
+    "dfn",
+    "em",
+    "hr",
+    "i",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "u",
+    "ul",
+    "var",
+];
+
+/// The attributes that are kept on any element.
+const ATTRIBUTES: &[&str] = &["dir", "lang", "title"];
+
+/// The text a thematic break (ie `<hr>`) is written as in plain text.
+pub const SCENE_BREAK: &str = "* * *";
+
+/// Removes everything that isn't in the whitelist.
+///
+/// # Note
+///
+/// Text without any elements is treated as plain text, each line is kept and
+/// blank lines separate paragraphs.
+pub fn sanitize(html: &str) -> String {
+    if !html.contains('<') {
+        return from_text(html);
+    }
+
+    clean(html)
+}
+
+/// Removes everything that isn't in the whitelist, without treating text
+/// without elements as plain text.
+///
+/// Stored parts have already been sanitized, this is for rendering them
+/// without trusting what's stored.
+pub fn clean(html: &str) -> String {
+    let mut tag_attributes = HashMap::new();
+
+    tag_attributes.insert("a", ["href"].iter().copied().collect());
+    tag_attributes.insert("ol", ["start", "reversed"].iter().copied().collect());
+
+    Builder::empty()
+        .tags(TAGS.iter().copied().collect())
+        .generic_attributes(ATTRIBUTES.iter().copied().collect())
+        .tag_attributes(tag_attributes)
+        .url_schemes(["http", "https", "mailto"].iter().copied().collect())
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .clean(html)
+        .to_string()
+        .trim()
+        .to_string()
+}
+
+/// Sanitizes every text part of the chapter, its draft and its comments, and
+/// recounts their words.
+pub fn sanitize_chapter(chapter: &mut Chapter) {
+    sanitize_parts(&mut chapter.prefix);
+    sanitize_parts(&mut chapter.main);
+    sanitize_parts(&mut chapter.suffix);
+    sanitize_comments(&mut chapter.comments);
+
+    if let Some(draft) = chapter.draft.as_mut() {
+        sanitize_parts(&mut draft.prefix);
+        sanitize_parts(&mut draft.main);
+        sanitize_parts(&mut draft.suffix);
+
+        draft.words = words::parts(&draft.main);
+    }
+
+    chapter.words = words::chapter(chapter);
+}
+
+/// Sanitizes every text part, including those in the parts' comments, and
+/// recounts their words.
+pub fn sanitize_parts(parts: &mut [Existing<Part>]) {
+    for part in parts {
+        let comments = match &mut **part {
+            Part::Heading(heading) => &mut heading.comments,
+            Part::Image(image) => &mut image.comments,
+            Part::Text(text) => {
+                text.content = sanitize(&text.content);
+                text.words = words(&text.content);
+
+                &mut text.comments
+            }
+        };
+
+        sanitize_comments(comments);
+    }
+}
+
+fn sanitize_comments(comments: &mut [Existing<Comment>]) {
+    for comment in comments {
+        sanitize_parts(&mut comment.main);
+        sanitize_comments(&mut comment.children);
+    }
+}
+
+/// Plain text as paragraphs, its lines separated by line breaks.
+pub fn from_text(text: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+
+    let mut out = String::with_capacity(text.len());
+
+    for paragraph in text.split("\n\n") {
+        let lines = paragraph
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(escape)
+            .collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        out.push_str("<p>");
+        out.push_str(&lines.join("<br>"));
+        out.push_str("</p>");
+    }
+
+    out
+}
+
+/// The HTML's text, with a blank line between each paragraph.
+pub fn to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+
+    // The next number of each list, `None` for bullet lists
+    let mut lists: Vec<Option<u64>> = Vec::new();
+    let mut pre = false;
+    // Parentheses are left out as `rt` is already wrapped in them
+    let mut rp = 0;
+
+    for token in tokens(html) {
+        match token {
+            Token::Start { name, attributes } => match name {
+                "p" | "blockquote" | "pre" => {
+                    paragraph(&mut out);
+
+                    pre |= name == "pre";
+                }
+                "ul" | "ol" => {
+                    line(&mut out);
+
+                    lists.push(if name == "ol" {
+                        Some(
+                            attributes
+                                .iter()
+                                .find(|(name, _)| *name == "start")
+                                .and_then(|(_, value)| value.parse().ok())
+                                .unwrap_or(1),
+                        )
+                    } else {
+                        None
+                    });
+                }
+                "li" => {
+                    line(&mut out);
+
+                    for _ in 1..lists.len() {
+                        out.push_str("  ");
+                    }
+
+                    match lists.last_mut() {
+                        Some(Some(number)) => {
+                            out.push_str(&format!("{}. ", number));
+
+                            *number += 1;
+                        }
+                        _ => out.push_str("- "),
+                    }
+                }
+                "br" => out.push('\n'),
+                "hr" => {
+                    paragraph(&mut out);
+                    out.push_str(SCENE_BREAK);
+                    paragraph(&mut out);
+                }
+                "rp" => rp += 1,
+                "rt" => out.push('('),
+                _ => {}
+            },
+            Token::End(name) => match name {
+                "p" | "blockquote" | "pre" => {
+                    paragraph(&mut out);
+
+                    pre &= name != "pre";
+                }
+                "ul" | "ol" => {
+                    lists.pop();
+
+                    if lists.is_empty() {
+                        paragraph(&mut out);
+                    }
+                }
+                "rp" => rp -= 1,
+                "rt" => out.push(')'),
+                _ => {}
+            },
+            Token::Text(_) if rp > 0 => {}
+            Token::Text(text) if pre => out.push_str(&text),
+            Token::Text(text) => {
+                let mut collapsed = String::with_capacity(text.len());
+
+                for c in text.chars() {
+                    if !c.is_whitespace() {
+                        collapsed.push(c);
+                    } else if !collapsed.ends_with(' ') {
+                        collapsed.push(' ');
+                    }
+                }
+
+                if out.is_empty() || out.ends_with('\n') || out.ends_with(' ') {
+                    out.push_str(collapsed.trim_start());
+                } else {
+                    out.push_str(&collapsed);
+                }
+            }
+        }
+    }
+
+    let lines = out.lines().map(str::trim_end).collect::<Vec<_>>();
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Writes the HTML back out with empty elements closed (ie `<br/>`), so it
+/// can be used in XHTML.
+pub fn to_xhtml(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+
+    for token in tokens(html) {
+        match token {
+            Token::Start { name, attributes } => {
+                out.push('<');
+                out.push_str(name);
+
+                for (name, value) in attributes {
+                    out.push_str(&format!(r#" {}="{}""#, name, escape(&value)));
+                }
+
+                out.push_str(if matches!(name, "br" | "hr") {
+                    "/>"
+                } else {
+                    ">"
+                });
+            }
+            Token::End(name) => {
+                out.push_str("</");
+                out.push_str(name);
+                out.push('>');
+            }
+            Token::Text(text) => out.push_str(&escape(&text)),
+        }
+    }
+
+    out
+}
+
+/// The number of words in the HTML's text, list markers and ruby
+/// annotations aren't counted, see [`words::count`].
+pub fn words(html: &str) -> i64 {
+    let mut text = String::with_capacity(html.len());
+    let mut annotation = 0;
+
+    for token in tokens(html) {
+        match token {
+            Token::Start {
+                name: "rp" | "rt", ..
+            } => annotation += 1,
+            Token::End("rp" | "rt") => annotation -= 1,
+            Token::Start { name, .. } | Token::End(name) if block(name) => text.push(' '),
+            Token::Text(content) if annotation == 0 => text.push_str(&content),
+            _ => {}
+        }
+    }
+
+    words::count(&text)
+}
+
+/// If the element separates the text before and after it.
+fn block(name: &str) -> bool {
+    matches!(
+        name,
+        "blockquote" | "br" | "hr" | "li" | "ol" | "p" | "pre" | "ul"
+    )
+}
+
+/// Ends the current line, unless nothing has been written to it.
+fn line(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Ends the current line and leaves a blank one.
+fn paragraph(out: &mut String) {
+    line(out);
+
+    if !out.is_empty() && !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A piece of HTML, as written out by [`sanitize`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Token<'h> {
+    Start {
+        name: &'h str,
+        attributes: Vec<(&'h str, String)>,
+    },
+    End(&'h str),
+    Text(String),
+}
+
+/// Splits sanitized HTML into its elements and text.
+///
+/// # Note
+///
+/// This isn't a full parser, it relies on the sanitizer having written out
+/// well formed elements with quoted attributes, so elements and their
+/// attributes are lowercase and comments have been removed.
+pub(crate) fn tokens(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let tag = rest
+            .strip_prefix('<')
+            .filter(|tag| tag.starts_with(|c: char| c == '/' || c.is_ascii_alphabetic()));
+
+        let (tag, end) = match tag.and_then(|tag| tag_end(tag).map(|end| (tag, end))) {
+            Some(found) => found,
+            None => {
+                // Text runs until the next element, a lone `<` is kept as text
+                let first = rest.chars().next().map(char::len_utf8).unwrap_or_default();
+                let end = rest[first..]
+                    .find('<')
+                    .map(|end| end + first)
+                    .unwrap_or(rest.len());
+
+                tokens.push(Token::Text(decode(&rest[..end])));
+                rest = &rest[end..];
+
+                continue;
+            }
+        };
+
+        let inner = &tag[..end];
+
+        rest = &tag[end + 1..];
+
+        if let Some(name) = inner.strip_prefix('/') {
+            tokens.push(Token::End(name.trim()));
+
+            continue;
+        }
+
+        let inner = inner.trim_end_matches('/');
+        let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+
+        tokens.push(Token::Start {
+            name: &inner[..name_end],
+            attributes: attributes(&inner[name_end..]),
+        });
+    }
+
+    tokens
+}
+
+/// The position of the `>` that closes the element, skipping any in quoted
+/// attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn attributes(mut rest: &str) -> Vec<(&str, String)> {
+    let mut attributes = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+
+        if rest.is_empty() {
+            return attributes;
+        }
+
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+
+                match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = value[1..]
+                            .find(quote)
+                            .map(|end| end + 1)
+                            .unwrap_or(value.len());
+
+                        rest = value.get(end + 1..).unwrap_or_default();
+
+                        &value[1..end]
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+
+                        rest = &value[end..];
+
+                        &value[..end]
+                    }
+                }
+            }
+            None => "",
+        };
+
+        attributes.push((name, decode(value)));
+    }
+}
+
+/// Replaces the character references the sanitizer writes out.
+fn decode(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest.find(';').map(|end| (&rest[1..end], end));
+
+        let c = reference.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = name.strip_prefix('#')?;
+
+                let code = match number.strip_prefix(|c| c == 'x' || c == 'X') {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+
+                char::from_u32(code)
+            }
+        });
+
+        match (c, reference) {
+            (Some(c), Some((_, end))) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_strips_scripts_and_handlers() {
+        assert_eq!(
+            sanitize(
+                r#"<p onclick="alert(1)">Hi<script>alert(2)</script><img src=x onerror="alert(3)"></p>"#
+            ),
+            "<p>Hi</p>"
+        );
+        assert_eq!(
+            sanitize(r#"<a href="javascript:alert(1)">link</a><style>p { color: red }</style>"#),
+            r#"<a rel="noopener noreferrer nofollow">link</a>"#
+        );
+        assert_eq!(sanitize("<blink>old</blink>"), "old");
+    }
+
+    #[test]
+    fn test_keeps_formatting() {
+        let html = concat!(
+            r#"<p dir="rtl" lang="ar">a <em>b</em> <strong>c</strong></p>"#,
+            "<blockquote><p>d</p></blockquote>",
+            "<ul><li>e</li></ul><ol><li>f</li></ol><hr>",
+            "<p><ruby>漢<rp>(</rp><rt>かん</rt><rp>)</rp></ruby></p>",
+        );
+
+        assert_eq!(sanitize(html), html);
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            sanitize("One & two\nthree\n\nFour"),
+            "<p>One &amp; two<br>three</p><p>Four</p>"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let html = sanitize(concat!(
+            "<p>One <em>two</em>\nthree<br>four</p><hr>",
+            "<ol start=\"3\"><li>a</li><li>b<ul><li>c</li></ul></li></ol>",
+            "<p><ruby>漢<rp>(</rp><rt>かん</rt><rp>)</rp></ruby> &amp; more</p>",
+        ));
+
+        assert_eq!(
+            to_text(&html),
+            "One two three\nfour\n\n* * *\n\n3. a\n4. b\n  - c\n\n漢(かん) & more"
+        );
+        assert_eq!(words(&html), 9);
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     io::{Cursor, Write as _},
+    net::IpAddr,
     time::SystemTime,
 };
 
@@ -10,6 +11,7 @@ use crate::{models::Id, prelude::*};
 
 #[rustfmt::skip]
 #[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Pagination {
     pub cursor: Option<Id>,
     #[serde(default  = "default_limit")]
@@ -29,6 +31,52 @@ impl Default for Pagination {
     }
 }
 
+/// The format of a chapter's body, either its parts or Markdown.
+#[rustfmt::skip]
+#[derive(Default, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct FormatQuery {
+    #[serde(default)]
+    pub format: Format,
+}
+
+/// If a chapter's draft is wanted instead of the chapter itself.
+#[rustfmt::skip]
+#[derive(Default, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DraftQuery {
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// The story a chapter is added to, for when it can't be part of the path.
+#[rustfmt::skip]
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StoryQuery {
+    pub story: Id,
+}
+
+/// The two revisions to compare, `from` being the older one.
+#[rustfmt::skip]
+#[derive(serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RevisionDiffQuery {
+    pub from: Id,
+    pub to: Id,
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Parts,
+    Markdown,
+}
+
 pub fn make_span(req: &Request<Body>) -> Span {
     let span = debug_span!(
         "request",
@@ -61,6 +109,35 @@ pub fn make_span(req: &Request<Body>) -> Span {
     span
 }
 
+/// The address of the client that made a request.
+///
+/// # Note
+///
+/// `X-Forwarded-For` is only trusted when the connecting peer is a loopback
+/// address (ie a reverse proxy like Caddy on the same machine), in which case
+/// the last address in the header is used as that is the one the proxy added.
+pub fn client_ip(peer: IpAddr, headers: &http::HeaderMap) -> IpAddr {
+    if !peer.is_loopback() {
+        return peer;
+    }
+
+    headers
+        .get("x-forwarded-for")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.rsplit(',').next())
+        .and_then(|addr| addr.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+/// A random url safe token, ie for CSRF protection.
+pub fn random_token() -> String {
+    let mut raw = [0; 32];
+
+    OsRng.fill_bytes(&mut raw);
+
+    base64::encode_config(raw, base64::URL_SAFE_NO_PAD)
+}
+
 fn random_id() -> String {
     let length = 32;
 
@@ -0,0 +1,114 @@
+use fluent::FluentArgs;
+use num_format::{Locale, ToFormattedString};
+use unic_langid::LanguageIdentifier;
+
+use crate::{error::FieldError, i18n::I18n};
+
+/// Formats a number with the digit grouping of the given language.
+///
+/// Falls back to the bare language (`de` for `de-AT`) and then to English
+/// if there is no matching locale.
+pub fn number<N: ToFormattedString>(lang: &LanguageIdentifier, number: N) -> String {
+    let locale = Locale::from_name(lang.to_string())
+        .or_else(|_| Locale::from_name(lang.language.as_str()))
+        .unwrap_or(Locale::en);
+
+    number.to_formatted_string(&locale)
+}
+
+/// The message for a field's error in the reader's language, from the
+/// `field-error-{code}` message.
+///
+/// Falls back to the error's own message, in the site's default language,
+/// for codes without one.
+pub fn field_error(i18n: &I18n, error: &FieldError) -> String {
+    let param = |key: &str| {
+        error.params.get(key).map(|value| match value {
+            serde_json::Value::Number(number) => match number.as_u64() {
+                Some(number) => self::number(i18n.lang(), number),
+                None => number.to_string(),
+            },
+            serde_json::Value::String(value) => value.clone(),
+            value => value.to_string(),
+        })
+    };
+
+    let mut args = FluentArgs::new();
+
+    let bounds = match (param("min"), param("max"), param("equal")) {
+        (_, _, Some(equal)) => {
+            args.set("equal", equal);
+
+            "exact"
+        }
+        (Some(min), Some(max), _) => {
+            args.set("min", min);
+            args.set("max", max);
+
+            "between"
+        }
+        (Some(min), None, _) => {
+            args.set("min", min);
+
+            "min"
+        }
+        (None, Some(max), _) => {
+            args.set("max", max);
+
+            "max"
+        }
+        (None, None, _) => "none",
+    };
+
+    args.set("bounds", bounds);
+
+    i18n.try_message(&format!("field-error-{}", error.code), Some(&args))
+        .unwrap_or_else(|| error.message.clone())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use unic_langid::langid;
+
+    use super::*;
+    use crate::i18n::FluentResources;
+
+    #[test]
+    fn test_field_error() {
+        let i18n = FluentResources::builder()
+            .add_dir(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../stry-service-html/localization"
+            ))
+            .unwrap()
+            .build()
+            .unwrap()
+            .negotiate(&[langid!("en-US")]);
+
+        let mut params = BTreeMap::new();
+
+        params.insert(String::from("min"), serde_json::json!(8));
+        params.insert(String::from("max"), serde_json::json!(1024));
+
+        assert_eq!(
+            "must be between 8 and 1,024 characters long",
+            field_error(&i18n, &FieldError::new("password", "length", params))
+                // Fluent wraps arguments in bidi isolation marks
+                .replace(['\u{2068}', '\u{2069}'], "")
+        );
+
+        assert_eq!(
+            "is already in use",
+            field_error(&i18n, &FieldError::new("email", "unique", BTreeMap::new()))
+        );
+
+        // Codes without a message use the default one
+        let mut error = FieldError::new("name", "unknown", BTreeMap::new());
+
+        error.message = String::from("is not allowed");
+
+        assert_eq!("is not allowed", field_error(&i18n, &error));
+    }
+}
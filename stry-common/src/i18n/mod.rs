@@ -0,0 +1,7 @@
+//! Fluent translations shared by the services, negotiated per request from
+//! its `Accept-Language`.
+
+pub mod format;
+pub mod resources;
+
+pub use self::resources::{FluentResources, FluentResourcesBuilder, I18n};
@@ -0,0 +1,292 @@
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use crate::prelude::*;
+
+use fluent::{FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use intl_memoizer::concurrent::IntlLangMemoizer;
+use unic_langid::{langid, LanguageIdentifier};
+
+type FluentBundle = fluent::bundle::FluentBundle<FluentResource, IntlLangMemoizer>;
+
+#[derive(Clone)]
+pub struct FluentResources {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    default: LanguageIdentifier,
+    bundles: HashMap<LanguageIdentifier, Arc<FluentBundle>>,
+    languages: Vec<LanguageIdentifier>,
+    strategy: NegotiationStrategy,
+}
+
+impl FluentResources {
+    #[must_use]
+    pub fn builder() -> FluentResourcesBuilder {
+        FluentResourcesBuilder {
+            default: langid!("en-US"),
+            resources: vec![],
+            strategy: NegotiationStrategy::Filtering,
+        }
+    }
+
+    /// Picks the bundles that best match the reader's `requested` languages,
+    /// always ending with the default language's bundle.
+    pub fn negotiate(&self, requested: &[LanguageIdentifier]) -> I18n {
+        let inner = &self.inner;
+
+        let languages = negotiate_languages(
+            requested,
+            &inner.languages,
+            Some(&inner.default),
+            inner.strategy,
+        );
+
+        let lang = languages
+            .first()
+            .map(|lang| (*lang).clone())
+            .unwrap_or_else(|| inner.default.clone());
+
+        let bundles = languages
+            .into_iter()
+            .filter_map(|lang| inner.bundles.get(lang).cloned())
+            .collect();
+
+        I18n { lang, bundles }
+    }
+}
+
+pub struct FluentResourcesBuilder {
+    default: LanguageIdentifier,
+    resources: Vec<(String, FluentBundle)>,
+    strategy: NegotiationStrategy,
+}
+
+impl FluentResourcesBuilder {
+    #[must_use]
+    pub fn add_bundle<L>(mut self, language: L, bundle: FluentBundle) -> Self
+    where
+        L: Into<String>,
+    {
+        self.resources.push((language.into(), bundle));
+        self
+    }
+
+    /// Adds a bundle for every language directory (ie `en-US/`) in `path`,
+    /// made from all of the `.ftl` files inside it.
+    pub fn add_dir<P>(mut self, path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        for entry in fs::read_dir(path).with_context(|| {
+            format!("unable to read localization directory `{}`", path.display())
+        })? {
+            let entry = entry?;
+
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let lang: LanguageIdentifier = name
+                .parse()
+                .with_context(|| format!("`{}` is not a valid language identifier", name))?;
+
+            let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+
+            for file in fs::read_dir(entry.path())? {
+                let file = file?.path();
+
+                if file.extension().and_then(|ext| ext.to_str()) != Some("ftl") {
+                    continue;
+                }
+
+                let source = fs::read_to_string(&file)?;
+
+                let resource = FluentResource::try_new(source).map_err(|(_, errors)| {
+                    err!("unable to parse `{}`: {:?}", file.display(), errors)
+                })?;
+
+                bundle
+                    .add_resource(resource)
+                    .map_err(|errors| err!("unable to add `{}`: {:?}", file.display(), errors))?;
+            }
+
+            self.resources.push((name, bundle));
+        }
+
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn default_language(mut self, language: LanguageIdentifier) -> Self {
+        self.default = language;
+        self
+    }
+
+    #[must_use]
+    pub fn negotiation_strategy(mut self, strategy: NegotiationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Errors if any language is invalid or there is no bundle for the default language.
+    pub fn build(self) -> Result<FluentResources, Error> {
+        let mut bundles = HashMap::with_capacity(self.resources.len());
+
+        for (language, bundle) in self.resources {
+            let lang: LanguageIdentifier = language
+                .parse()
+                .with_context(|| format!("`{}` is not a valid language identifier", language))?;
+
+            bundles.insert(lang, Arc::new(bundle));
+        }
+
+        ensure!(
+            bundles.contains_key(&self.default),
+            "there is no bundle for the default language `{}`",
+            self.default
+        );
+
+        let mut languages = bundles.keys().cloned().collect::<Vec<_>>();
+
+        // Keeps negotiation stable between runs, `HashMap`'s order is random
+        languages.sort_by_key(|lang| lang.to_string());
+
+        Ok(FluentResources {
+            inner: Arc::new(Inner {
+                default: self.default,
+                bundles,
+                languages,
+                strategy: self.strategy,
+            }),
+        })
+    }
+}
+
+/// The negotiated language and its bundles for a single request.
+///
+/// # Note
+///
+/// Messages are looked up in every bundle in order so a partially translated
+/// language falls back to the default one.
+pub struct I18n {
+    lang: LanguageIdentifier,
+    bundles: Vec<Arc<FluentBundle>>,
+}
+
+impl I18n {
+    /// The language the page is being rendered in, for `<html lang>` and formatting.
+    pub fn lang(&self) -> &LanguageIdentifier {
+        &self.lang
+    }
+
+    /// Formats the message with the `id`, or returns the `id` itself if no
+    /// bundle has it.
+    pub fn message(&self, id: &str, args: Option<&FluentArgs<'_>>) -> String {
+        self.try_message(id, args).unwrap_or_else(|| {
+            warn!(id, "missing message");
+
+            id.to_string()
+        })
+    }
+
+    /// Formats the message with the `id`, if any bundle has it.
+    pub fn try_message(&self, id: &str, args: Option<&FluentArgs<'_>>) -> Option<String> {
+        for bundle in &self.bundles {
+            let pattern = match bundle.get_message(id).and_then(|message| message.value()) {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            let mut errors = Vec::new();
+            let value = bundle.format_pattern(pattern, args, &mut errors);
+
+            if !errors.is_empty() {
+                warn!(id, ?errors, "unable to format message");
+            }
+
+            return Some(value.into_owned());
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bundle(lang: LanguageIdentifier, source: &str) -> FluentBundle {
+        let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+
+        bundle
+            .add_resource(FluentResource::try_new(source.to_string()).unwrap())
+            .unwrap();
+
+        bundle
+    }
+
+    fn resources() -> FluentResources {
+        FluentResources::builder()
+            .add_bundle(
+                "en-US",
+                bundle(langid!("en-US"), "hello = Hello\nsign-in = Sign in"),
+            )
+            .add_bundle("de", bundle(langid!("de"), "hello = Hallo"))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let resources = resources();
+
+        let i18n = resources.negotiate(&[langid!("de")]);
+
+        assert_eq!(i18n.lang(), &langid!("de"));
+        assert_eq!(i18n.message("hello", None), "Hallo");
+
+        // Regional variants fall back to their language
+        let i18n = resources.negotiate(&[langid!("de-AT"), langid!("en-US")]);
+
+        assert_eq!(i18n.lang(), &langid!("de"));
+    }
+
+    #[test]
+    fn test_negotiate_default() {
+        let resources = resources();
+
+        assert_eq!(
+            resources.negotiate(&[langid!("fr")]).lang(),
+            &langid!("en-US")
+        );
+        assert_eq!(resources.negotiate(&[]).lang(), &langid!("en-US"));
+    }
+
+    #[test]
+    fn test_message_fallback() {
+        let i18n = resources().negotiate(&[langid!("de")]);
+
+        // Messages missing from a partial translation come from the default
+        assert_eq!(
+            i18n.try_message("sign-in", None).as_deref(),
+            Some("Sign in")
+        );
+
+        assert_eq!(i18n.try_message("missing", None), None);
+        assert_eq!(i18n.message("missing", None), "missing");
+    }
+
+    #[test]
+    fn test_build_without_default() {
+        let result = FluentResources::builder()
+            .add_bundle("de", bundle(langid!("de"), "hello = Hallo"))
+            .build();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,1250 @@
+//! Imports works downloaded from AO3 (or an archive using the same layout)
+//! as HTML or EPUB files.
+//!
+//! A download is read into a [`Work`] without touching the database, its
+//! tags are then matched against the existing ones by [`resolve`] and the
+//! story and its chapters are created by [`save`].
+//!
+//! # Note
+//!
+//! Tags that don't match an existing one are queued as [`TagRequest`]s
+//! rather than being created, so they can be checked by hand first.
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Read},
+};
+
+use html5ever::{
+    tendril::StrTendril,
+    tokenizer::{
+        states::RawKind, BufferQueue, Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer,
+        TokenizerOpts,
+    },
+};
+use zip::ZipArchive;
+
+use crate::{
+    backend::{
+        ArcBackend, CharacterEntity, OriginEntity, PairingEntity, StoryEntity, TagEntity,
+        UserEntity, WarningEntity,
+    },
+    error::Invalid,
+    html, markdown,
+    models::{
+        story::{
+            Chapter, ChapterMarkdown, Rating, Relationship, State, Story, TagRequest,
+            TagRequestKind,
+        },
+        Either, Id, New,
+    },
+    prelude::{ensure, err, Context, Error, TryFrom, Validate},
+};
+
+/// A work as it was read from a download, with its tags as they were written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Work {
+    pub name: String,
+    /// The summary as plain text.
+    pub summary: String,
+    /// The language as a BCP-47 tag, `en` if it wasn't recognised.
+    pub language: String,
+
+    /// The rating, works that weren't rated are treated as explicit.
+    pub rating: Rating,
+    /// The state, works without chapter counts are treated as completed.
+    pub state: State,
+
+    /// Fandoms.
+    pub origins: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Relationships, ie `A/B` for romantic and `A & B` for platonic ones.
+    pub pairings: Vec<String>,
+    pub characters: Vec<String>,
+    /// Freeform (additional) tags.
+    pub tags: Vec<String>,
+
+    /// The chapters, with the work's notes at the start of the first one and
+    /// its end notes at the end of the last one.
+    pub chapters: Vec<Chapter>,
+}
+
+/// A work whose tags have been matched, ready to be saved.
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub story: Story,
+    pub chapters: Vec<Chapter>,
+    /// The tags that didn't match an existing one.
+    pub requests: Vec<TagRequest>,
+}
+
+/// The story an import created.
+#[rustfmt::skip]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Imported {
+    pub id: Id,
+    /// The tags that were queued to be created, see [`TagRequest`].
+    pub requests: Vec<TagRequest>,
+}
+
+/// Reads a download, telling EPUB and HTML files apart by their contents.
+pub fn read(bytes: &[u8]) -> Result<Work, Error> {
+    if bytes.starts_with(b"PK\x03\x04") {
+        from_epub(bytes)
+    } else {
+        from_html(std::str::from_utf8(bytes).context("HTML download is not valid UTF-8")?)
+    }
+}
+
+/// Reads a work from a single HTML file.
+pub fn from_html(html: &str) -> Result<Work, Error> {
+    from_document(&Element::parse(html))
+}
+
+/// Reads a work from an EPUB, its documents are read in reading (spine)
+/// order as if they were one HTML file.
+pub fn from_epub(bytes: &[u8]) -> Result<Work, Error> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("EPUB is not a valid archive")?;
+
+    let container = Element::parse(&entry(&mut archive, "META-INF/container.xml")?);
+
+    let package_path = container
+        .find(&|element| element.name == "rootfile")
+        .and_then(|rootfile| rootfile.attribute("full-path"))
+        .ok_or_else(|| err!("EPUB is missing its package document"))?
+        .to_string();
+
+    let package = Element::parse(&entry(&mut archive, &package_path)?);
+
+    // Paths in the package are relative to its directory
+    let base = package_path
+        .rfind('/')
+        .map(|end| &package_path[..=end])
+        .unwrap_or_default();
+
+    let mut manifest = HashMap::new();
+
+    package.each(&mut |element| {
+        if element.name == "item" {
+            if let (Some(id), Some(href)) = (element.attribute("id"), element.attribute("href")) {
+                manifest.insert(id.to_string(), href.to_string());
+            }
+        }
+    });
+
+    let mut spine = Vec::new();
+
+    package.each(&mut |element| {
+        if element.name == "itemref" {
+            if let Some(href) = element.attribute("idref").and_then(|id| manifest.get(id)) {
+                spine.push(format!("{}{}", base, href));
+            }
+        }
+    });
+
+    let mut root = Element::new("html");
+    let mut body = Element::new("body");
+
+    for path in spine {
+        let document = Element::parse(&entry(&mut archive, &path)?);
+
+        if let Some(title) = document.find(&|element| element.name == "title") {
+            if root.children.is_empty() {
+                root.children.push(Node::Element(title.clone()));
+            }
+        }
+
+        match document.find(&|element| element.name == "body") {
+            Some(inner) => body.children.extend(inner.children.iter().cloned()),
+            None => body.children.extend(document.children),
+        }
+    }
+
+    root.children.push(Node::Element(body));
+
+    from_document(&root)
+}
+
+/// The most a file in an EPUB can decompress to, so a small archive can't
+/// inflate into gigabytes.
+const MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+
+fn entry(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Result<String, Error> {
+    let file = archive
+        .by_name(path)
+        .with_context(|| format!("EPUB is missing `{}`", path))?;
+
+    ensure!(
+        file.size() <= MAX_ENTRY_SIZE,
+        "`{}` is larger than {} bytes",
+        path,
+        MAX_ENTRY_SIZE
+    );
+
+    let mut content = String::new();
+
+    // The size is whatever the archive claims, so the read is limited too
+    file.take(MAX_ENTRY_SIZE + 1)
+        .read_to_string(&mut content)
+        .with_context(|| format!("`{}` is not valid UTF-8", path))?;
+
+    ensure!(
+        content.len() as u64 <= MAX_ENTRY_SIZE,
+        "`{}` is larger than {} bytes",
+        path,
+        MAX_ENTRY_SIZE
+    );
+
+    Ok(content)
+}
+
+fn from_document(document: &Element) -> Result<Work, Error> {
+    let body = document
+        .find(&|element| element.name == "body")
+        .unwrap_or(document);
+
+    let preface = body.find(&|element| element.id() == Some("preface"));
+    let afterword = body.find(&|element| element.id() == Some("afterword"));
+
+    let name = body
+        .find(&|element| element.name == "h1")
+        .map(Element::text)
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            // The page title is `{title} - {author} - {fandom}`
+            let title = document.find(&|element| element.name == "title")?.text();
+
+            title
+                .split(" - ")
+                .next()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| err!("the work is missing its title"))?;
+
+    let mut work = Work {
+        name: truncate(name, 256),
+        summary: String::new(),
+        language: String::from("en"),
+        rating: Rating::Explicit,
+        state: State::Completed,
+        origins: Vec::new(),
+        warnings: Vec::new(),
+        pairings: Vec::new(),
+        characters: Vec::new(),
+        tags: Vec::new(),
+        chapters: Vec::new(),
+    };
+
+    if let Some(tags) = body.find(&|element| element.name == "dl" && element.has_class("tags")) {
+        for (label, values) in definitions(tags) {
+            match label.as_str() {
+                "rating" | "ratings" => work.rating = rating(values.first()),
+                "archive warning" | "archive warnings" | "warning" | "warnings" => {
+                    work.warnings = values
+                }
+                "fandom" | "fandoms" => work.origins = values,
+                "relationship" | "relationships" => work.pairings = values,
+                "character" | "characters" => work.characters = values,
+                "additional tags" | "freeform" | "tags" => work.tags = values,
+                "language" => work.language = language(values.first()),
+                "stats" => work.state = state(&values.join(" ")),
+                _ => {}
+            }
+        }
+    }
+
+    let mut notes = Vec::new();
+
+    // Without a preface the summary is looked for anywhere, but the notes
+    // would be the first chapter's
+    match preface {
+        Some(preface) => labelled(preface, &mut notes),
+        None => labelled(body, &mut notes),
+    }
+
+    if let Some(afterword) = afterword {
+        labelled(afterword, &mut notes);
+    }
+
+    let note = |name: &str| {
+        notes
+            .iter()
+            .filter(|(label, _)| label == name)
+            .map(|(_, quote)| quote.inner_html())
+            .collect::<String>()
+    };
+
+    work.summary = truncate(html::to_text(&html::sanitize(&note("summary"))), 4096);
+
+    let mut drafts = Vec::new();
+
+    let chapters = body
+        .find(&|element| element.id() == Some("chapters"))
+        .unwrap_or(body);
+
+    walk(chapters, false, &mut drafts);
+
+    drafts.retain(|draft| !draft.is_empty());
+
+    ensure!(!drafts.is_empty(), "the work doesn't have any chapters");
+
+    if preface.is_some() {
+        if let Some(first) = drafts.first_mut() {
+            first.prefix.insert_str(0, &note("notes"));
+        }
+    }
+
+    if let Some(last) = drafts.last_mut() {
+        last.suffix.push_str(&note("end notes"));
+    }
+
+    for draft in drafts {
+        let chapter = ChapterMarkdown {
+            name: draft
+                .name
+                .filter(|name| !name.is_empty())
+                .map(|name| truncate(name, 256)),
+            published: true,
+            scheduled: None,
+            prefix: markdown::from_html(&html::sanitize(&draft.prefix)),
+            main: markdown::from_html(&html::sanitize(&draft.main)),
+            suffix: markdown::from_html(&html::sanitize(&draft.suffix)),
+        };
+
+        work.chapters.push(markdown::to_chapter(chapter, None)?);
+    }
+
+    Ok(work)
+}
+
+/// Matches the work's tags against the existing ones, making `author` the
+/// story's author.
+///
+/// # Note
+///
+/// A relationship is only matched if all of its characters are.
+pub async fn resolve(data: &ArcBackend, work: Work, author: Id) -> Result<Import, Error> {
+    let author = UserEntity::get(data, author).await?;
+
+    let mut story = Story::new(
+        work.name,
+        work.summary,
+        work.language,
+        work.rating,
+        work.state,
+    );
+
+    story.authors.push(author);
+
+    let mut requests = Vec::new();
+
+    macro_rules! find {
+        ($entity:ident, $tags:expr, $field:expr, $kind:expr) => {
+            for content in $tags {
+                match $entity::find(data, content.clone()).await? {
+                    Some(found) if $field.iter().any(|tag| tag.id == found.id) => {}
+                    Some(found) => $field.push(found),
+                    None => request(&mut requests, $kind, content),
+                }
+            }
+        };
+    }
+
+    find!(
+        OriginEntity,
+        work.origins,
+        story.origins,
+        TagRequestKind::Origin
+    );
+    find!(
+        WarningEntity,
+        work.warnings,
+        story.warnings,
+        TagRequestKind::Warning
+    );
+    find!(
+        CharacterEntity,
+        work.characters,
+        story.characters,
+        TagRequestKind::Character
+    );
+    find!(TagEntity, work.tags, story.tags, TagRequestKind::General);
+
+    for content in work.pairings {
+        let mut found = None;
+
+        if let Some((relationship, names)) = pairing(&content) {
+            let mut characters = Vec::with_capacity(names.len());
+
+            for name in names {
+                match CharacterEntity::find(data, name).await? {
+                    Some(character) => characters.push(character.id),
+                    None => break,
+                }
+            }
+
+            if characters.len() >= 2 {
+                found = PairingEntity::find(data, characters, relationship).await?;
+            }
+        }
+
+        match found {
+            Some(found) if story.pairings.iter().any(|tag| tag.id == found.id) => {}
+            Some(found) => story.pairings.push(found),
+            None => request(&mut requests, TagRequestKind::Pairing, content),
+        }
+    }
+
+    let words = work
+        .chapters
+        .iter()
+        .map(|chapter| chapter.words)
+        .sum::<i64>();
+
+    story.words = i32::try_from(words).unwrap_or(i32::MAX);
+
+    Ok(Import {
+        story,
+        chapters: work.chapters,
+        requests,
+    })
+}
+
+/// Creates the story and its chapters, queueing its tag requests.
+pub async fn save(data: &ArcBackend, import: Import) -> Result<Imported, Error> {
+    import.story.validate().map_err(Invalid::from)?;
+
+    for chapter in &import.chapters {
+        chapter.validate().map_err(Invalid::from)?;
+    }
+
+    let mut story = import.story;
+
+    // The chapters' first revisions are credited to the importing author
+    let editor = story
+        .authors
+        .first()
+        .map(|author| author.id)
+        .ok_or_else(|| err!("imported story has no author"))?;
+
+    story.chapters = Some(Either::Right(Vec::new()));
+
+    let id = StoryEntity::import(
+        data,
+        New::from(story),
+        import.chapters.into_iter().map(New::from).collect(),
+        import.requests.clone(),
+        editor,
+    )
+    .await?;
+
+    Ok(Imported {
+        id,
+        requests: import.requests,
+    })
+}
+
+fn request(requests: &mut Vec<TagRequest>, kind: TagRequestKind, content: String) {
+    let exists = requests
+        .iter()
+        .any(|request| request.kind == kind && request.content.eq_ignore_ascii_case(&content));
+
+    if !exists {
+        requests.push(TagRequest { kind, content });
+    }
+}
+
+/// Splits a relationship into its characters, `/` separates romantic ones
+/// and `&` platonic ones.
+fn pairing(content: &str) -> Option<(Relationship, Vec<String>)> {
+    let (relationship, separator) = if content.contains('/') {
+        (Relationship::Romantic, '/')
+    } else if content.contains('&') {
+        (Relationship::Friends, '&')
+    } else {
+        return None;
+    };
+
+    let characters = content
+        .split(separator)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>();
+
+    (characters.len() >= 2).then_some((relationship, characters))
+}
+
+fn rating(value: Option<&String>) -> Rating {
+    let value = value.map(|value| value.to_lowercase()).unwrap_or_default();
+
+    if value.starts_with("general") {
+        Rating::General
+    } else if value.starts_with("teen") {
+        Rating::Teen
+    } else if value.starts_with("mature") {
+        Rating::Mature
+    } else {
+        Rating::Explicit
+    }
+}
+
+/// Reads the state from the stats' chapter count, ie `Chapters: 3/3` is
+/// completed and `Chapters: 2/?` is in progress.
+fn state(stats: &str) -> State {
+    let count = stats
+        .split("Chapters:")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|count| count.split_once('/'));
+
+    match count {
+        Some((written, total)) if written != total => State::InProgress,
+        _ => State::Completed,
+    }
+}
+
+/// The language's tag from the name AO3 gives it, names that already look
+/// like a tag are kept.
+fn language(value: Option<&String>) -> String {
+    const NAMES: &[(&str, &str)] = &[
+        ("english", "en"),
+        ("español", "es"),
+        ("français", "fr"),
+        ("deutsch", "de"),
+        ("italiano", "it"),
+        ("português brasileiro", "pt-BR"),
+        ("português europeu", "pt-PT"),
+        ("português", "pt"),
+        ("русский", "ru"),
+        ("українська", "uk"),
+        ("polski", "pl"),
+        ("čeština", "cs"),
+        ("nederlands", "nl"),
+        ("svenska", "sv"),
+        ("dansk", "da"),
+        ("norsk", "no"),
+        ("suomi", "fi"),
+        ("magyar", "hu"),
+        ("română", "ro"),
+        ("ελληνικά", "el"),
+        ("türkçe", "tr"),
+        ("català", "ca"),
+        ("bahasa indonesia", "id"),
+        ("tiếng việt", "vi"),
+        ("ไทย", "th"),
+        ("日本語", "ja"),
+        ("한국어", "ko"),
+        ("中文", "zh"),
+        ("العربية", "ar"),
+        ("עברית", "he"),
+    ];
+
+    let value = match value {
+        Some(value) => value.trim(),
+        None => return String::from("en"),
+    };
+
+    let lower = value.to_lowercase();
+
+    if let Some((_, tag)) = NAMES.iter().find(|(name, _)| lower.starts_with(name)) {
+        return tag.to_string();
+    }
+
+    let tag_like = !value.is_empty()
+        && value.len() <= 16
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+    if tag_like {
+        value.to_string()
+    } else {
+        String::from("en")
+    }
+}
+
+/// The values of each term in the work's tag list, with lowercase terms.
+fn definitions(list: &Element) -> Vec<(String, Vec<String>)> {
+    let mut definitions: Vec<(String, Vec<String>)> = Vec::new();
+
+    for child in list.elements() {
+        match child.name.as_str() {
+            "dt" => definitions.push((label(&child.text()), Vec::new())),
+            "dd" => {
+                if let Some((label, values)) = definitions.last_mut() {
+                    let mut links = Vec::new();
+
+                    child.each(&mut |element| {
+                        if element.name == "a" {
+                            links.push(element.text());
+                        }
+                    });
+
+                    *values = if label == "stats" {
+                        vec![child.text()]
+                    } else if links.is_empty() {
+                        child
+                            .text()
+                            .split(',')
+                            .map(|value| value.trim().to_string())
+                            .filter(|value| !value.is_empty())
+                            .collect()
+                    } else {
+                        links
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    definitions
+}
+
+/// Collects every block quote with the label that came before it, ie the
+/// `Summary` and `Notes` of the preface.
+fn labelled<'e>(element: &'e Element, found: &mut Vec<(String, &'e Element)>) {
+    let mut last = None;
+
+    for child in element.elements() {
+        if child.name == "blockquote" {
+            if let Some(label) = last.take() {
+                found.push((label, child));
+            }
+        } else if let Some(text) = note_label(child) {
+            last = Some(text);
+        } else if child.id() != Some("chapters") {
+            labelled(child, found);
+        }
+    }
+}
+
+/// A chapter as it is being read, as HTML.
+#[derive(Default)]
+struct Draft {
+    name: Option<String>,
+    prefix: String,
+    main: String,
+    suffix: String,
+}
+
+impl Draft {
+    fn is_empty(&self) -> bool {
+        self.prefix.trim().is_empty()
+            && self.main.trim().is_empty()
+            && self.suffix.trim().is_empty()
+    }
+}
+
+/// Reads chapters out of the element, a heading starts a new chapter and
+/// `userstuff` elements are their text.
+///
+/// Anything in a `meta` element that isn't a heading or note is left out,
+/// ie the "See the end of the chapter for more notes" links.
+fn walk(element: &Element, meta: bool, drafts: &mut Vec<Draft>) {
+    let mut last = None;
+
+    for child in &element.children {
+        let child = match child {
+            Node::Element(child) => child,
+            Node::Text(text) if !meta && !text.trim().is_empty() => {
+                current(drafts).main.push_str(&html::escape(text));
+
+                continue;
+            }
+            Node::Text(_) => continue,
+        };
+
+        if matches!(child.id(), Some("preface") | Some("afterword")) {
+            continue;
+        }
+
+        if matches!(child.name.as_str(), "h1" | "h2" | "h3") {
+            start(drafts, chapter_name(&child.text()));
+
+            continue;
+        }
+
+        if let Some(label) = note_label(child) {
+            last = Some(label);
+
+            continue;
+        }
+
+        if child.name == "blockquote" {
+            if let Some(label) = last.take() {
+                let draft = current(drafts);
+
+                match label.as_str() {
+                    "end notes" => draft.suffix.push_str(&child.inner_html()),
+                    _ => draft.prefix.push_str(&child.inner_html()),
+                }
+
+                continue;
+            }
+        }
+
+        last = None;
+
+        if child.has_class("userstuff") {
+            current(drafts).main.push_str(&child.inner_html());
+        } else if child.has_class("meta") {
+            walk(child, true, drafts);
+        } else if child
+            .contains(&|element| element.has_class("userstuff") || element.has_class("meta"))
+        {
+            walk(child, meta, drafts);
+        } else if !meta {
+            current(drafts).main.push_str(&child.outer_html());
+        }
+    }
+}
+
+fn current(drafts: &mut Vec<Draft>) -> &mut Draft {
+    if drafts.is_empty() {
+        drafts.push(Draft::default());
+    }
+
+    let last = drafts.len() - 1;
+
+    &mut drafts[last]
+}
+
+/// Starts a new chapter, unless the current one has no text yet in which
+/// case the heading is its name (ie a table of contents heading followed by
+/// the chapter's own heading).
+fn start(drafts: &mut Vec<Draft>, name: Option<String>) {
+    match drafts.last_mut() {
+        Some(draft) if draft.main.trim().is_empty() => draft.name = name,
+        _ => drafts.push(Draft {
+            name,
+            ..Draft::default()
+        }),
+    }
+}
+
+/// The name from a chapter heading, `Chapter 2: Name` is `Name` while
+/// `Chapter 2` doesn't have one.
+fn chapter_name(heading: &str) -> Option<String> {
+    let heading = heading.trim();
+
+    let numbered = heading
+        .get(..8)
+        .filter(|start| start.eq_ignore_ascii_case("chapter "));
+
+    match numbered {
+        Some(_) => heading
+            .split_once(':')
+            .map(|(_, name)| name.trim().to_string()),
+        None => Some(heading.to_string()),
+    }
+    .filter(|name| !name.is_empty())
+}
+
+/// The label of the note that follows the element, if it is one.
+fn note_label(element: &Element) -> Option<String> {
+    if !matches!(element.name.as_str(), "p" | "h3" | "h4" | "b" | "strong") {
+        return None;
+    }
+
+    let label = label(&element.text());
+    let label = label.strip_prefix("chapter ").unwrap_or(&label);
+
+    matches!(label, "summary" | "notes" | "end notes").then(|| label.to_string())
+}
+
+fn label(text: &str) -> String {
+    text.trim().trim_end_matches(':').trim().to_lowercase()
+}
+
+fn truncate(mut text: String, max: usize) -> String {
+    if let Some((end, _)) = text.char_indices().nth(max) {
+        text.truncate(end);
+    }
+
+    text
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+/// An element of a downloaded document.
+///
+/// # Note
+///
+/// This is a lenient tree, unclosed elements are closed by their parent and
+/// stray end tags are ignored.
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Reads a (possibly malformed) HTML or XML document with html5ever's
+    /// tokenizer, keeping only the elements and their text.
+    fn parse(document: &str) -> Self {
+        let mut tokenizer = Tokenizer::new(
+            Builder {
+                stack: vec![Element::new("#document")],
+            },
+            TokenizerOpts::default(),
+        );
+
+        let mut input = BufferQueue::new();
+        input.push_back(StrTendril::from_slice(document));
+
+        let _ = tokenizer.feed(&mut input);
+        tokenizer.end();
+
+        let mut stack = tokenizer.sink.stack;
+
+        while stack.len() > 1 {
+            close(&mut stack);
+        }
+
+        stack.pop().unwrap_or_else(|| Element::new("#document"))
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn id(&self) -> Option<&str> {
+        self.attribute("id")
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .map(|classes| classes.split_whitespace().any(|name| name == class))
+            .unwrap_or(false)
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Calls `f` with every element inside this one, depth first.
+    fn each<'e>(&'e self, f: &mut dyn FnMut(&'e Element)) {
+        for child in self.elements() {
+            f(child);
+            child.each(f);
+        }
+    }
+
+    /// The first element inside this one that matches, depth first.
+    fn find(&self, predicate: &dyn Fn(&Element) -> bool) -> Option<&Element> {
+        for child in self.elements() {
+            if predicate(child) {
+                return Some(child);
+            }
+
+            if let Some(found) = child.find(predicate) {
+                return Some(found);
+            }
+        }
+
+        None
+    }
+
+    fn contains(&self, predicate: &dyn Fn(&Element) -> bool) -> bool {
+        self.find(predicate).is_some()
+    }
+
+    /// The element's text with its whitespace collapsed.
+    fn text(&self) -> String {
+        fn collect(element: &Element, out: &mut String) {
+            for child in &element.children {
+                match child {
+                    Node::Element(child) => collect(child, out),
+                    Node::Text(text) => {
+                        out.push_str(text);
+                        out.push(' ');
+                    }
+                }
+            }
+        }
+
+        let mut text = String::new();
+
+        collect(self, &mut text);
+
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn inner_html(&self) -> String {
+        let mut out = String::new();
+
+        for child in &self.children {
+            match child {
+                Node::Element(child) => out.push_str(&child.outer_html()),
+                Node::Text(text) => out.push_str(&html::escape(text)),
+            }
+        }
+
+        out
+    }
+
+    fn outer_html(&self) -> String {
+        let mut out = format!("<{}", self.name);
+
+        for (name, value) in &self.attributes {
+            out.push_str(&format!(r#" {}="{}""#, name, html::escape(value)));
+        }
+
+        out.push('>');
+        out.push_str(&self.inner_html());
+        out.push_str(&format!("</{}>", self.name));
+
+        out
+    }
+}
+
+fn close(stack: &mut Vec<Element>) {
+    if stack.len() < 2 {
+        return;
+    }
+
+    if let Some(element) = stack.pop() {
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(Node::Element(element));
+        }
+    }
+}
+
+/// Builds the [`Element`] tree from the tokens of a document, closing any
+/// elements left open the way a browser would for the few that matter here.
+struct Builder {
+    stack: Vec<Element>,
+}
+
+impl Builder {
+    const VOID: &'static [&'static str] = &[
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "wbr",
+    ];
+
+    fn start_tag(&mut self, tag: Tag) -> TokenSinkResult<()> {
+        // Namespaced elements (ie `opf:item`) are read by their local name
+        let name = local(&tag.name);
+
+        // These close the one before them when they aren't
+        if matches!(name.as_str(), "p" | "li" | "dt" | "dd")
+            && self
+                .stack
+                .last()
+                .map(|open| open.name == name)
+                .unwrap_or(false)
+        {
+            close(&mut self.stack);
+        }
+
+        let element = Element {
+            attributes: tag
+                .attrs
+                .into_iter()
+                .map(|attribute| {
+                    (
+                        attribute.name.local.to_lowercase(),
+                        attribute.value.to_string(),
+                    )
+                })
+                .collect(),
+            children: Vec::new(),
+            name,
+        };
+
+        if tag.self_closing || Self::VOID.contains(&element.name.as_str()) {
+            if let Some(parent) = self.stack.last_mut() {
+                parent.children.push(Node::Element(element));
+            }
+
+            return TokenSinkResult::Continue;
+        }
+
+        // Their content is text, not markup, until their end tag
+        let raw = match element.name.as_str() {
+            "script" => Some(RawKind::ScriptData),
+            "style" => Some(RawKind::Rawtext),
+            "title" | "textarea" => Some(RawKind::Rcdata),
+            _ => None,
+        };
+
+        self.stack.push(element);
+
+        match raw {
+            Some(raw) => TokenSinkResult::RawData(raw),
+            None => TokenSinkResult::Continue,
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        let name = local(&tag.name);
+
+        if let Some(open) = self
+            .stack
+            .iter()
+            .skip(1)
+            .rposition(|open| open.name == name)
+        {
+            while self.stack.len() > open + 1 {
+                close(&mut self.stack);
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(parent) = self.stack.last_mut() {
+            // The tokenizer can split a run of text into several tokens
+            match parent.children.last_mut() {
+                Some(Node::Text(last)) => last.push_str(text),
+                _ => parent.children.push(Node::Text(text.to_string())),
+            }
+        }
+    }
+}
+
+impl TokenSink for Builder {
+    type Handle = ();
+
+    fn process_token(&mut self, token: Token, _line: u64) -> TokenSinkResult<()> {
+        match token {
+            Token::TagToken(tag) if tag.kind == TagKind::StartTag => return self.start_tag(tag),
+            Token::TagToken(tag) => self.end_tag(tag),
+            Token::CharacterTokens(text) => self.text(&text),
+            // Comments, doctypes and processing instructions aren't content
+            _ => {}
+        }
+
+        TokenSinkResult::Continue
+    }
+}
+
+/// The lowercase name of an element without its namespace prefix.
+fn local(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::models::core::Part;
+
+    const WORK: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="UTF-8">
+<title>The Long Way - someone - Original Work [Archive of Our Own]</title>
+</head>
+<body>
+<div id="preface">
+  <p class="message"><b>The Long Way</b><br/>Posted originally on the <a href="https://archiveofourown.org/">Archive of Our Own</a>.</p>
+  <div class="meta">
+    <dl class="tags">
+      <dt>Rating:</dt>
+      <dd><a href="/tags/Teen">Teen And Up Audiences</a></dd>
+      <dt>Archive Warning:</dt>
+      <dd><a href="/tags/None">No Archive Warnings Apply</a></dd>
+      <dt>Fandom:</dt>
+      <dd><a href="/tags/Original">Original Work</a></dd>
+      <dt>Relationship:</dt>
+      <dd><a href="/tags/1">Ana/Bea</a>, <a href="/tags/2">Ana &amp; Cal</a></dd>
+      <dt>Character:</dt>
+      <dd><a href="/tags/Ana">Ana</a>, <a href="/tags/Bea">Bea</a></dd>
+      <dt>Additional Tags:</dt>
+      <dd><a href="/tags/Fluff">Fluff</a>, <a href="/tags/Road">Road Trips</a></dd>
+      <dt>Language:</dt>
+      <dd>Español</dd>
+      <dt>Stats:</dt>
+      <dd>Published: 2020-01-01 Updated: 2020-02-01 Words: 12 Chapters: 2/3</dd>
+    </dl>
+    <h1>The Long Way</h1>
+    <div class="byline">by <a rel="author" href="/users/someone">someone</a></div>
+    <p>Summary</p>
+    <blockquote class="userstuff"><p>Two friends &amp; a <em>car</em>.</p></blockquote>
+    <p>Notes</p>
+    <blockquote class="userstuff"><p>Thanks for reading!</p></blockquote>
+  </div>
+</div>
+<div id="chapters" class="userstuff">
+  <h2 class="toc-heading">Chapter 1</h2>
+  <div class="meta group">
+    <h2 class="heading">Chapter 1: Leaving</h2>
+    <p>Chapter Notes</p>
+    <blockquote class="userstuff"><p>First!</p></blockquote>
+    <p>(See the end of the chapter for <a href="#end">more notes</a>.)</p>
+  </div>
+  <div class="userstuff"><p>They left <strong>early</strong>.</p><p>It rained.<script>alert(1)</script></p></div>
+  <div class="meta group">
+    <p>Chapter End Notes</p>
+    <blockquote class="userstuff"><p>Soon.</p></blockquote>
+  </div>
+  <h2 class="toc-heading">Chapter 2</h2>
+  <div class="meta group"><h2 class="heading">Chapter 2</h2></div>
+  <div class="userstuff"><p>They arrived.</p></div>
+</div>
+<div id="afterword">
+  <div id="endnotes"><p>End Notes</p><blockquote class="userstuff"><p>The end, for now.</p></blockquote></div>
+</div>
+</body>
+</html>"##;
+
+    fn texts(parts: &[crate::models::Existing<Part>]) -> Vec<String> {
+        parts
+            .iter()
+            .map(|part| match &**part {
+                Part::Text(text) => text.content.clone(),
+                part => format!("{:?}", part),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_html() {
+        let work = from_html(WORK).unwrap();
+
+        assert_eq!(work.name, "The Long Way");
+        assert_eq!(work.summary, "Two friends & a car.");
+        assert_eq!(work.language, "es");
+        assert_eq!(work.rating, Rating::Teen);
+        assert_eq!(work.state, State::InProgress);
+        assert_eq!(work.warnings, vec!["No Archive Warnings Apply"]);
+        assert_eq!(work.origins, vec!["Original Work"]);
+        assert_eq!(work.pairings, vec!["Ana/Bea", "Ana & Cal"]);
+        assert_eq!(work.characters, vec!["Ana", "Bea"]);
+        assert_eq!(work.tags, vec!["Fluff", "Road Trips"]);
+
+        assert_eq!(work.chapters.len(), 2);
+
+        let first = &work.chapters[0];
+
+        assert_eq!(first.name.as_deref(), Some("Leaving"));
+        assert_eq!(
+            texts(&first.prefix),
+            vec!["<p>Thanks for reading!</p>", "<p>First!</p>"]
+        );
+        assert_eq!(
+            texts(&first.main),
+            vec![
+                "<p>They left <strong>early</strong>.</p>",
+                "<p>It rained.</p>"
+            ]
+        );
+        assert_eq!(texts(&first.suffix), vec!["<p>Soon.</p>"]);
+        assert_eq!(first.words, 5);
+
+        let second = &work.chapters[1];
+
+        assert_eq!(second.name, None);
+        assert_eq!(texts(&second.prefix), Vec::<String>::new());
+        assert_eq!(texts(&second.main), vec!["<p>They arrived.</p>"]);
+        assert_eq!(texts(&second.suffix), vec!["<p>The end, for now.</p>"]);
+    }
+
+    #[test]
+    fn test_epub() {
+        use std::io::Write;
+
+        use zip::write::{FileOptions, ZipWriter};
+
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+
+        let files = [
+            ("mimetype", "application/epub+zip"),
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0"?><container><rootfiles><rootfile full-path="OEBPS/content.opf"/></rootfiles></container>"#,
+            ),
+            (
+                "OEBPS/content.opf",
+                r#"<?xml version="1.0"?><package><manifest><item id="preface" href="preface.xhtml"/><item id="c1" href="chapter1.xhtml"/></manifest><spine><itemref idref="preface"/><itemref idref="c1"/></spine></package>"#,
+            ),
+            (
+                "OEBPS/preface.xhtml",
+                r#"<?xml version="1.0"?><html><head><title>Short</title></head><body><div id="preface"><h1>Short</h1><dl class="tags"><dt>Rating:</dt><dd>Not Rated</dd></dl></div></body></html>"#,
+            ),
+            (
+                "OEBPS/chapter1.xhtml",
+                r#"<?xml version="1.0"?><html><body><div class="meta group"><h2 class="heading">Chapter 1</h2></div><div class="userstuff"><p>Only one.</p></div></body></html>"#,
+            ),
+        ];
+
+        for (name, content) in files {
+            archive.start_file(name, FileOptions::default()).unwrap();
+            archive.write_all(content.as_bytes()).unwrap();
+        }
+
+        let bytes = archive.finish().unwrap().into_inner();
+
+        let work = read(&bytes).unwrap();
+
+        assert_eq!(work.name, "Short");
+        assert_eq!(work.rating, Rating::Explicit);
+        assert_eq!(work.state, State::Completed);
+        assert_eq!(work.chapters.len(), 1);
+        assert_eq!(texts(&work.chapters[0].main), vec!["<p>Only one.</p>"]);
+    }
+
+    #[test]
+    fn test_oversized_entry() {
+        use std::io::Write;
+
+        use zip::write::{FileOptions, ZipWriter};
+
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+
+        // Spaces compress to almost nothing, but inflate past the limit
+        archive
+            .start_file("META-INF/container.xml", FileOptions::default())
+            .unwrap();
+        archive
+            .write_all(&vec![b' '; MAX_ENTRY_SIZE as usize + 1])
+            .unwrap();
+
+        let bytes = archive.finish().unwrap().into_inner();
+
+        assert!(bytes.len() < 1024 * 1024);
+
+        let err = from_epub(&bytes).unwrap_err();
+
+        assert!(err.to_string().contains("is larger than"), "{}", err);
+    }
+
+    #[test]
+    fn test_pairings() {
+        assert_eq!(
+            pairing("Ana/Bea"),
+            Some((
+                Relationship::Romantic,
+                vec![String::from("Ana"), String::from("Bea")]
+            ))
+        );
+        assert_eq!(
+            pairing("Ana & Cal"),
+            Some((
+                Relationship::Friends,
+                vec![String::from("Ana"), String::from("Cal")]
+            ))
+        );
+        assert_eq!(pairing("Ana"), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! The background jobs, each run by its own [`Evermore`] worker until the
+//! server shuts down.
+
+use std::{
+    convert::Infallible,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use evermore::{Evermore, Worker};
+use futures_util::FutureExt as _;
+
+use crate::{abandon, backend::ArcBackend, prelude::*, schedule, settings::Settings};
+
+/// How often a stopping worker notices it should stop.
+const POLL: Duration = Duration::from_secs(1);
+
+/// Runs every job until `signal` completes.
+pub async fn run<S>(data: ArcBackend, settings: Settings, signal: S)
+where
+    S: Future<Output = ()> + Send + 'static,
+{
+    let signal = signal.shared();
+
+    futures_util::future::join(
+        Evermore::new(signal.clone(), 1, data, |worker| {
+            Box::pin(schedule::task(worker))
+        }),
+        Evermore::new(signal, 1, settings, |worker| {
+            Box::pin(abandon::task(worker))
+        }),
+    )
+    .await;
+}
+
+/// Runs `job` every `period` until the worker is told to stop.
+///
+/// # Note
+///
+/// Errors are logged and the job is retried on the next period, so a worker
+/// never fails.
+pub async fn repeat<D, F, Fut>(
+    worker: &Worker<D>,
+    name: &'static str,
+    period: Duration,
+    mut job: F,
+) -> Result<(), Infallible>
+where
+    D: Clone,
+    F: FnMut(D) -> Fut,
+    Fut: Future<Output = Result<(), Error>>,
+{
+    let mut last: Option<Instant> = None;
+
+    while !worker.should_stop() {
+        let due = match last {
+            Some(last) => last.elapsed() >= period,
+            None => true,
+        };
+
+        if due {
+            last = Some(Instant::now());
+
+            if let Err(err) = job(worker.data().clone()).await {
+                error!(job = name, error = ?err, "job failed");
+            }
+        }
+
+        tokio::time::sleep(POLL).await;
+    }
+
+    Ok(())
+}
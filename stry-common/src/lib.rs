@@ -2,17 +2,30 @@
 
 pub mod utils;
 
+pub mod abandon;
+pub mod auth;
 pub mod backend;
 pub mod dataloader;
+pub mod diff;
+pub mod i18n;
+pub mod import;
+pub mod jobs;
+pub mod limiter;
 pub mod loader;
+pub mod markdown;
+pub mod media;
 pub mod members;
 pub mod models;
+pub mod schedule;
+pub mod settings;
 
 pub mod config;
 pub mod error;
+pub mod html;
 pub mod http;
 // pub mod layered;
 pub mod uri;
+pub mod words;
 
 pub mod prelude {
     pub use crate::{members, utils::Member};
@@ -0,0 +1,399 @@
+//! Sliding window attempt limiting and temporary account lockouts.
+//!
+//! Attempts are tracked per key (ie `session:ip:127.0.0.1`) in a
+//! [`LimiterStore`], either in-process with [`MemoryStore`] or in a database
+//! backend so the limits survive restarts and are shared across instances.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+};
+
+use dashmap::DashMap;
+
+pub use time::Duration;
+
+use crate::{error::TooManyRequests, prelude::*};
+
+/// A maximum number of attempts allowed within a sliding window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    attempts: usize,
+    window: Duration,
+}
+
+impl Rule {
+    /// # Panics
+    ///
+    /// If `attempts` is zero, a rule has to allow at least one attempt.
+    pub const fn new(attempts: usize, window: Duration) -> Self {
+        assert!(attempts > 0, "a rule has to allow at least one attempt");
+
+        Self { attempts, window }
+    }
+
+    pub const fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    pub const fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Whether `count` attempts within the window use up the rule, once they
+    /// do any other attempt is refused.
+    pub const fn reached(&self, count: usize) -> bool {
+        count >= self.attempts
+    }
+}
+
+/// How many failures are allowed before a key is locked, and for how long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lockout {
+    pub failures: Rule,
+    pub duration: Duration,
+}
+
+impl Lockout {
+    pub const fn new(failures: Rule, duration: Duration) -> Self {
+        Self { failures, duration }
+    }
+}
+
+/// A storage location for attempt timestamps and lockouts.
+///
+/// # Note
+///
+/// The store is in charge of its own clock, this allows database stores to
+/// use the database's time so multiple instances agree with each other.
+#[async_trait]
+pub trait LimiterStore {
+    /// Records an attempt for `key`, returning every attempt (including the
+    /// new one) that is still within the `window`, oldest first.
+    async fn record(&self, key: &str, window: Duration) -> Result<Vec<OffsetDateTime>, Error>;
+
+    /// Removes every recorded attempt for `key`.
+    async fn clear(&self, key: &str) -> Result<(), Error>;
+
+    /// Locks `key` for the given `duration`, replacing any existing lock.
+    async fn lock(&self, key: &str, duration: Duration) -> Result<(), Error>;
+
+    /// Returns the time the lock on `key` expires, if it is currently locked.
+    async fn locked(&self, key: &str) -> Result<Option<OffsetDateTime>, Error>;
+}
+
+/// An in-process [`LimiterStore`], limits are lost on restart and are not
+/// shared between instances.
+///
+/// Keys that have no attempts left in their window and expired locks are
+/// swept at most once every [`MemoryStore::SWEEP_INTERVAL`], so keys that
+/// are never seen again don't stay around forever.
+#[derive(Default)]
+pub struct MemoryStore {
+    attempts: DashMap<String, Attempts>,
+    locks: DashMap<String, OffsetDateTime>,
+    /// The unix timestamp of the last sweep.
+    swept: AtomicI64,
+}
+
+#[derive(Default)]
+struct Attempts {
+    window: Duration,
+    times: VecDeque<OffsetDateTime>,
+}
+
+impl Attempts {
+    fn prune(&mut self, now: OffsetDateTime) {
+        while matches!(self.times.front(), Some(attempt) if *attempt <= now - self.window) {
+            self.times.pop_front();
+        }
+    }
+}
+
+impl MemoryStore {
+    pub const SWEEP_INTERVAL: Duration = Duration::minutes(1);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes every key without attempts in its window and every expired
+    /// lock, if the last sweep was long enough ago.
+    fn sweep(&self, now: OffsetDateTime) {
+        let last = self.swept.load(Ordering::Relaxed);
+
+        if now.unix_timestamp() - last < Self::SWEEP_INTERVAL.whole_seconds() {
+            return;
+        }
+
+        // Only one caller gets to sweep, the rest carry on
+        if self
+            .swept
+            .compare_exchange(
+                last,
+                now.unix_timestamp(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return;
+        }
+
+        self.attempts.retain(|_, attempts| {
+            attempts.prune(now);
+
+            !attempts.times.is_empty()
+        });
+
+        self.locks.retain(|_, until| *until > now);
+    }
+}
+
+#[async_trait]
+impl LimiterStore for MemoryStore {
+    async fn record(&self, key: &str, window: Duration) -> Result<Vec<OffsetDateTime>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        self.sweep(now);
+
+        let mut attempts = self.attempts.entry(key.to_string()).or_default();
+
+        attempts.window = window;
+        attempts.prune(now);
+        attempts.times.push_back(now);
+
+        Ok(attempts.times.iter().copied().collect())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), Error> {
+        self.attempts.remove(key);
+
+        Ok(())
+    }
+
+    async fn lock(&self, key: &str, duration: Duration) -> Result<(), Error> {
+        self.locks
+            .insert(key.to_string(), OffsetDateTime::now_utc() + duration);
+
+        Ok(())
+    }
+
+    async fn locked(&self, key: &str) -> Result<Option<OffsetDateTime>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        self.sweep(now);
+
+        // The lock being asked about is removed straight away rather than
+        // waiting for the next sweep
+        if self
+            .locks
+            .remove_if(key, |_, until| *until <= now)
+            .is_some()
+        {
+            return Ok(None);
+        }
+
+        Ok(self.locks.get(key).map(|until| *until))
+    }
+}
+
+/// Applies [`Rule`]s and [`Lockout`]s on top of a shared [`LimiterStore`].
+///
+/// Every check returns a [`TooManyRequests`] error when the limit is hit,
+/// which the services turn into a `429` response with `Retry-After`.
+#[derive(Clone)]
+pub struct Limiter {
+    store: Arc<dyn LimiterStore + Send + Sync + 'static>,
+}
+
+impl Limiter {
+    pub fn new<S>(store: S) -> Self
+    where
+        S: LimiterStore + Send + Sync + 'static,
+    {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    /// Records an attempt for `key`, erroring if the attempts before it
+    /// already reached the rule within its window.
+    pub async fn check(&self, key: &str, rule: Rule) -> Result<(), Error> {
+        let attempts = self.store.record(key, rule.window).await?;
+
+        // The store includes the attempt being checked
+        if rule.reached(attempts.len() - 1) {
+            // The attempt that has to leave the window before another is
+            // allowed, rules always allow at least one so this is never the
+            // one being checked
+            let expiring = attempts[attempts.len() - rule.attempts];
+
+            return Err(TooManyRequests::until(expiring + rule.window).into());
+        }
+
+        Ok(())
+    }
+
+    /// Errors if `key` is currently locked out.
+    pub async fn ensure_unlocked(&self, key: &str) -> Result<(), Error> {
+        match self.store.locked(key).await? {
+            Some(until) => Err(TooManyRequests::until(until).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Records a failed attempt for `key`, locking it once the failures reach
+    /// the lockout's rule within its window.
+    pub async fn failure(&self, key: &str, lockout: Lockout) -> Result<(), Error> {
+        let failures = self
+            .store
+            .record(&failure_key(key), lockout.failures.window)
+            .await?;
+
+        if lockout.failures.reached(failures.len()) {
+            warn!(key = %key, failures = failures.len(), "locking key after repeated failures");
+
+            self.store.lock(key, lockout.duration).await?;
+            self.store.clear(&failure_key(key)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Forgets any previous failures for `key`.
+    pub async fn success(&self, key: &str) -> Result<(), Error> {
+        self.store.clear(&failure_key(key)).await
+    }
+}
+
+fn failure_key(key: &str) -> String {
+    format!("{}:failures", key)
+}
+
+/// A type used for database responses, maps to a row in the limiter table.
+pub struct AttemptRecord {
+    pub attempted: OffsetDateTime,
+}
+
+/// A type used for database responses, maps to a row in the limiter lock table.
+pub struct LockRecord {
+    pub until: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RULE: Rule = Rule::new(2, Duration::minutes(1));
+    const LOCKOUT: Lockout = Lockout::new(
+        Rule::new(2, Duration::minutes(1)),
+        Duration::milliseconds(200),
+    );
+
+    fn is_too_many_requests(result: Result<(), Error>) -> bool {
+        matches!(result, Err(err) if err.downcast_ref::<TooManyRequests>().is_some())
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let limiter = Limiter::new(MemoryStore::new());
+
+        assert!(limiter.check("a", RULE).await.is_ok());
+        assert!(limiter.check("a", RULE).await.is_ok());
+        assert!(is_too_many_requests(limiter.check("a", RULE).await));
+
+        // Keys are limited separately
+        assert!(limiter.check("b", RULE).await.is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one attempt")]
+    fn test_rule_zero() {
+        let _ = Rule::new(0, Duration::minutes(1));
+    }
+
+    #[tokio::test]
+    async fn test_check_matches_failure() {
+        // Both allow the same number of attempts before refusing the next
+        let limiter = Limiter::new(MemoryStore::new());
+
+        for _ in 0..RULE.attempts() {
+            assert!(limiter.check("a", RULE).await.is_ok());
+            assert!(limiter.ensure_unlocked("b").await.is_ok());
+
+            limiter.failure("b", LOCKOUT).await.unwrap();
+        }
+
+        assert!(is_too_many_requests(limiter.check("a", RULE).await));
+        assert!(is_too_many_requests(limiter.ensure_unlocked("b").await));
+    }
+
+    #[tokio::test]
+    async fn test_check_window() {
+        let limiter = Limiter::new(MemoryStore::new());
+        let rule = Rule::new(1, Duration::milliseconds(100));
+
+        assert!(limiter.check("a", rule).await.is_ok());
+        assert!(is_too_many_requests(limiter.check("a", rule).await));
+
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+        assert!(limiter.check("a", rule).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failure_lockout() {
+        let limiter = Limiter::new(MemoryStore::new());
+
+        assert!(limiter.ensure_unlocked("a").await.is_ok());
+
+        limiter.failure("a", LOCKOUT).await.unwrap();
+        assert!(limiter.ensure_unlocked("a").await.is_ok());
+
+        limiter.failure("a", LOCKOUT).await.unwrap();
+        assert!(is_too_many_requests(limiter.ensure_unlocked("a").await));
+        assert!(limiter.ensure_unlocked("b").await.is_ok());
+
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+
+        assert!(limiter.ensure_unlocked("a").await.is_ok());
+
+        // The failures were cleared when the key was locked
+        limiter.failure("a", LOCKOUT).await.unwrap();
+        assert!(limiter.ensure_unlocked("a").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_success_clears_failures() {
+        let limiter = Limiter::new(MemoryStore::new());
+
+        limiter.failure("a", LOCKOUT).await.unwrap();
+        limiter.success("a").await.unwrap();
+        limiter.failure("a", LOCKOUT).await.unwrap();
+
+        assert!(limiter.ensure_unlocked("a").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_sweep() {
+        let store = MemoryStore::new();
+
+        store.record("a", Duration::milliseconds(50)).await.unwrap();
+        store.record("b", Duration::minutes(1)).await.unwrap();
+        store.lock("c", Duration::milliseconds(50)).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        // Pretend the last sweep was long enough ago
+        store.swept.store(0, Ordering::Relaxed);
+        store.sweep(OffsetDateTime::now_utc());
+
+        assert!(!store.attempts.contains_key("a"));
+        assert!(store.attempts.contains_key("b"));
+        assert!(!store.locks.contains_key("c"));
+    }
+}
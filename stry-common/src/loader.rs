@@ -1,18 +1,18 @@
 macro_rules! impl_loader {
-    ($( $name:ident => ($entity:path, $value:path) , )*) => {
+    ($( $name:ident => ($entity:path, $value:path $(, $arg:expr)*) , )*) => {
         $(
-            pub struct $name<B: crate::backend::Backend + Send + Sync + 'static> {
+            pub struct $name<B: $entity + Send + Sync + 'static> {
                 backend: B,
             }
 
-            impl<B: crate::backend::Backend + Send + Sync + 'static> $name<B> {
+            impl<B: $entity + Send + Sync + 'static> $name<B> {
                 pub fn new(backend: B) -> crate::dataloader::Batcher<$name<B>> {
                     crate::dataloader::Batcher::builder(Self { backend }).build()
                 }
             }
 
             #[crate::prelude::async_trait]
-            impl<B: crate::backend::Backend + Send + Sync + 'static> crate::dataloader::Fetcher for $name<B> {
+            impl<B: $entity + Send + Sync + 'static> crate::dataloader::Fetcher for $name<B> {
                 type Key = crate::models::Id;
                 type Value = crate::models::Existing<$value>;
                 type Error = crate::prelude::Error;
@@ -22,10 +22,12 @@ macro_rules! impl_loader {
                     keys: &[Self::Key],
                     values: &mut crate::dataloader::Cache<'_, Self::Key, Self::Value>,
                 ) -> Result<(), Self::Error> {
-                    for id in keys {
-                        let value = <B as $entity>::get(&self.backend, *id).await?;
+                    // The whole batch is one lookup, anything missing from it is marked
+                    // as not found by the batcher instead of failing the other keys
+                    let found = <B as $entity>::many(&self.backend, keys.to_vec() $(, $arg)*).await?;
 
-                        values.insert(*id, value);
+                    for value in found {
+                        values.insert(value.id, value);
                     }
 
                     Ok(())
@@ -54,6 +56,10 @@ pub mod story {
         WarningLoader => (crate::backend::WarningEntity ,crate::models::story::Warning),
         PairingLoader => (crate::backend::PairingEntity ,crate::models::story::Pairing),
         CharacterLoader => (crate::backend::CharacterEntity ,crate::models::story::Character),
+        // Chapters are loaded as any reader would see them
+        ChapterLoader => (crate::backend::ChapterEntity ,crate::models::story::Chapter, None),
+        StoryLoader => (crate::backend::StoryEntity ,crate::models::story::Story),
+        SeriesLoader => (crate::backend::SeriesEntity ,crate::models::story::Series),
     }
 
     #[derive(Clone)]
@@ -63,6 +69,10 @@ pub mod story {
 
         pub origin: Batcher<OriginLoader<B>>,
         pub warning: Batcher<WarningLoader<B>>,
+        pub pairing: Batcher<PairingLoader<B>>,
+        pub character: Batcher<CharacterLoader<B>>,
+
+        pub series: Batcher<SeriesLoader<B>>,
     }
 
     impl<B: Backend + Clone + Send + Sync + 'static> StoryLoaders<B> {
@@ -72,8 +82,120 @@ pub mod story {
                 tag: TagLoader::new(backend.clone()),
 
                 origin: OriginLoader::new(backend.clone()),
-                warning: WarningLoader::new(backend),
+                warning: WarningLoader::new(backend.clone()),
+                pairing: PairingLoader::new(backend.clone()),
+                character: CharacterLoader::new(backend.clone()),
+
+                series: SeriesLoader::new(backend),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        backend::TagEntity,
+        dataloader::LoadError,
+        models::{core::Tag, story::TagRequest, Existing, Id},
+        prelude::*,
+    };
+
+    use super::core::TagLoader;
+
+    /// Has every tag except `missing0`, remembering the keys of every lookup.
+    #[derive(Clone, Default)]
+    struct Tags {
+        lookups: Arc<Mutex<Vec<Vec<Id>>>>,
+    }
+
+    fn id(id: &str) -> Id {
+        Id::try_from(id).unwrap()
+    }
+
+    #[async_trait]
+    impl TagEntity for Tags {
+        async fn get(&self, _id: Id) -> Result<Existing<Tag>, Error> {
+            unreachable!("tags are only loaded in batches")
+        }
+
+        async fn many(&self, ids: Vec<Id>) -> Result<Vec<Existing<Tag>>, Error> {
+            self.lookups.lock().unwrap().push(ids.clone());
+
+            Ok(ids
+                .into_iter()
+                .filter(|tag| tag.as_str() != "missing0")
+                .map(|tag| {
+                    let data = Tag {
+                        content: tag.as_str().to_string(),
+                        description: String::new(),
+                    };
+
+                    Existing::new(
+                        tag,
+                        data,
+                        OffsetDateTime::now_utc(),
+                        OffsetDateTime::now_utc(),
+                    )
+                })
+                .collect())
+        }
+
+        async fn all(&self, _cursor: Option<Id>, _limit: i64) -> Result<Vec<Existing<Tag>>, Error> {
+            unreachable!()
+        }
+
+        async fn find(&self, _content: String) -> Result<Option<Existing<Tag>>, Error> {
+            unreachable!()
+        }
+
+        async fn request(&self, _story: Id, _request: TagRequest) -> Result<(), Error> {
+            unreachable!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_keys() {
+        let tags = Tags::default();
+        let loader = TagLoader::new(tags.clone());
+
+        let keys = [id("cccccccc"), id("aaaaaaaa")];
+
+        let (a, b, many) = tokio::join!(
+            loader.load(id("aaaaaaaa")),
+            loader.load(id("bbbbbbbb")),
+            loader.load_many(&keys),
+        );
+
+        assert_eq!(a.unwrap().content, "aaaaaaaa");
+        assert_eq!(b.unwrap().content, "bbbbbbbb");
+        assert_eq!(many.unwrap().len(), 2);
+
+        let lookups = tags.lookups.lock().unwrap();
+
+        assert_eq!(
+            lookups.len(),
+            1,
+            "every key should be fetched in one lookup"
+        );
+
+        let mut keys = lookups[0].iter().map(Id::as_str).collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        assert_eq!(keys, ["aaaaaaaa", "bbbbbbbb", "cccccccc"]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_keys() {
+        let loader = TagLoader::new(Tags::default());
+
+        let (found, missing) =
+            tokio::join!(loader.load(id("aaaaaaaa")), loader.load(id("missing0")),);
+
+        // A missing key doesn't fail the rest of its batch
+        assert!(found.is_ok());
+        assert!(matches!(missing, Err(LoadError::NotFound)));
+    }
+}
@@ -0,0 +1,719 @@
+//! Converts Markdown (CommonMark) to and from parts, so authors can write
+//! chapters in Markdown.
+//!
+//! Each paragraph, heading, image and other block (ie lists and block quotes)
+//! becomes its own part, keeping comments anchored to the paragraph they
+//! were made on.
+//!
+//! # Note
+//!
+//! Text parts are stored as HTML, so any HTML in the Markdown is sanitized
+//! (see [`crate::html`]) and formatting Markdown doesn't have is written
+//! back out as HTML.
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::{
+    html::{self, Token},
+    models::{
+        core::{Part, PartHeading, PartImage, PartText},
+        story::{Chapter, ChapterMarkdown},
+        Existing,
+    },
+    prelude::*,
+    utils::nanoid,
+    words,
+};
+
+/// Builds a chapter from its Markdown, `current` being the chapter that is
+/// being replaced, if any.
+pub fn to_chapter(chapter: ChapterMarkdown, current: Option<&Chapter>) -> Result<Chapter, Error> {
+    let (prefix, main, suffix, comments) = match current {
+        Some(current) => (
+            to_parts(&chapter.prefix, &current.prefix)?,
+            to_parts(&chapter.main, &current.main)?,
+            to_parts(&chapter.suffix, &current.suffix)?,
+            current.comments.clone(),
+        ),
+        None => (
+            to_parts(&chapter.prefix, &[])?,
+            to_parts(&chapter.main, &[])?,
+            to_parts(&chapter.suffix, &[])?,
+            Vec::new(),
+        ),
+    };
+
+    let mut chapter = Chapter {
+        name: chapter.name,
+        published: chapter.published,
+        scheduled: chapter.scheduled,
+        published_at: None,
+        prefix,
+        main,
+        suffix,
+        comments,
+        words: 0,
+        draft: None,
+    };
+
+    chapter.words = words::chapter(&chapter);
+
+    Ok(chapter)
+}
+
+/// The chapter's parts as Markdown, for editing.
+pub fn from_chapter(chapter: &Chapter) -> ChapterMarkdown {
+    ChapterMarkdown {
+        name: chapter.name.clone(),
+        published: chapter.published,
+        scheduled: chapter.scheduled,
+        prefix: from_parts(&chapter.prefix),
+        main: from_parts(&chapter.main),
+        suffix: from_parts(&chapter.suffix),
+    }
+}
+
+/// Parses the Markdown into parts.
+///
+/// Parts that are unchanged from one in `current` keep its id, times and
+/// comments, every other part is given a new id.
+pub fn to_parts(markdown: &str, current: &[Existing<Part>]) -> Result<Vec<Existing<Part>>, Error> {
+    let mut builder = Builder::default();
+
+    for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+        builder.event(event);
+    }
+
+    builder.flush();
+
+    let now = OffsetDateTime::now_utc();
+
+    let mut used = vec![false; current.len()];
+    let mut parts = Vec::with_capacity(builder.parts.len());
+
+    for part in builder.parts {
+        let existing = current
+            .iter()
+            .enumerate()
+            .find(|(index, existing)| !used[*index] && same(existing, &part));
+
+        let part = match existing {
+            Some((index, existing)) => {
+                used[index] = true;
+
+                Existing::new(
+                    existing.id,
+                    with_comments(part, existing),
+                    existing.created,
+                    existing.updated,
+                )
+            }
+            None => {
+                let id = nanoid::new_id().ok_or_else(|| err!("unable to generate part id"))?;
+
+                Existing::new(id, part, now, now)
+            }
+        };
+
+        parts.push(part);
+    }
+
+    Ok(parts)
+}
+
+/// Writes the parts out as Markdown blocks, separated by blank lines.
+pub fn from_parts(parts: &[Existing<Part>]) -> String {
+    let mut blocks = Vec::with_capacity(parts.len());
+
+    for part in parts {
+        let block = match &**part {
+            Part::Heading(heading) => format!(
+                "{} {}",
+                "#".repeat(usize::from(heading.level.clamp(1, 6))),
+                escape(&heading.content)
+            ),
+            Part::Image(image) => format!(
+                "![{}](<{}>)",
+                escape(image.alt.as_deref().unwrap_or_default()),
+                image.url.replace('<', "%3C").replace('>', "%3E")
+            ),
+            Part::Text(text) => from_html(&text.content),
+        };
+
+        if !block.is_empty() {
+            blocks.push(block);
+        }
+    }
+
+    blocks.join("\n\n")
+}
+
+/// Escapes the characters Markdown would treat as formatting, list markers
+/// and headings only matter at the start of a line.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+
+    for (index, c) in text.char_indices() {
+        let block = index == 0 && matches!(c, '-' | '+' | '=' | '#' | '>');
+        // Ordered list markers, ie `1.` or `1)`
+        let ordered = digits > 0 && index == digits && matches!(c, '.' | ')');
+        let inline = matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' | '&'
+        );
+
+        if block || ordered || inline {
+            out.push('\\');
+        }
+
+        out.push(c);
+    }
+
+    out
+}
+
+/// If the part has the same content as the existing one, ignoring comments.
+fn same(existing: &Part, part: &Part) -> bool {
+    match (existing, part) {
+        (Part::Heading(a), Part::Heading(b)) => a.level == b.level && a.content == b.content,
+        (Part::Image(a), Part::Image(b)) => a.url == b.url && a.alt == b.alt,
+        (Part::Text(a), Part::Text(b)) => a.content == b.content,
+        _ => false,
+    }
+}
+
+fn with_comments(part: Part, existing: &Part) -> Part {
+    match (part, existing) {
+        (Part::Heading(mut part), Part::Heading(existing)) => {
+            part.comments = existing.comments.clone();
+
+            Part::Heading(part)
+        }
+        (Part::Image(mut part), Part::Image(existing)) => {
+            part.media = existing.media;
+            part.comments = existing.comments.clone();
+
+            Part::Image(part)
+        }
+        (Part::Text(mut part), Part::Text(existing)) => {
+            part.comments = existing.comments.clone();
+
+            Part::Text(part)
+        }
+        (part, _) => part,
+    }
+}
+
+/// The HTML of a text part as Markdown, elements Markdown doesn't have are
+/// kept as HTML.
+pub fn from_html(html: &str) -> String {
+    let mut writer = Writer::default();
+
+    for token in html::tokens(html) {
+        writer.token(token);
+    }
+
+    // Close any blocks the HTML left open
+    while writer.frames.len() > 1 {
+        writer.close();
+    }
+
+    str::trim(writer.out()).to_string()
+}
+
+/// Collects the events of each top level block as the parser walks through
+/// them.
+#[derive(Default)]
+struct Builder<'m> {
+    parts: Vec<Part>,
+
+    /// The events of the block being read.
+    block: Vec<Event<'m>>,
+    /// How many elements the current event is in.
+    depth: usize,
+
+    /// The level and text of the heading being read, if any.
+    heading: Option<(u8, String)>,
+    /// The url and description of the image being read, if any.
+    image: Option<(String, String)>,
+}
+
+impl<'m> Builder<'m> {
+    fn event(&mut self, event: Event<'m>) {
+        if let Some((url, alt)) = &mut self.image {
+            match event {
+                Event::End(Tag::Image(..)) => {
+                    let alt = str::trim(alt).to_string();
+
+                    self.parts.push(Part::Image(PartImage {
+                        media: None,
+                        url: std::mem::take(url),
+                        alt: if alt.is_empty() { None } else { Some(alt) },
+                        comments: Vec::new(),
+                    }));
+
+                    self.image = None;
+
+                    // The rest of the paragraph is read as a new one
+                    self.block.push(Event::Start(Tag::Paragraph));
+                }
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                Event::SoftBreak | Event::HardBreak => alt.push(' '),
+                _ => {}
+            }
+
+            return;
+        }
+
+        if let Some((level, content)) = &mut self.heading {
+            match event {
+                Event::End(Tag::Heading(..)) => {
+                    let content = str::trim(content).to_string();
+
+                    if !content.is_empty() {
+                        self.parts.push(Part::Heading(PartHeading {
+                            level: *level,
+                            content,
+                            comments: Vec::new(),
+                        }));
+                    }
+
+                    self.heading = None;
+                }
+                Event::Text(text) | Event::Code(text) => content.push_str(&text),
+                Event::SoftBreak | Event::HardBreak => content.push(' '),
+                _ => {}
+            }
+
+            return;
+        }
+
+        match event {
+            Event::Start(Tag::Heading(level, _, _)) if self.depth == 0 => {
+                self.flush();
+
+                self.heading = Some((level as u8, String::new()));
+            }
+            // Images are split out of paragraphs as their own part
+            Event::Start(Tag::Image(_, url, _))
+                if self.depth == 1
+                    && matches!(self.block.first(), Some(Event::Start(Tag::Paragraph))) =>
+            {
+                self.block.push(Event::End(Tag::Paragraph));
+                self.flush();
+
+                self.image = Some((url.to_string(), String::new()));
+            }
+            Event::Rule if self.depth == 0 => {
+                self.flush();
+
+                self.parts.push(text(String::from("<hr>")));
+            }
+            Event::Start(tag) => {
+                if self.depth == 0 {
+                    self.flush();
+                }
+
+                self.depth += 1;
+                self.block.push(Event::Start(tag));
+            }
+            Event::End(tag) => {
+                self.depth = self.depth.saturating_sub(1);
+                self.block.push(Event::End(tag));
+
+                if self.depth == 0 {
+                    self.flush();
+                }
+            }
+            // Kept as a space so the line isn't broken once it is HTML
+            Event::SoftBreak => self.block.push(Event::Text(" ".into())),
+            event => self.block.push(event),
+        }
+    }
+
+    /// Ends the current block, adding it as a part if it has any text.
+    fn flush(&mut self) {
+        if self.block.is_empty() {
+            return;
+        }
+
+        let mut content = String::new();
+
+        pulldown_cmark::html::push_html(&mut content, self.block.drain(..));
+
+        let content = html::sanitize(&content);
+
+        if html::words(&content) != 0 || content.contains("<hr>") {
+            self.parts.push(text(content));
+        }
+    }
+}
+
+fn text(content: String) -> Part {
+    Part::Text(PartText {
+        words: html::words(&content),
+        content,
+        comments: Vec::new(),
+    })
+}
+
+/// A block being written, its text is quoted or indented once it ends.
+#[derive(Default)]
+struct Frame {
+    kind: FrameKind,
+    out: String,
+}
+
+#[derive(Default)]
+enum FrameKind {
+    #[default]
+    Root,
+    Quote,
+    /// A list item and its marker.
+    Item(String),
+    Code,
+}
+
+/// Writes HTML tokens out as Markdown.
+#[derive(Default)]
+struct Writer {
+    frames: Vec<Frame>,
+    /// The next number of each list, `None` for bullet lists.
+    lists: Vec<Option<u64>>,
+    /// The url of each link, `None` for links that had theirs removed.
+    links: Vec<Option<String>>,
+    /// If the text is in a code span.
+    code: bool,
+}
+
+impl Writer {
+    fn token(&mut self, token: Token<'_>) {
+        match token {
+            Token::Start { name, attributes } => match name {
+                "p" => self.paragraph(),
+                "em" | "i" => self.out().push('*'),
+                "strong" | "b" => self.out().push_str("**"),
+                "del" | "s" => self.out().push_str("~~"),
+                "code" if !self.in_code_block() => {
+                    self.code = true;
+
+                    self.out().push('`');
+                }
+                "code" => {}
+                "br" => self.out().push_str("\\\n"),
+                "a" => {
+                    let href = attributes
+                        .into_iter()
+                        .find(|(name, _)| *name == "href")
+                        .map(|(_, value)| value);
+
+                    if href.is_some() {
+                        self.out().push('[');
+                    }
+
+                    self.links.push(href);
+                }
+                "hr" => {
+                    self.paragraph();
+                    self.out().push_str("***");
+                    self.paragraph();
+                }
+                "blockquote" => self.open(FrameKind::Quote),
+                "pre" => self.open(FrameKind::Code),
+                "ul" => self.lists.push(None),
+                "ol" => {
+                    let start = attributes
+                        .iter()
+                        .find(|(name, _)| *name == "start")
+                        .and_then(|(_, value)| value.parse().ok())
+                        .unwrap_or(1);
+
+                    self.lists.push(Some(start));
+                }
+                "li" => {
+                    let marker = match self.lists.last_mut() {
+                        Some(Some(number)) => {
+                            *number += 1;
+
+                            format!("{}. ", *number - 1)
+                        }
+                        _ => String::from("- "),
+                    };
+
+                    self.open(FrameKind::Item(marker));
+                }
+                _ => {
+                    let out = self.out();
+
+                    out.push('<');
+                    out.push_str(name);
+
+                    for (name, value) in attributes {
+                        out.push_str(&format!(
+                            r#" {}="{}""#,
+                            name,
+                            value.replace('&', "&amp;").replace('"', "&quot;")
+                        ));
+                    }
+
+                    out.push('>');
+                }
+            },
+            Token::End(name) => match name {
+                "p" => self.paragraph(),
+                "em" | "i" => self.out().push('*'),
+                "strong" | "b" => self.out().push_str("**"),
+                "del" | "s" => self.out().push_str("~~"),
+                "code" if self.code => {
+                    self.code = false;
+
+                    self.out().push('`');
+                }
+                "code" => {}
+                "a" => {
+                    if let Some(href) = self.links.pop().flatten() {
+                        let href = href.replace('<', "%3C").replace('>', "%3E");
+
+                        self.out().push_str(&format!("](<{}>)", href));
+                    }
+                }
+                "blockquote" | "pre" | "li" => self.close(),
+                "ul" | "ol" => {
+                    self.lists.pop();
+
+                    if self.lists.is_empty() {
+                        self.paragraph();
+                    }
+                }
+                _ => {
+                    let out = self.out();
+
+                    out.push_str("</");
+                    out.push_str(name);
+                    out.push('>');
+                }
+            },
+            Token::Text(text) if self.in_code_block() => self.out().push_str(&text),
+            Token::Text(text) => {
+                let code = self.code;
+                let out = self.out();
+
+                let mut collapsed = String::with_capacity(text.len());
+
+                for c in text.chars() {
+                    if !c.is_whitespace() || c == '\u{a0}' {
+                        collapsed.push(c);
+                    } else if !collapsed.ends_with(' ') {
+                        collapsed.push(' ');
+                    }
+                }
+
+                let collapsed = if out.is_empty() || out.ends_with('\n') {
+                    str::trim_start(&collapsed)
+                } else {
+                    &collapsed
+                };
+
+                if code {
+                    out.push_str(collapsed);
+                } else {
+                    out.push_str(&escape(collapsed));
+                }
+            }
+        }
+    }
+
+    fn out(&mut self) -> &mut String {
+        if self.frames.is_empty() {
+            self.frames.push(Frame::default());
+        }
+
+        let last = self.frames.len() - 1;
+
+        &mut self.frames[last].out
+    }
+
+    fn in_code_block(&self) -> bool {
+        matches!(
+            self.frames.last(),
+            Some(Frame {
+                kind: FrameKind::Code,
+                ..
+            })
+        )
+    }
+
+    /// Ends the current line and leaves a blank one, unless nothing has been
+    /// written yet.
+    fn paragraph(&mut self) {
+        let out = self.out();
+
+        out.truncate(str::trim_end_matches(out.as_str(), ' ').len());
+
+        if !out.is_empty() && !out.ends_with("\n\n") {
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+
+            out.push('\n');
+        }
+    }
+
+    fn open(&mut self, kind: FrameKind) {
+        if !matches!(kind, FrameKind::Item(_)) {
+            self.paragraph();
+        } else {
+            let out = self.out();
+
+            out.truncate(str::trim_end_matches(out.as_str(), ' ').len());
+
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+
+        self.frames.push(Frame {
+            kind,
+            out: String::new(),
+        });
+    }
+
+    /// Ends the current block, writing it out into the one it is in.
+    fn close(&mut self) {
+        // The root is never closed, even if the HTML has extra end tags
+        if self.frames.len() < 2 {
+            return;
+        }
+
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let content = str::trim(&frame.out);
+        let item = matches!(frame.kind, FrameKind::Item(_));
+
+        let mut block = String::with_capacity(content.len());
+
+        match frame.kind {
+            FrameKind::Root => block.push_str(content),
+            FrameKind::Quote => {
+                for line in content.lines() {
+                    if line.is_empty() {
+                        block.push_str(">\n");
+                    } else {
+                        block.push_str(&format!("> {}\n", line));
+                    }
+                }
+            }
+            FrameKind::Item(marker) => {
+                let indent = " ".repeat(marker.len());
+
+                for (index, line) in content.lines().enumerate() {
+                    if index == 0 {
+                        block.push_str(&marker);
+                    } else if !line.is_empty() {
+                        block.push_str(&indent);
+                    }
+
+                    block.push_str(line);
+                    block.push('\n');
+                }
+            }
+            FrameKind::Code => {
+                block.push_str("```\n");
+                block.push_str(str::trim_end(&frame.out));
+                block.push_str("\n```\n");
+            }
+        }
+
+        self.out().push_str(&block);
+
+        if !item {
+            self.paragraph();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn content(parts: &[Existing<Part>]) -> Vec<String> {
+        parts
+            .iter()
+            .map(|part| match &**part {
+                Part::Heading(heading) => format!("h{} {}", heading.level, heading.content),
+                Part::Image(image) => format!("img {}", image.url),
+                Part::Text(text) => text.content.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let parts = to_parts(
+            concat!(
+                "## Start\n\n",
+                "The *first* paragraph\nstill going.  \nNew line. ![A door](/media/door.png) After.\n\n",
+                "***\n\n",
+                "- one\n- <ruby>two<rt>2</rt></ruby>\n\n",
+                "<script>alert(1)</script>\n\n",
+                "> Quoted <span onclick=\"alert(1)\" lang=\"fr\">text</span>",
+            ),
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            content(&parts),
+            vec![
+                "h2 Start",
+                "<p>The <em>first</em> paragraph still going.<br>\nNew line. </p>",
+                "img /media/door.png",
+                "<p> After.</p>",
+                "<hr>",
+                "<ul>\n<li>one</li>\n<li><ruby>two<rt>2</rt></ruby></li>\n</ul>",
+                "<blockquote>\n<p>Quoted <span lang=\"fr\">text</span></p>\n</blockquote>",
+            ]
+        );
+
+        match &*parts[1] {
+            Part::Text(text) => assert_eq!(text.words, 7),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let markdown = concat!(
+            "# A \\[b\\]\n\n",
+            "Some\\_text with *em*, **strong**, `code` and [a link](<https://example.com>)\\\n",
+            "1\\. More\n\n",
+            "***\n\n",
+            "> Quote\n>\n> - item\n\n",
+            "1. One\n2. Two\n   - Nested\n\n",
+            "<span lang=\"fr\">Bonjour</span>",
+        );
+
+        let parts = to_parts(markdown, &[]).unwrap();
+
+        assert_eq!(from_parts(&parts), markdown);
+        assert_eq!(
+            content(&to_parts(&from_parts(&parts), &[]).unwrap()),
+            content(&parts)
+        );
+    }
+
+    #[test]
+    fn test_keeps_unchanged_parts() {
+        let before = to_parts("First.\n\nSecond.", &[]).unwrap();
+        let after = to_parts("Before.\n\nSecond.\n\nFirst, changed.", &before).unwrap();
+
+        assert_ne!(after[0].id, before[0].id);
+        assert_eq!(after[1].id, before[1].id);
+        assert_ne!(after[2].id, before[0].id);
+    }
+}
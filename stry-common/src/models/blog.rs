@@ -1,13 +1,17 @@
 //! Entities for the blog 'module', for both user and site blog posts.
 
-use crate::models::{
-    core::{Comment, Part, Tag},
-    Existing,
+use crate::{
+    models::{
+        core::{Comment, Part, Tag},
+        Existing,
+    },
+    prelude::OffsetDateTime,
 };
 
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Post {
     /// The parts that make up this blog post.
     pub parts: Vec<Existing<Part>>,
@@ -17,3 +21,14 @@ pub struct Post {
 
     pub tags: Vec<Existing<Tag>>,
 }
+
+/// A type used for database responses, maps to a row in the posts table.
+pub struct PostRecordId {
+    pub id: String,
+
+    /// The post as JSON.
+    pub content: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
@@ -3,13 +3,20 @@
 use sodiumoxide::crypto::pwhash::argon2id13;
 
 use crate::{
-    models::{blog::Post, story::Story, Existing},
-    prelude::{err, Error, Validate},
+    models::{
+        blog::Post,
+        story::{Rating, Story},
+        Existing, Id,
+    },
+    prelude::{err, Error, OffsetDateTime, Validate},
 };
 
+use validator::ValidationErrors;
+
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[derive(Validate)]
 pub struct UserRegisterForm {
     #[validate(length(min = 4))]
@@ -20,14 +27,28 @@ pub struct UserRegisterForm {
     pub password: String,
 }
 
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct UserSessionForm {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 8, max = 512))]
+    pub password: String,
+}
+
 /// A user of the website, used from displaying authors to signing in.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct User {
     pub account: Account,
     pub appearance: Appearance,
     pub notifications: Notifications,
+    pub content: Content,
 
     /// Stores all the stories that the user owns.
     ///
@@ -50,16 +71,44 @@ impl User {
             account,
             appearance: Default::default(),
             notifications: Default::default(),
+            content: Default::default(),
             stories: None,
             posts: None,
         }
     }
 }
 
+/// A type used for database responses, maps to a row in the users table.
+pub struct UserRecordId {
+    pub id: String,
+
+    pub name: String,
+    pub email: String,
+    pub hash: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// A type used for database responses, maps to a row in the users table
+/// without the private account details.
+pub struct UserRecordProfile {
+    pub id: String,
+
+    pub name: String,
+    pub biography: String,
+    /// The user's appearance, notification and content settings as JSON.
+    pub settings: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
 /// Information and settings for a user, ie name, biography, and security details.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Account {
     /// The user's visible username.
     ///
@@ -123,20 +172,51 @@ impl Account {
             biography: None,
         })
     }
+
+    /// Checks the supplied password against the account's stored hash.
+    ///
+    /// Always returns `false` if the hash was not loaded.
+    pub fn verify(&self, password: &str) -> Result<bool, Error> {
+        sodiumoxide::init().map_err(|_| err!("unable to initialize sodiumoxide"))?;
+
+        let hash = match self.hash.as_ref() {
+            Some(hash) => hash,
+            None => return Ok(false),
+        };
+
+        // Database backends may store the hash without its trailing null padding
+        let mut padded = [0; argon2id13::HASHEDPASSWORDBYTES];
+        let len = hash.len().min(padded.len());
+        padded[..len].copy_from_slice(&hash[..len]);
+
+        Ok(argon2id13::pwhash_verify(
+            &argon2id13::HashedPassword(padded),
+            password.as_bytes(),
+        ))
+    }
 }
 
 /// User website/app appearance settings.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
 pub struct Appearance {
     pub theme: SiteTheme,
+
+    /// The size of a chapter's text in the reader.
+    pub font_size: FontSize,
+
+    /// Starts a chapter's author's notes collapsed.
+    pub collapse_notes: bool,
 }
 
 /// Website theme the user currently has selected, takes precedence over `prefers-color-scheme`.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum SiteTheme {
     Dark,
     Light,
@@ -148,10 +228,22 @@ impl Default for SiteTheme {
     }
 }
 
+#[rustfmt::skip]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FontSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
 /// User notification settings.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Notifications {
     pub comments: NotificationPreference,
 }
@@ -159,6 +251,7 @@ pub struct Notifications {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum NotificationPreference {
     Both,
     Neither,
@@ -172,6 +265,24 @@ impl Default for NotificationPreference {
     }
 }
 
+/// The stories a user doesn't want to see, hidden from listings and searches.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Content {
+    /// Hides stories rated above this, ie [`Rating::Teen`] hides mature and
+    /// explicit stories.
+    pub rating: Option<Rating>,
+
+    /// Hides stories with any of these warnings.
+    pub warnings: Vec<Id>,
+
+    /// Hides stories with any of these tags.
+    pub tags: Vec<Id>,
+}
+
 /// A chapter or comment segment that can be commented on.
 ///
 /// # Notes
@@ -182,17 +293,35 @@ impl Default for NotificationPreference {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Part {
     Heading(PartHeading),
     Image(PartImage),
     Text(PartText),
 }
 
+// Errors are given for the variant's fields directly, so they show up as
+// `main[2].content` rather than `main[2].Text.content`
+impl Validate for Part {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        match self {
+            Part::Heading(heading) => heading.validate(),
+            Part::Image(image) => image.validate(),
+            Part::Text(text) => text.validate(),
+        }
+    }
+}
+
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
 pub struct PartHeading {
+    #[validate(range(min = 1, max = 6))]
     pub level: u8,
+    #[validate(length(max = 256))]
+    pub content: String,
 
     /// Any comments on or replying to the current part.
     pub comments: Vec<Existing<Comment>>,
@@ -201,8 +330,20 @@ pub struct PartHeading {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
 pub struct PartImage {
+    /// The uploaded [`Media`] the image is of, if it isn't hosted elsewhere.
+    ///
+    /// # Note
+    ///
+    /// When set, the `url` is replaced with the media's own whenever the part
+    /// is written, see [`crate::media::resolve`].
+    #[serde(default)]
+    pub media: Option<Id>,
+    #[validate(length(max = 2048))]
     pub url: String,
+    #[validate(length(max = 1024))]
     pub alt: Option<String>,
 
     /// Any comments on or replying to the current part.
@@ -212,7 +353,10 @@ pub struct PartImage {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
 pub struct PartText {
+    #[validate(length(max = 65536))]
     pub content: String,
     pub words: i64,
 
@@ -230,6 +374,7 @@ pub struct PartText {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Comment {
     pub author: Existing<User>,
     pub main: Vec<Existing<Part>>,
@@ -239,8 +384,175 @@ pub struct Comment {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Tag {
     pub content: String,
 
     pub description: String,
 }
+
+/// A type used for database responses, maps to a row in the tags table (or
+/// any of the story tag tables that share its columns).
+pub struct TagRecordId {
+    pub id: String,
+
+    pub content: String,
+    pub description: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// An uploaded image, stored under the hash of its contents so its url never
+/// points to anything else.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Media {
+    /// The user that uploaded it.
+    pub uploader: Id,
+
+    /// The lowercase hex SHA-256 of the re-encoded image.
+    pub hash: String,
+    /// The stored format's file extension (ie `png`).
+    pub extension: String,
+
+    pub width: i32,
+    pub height: i32,
+
+    /// The hash of the thumbnail, stored with the same extension.
+    ///
+    /// # Note
+    ///
+    /// Images already small enough to be their own thumbnail have the same
+    /// hash as the image.
+    pub thumbnail: String,
+}
+
+impl Media {
+    /// The name the image is stored under in a [`crate::media::MediaStore`].
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.hash, self.extension)
+    }
+
+    pub fn thumbnail_name(&self) -> String {
+        format!("{}.{}", self.thumbnail, self.extension)
+    }
+
+    pub fn url(&self) -> String {
+        format!("/media/{}", self.name())
+    }
+
+    pub fn thumbnail_url(&self) -> String {
+        format!("/media/{}", self.thumbnail_name())
+    }
+}
+
+/// A type used for database responses, maps to a row in the media table.
+pub struct MediaRecordId {
+    pub id: String,
+    pub uploader_id: String,
+
+    pub hash: String,
+    pub extension: String,
+
+    pub width: i32,
+    pub height: i32,
+
+    pub thumbnail: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// Something a user is told about by the site itself.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Notification {
+    /// One of the user's stories hasn't been updated in a while and will be
+    /// marked as abandoned, see [`crate::abandon`].
+    StoryAbandoning(NotificationStoryAbandoning),
+}
+
+impl Notification {
+    /// The story the notification is about.
+    pub fn story(&self) -> Option<Id> {
+        match self {
+            Notification::StoryAbandoning(notification) => Some(notification.story),
+        }
+    }
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NotificationStoryAbandoning {
+    pub story: Id,
+
+    /// When the story will be abandoned unless it's updated before then.
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "super::timestamp_schema"))]
+    pub at: OffsetDateTime,
+}
+
+/// A type used for database responses, maps to a row in the notifications
+/// table.
+///
+/// The `content` column is JSON, given as text.
+pub struct NotificationRecordId {
+    pub id: String,
+
+    pub content: String,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// A site-wide setting, as it is shown to admins, see [`crate::settings`].
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Setting {
+    pub key: String,
+
+    pub value: SettingValue,
+
+    /// The value used when the setting hasn't been changed.
+    pub default: SettingValue,
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum SettingValue {
+    Bool(bool),
+    Integer(i64),
+    Text(String),
+}
+
+impl std::fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingValue::Bool(value) => write!(f, "{}", value),
+            SettingValue::Integer(value) => write!(f, "{}", value),
+            SettingValue::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Changes to any number of settings, by key.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct SettingsForm {
+    #[serde(flatten)]
+    pub values: std::collections::BTreeMap<String, SettingValue>,
+}
@@ -14,7 +14,12 @@ use std::{
 
 use arrayvec::ArrayString;
 
-use crate::{prelude::OffsetDateTime, utils::nanoid};
+use validator::ValidationErrors;
+
+use crate::{
+    prelude::{OffsetDateTime, Validate},
+    utils::nanoid,
+};
 
 fenn::newtype! {
     /// The database entry id newtype, is a [`ArrayString`] by default
@@ -38,6 +43,26 @@ impl Id {
     }
 }
 
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Id {
+    fn schema_name() -> String {
+        String::from("Id")
+    }
+
+    fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            string: Some(Box::new(schemars::schema::StringValidation {
+                max_length: Some(nanoid::ID_SIZE as u32),
+                min_length: Some(nanoid::ID_SIZE as u32),
+                pattern: None,
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl TryFrom<&str> for Id {
     type Error = anyhow::Error;
 
@@ -106,6 +131,7 @@ pub struct IdRecord {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct New<T> {
     inner: T,
 }
@@ -118,16 +144,29 @@ impl<T> Deref for New<T> {
     }
 }
 
+impl<T> DerefMut for New<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
 impl<T> From<T> for New<T> {
     fn from(t: T) -> Self {
         New { inner: t }
     }
 }
 
+impl<T: Validate> Validate for New<T> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.inner.validate()
+    }
+}
+
 /// A wrapper type to indicate that a type has a backend id.
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Existing<T> {
     /// The entity's `Id`.
     ///
@@ -148,9 +187,11 @@ pub struct Existing<T> {
     /// # Note
     ///
     /// Once created this should never change.
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "timestamp_schema"))]
     pub created: OffsetDateTime,
 
     /// The last time this entity was updated.
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "timestamp_schema"))]
     pub updated: OffsetDateTime,
 }
 
@@ -179,10 +220,45 @@ impl<T> DerefMut for Existing<T> {
     }
 }
 
+impl<T: Validate> Validate for Existing<T> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.inner.validate()
+    }
+}
+
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Either<L, R> {
     Left(L),
     Right(R),
 }
+
+/// The schema of a serialized [`OffsetDateTime`].
+///
+/// # Note
+///
+/// Without `time`'s human readable feature timestamps are sent as a tuple of
+/// `[year, ordinal, hour, minute, second, nanosecond, offset hours, offset minutes, offset seconds]`.
+#[cfg(feature = "schemars")]
+fn timestamp_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    let mut schema = gen.subschema_for::<Vec<i32>>().into_object();
+
+    schema.metadata().description = Some(String::from(
+        "[year, ordinal, hour, minute, second, nanosecond, offset hours, offset minutes, offset seconds]",
+    ));
+    schema.array().min_items = Some(9);
+    schema.array().max_items = Some(9);
+
+    schema.into()
+}
+
+#[cfg(feature = "schemars")]
+fn optional_timestamp_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    let mut schema = timestamp_schema(gen).into_object();
+
+    schema.extensions.insert("nullable".into(), true.into());
+
+    schema.into()
+}
@@ -3,27 +3,48 @@
 
 use crate::{
     models::{
-        core::{Comment, Part, Tag, User},
+        core::{Comment, Content, Part, Tag, User},
         Either, Existing, Id,
     },
-    prelude::{members, Member, OffsetDateTime, TryFrom},
+    prelude::{members, Member, OffsetDateTime, TryFrom, Validate},
+    words,
 };
 
+use time::Duration;
+use validator::ValidationError;
+
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
 pub struct Story {
     /// The title of the story, this is not unique as stories are tracked
     /// with its `Id`.
+    #[validate(length(min = 1, max = 256))]
     pub name: String,
+    #[validate(length(max = 4096))]
     pub summary: String,
 
+    /// The language the story is written in, as a BCP-47 tag (ie `en` or `pt-BR`).
+    #[validate(custom = "language_tag")]
+    pub language: String,
+
+    /// The story this is a translation of.
+    ///
+    /// # Variant
+    ///
+    /// Is `None` for original works.
+    pub original: Option<Id>,
+
     pub rating: Rating,
     pub state: State,
 
     pub authors: Vec<Existing<User>>,
     pub commissioners: Vec<Existing<User>>,
     pub dedicatees: Vec<Existing<User>>,
+    /// The users credited with translating the original work.
+    pub translators: Vec<Existing<User>>,
 
     pub origins: Vec<Existing<Origin>>,
     pub warnings: Vec<Existing<Warning>>,
@@ -53,17 +74,27 @@ pub struct Story {
 }
 
 impl Story {
-    pub fn new(name: String, summary: String, rating: Rating, state: State) -> Self {
+    pub fn new(
+        name: String,
+        summary: String,
+        language: String,
+        rating: Rating,
+        state: State,
+    ) -> Self {
         Self {
             name,
             summary,
 
+            language,
+            original: None,
+
             rating,
             state,
 
             authors: Vec::new(),
             commissioners: Vec::new(),
             dedicatees: Vec::new(),
+            translators: Vec::new(),
 
             origins: Vec::new(),
             warnings: Vec::new(),
@@ -79,6 +110,31 @@ impl Story {
             comments: Vec::new(),
         }
     }
+
+    /// How long the story takes to read, see [`words::reading_time`].
+    pub fn reading_time(&self) -> Duration {
+        words::reading_time(i64::from(self.words))
+    }
+}
+
+/// Checks that the value is shaped like a BCP-47 language tag, a 2-3 (or 5-8)
+/// letter language followed by `-` separated subtags of up to 8 letters or digits.
+fn language_tag(value: &str) -> Result<(), ValidationError> {
+    let mut subtags = value.split('-');
+
+    let language = subtags.next().unwrap_or_default();
+
+    let valid = matches!(language.len(), 2..=3 | 5..=8)
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("language"))
+    }
 }
 
 impl Member for Story {
@@ -90,11 +146,82 @@ impl Member for Story {
     }
 }
 
+/// Narrows down a list of stories, a story has to match every field that is set.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StoryFilter {
+    pub author: Option<Id>,
+
+    pub origin: Option<Id>,
+    pub warning: Option<Id>,
+    pub pairing: Option<Id>,
+    pub character: Option<Id>,
+    pub tag: Option<Id>,
+
+    pub rating: Option<Rating>,
+    pub state: Option<State>,
+
+    /// Matches the language and any of its regional variants, so `pt` also
+    /// matches `pt-BR`.
+    pub language: Option<String>,
+
+    /// Hides stories rated above this, see [`Content::rating`].
+    pub max_rating: Option<Rating>,
+    /// Hides stories with any of these warnings.
+    #[serde(default)]
+    pub without_warnings: Vec<Id>,
+    /// Hides stories with any of these tags.
+    #[serde(default)]
+    pub without_tags: Vec<Id>,
+}
+
+impl StoryFilter {
+    /// Also hides the stories a reader has chosen not to see.
+    ///
+    /// The stricter rating wins if both set one.
+    #[must_use]
+    pub fn hide(mut self, content: &Content) -> Self {
+        // Ratings are ordered from explicit to general so the greater one is stricter
+        self.max_rating = self.max_rating.max(content.rating);
+
+        self.without_warnings
+            .extend(content.warnings.iter().copied());
+        self.without_tags.extend(content.tags.iter().copied());
+
+        self
+    }
+}
+
+/// A page of the stories matching the `filter`.
+#[rustfmt::skip]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct StorySearch {
+    #[serde(default)]
+    pub filter: StoryFilter,
+
+    pub cursor: Option<Id>,
+    #[serde(default = "default_limit")]
+    #[validate(range(min = 1, max = 100))]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    10
+}
+
 /// A type used for database responses, maps to a row in the stories table.
 pub struct StoryRecord {
     pub name: String,
     pub summary: String,
 
+    pub language: String,
+    pub original: Option<String>,
+
     pub rating: Rating,
     pub state: State,
 
@@ -109,6 +236,9 @@ pub struct StoryRecordId {
     pub name: String,
     pub summary: String,
 
+    pub language: String,
+    pub original: Option<String>,
+
     pub rating: Rating,
     pub state: State,
 
@@ -119,29 +249,56 @@ pub struct StoryRecordId {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
 pub struct Chapter {
     /// The title of the chapter.
     ///
     /// # Variant
     ///
     /// To display use the default title (`Chapter {number}`) if is [`None`].
+    #[validate(length(min = 1, max = 256))]
     pub name: Option<String>,
 
     /// Marks the chapter was published allowing non authors to view it.
     ///
     /// # Note
     ///
-    /// Even when `true` a chapter can still be edited.
+    /// Even when `true` a chapter can still be edited, changes that shouldn't
+    /// be seen yet are kept in its [`draft`].
+    ///
+    /// [`draft`]: #structfield.draft
     pub published: bool,
 
+    /// When the chapter is to be published, along with its draft if it has
+    /// one, see [`Chapter::publish`].
+    ///
+    /// # Note
+    ///
+    /// Only the story's authors can see this.
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "super::optional_timestamp_schema"))]
+    pub scheduled: Option<OffsetDateTime>,
+
+    /// When the chapter was first published.
+    ///
+    /// # Note
+    ///
+    /// This is set by the backend, any given value is ignored.
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "super::optional_timestamp_schema"))]
+    pub published_at: Option<OffsetDateTime>,
+
     /// The parts the make up the beginning author's note.
     ///
     /// # Note
     ///
     /// This does not contribute to word count.
+    #[validate]
     pub prefix: Vec<Existing<Part>>,
 
     /// THe parts of the actual chapter itself.
+    #[validate]
     pub main: Vec<Existing<Part>>,
 
     /// The parts the make up the ending author's note.
@@ -149,6 +306,7 @@ pub struct Chapter {
     /// # Note
     ///
     /// This does not contribute to word count.
+    #[validate]
     pub suffix: Vec<Existing<Part>>,
 
     /// Comments on the chapter itself not its parts.
@@ -162,11 +320,283 @@ pub struct Chapter {
     ///
     /// [`main`]: #structfield.main
     pub words: i64,
+
+    /// Unpublished changes to the chapter, that replace it once published.
+    ///
+    /// # Note
+    ///
+    /// Only the story's authors can see this.
+    #[serde(default)]
+    #[validate]
+    pub draft: Option<ChapterDraft>,
 }
 
+impl Chapter {
+    /// How long the chapter takes to read, see [`words::reading_time`].
+    pub fn reading_time(&self) -> Duration {
+        words::reading_time(self.words)
+    }
+
+    /// Replaces the chapter's title and parts with its draft, if it has one.
+    pub fn apply_draft(&mut self) {
+        if let Some(draft) = self.draft.take() {
+            self.name = draft.name;
+            self.prefix = draft.prefix;
+            self.main = draft.main;
+            self.suffix = draft.suffix;
+            self.words = draft.words;
+        }
+    }
+
+    /// Publishes the chapter as its draft, clearing any schedule.
+    pub fn publish(&mut self) {
+        self.apply_draft();
+
+        self.published = true;
+        self.scheduled = None;
+    }
+
+    /// Removes everything only the story's authors can see.
+    pub fn for_readers(&mut self) {
+        self.draft = None;
+        self.scheduled = None;
+    }
+}
+
+/// A type used for database responses, maps to a row in the chapters table.
+///
+/// The `content` and `draft` columns are JSON, given as text.
+pub struct ChapterRecordId {
+    pub id: String,
+    pub story_id: String,
+
+    pub name: Option<String>,
+
+    pub published: bool,
+    pub scheduled: Option<OffsetDateTime>,
+    pub published_at: Option<OffsetDateTime>,
+
+    pub content: String,
+    pub draft: Option<String>,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// The unpublished changes to a [`Chapter`].
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct ChapterDraft {
+    #[validate(length(min = 1, max = 256))]
+    pub name: Option<String>,
+
+    #[validate]
+    pub prefix: Vec<Existing<Part>>,
+    #[validate]
+    pub main: Vec<Existing<Part>>,
+    #[validate]
+    pub suffix: Vec<Existing<Part>>,
+
+    pub words: i64,
+}
+
+impl From<Chapter> for ChapterDraft {
+    fn from(chapter: Chapter) -> Self {
+        Self {
+            name: chapter.name,
+            prefix: chapter.prefix,
+            main: chapter.main,
+            suffix: chapter.suffix,
+            words: chapter.words,
+        }
+    }
+}
+
+/// The order of a story's chapters, which has to list every one of them.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct ChapterOrder {
+    pub chapters: Vec<Id>,
+}
+
+/// A [`Chapter`] written in Markdown, see [`crate::markdown`].
+///
+/// # Note
+///
+/// Comments are not included, when replacing a chapter they are kept on any
+/// part whose content hasn't changed.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Validate)]
+pub struct ChapterMarkdown {
+    /// The title of the chapter.
+    #[validate(length(min = 1, max = 256))]
+    pub name: Option<String>,
+
+    pub published: bool,
+
+    /// When the chapter is to be published, see [`Chapter::scheduled`].
+    #[serde(default)]
+    #[cfg_attr(feature = "schemars", schemars(schema_with = "super::optional_timestamp_schema"))]
+    pub scheduled: Option<OffsetDateTime>,
+
+    /// The beginning author's note.
+    #[serde(default)]
+    pub prefix: String,
+
+    /// The chapter itself.
+    pub main: String,
+
+    /// The ending author's note.
+    #[serde(default)]
+    pub suffix: String,
+}
+
+/// A past version of a chapter or story, as it is listed without its content.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Revision {
+    /// Who made the change.
+    ///
+    /// # Variant
+    ///
+    /// Is `None` for changes made by the site itself (ie scheduled publishing
+    /// or abandoning), by a removed user, or from before revisions were kept.
+    pub editor: Option<Existing<User>>,
+}
+
+/// A past version of a chapter's name and parts, recorded whenever they change.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ChapterRevision {
+    /// See [`Revision::editor`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<Existing<User>>,
+
+    pub name: Option<String>,
+
+    pub prefix: Vec<Existing<Part>>,
+    pub main: Vec<Existing<Part>>,
+    pub suffix: Vec<Existing<Part>>,
+}
+
+impl ChapterRevision {
+    pub fn new(chapter: &Chapter) -> Self {
+        Self {
+            editor: None,
+            name: chapter.name.clone(),
+            prefix: chapter.prefix.clone(),
+            main: chapter.main.clone(),
+            suffix: chapter.suffix.clone(),
+        }
+    }
+
+    /// Puts the revision's name and parts back on the chapter, its draft,
+    /// schedule and comments are kept.
+    pub fn restore(&self, chapter: &mut Chapter) {
+        chapter.name = self.name.clone();
+        chapter.prefix = self.prefix.clone();
+        chapter.main = self.main.clone();
+        chapter.suffix = self.suffix.clone();
+        chapter.words = words::chapter(chapter);
+    }
+}
+
+/// A past version of a story's metadata, recorded whenever it changes.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct StoryRevision {
+    /// See [`Revision::editor`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<Existing<User>>,
+
+    pub name: String,
+    pub summary: String,
+
+    pub language: String,
+    pub original: Option<Id>,
+
+    pub rating: Rating,
+    pub state: State,
+}
+
+impl StoryRevision {
+    pub fn new(story: &Story) -> Self {
+        Self {
+            editor: None,
+            name: story.name.clone(),
+            summary: story.summary.clone(),
+            language: story.language.clone(),
+            original: story.original,
+            rating: story.rating,
+            state: story.state,
+        }
+    }
+
+    /// Puts the revision's metadata back on the story, its tags, people and
+    /// chapters are kept.
+    pub fn restore(&self, story: &mut Story) {
+        story.name = self.name.clone();
+        story.summary = self.summary.clone();
+        story.language = self.language.clone();
+        story.original = self.original;
+        story.rating = self.rating;
+        story.state = self.state;
+    }
+}
+
+/// A type used for database responses, maps to a row in the revisions table
+/// (without its content) along with its editor.
+pub struct RevisionRecordId {
+    pub id: String,
+
+    pub editor_id: Option<String>,
+    pub editor_name: Option<String>,
+    pub editor_created: Option<OffsetDateTime>,
+    pub editor_updated: Option<OffsetDateTime>,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// A variant of [`RevisionRecordId`] that includes what the revision is of
+/// and its content.
+///
+/// The `content` column is JSON, given as text.
+pub struct RevisionRecordContent {
+    pub id: String,
+    pub story_id: String,
+    pub chapter_id: Option<String>,
+
+    pub content: String,
+
+    pub editor_id: Option<String>,
+    pub editor_name: Option<String>,
+    pub editor_created: Option<OffsetDateTime>,
+    pub editor_updated: Option<OffsetDateTime>,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Series {
     /// The title of the series, this is not unique as series are tracked
     /// with its `Id`.
@@ -183,10 +613,24 @@ pub struct Series {
     pub stories: Either<Vec<Existing<Story>>, Vec<Id>>,
 }
 
+/// A type used for database responses, maps to a row in the series table.
+pub struct SeriesRecordId {
+    pub id: String,
+
+    pub name: String,
+    pub summary: String,
+
+    pub state: State,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
 /// The type of tag, used for generic rendering.
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum TagKind {
     Warning,
     Pairing,
@@ -197,11 +641,22 @@ pub enum TagKind {
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum TagLevel {
     Major,
     Minor,
 }
 
+impl TagLevel {
+    /// The level as its stored, the reverse of its [`TryFrom`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TagLevel::Major => "major",
+            TagLevel::Minor => "minor",
+        }
+    }
+}
+
 impl TryFrom<&str> for TagLevel {
     type Error = crate::prelude::Error;
 
@@ -243,6 +698,7 @@ impl StoryTag for Tag {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Origin {
     pub content: String,
 
@@ -254,6 +710,7 @@ pub struct Origin {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Warning {
     pub content: String,
 
@@ -275,6 +732,7 @@ impl StoryTag for Warning {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Character {
     pub content: String,
 
@@ -296,7 +754,10 @@ impl StoryTag for Character {
 #[rustfmt::skip]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Pairing {
+    /// The relationship and the sorted ids of the characters, unique to the
+    /// pairing.
     pub hash: String,
 
     pub relationship: Relationship,
@@ -316,6 +777,46 @@ impl StoryTag for Pairing {
     }
 }
 
+/// A type used for database responses, maps to a row in the pairing table.
+pub struct PairingRecordId {
+    pub id: String,
+
+    pub hash: String,
+
+    pub relationship: Relationship,
+
+    pub created: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+/// A tag an imported story used that didn't match an existing one, queued
+/// so it can be created (or pointed at an existing tag) by hand.
+#[rustfmt::skip]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TagRequest {
+    pub kind: TagRequestKind,
+
+    /// The tag as it was written in the imported story.
+    pub content: String,
+}
+
+/// The entity a [`TagRequest`] would become.
+#[rustfmt::skip]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "story_tag_request_kind", rename_all = "snake_case"))]
+pub enum TagRequestKind {
+    Origin,
+    Warning,
+    Pairing,
+    Character,
+    General,
+}
+
 pub struct IdLevelRecord {
     pub id: String,
     pub level: String,
@@ -324,6 +825,9 @@ pub struct IdLevelRecord {
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "story_pairing_relationship", rename_all = "snake_case"))]
 pub enum Relationship {
     Family,
     Friends,
@@ -333,6 +837,7 @@ pub enum Relationship {
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
 #[cfg_attr(feature = "sqlx", sqlx(type_name = "story_rating", rename_all = "snake_case"))]
 pub enum Rating {
@@ -353,11 +858,79 @@ pub enum Rating {
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[derive(serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
-#[cfg_attr(feature = "sqlx", sqlx(type_name = "story_state", rename_all = "snake_case"))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "story_state", rename_all = "kebab-case"))]
 pub enum State {
     Completed,
     InProgress,
     Hiatus,
     Abandoned,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn id(id: &str) -> Id {
+        Id::try_from(id).unwrap()
+    }
+
+    #[test]
+    fn test_hide_rating() {
+        let hide = |filter: Option<Rating>, content: Option<Rating>| {
+            let filter = StoryFilter {
+                max_rating: filter,
+                ..StoryFilter::default()
+            };
+
+            let content = Content {
+                rating: content,
+                ..Content::default()
+            };
+
+            filter.hide(&content).max_rating
+        };
+
+        assert_eq!(hide(None, None), None);
+        assert_eq!(hide(Some(Rating::Mature), None), Some(Rating::Mature));
+        assert_eq!(hide(None, Some(Rating::Teen)), Some(Rating::Teen));
+
+        // Whichever hides more wins, no matter which side set it
+        assert_eq!(
+            hide(Some(Rating::Mature), Some(Rating::Teen)),
+            Some(Rating::Teen)
+        );
+        assert_eq!(
+            hide(Some(Rating::General), Some(Rating::Explicit)),
+            Some(Rating::General)
+        );
+    }
+
+    #[test]
+    fn test_hide_warnings_and_tags() {
+        let filter = StoryFilter {
+            without_warnings: vec![id("warning1")],
+            without_tags: vec![id("tagaaaa1")],
+            ..StoryFilter::default()
+        };
+
+        let content = Content {
+            rating: None,
+            warnings: vec![id("warning2")],
+            tags: vec![id("tagaaaa2"), id("tagaaaa3")],
+        };
+
+        let filter = filter.hide(&content);
+
+        assert_eq!(filter.without_warnings, [id("warning1"), id("warning2")]);
+        assert_eq!(
+            filter.without_tags,
+            [id("tagaaaa1"), id("tagaaaa2"), id("tagaaaa3")]
+        );
+
+        // Everything else the request asked for is left alone
+        assert_eq!(filter.max_rating, None);
+        assert_eq!(filter.tag, None);
+    }
+}
@@ -0,0 +1,30 @@
+//! Publishes chapters once the time they were scheduled for has passed.
+
+use std::{convert::Infallible, time::Duration};
+
+use evermore::Worker;
+
+use crate::{
+    backend::{ArcBackend, ChapterEntity},
+    jobs,
+    prelude::*,
+};
+
+/// How often scheduled chapters are checked for, so a chapter is published at
+/// most this late.
+pub const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Publishes scheduled chapters every [`INTERVAL`], see [`jobs::run`].
+pub async fn task(worker: Worker<ArcBackend>) -> Result<(), Infallible> {
+    jobs::repeat(&worker, "schedule", INTERVAL, publish).await
+}
+
+async fn publish(data: ArcBackend) -> Result<(), Error> {
+    let chapters = ChapterEntity::publish_scheduled(&data, OffsetDateTime::now_utc()).await?;
+
+    if !chapters.is_empty() {
+        info!(?chapters, "published scheduled chapters");
+    }
+
+    Ok(())
+}
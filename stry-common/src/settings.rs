@@ -0,0 +1,330 @@
+//! Site-wide settings that admins can change while the site is running,
+//! stored in the settings table.
+//!
+//! Every setting is listed in [`DEFINITIONS`], along with its default and the
+//! values it accepts. Values are cached once read, and writes made through
+//! [`Settings`] invalidate the keys they change.
+//!
+//! # Note
+//!
+//! The cache belongs to a single instance, other instances see a change once
+//! their cached value is older than [`CACHE_TTL`].
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration as StdDuration, Instant},
+};
+
+use dashmap::DashMap;
+use time::Duration;
+
+use crate::{
+    backend::{ArcBackend, SettingEntity},
+    error::{FieldError, Invalid},
+    models::core::{Setting, SettingValue},
+    prelude::*,
+};
+
+/// Whether anyone can register an account.
+pub const REGISTRATION_OPEN: &str = "registration-open";
+
+/// The number of days before an in progress story is abandoned, `0` (the
+/// default) turns abandoning off, see [`crate::abandon`].
+pub const AUTO_ABANDON: &str = "story-auto-abandon";
+
+/// The largest image that can be uploaded, in bytes, it can't be larger than
+/// [`crate::media::MAX_SIZE`].
+pub const MAX_UPLOAD_SIZE: &str = "media-max-upload-size";
+
+/// The name shown in the site's header and page titles.
+pub const SITE_NAME: &str = "site-name";
+
+/// How long a value is cached before it's read again.
+pub const CACHE_TTL: StdDuration = StdDuration::from_secs(60);
+
+/// Every known setting.
+pub const DEFINITIONS: &[Definition] = &[
+    Definition {
+        key: REGISTRATION_OPEN,
+        kind: Kind::Bool,
+        default: "true",
+    },
+    Definition {
+        key: AUTO_ABANDON,
+        kind: Kind::Integer { min: 0, max: 36500 },
+        default: "0",
+    },
+    Definition {
+        key: MAX_UPLOAD_SIZE,
+        kind: Kind::Integer {
+            min: 1,
+            max: crate::media::MAX_SIZE as i64,
+        },
+        default: "10485760",
+    },
+    Definition {
+        key: SITE_NAME,
+        kind: Kind::Text { max: 64 },
+        default: "stry",
+    },
+];
+
+/// The values a setting accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Either `true` or `false`.
+    Bool,
+    /// A whole number between `min` and `max`, inclusive.
+    Integer { min: i64, max: i64 },
+    /// Between one and `max` characters, surrounding whitespace is trimmed.
+    Text { max: usize },
+}
+
+/// A known setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Definition {
+    pub key: &'static str,
+    pub kind: Kind,
+    /// The value used when the setting hasn't been set, as it would be stored.
+    pub default: &'static str,
+}
+
+impl Definition {
+    /// The definition of the setting with the key.
+    pub fn find(key: &str) -> Option<&'static Definition> {
+        DEFINITIONS.iter().find(|definition| definition.key == key)
+    }
+
+    /// Validates a value, the error's path is the setting's key.
+    pub fn parse(&self, value: &str) -> Result<SettingValue, FieldError> {
+        let value = str::trim(value);
+
+        match self.kind {
+            Kind::Bool => match value {
+                "true" => Ok(SettingValue::Bool(true)),
+                "false" => Ok(SettingValue::Bool(false)),
+                _ => Err(FieldError::new(self.key, "boolean", BTreeMap::new())),
+            },
+            Kind::Integer { min, max } => {
+                let number = value
+                    .parse::<i64>()
+                    .map_err(|_| FieldError::new(self.key, "number", BTreeMap::new()))?;
+
+                if !(min..=max).contains(&number) {
+                    return Err(FieldError::new(self.key, "range", params(min, max)));
+                }
+
+                Ok(SettingValue::Integer(number))
+            }
+            Kind::Text { max } => {
+                if !(1..=max).contains(&value.chars().count()) {
+                    return Err(FieldError::new(self.key, "length", params(1, max)));
+                }
+
+                Ok(SettingValue::Text(value.to_string()))
+            }
+        }
+    }
+
+    /// The parsed default, see [`Definition::default`].
+    pub fn default_value(&self) -> SettingValue {
+        self.parse(self.default).unwrap_or_else(|err| {
+            panic!("the default of `{}` is invalid: {}", self.key, err.message)
+        })
+    }
+}
+
+fn params<N>(min: N, max: N) -> BTreeMap<String, serde_json::Value>
+where
+    N: Into<serde_json::Value>,
+{
+    let mut params = BTreeMap::new();
+
+    params.insert(String::from("min"), min.into());
+    params.insert(String::from("max"), max.into());
+
+    params
+}
+
+/// Reads and writes the settings, with typed getters for each of them.
+#[derive(Clone)]
+pub struct Settings {
+    data: ArcBackend,
+    cache: Arc<DashMap<&'static str, (SettingValue, Instant)>>,
+}
+
+impl Settings {
+    pub fn new(data: ArcBackend) -> Self {
+        Self {
+            data,
+            cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// The backend the settings are stored in.
+    pub fn data(&self) -> &ArcBackend {
+        &self.data
+    }
+
+    /// The setting's value, or its default if it hasn't been set.
+    ///
+    /// # Note
+    ///
+    /// A stored value that isn't valid (ie changed by hand) is logged and
+    /// the default is used instead.
+    pub async fn get(&self, key: &str) -> Result<SettingValue, Error> {
+        let definition = Definition::find(key).ok_or_else(|| err!("`{}` is not a setting", key))?;
+
+        if let Some(entry) = self.cache.get(definition.key) {
+            let (value, read) = entry.value();
+
+            if read.elapsed() < CACHE_TTL {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = match SettingEntity::get(&self.data, definition.key.to_string()).await? {
+            Some(value) => definition.parse(&value).unwrap_or_else(|err| {
+                warn!(
+                    setting = definition.key,
+                    value = %value,
+                    error = %err.message,
+                    "stored setting is invalid, using its default"
+                );
+
+                definition.default_value()
+            }),
+            None => definition.default_value(),
+        };
+
+        self.cache
+            .insert(definition.key, (value.clone(), Instant::now()));
+
+        Ok(value)
+    }
+
+    /// Every setting, in the order of [`DEFINITIONS`].
+    pub async fn all(&self) -> Result<Vec<Setting>, Error> {
+        let mut settings = Vec::with_capacity(DEFINITIONS.len());
+
+        for definition in DEFINITIONS {
+            settings.push(Setting {
+                key: definition.key.to_string(),
+                value: self.get(definition.key).await?,
+                default: definition.default_value(),
+            });
+        }
+
+        Ok(settings)
+    }
+
+    /// Validates and stores the values, errors with [`Invalid`] and stores
+    /// nothing if any of the keys or values are invalid.
+    pub async fn set(&self, values: BTreeMap<String, SettingValue>) -> Result<(), Error> {
+        let mut parsed = Vec::with_capacity(values.len());
+        let mut fields = Vec::new();
+
+        for (key, value) in values {
+            match Definition::find(&key) {
+                Some(definition) => match definition.parse(&value.to_string()) {
+                    Ok(value) => parsed.push((definition.key, value)),
+                    Err(err) => fields.push(err),
+                },
+                None => fields.push(FieldError::new(key, "setting", BTreeMap::new())),
+            }
+        }
+
+        if !fields.is_empty() {
+            return Err(Invalid { fields }.into());
+        }
+
+        for (key, value) in parsed {
+            SettingEntity::set(&self.data, key.to_string(), value.to_string()).await?;
+
+            self.cache.remove(key);
+        }
+
+        Ok(())
+    }
+
+    /// See [`REGISTRATION_OPEN`].
+    pub async fn registration_open(&self) -> Result<bool, Error> {
+        match self.get(REGISTRATION_OPEN).await? {
+            SettingValue::Bool(open) => Ok(open),
+            value => bail!("`{}` is not a boolean: {:?}", REGISTRATION_OPEN, value),
+        }
+    }
+
+    /// See [`AUTO_ABANDON`], `None` if abandoning is turned off.
+    pub async fn auto_abandon(&self) -> Result<Option<Duration>, Error> {
+        let days = self.integer(AUTO_ABANDON).await?;
+
+        Ok((days != 0).then(|| Duration::days(days)))
+    }
+
+    /// See [`MAX_UPLOAD_SIZE`].
+    pub async fn max_upload_size(&self) -> Result<usize, Error> {
+        Ok(usize::try_from(self.integer(MAX_UPLOAD_SIZE).await?)?)
+    }
+
+    /// See [`SITE_NAME`].
+    pub async fn site_name(&self) -> Result<String, Error> {
+        match self.get(SITE_NAME).await? {
+            SettingValue::Text(name) => Ok(name),
+            value => bail!("`{}` is not text: {:?}", SITE_NAME, value),
+        }
+    }
+
+    async fn integer(&self, key: &str) -> Result<i64, Error> {
+        match self.get(key).await? {
+            SettingValue::Integer(number) => Ok(number),
+            value => bail!("`{}` is not a number: {:?}", key, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        for definition in DEFINITIONS {
+            assert!(
+                definition.parse(definition.default).is_ok(),
+                "{}",
+                definition.key
+            );
+        }
+
+        assert_eq!(
+            Definition::find(MAX_UPLOAD_SIZE).unwrap().default_value(),
+            SettingValue::Integer(crate::media::MAX_SIZE as i64)
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let abandon = Definition::find(AUTO_ABANDON).unwrap();
+
+        assert_eq!(abandon.parse(" 30\n").unwrap(), SettingValue::Integer(30));
+        assert_eq!(abandon.parse("0").unwrap(), SettingValue::Integer(0));
+        assert_eq!(abandon.parse("-1").unwrap_err().code, "range");
+        assert_eq!(abandon.parse("a year").unwrap_err().code, "number");
+
+        let open = Definition::find(REGISTRATION_OPEN).unwrap();
+
+        assert_eq!(open.parse("false").unwrap(), SettingValue::Bool(false));
+        assert_eq!(open.parse("no").unwrap_err().code, "boolean");
+
+        let name = Definition::find(SITE_NAME).unwrap();
+
+        assert_eq!(
+            name.parse("  Archive ").unwrap(),
+            SettingValue::Text(String::from("Archive"))
+        );
+        assert_eq!(name.parse(" ").unwrap_err().code, "length");
+        assert_eq!(name.parse(&"a".repeat(65)).unwrap_err().code, "length");
+    }
+}
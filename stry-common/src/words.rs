@@ -0,0 +1,245 @@
+//! Word counts and reading time estimates.
+//!
+//! Text is split into words without a dictionary:
+//!
+//!   - Scripts that separate words with spaces (ie Latin, Cyrillic or Hangul)
+//!     are split on whitespace, runs of only punctuation aren't counted.
+//!   - Chinese and Japanese are counted by their characters, like their
+//!     writers usually do.
+//!   - Thai, Lao, Khmer and Myanmar don't separate their words, their runs
+//!     are counted by dividing their length by the script's average word
+//!     length.
+//!
+//! # Note
+//!
+//! Counts are worked out from the text whenever parts are written, see
+//! [`crate::html::sanitize_chapter`], any count sent by a client is replaced.
+
+use std::convert::TryFrom;
+
+use time::Duration;
+
+use crate::models::{
+    core::Part,
+    story::{Chapter, Story},
+    Either, Existing,
+};
+
+/// The average reading speed, in words (or Chinese and Japanese characters)
+/// per minute.
+pub const WORDS_PER_MINUTE: i64 = 238;
+
+/// How a character is counted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Space,
+    /// Part of a word in a script that uses spaces.
+    Spaced,
+    /// A word on its own.
+    Character,
+    /// Part of a run in a script without spaces, with the script's average
+    /// word length in characters.
+    Unspaced(u32),
+}
+
+fn class(c: char) -> Class {
+    if c.is_whitespace() {
+        return Class::Space;
+    }
+
+    match u32::from(c) {
+        // Thai and Lao
+        0x0E00..=0x0EFF => Class::Unspaced(5),
+        // Myanmar
+        0x1000..=0x109F | 0xA9E0..=0xA9FF | 0xAA60..=0xAA7F => Class::Unspaced(6),
+        // Khmer
+        0x1780..=0x17FF | 0x19E0..=0x19FF => Class::Unspaced(6),
+        // CJK radicals, kana, bopomofo and ideographs
+        0x2E80..=0x2FDF
+        | 0x3005..=0x3007
+        | 0x3021..=0x3029
+        | 0x3031..=0x3035
+        | 0x3038..=0x303C
+        | 0x3040..=0x30FF
+        | 0x3100..=0x312F
+        | 0x31A0..=0x31FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xF900..=0xFAFF
+        | 0xFF66..=0xFF9F
+        | 0x20000..=0x3134F => Class::Character,
+        _ => Class::Spaced,
+    }
+}
+
+/// The number of words in the text.
+pub fn count(text: &str) -> i64 {
+    let mut words = 0;
+
+    // The run being read, if any, and how long it is (or if it has any
+    // letters or digits for spaced runs)
+    let mut run: Option<(Class, u32)> = None;
+
+    let end = |run: &mut Option<(Class, u32)>, words: &mut i64| match run.take() {
+        Some((Class::Spaced, letters)) if letters != 0 => *words += 1,
+        Some((Class::Unspaced(average), length)) => *words += i64::from(length.div_ceil(average)),
+        _ => {}
+    };
+
+    for c in text.chars() {
+        let class = class(c);
+
+        match (&mut run, class) {
+            (_, Class::Space) => end(&mut run, &mut words),
+            (_, Class::Character) => {
+                end(&mut run, &mut words);
+
+                words += 1;
+            }
+            (Some((current, length)), _) if *current == class => {
+                if !matches!(class, Class::Spaced) || c.is_alphanumeric() {
+                    *length += 1;
+                }
+            }
+            _ => {
+                end(&mut run, &mut words);
+
+                let length = match class {
+                    Class::Spaced => u32::from(c.is_alphanumeric()),
+                    _ => 1,
+                };
+
+                run = Some((class, length));
+            }
+        }
+    }
+
+    end(&mut run, &mut words);
+
+    words
+}
+
+/// Splits the text into the tokens that are compared by [`crate::diff`],
+/// joining the tokens gives back the text.
+///
+/// Runs of whitespace, runs of a script that uses spaces, runs of a script
+/// without spaces, and each Chinese or Japanese character are their own
+/// tokens, so a changed character is only shown as a changed word.
+pub fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+
+    let mut start = 0;
+    let mut current: Option<Class> = None;
+
+    for (i, c) in text.char_indices() {
+        let class = class(c);
+
+        let joins = match (current, class) {
+            (_, Class::Character) => false,
+            (Some(current), class) => current == class,
+            (None, _) => false,
+        };
+
+        if !joins {
+            if i != start {
+                tokens.push(&text[start..i]);
+            }
+
+            start = i;
+        }
+
+        current = Some(class);
+    }
+
+    if start != text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// How long the words take to read, rounded up to the minute.
+pub fn reading_time(words: i64) -> Duration {
+    let words = words.max(0);
+
+    Duration::minutes((words + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE)
+}
+
+/// The number of words in the chapter's text parts, author's notes aren't
+/// counted.
+pub fn chapter(chapter: &Chapter) -> i64 {
+    parts(&chapter.main)
+}
+
+/// The number of words in the text parts.
+pub fn parts(parts: &[Existing<Part>]) -> i64 {
+    parts
+        .iter()
+        .map(|part| match &**part {
+            Part::Text(text) => text.words,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The sum of the story's chapters' word counts.
+///
+/// # Note
+///
+/// Is `0` if the chapters haven't been loaded, a count is never taken from
+/// the story itself as that could have been sent by a client.
+pub fn story(story: &Story) -> i32 {
+    let words = match &story.chapters {
+        Some(Either::Left(chapters)) => chapters.iter().map(|chapter| chapter.words).sum(),
+        _ => 0,
+    };
+
+    i32::try_from(words).unwrap_or(i32::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spaced() {
+        assert_eq!(count("The quick  brown fox — jumps."), 5);
+        assert_eq!(count("don't stop, co-op"), 3);
+        assert_eq!(count("Привет, мир! 안녕하세요 세계"), 4);
+        assert_eq!(count(" ... "), 0);
+    }
+
+    #[test]
+    fn test_characters() {
+        assert_eq!(count("我爱你。"), 3);
+        assert_eq!(count("日本語のテキスト"), 8);
+        assert_eq!(count("東京Tower 2020年"), 5);
+    }
+
+    #[test]
+    fn test_unspaced() {
+        // ภาษาไทย (Thai language) is 7 characters, two words
+        assert_eq!(count("ภาษาไทย"), 2);
+        assert_eq!(count("ภาษาไทย ง่าย"), 3);
+        assert_eq!(count("Thaiภาษา"), 2);
+    }
+
+    #[test]
+    fn test_split() {
+        assert_eq!(
+            tokens("The  fox, 東京Tower"),
+            vec!["The", "  ", "fox,", " ", "東", "京", "Tower"]
+        );
+        assert_eq!(tokens("ภาษา ไทย").concat(), "ภาษา ไทย");
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn test_minutes() {
+        assert_eq!(reading_time(0), Duration::ZERO);
+        assert_eq!(reading_time(1), Duration::minutes(1));
+        assert_eq!(reading_time(WORDS_PER_MINUTE * 3), Duration::minutes(3));
+        assert_eq!(reading_time(WORDS_PER_MINUTE * 3 + 1), Duration::minutes(4));
+    }
+}
@@ -0,0 +1,212 @@
+## Navigation
+
+browse-authors = authors
+browse-origins = origins
+browse-warnings = warnings
+browse-pairings = pairings
+browse-characters = characters
+browse-tags = tags
+browse-series = series
+
+nav-register = register
+nav-sign-in = sign-in
+
+next-page = next page
+
+## Feeds
+
+feed = feed
+feed-stories = New stories
+# $name (String) - The tag's name
+feed-tag = Stories tagged { $name }
+# $name (String) - The author's username
+feed-author = Stories by { $name }
+# $name (String) - The series' name
+feed-series = { $name }
+# $story (String) - The story's name
+# $chapter (String) - The chapter's name, or its number if it doesn't have one
+feed-chapter = { $story }: { $chapter }
+
+## Catalog
+
+opds-catalog = stry catalog
+
+## Stories
+
+tile-rating = Rating
+tile-warning = Warning
+tile-state = State
+
+# $count (Number) - The number of words, used to pick the plural form
+# $words (String) - The number of words formatted for the reader's language
+story-words = { $count ->
+    [one] { $words } word
+   *[other] { $words } words
+}
+
+# $count (Number) - The estimated reading time in minutes, used to pick the plural form
+# $minutes (String) - The number of minutes formatted for the reader's language
+story-reading-time = { $count ->
+    [one] { $minutes } minute
+   *[other] { $minutes } minutes
+}
+
+# $count (Number) - The number of chapters, used to pick the plural form
+# $chapters (String) - The number of chapters formatted for the reader's language
+story-chapters = { $count ->
+    [one] { $chapters } chapter
+   *[other] { $chapters } chapters
+}
+
+story-rating = Rating
+story-state = State
+story-words-label = Words
+story-reading-time-label = Reading time
+story-chapters-label = Chapters
+story-published = Published
+story-updated = Updated
+story-language = Language
+story-translators = Translated by
+story-translations = Translations
+story-original = original
+
+rating-general = General
+rating-teen = Teen
+rating-mature = Mature
+rating-explicit = Explicit
+
+state-completed = Completed
+state-in-progress = In Progress
+state-hiatus = Hiatus
+state-abandoned = Abandoned
+
+filter-any-rating = any rating
+filter-any-state = any state
+filter-any-language = any language
+filter-submit = filter
+
+## Chapters
+
+# $number (Number) - The chapter's position in the story, starting at one
+chapter-title = Chapter { $number }
+
+chapter-previous = previous
+chapter-next = next
+entire-work = entire work
+chapter-notes = Author's note
+
+## Downloads
+
+download = download as
+download-epub = EPUB
+download-html = HTML
+download-markdown = Markdown
+download-text = plain text
+
+# $authors (String) - The authors' usernames, separated by commas
+export-by = by { $authors }
+export-origins = Origins
+export-warnings = Warnings
+export-pairings = Pairings
+export-characters = Characters
+export-tags = Tags
+export-contents = Contents
+# $site (String) - The address of the site the story was downloaded from
+export-source = Read online at { $site }
+
+## Authors
+
+author-joined = joined
+author-stories = stories
+author-posts = posts
+
+## Accounts
+
+field-username = username
+field-email = email
+field-password = password
+
+register-title = register
+register-submit = register
+register-have-account = already have an account?
+register-sign-in = sign in
+register-closed = registrations are closed
+
+sign-in-title = sign in
+sign-in-submit = sign in
+sign-in-no-account = don't have an account?
+sign-in-register = register
+sign-in-incorrect = incorrect email or password
+
+too-many-attempts = too many attempts, try again later
+
+settings-title = settings
+settings-saved = settings saved
+settings-theme = theme
+settings-font-size = reader font size
+settings-collapse-notes = collapse author's notes
+settings-comments = comment notifications
+settings-content = hidden stories
+settings-rating = highest rating shown
+settings-hidden-warnings = hide stories with these warnings
+settings-hidden-tags = hide stories with these tags
+settings-ids-hint = ids, separated by spaces
+settings-save = save
+
+theme-dark = Dark
+theme-light = Light
+
+font-size-small = Small
+font-size-medium = Medium
+font-size-large = Large
+
+notifications-both = Email and website
+notifications-email = Email only
+notifications-web = Website only
+notifications-neither = Never
+
+sign-out = sign out
+
+## Admin
+
+admin-settings-title = site settings
+admin-setting-registration-open = registrations
+admin-setting-story-auto-abandon = days before an in progress story is abandoned, 0 to never abandon
+admin-setting-media-max-upload-size = largest image upload, in bytes
+admin-setting-site-name = site name
+admin-setting-on = Open
+admin-setting-off = Closed
+admin-setting-default = default: { $value }
+
+## Field errors
+
+# $bounds (String) - Which limits the field has, one of `exact`, `between`, `min`, `max` or `none`
+# $min (String) - The smallest allowed length or value
+# $max (String) - The largest allowed length or value
+# $equal (String) - The only allowed length
+field-error-length = { $bounds ->
+    [exact] must be exactly { $equal } characters long
+    [between] must be between { $min } and { $max } characters long
+    [min] must be at least { $min } characters long
+    [max] must be at most { $max } characters long
+   *[none] has an invalid length
+}
+field-error-range = { $bounds ->
+    [between] must be between { $min } and { $max }
+    [min] must be at least { $min }
+    [max] must be at most { $max }
+   *[none] is out of range
+}
+field-error-email = must be a valid email address
+field-error-url = must be a valid url
+field-error-required = is required
+field-error-type = has the wrong type
+field-error-unique = is already in use
+field-error-language = must be a language tag, ie `en` or `pt-BR`
+field-error-ids = must be ids separated by spaces
+field-error-media = must be the id of uploaded media
+field-error-chapters = must list each of the story's chapters once
+field-error-boolean = must be `true` or `false`
+field-error-number = must be a whole number
+field-error-setting = is not a setting
+field-error-exists = does not exist
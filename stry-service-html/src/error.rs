@@ -67,6 +67,7 @@ impl axum::response::IntoResponse for Error {
                 code: status.as_u16(),
                 status: status.canonical_reason(),
                 message,
+                fields: Vec::new(),
             },
         };
 
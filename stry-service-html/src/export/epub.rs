@@ -0,0 +1,582 @@
+//! EPUB 3 books of a story, or of every story in a series.
+//!
+//! The documents are written with [`super::html`], which is already valid XHTML.
+
+use std::{
+    fmt::Write as _,
+    io::{Cursor, Write as _},
+};
+
+use stry_common::{
+    config::Config,
+    media::MediaStorage,
+    models::{
+        story::{Chapter, Series, Story},
+        Existing,
+    },
+    prelude::*,
+};
+
+use time::UtcOffset;
+use windswept::Escape;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::i18n::{t, I18n};
+
+use super::{
+    html::{self, Html},
+    Format as _, Image, Images,
+};
+
+pub const CONTENT_TYPE: &str = "application/epub+zip";
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// A book of the story's title page and each of its chapters.
+pub async fn story(
+    i18n: &I18n,
+    config: &Config,
+    storage: &MediaStorage,
+    story: &Existing<Story>,
+    chapters: &[Existing<Chapter>],
+) -> Result<Vec<u8>, Error> {
+    let mut book = Book::new(i18n, config, storage);
+
+    book.story(story, chapters, 0).await?;
+
+    book.finish(&Metadata {
+        id: format!("{}/stories/{}", config.url, story.id.as_str()),
+        title: story.name.clone(),
+        language: story.language.clone(),
+        authors: story
+            .authors
+            .iter()
+            .map(|author| author.account.name.clone())
+            .collect(),
+        summary: story.summary.clone(),
+        subjects: story.tags.iter().map(|tag| tag.content.clone()).collect(),
+        modified: chapters
+            .iter()
+            .map(|chapter| chapter.updated)
+            .fold(story.updated, Ord::max),
+    })
+}
+
+/// A book of the series' stories, each with its own title page and chapters.
+pub async fn series(
+    i18n: &I18n,
+    config: &Config,
+    storage: &MediaStorage,
+    series: &Existing<Series>,
+    works: &[(Existing<Story>, Vec<Existing<Chapter>>)],
+) -> Result<Vec<u8>, Error> {
+    let mut book = Book::new(i18n, config, storage);
+
+    let mut body = String::new();
+
+    writeln!(body, "<h1>{}</h1>", Escape(&series.name))?;
+    Html { src: &|_| None }.text(&mut body, &series.summary)?;
+    writeln!(body, "<dl>")?;
+    writeln!(
+        body,
+        "<dt>{}</dt><dd>{}</dd>",
+        t!(i18n, "story-state"),
+        t!(i18n, super::state(series.state))
+    )?;
+    writeln!(body, "</dl>")?;
+
+    book.documents.push(Document {
+        file: format!("{}.xhtml", series.id.as_str()),
+        title: series.name.clone(),
+        language: i18n.lang().to_string(),
+        depth: 0,
+        body,
+    });
+
+    let mut authors = Vec::new();
+
+    for (story, chapters) in works {
+        book.story(story, chapters, 1).await?;
+
+        for author in &story.authors {
+            if !authors.contains(&author.account.name) {
+                authors.push(author.account.name.clone());
+            }
+        }
+    }
+
+    book.finish(&Metadata {
+        id: format!("{}/series/{}", config.url, series.id.as_str()),
+        title: series.name.clone(),
+        language: works
+            .first()
+            .map(|(story, _)| story.language.clone())
+            .unwrap_or_else(|| i18n.lang().to_string()),
+        authors,
+        summary: series.summary.clone(),
+        subjects: Vec::new(),
+        modified: works
+            .iter()
+            .flat_map(|(story, chapters)| {
+                std::iter::once(story.updated).chain(chapters.iter().map(|chapter| chapter.updated))
+            })
+            .fold(series.updated, Ord::max),
+    })
+}
+
+/// The package's metadata, what readers show in their library.
+struct Metadata {
+    /// An absolute url, used as the book's unique identifier.
+    id: String,
+    title: String,
+    language: String,
+    authors: Vec<String>,
+    summary: String,
+    subjects: Vec<String>,
+    modified: OffsetDateTime,
+}
+
+struct Document {
+    file: String,
+    title: String,
+    language: String,
+    /// How far the document is nested in the table of contents, chapters of
+    /// a series' story are under the story's title page.
+    depth: usize,
+    body: String,
+}
+
+struct Book<'r> {
+    i18n: &'r I18n,
+    config: &'r Config,
+    storage: &'r MediaStorage,
+    /// In reading order.
+    documents: Vec<Document>,
+    images: Images,
+}
+
+impl<'r> Book<'r> {
+    fn new(i18n: &'r I18n, config: &'r Config, storage: &'r MediaStorage) -> Self {
+        Self {
+            i18n,
+            config,
+            storage,
+            documents: Vec::new(),
+            images: Images::default(),
+        }
+    }
+
+    async fn story(
+        &mut self,
+        story: &Existing<Story>,
+        chapters: &[Existing<Chapter>],
+        depth: usize,
+    ) -> Result<(), Error> {
+        self.images.add(self.storage, chapters).await;
+
+        let mut body = String::new();
+
+        html::title_page(&mut body, self.i18n, self.config, story, chapters.len())?;
+
+        self.documents.push(Document {
+            file: format!("{}.xhtml", story.id.as_str()),
+            title: story.name.clone(),
+            language: self.i18n.lang().to_string(),
+            depth: depth.saturating_sub(1),
+            body,
+        });
+
+        // Books can't show images from elsewhere, only what they were of is kept
+        let images = &self.images;
+        let src = |url: &str| images.get(url).map(|(index, image)| file(index, image));
+        let format = Html { src: &src };
+
+        for (index, chapter) in chapters.iter().enumerate() {
+            let mut body = String::new();
+
+            html::chapter(&mut body, &format, self.i18n, index + 1, chapter)?;
+
+            self.documents.push(Document {
+                file: format!("{}-{}.xhtml", story.id.as_str(), index + 1),
+                title: super::chapter_title(self.i18n, index + 1, chapter),
+                language: story.language.clone(),
+                depth,
+                body,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn finish(self, metadata: &Metadata) -> Result<Vec<u8>, Error> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        // Readers expect the mimetype first and uncompressed so they can sniff it
+        zip.start_file(
+            "mimetype",
+            FileOptions::default().compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(CONTENT_TYPE.as_bytes())?;
+
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("META-INF/container.xml", options)?;
+        zip.write_all(CONTAINER.as_bytes())?;
+
+        zip.start_file("OEBPS/content.opf", options)?;
+        zip.write_all(self.package(metadata)?.as_bytes())?;
+
+        zip.start_file("OEBPS/nav.xhtml", options)?;
+        zip.write_all(self.nav()?.as_bytes())?;
+
+        for document in &self.documents {
+            zip.start_file(format!("OEBPS/{}", document.file), options)?;
+            zip.write_all(xhtml(&document.language, &document.title, &document.body)?.as_bytes())?;
+        }
+
+        for (index, image) in self.images.iter().enumerate() {
+            zip.start_file(format!("OEBPS/{}", file(index, image)), options)?;
+            zip.write_all(&image.data)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    fn package(&self, metadata: &Metadata) -> Result<String, Error> {
+        let modified = metadata.modified.to_offset(UtcOffset::UTC);
+
+        let mut out = String::new();
+
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id" xml:lang="{}">"#,
+            Escape(&metadata.language)
+        )?;
+        writeln!(
+            out,
+            r#"<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">"#
+        )?;
+        writeln!(
+            out,
+            r#"<dc:identifier id="id">{}</dc:identifier>"#,
+            Escape(&metadata.id)
+        )?;
+        writeln!(out, "<dc:title>{}</dc:title>", Escape(&metadata.title))?;
+        writeln!(
+            out,
+            "<dc:language>{}</dc:language>",
+            Escape(&metadata.language)
+        )?;
+
+        for author in &metadata.authors {
+            writeln!(out, "<dc:creator>{}</dc:creator>", Escape(author))?;
+        }
+
+        if !metadata.summary.is_empty() {
+            writeln!(
+                out,
+                "<dc:description>{}</dc:description>",
+                Escape(&metadata.summary)
+            )?;
+        }
+
+        for subject in &metadata.subjects {
+            writeln!(out, "<dc:subject>{}</dc:subject>", Escape(subject))?;
+        }
+
+        // EPUB requires the timestamp without fractional seconds
+        writeln!(
+            out,
+            r#"<meta property="dcterms:modified">{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z</meta>"#,
+            modified.year(),
+            u8::from(modified.month()),
+            modified.day(),
+            modified.hour(),
+            modified.minute(),
+            modified.second()
+        )?;
+        writeln!(out, "</metadata>")?;
+
+        writeln!(out, "<manifest>")?;
+        writeln!(
+            out,
+            r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#
+        )?;
+
+        for (index, document) in self.documents.iter().enumerate() {
+            writeln!(
+                out,
+                r#"<item id="document-{}" href="{}" media-type="application/xhtml+xml"/>"#,
+                index,
+                Escape(&document.file)
+            )?;
+        }
+
+        for (index, image) in self.images.iter().enumerate() {
+            writeln!(
+                out,
+                r#"<item id="image-{}" href="{}" media-type="{}"/>"#,
+                index,
+                Escape(file(index, image)),
+                image.media_type
+            )?;
+        }
+
+        writeln!(out, "</manifest>")?;
+
+        // The table of contents comes right after the first title page
+        writeln!(out, "<spine>")?;
+
+        for index in 0..self.documents.len() {
+            writeln!(out, r#"<itemref idref="document-{}"/>"#, index)?;
+
+            if index == 0 {
+                writeln!(out, r#"<itemref idref="nav"/>"#)?;
+            }
+        }
+
+        writeln!(out, "</spine>")?;
+        writeln!(out, "</package>")?;
+
+        Ok(out)
+    }
+
+    fn nav(&self) -> Result<String, Error> {
+        let title = t!(self.i18n, "export-contents");
+
+        let mut body = String::new();
+
+        writeln!(body, r#"<nav epub:type="toc">"#)?;
+        writeln!(body, "<h1>{}</h1>", title)?;
+        writeln!(body, "<ol>")?;
+
+        let mut depth = 0;
+
+        for (index, document) in self.documents.iter().enumerate() {
+            if index != 0 {
+                if document.depth > depth {
+                    writeln!(body, "<ol>")?;
+                } else {
+                    writeln!(body, "</li>")?;
+
+                    for _ in document.depth..depth {
+                        writeln!(body, "</ol></li>")?;
+                    }
+                }
+            }
+
+            write!(
+                body,
+                r#"<li><a href="{}">{}</a>"#,
+                Escape(&document.file),
+                Escape(&document.title)
+            )?;
+
+            depth = document.depth;
+        }
+
+        if !self.documents.is_empty() {
+            writeln!(body, "</li>")?;
+
+            for _ in 0..depth {
+                writeln!(body, "</ol></li>")?;
+            }
+        }
+
+        writeln!(body, "</ol>")?;
+        writeln!(body, "</nav>")?;
+
+        xhtml(&self.i18n.lang().to_string(), &title.0, &body)
+    }
+}
+
+fn xhtml(language: &str, title: &str, body: &str) -> Result<String, Error> {
+    let mut out = String::new();
+
+    writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(
+        out,
+        r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{0}" xml:lang="{0}">"#,
+        Escape(language)
+    )?;
+    writeln!(out, "<head>")?;
+    writeln!(out, r#"<meta charset="utf-8"/>"#)?;
+    writeln!(out, "<title>{}</title>", Escape(title))?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    out.push_str(body);
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+
+    Ok(out)
+}
+
+/// Where the image is stored in the book.
+fn file(index: usize, image: &Image) -> String {
+    format!("images/{}.{}", index + 1, image.extension)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read as _;
+
+    use stry_common::{
+        media::LocalStore,
+        models::{story::State, Either},
+    };
+
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::{
+        export::test::{chapter, config, existing, story},
+        i18n::english,
+    };
+
+    /// Every value of the `attribute` of the `element`s in the document.
+    fn attributes(xml: &str, element: &str, attribute: &str) -> Vec<String> {
+        xml.split(&format!("<{} ", element))
+            .skip(1)
+            .filter_map(|tag| {
+                let tag = format!(" {}", &tag[..tag.find('>')?]);
+                let start = tag.find(&format!(" {}=\"", attribute))? + attribute.len() + 3;
+                let end = start + tag[start..].find('"')?;
+
+                Some(tag[start..end].to_string())
+            })
+            .collect()
+    }
+
+    /// Checks the book is a valid container, returning the files the table
+    /// of contents links to.
+    fn check(book: Vec<u8>) -> Vec<String> {
+        let mut zip = ZipArchive::new(Cursor::new(book)).unwrap();
+
+        let files = zip.file_names().map(String::from).collect::<Vec<_>>();
+
+        {
+            let mut mimetype = zip.by_index(0).unwrap();
+            let mut content = String::new();
+
+            mimetype.read_to_string(&mut content).unwrap();
+
+            assert_eq!("mimetype", mimetype.name());
+            assert_eq!(CompressionMethod::Stored, mimetype.compression());
+            assert_eq!(CONTENT_TYPE, content);
+        }
+
+        let mut read = |name: &str| {
+            let mut content = String::new();
+
+            zip.by_name(name)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+
+            content
+        };
+
+        let container = read("META-INF/container.xml");
+
+        assert_eq!(
+            vec!["OEBPS/content.opf"],
+            attributes(&container, "rootfile", "full-path")
+        );
+
+        let package = read("OEBPS/content.opf");
+
+        let ids = attributes(&package, "item", "id");
+        let hrefs = attributes(&package, "item", "href");
+
+        for href in &hrefs {
+            assert!(files.contains(&format!("OEBPS/{}", href)), "{}", href);
+        }
+
+        // The manifest has everything but the mimetype, container and itself
+        assert_eq!(hrefs.len() + 3, files.len());
+        assert!(package.contains(
+            r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#
+        ));
+
+        let spine = attributes(&package, "itemref", "idref");
+
+        assert_eq!(ids.len(), spine.len());
+
+        for idref in &spine {
+            assert!(ids.contains(idref), "{}", idref);
+        }
+
+        let nav = read("OEBPS/nav.xhtml");
+
+        let links = attributes(&nav, "a", "href");
+
+        // Everything but the table of contents itself, in reading order
+        assert_eq!(
+            hrefs
+                .into_iter()
+                .filter(|href| href != "nav.xhtml")
+                .collect::<Vec<_>>(),
+            links
+        );
+
+        links
+    }
+
+    #[tokio::test]
+    async fn test_story() {
+        let i18n = english();
+        let storage = MediaStorage::new(LocalStore::new(std::env::temp_dir()));
+
+        let chapters = [
+            chapter("chapter1", Some("Beginning"), "<p>One</p>"),
+            chapter("chapter2", None, "<p>Two</p>"),
+        ];
+
+        let book = super::story(&i18n, &config(), &storage, &story(), &chapters)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec!["story001.xhtml", "story001-1.xhtml", "story001-2.xhtml"],
+            check(book)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_series() {
+        let i18n = english();
+        let storage = MediaStorage::new(LocalStore::new(std::env::temp_dir()));
+
+        let series = existing(
+            "series01",
+            Series {
+                name: String::from("Pets"),
+                summary: String::new(),
+                state: State::InProgress,
+                stories: Either::Right(vec![]),
+            },
+        );
+
+        let works = [(
+            story(),
+            vec![chapter("chapter1", Some("Beginning"), "<p>One</p>")],
+        )];
+
+        let book = super::series(&i18n, &config(), &storage, &series, &works)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec!["series01.xhtml", "story001.xhtml", "story001-1.xhtml"],
+            check(book)
+        );
+    }
+}
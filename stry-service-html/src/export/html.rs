@@ -0,0 +1,266 @@
+//! A story or chapter as a single HTML file, with its styles and any locally
+//! stored images inlined.
+//!
+//! Elements are written by hand and empty elements closed (ie `<br/>`), so
+//! the same output is valid XHTML for [`super::epub`].
+
+use std::fmt::{self, Write as _};
+
+use stry_common::{
+    config::Config,
+    html::{from_text, to_xhtml},
+    media::MediaStorage,
+    models::{
+        core::Part,
+        story::{Chapter, Story},
+        Existing,
+    },
+    prelude::*,
+};
+
+use windswept::Escape;
+
+use crate::i18n::{t, I18n};
+
+use super::{Format, Images};
+
+pub const CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+const STYLE: &str = "body { max-width: 40em; margin: 0 auto; padding: 1em; font-family: serif; line-height: 1.5; } \
+    dt { font-weight: bold; } \
+    figure { margin: 1em 0; text-align: center; } \
+    img { max-width: 100%; } \
+    aside { margin: 1em 0; padding: 0 1em; border-left: 0.25em solid #999; } \
+    section { margin-top: 3em; }";
+
+/// Parts as HTML elements.
+pub struct Html<'r> {
+    /// What an image's url is replaced with, images without one only keep
+    /// their description.
+    pub src: &'r (dyn Fn(&str) -> Option<String> + Sync),
+}
+
+impl<'r> Format for Html<'r> {
+    fn heading(&self, out: &mut String, level: u8, content: &str) -> fmt::Result {
+        let level = level.clamp(1, 5) + 1;
+
+        writeln!(out, "<h{0}>{1}</h{0}>", level, Escape(content))
+    }
+
+    fn image(&self, out: &mut String, url: &str, alt: &str) -> fmt::Result {
+        match (self.src)(url) {
+            Some(src) => writeln!(
+                out,
+                r#"<figure><img src="{}" alt="{}"/></figure>"#,
+                Escape(src),
+                Escape(alt)
+            ),
+            None if !alt.is_empty() => writeln!(out, "<p>[{}]</p>", Escape(alt)),
+            None => Ok(()),
+        }
+    }
+
+    fn text(&self, out: &mut String, content: &str) -> fmt::Result {
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}", to_xhtml(content))
+    }
+}
+
+/// The story's details and the given chapters, `first` being the number of
+/// the first chapter.
+pub async fn document(
+    i18n: &I18n,
+    config: &Config,
+    storage: &MediaStorage,
+    story: &Existing<Story>,
+    chapters: &[Existing<Chapter>],
+    first: usize,
+) -> Result<String, Error> {
+    let mut images = Images::default();
+
+    images.add(storage, chapters).await;
+
+    let src = |url: &str| match images.get(url) {
+        Some((_, image)) => Some(format!(
+            "data:{};base64,{}",
+            image.media_type,
+            base64::encode(&image.data)
+        )),
+        None => Some(super::absolute(config, url)),
+    };
+
+    let html = Html { src: &src };
+
+    let mut out = String::new();
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, r#"<html lang="{}">"#, Escape(i18n.lang().to_string()))?;
+    writeln!(out, "<head>")?;
+    writeln!(out, r#"<meta charset="utf-8"/>"#)?;
+    writeln!(
+        out,
+        r#"<meta name="viewport" content="width=device-width, initial-scale=1"/>"#
+    )?;
+    writeln!(out, "<title>{}</title>", Escape(&story.name))?;
+    writeln!(out, "<style>{}</style>", STYLE)?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    writeln!(out, "<header>")?;
+    title_page(&mut out, i18n, config, story, chapters.len())?;
+    writeln!(out, "</header>")?;
+
+    for (index, chapter_) in chapters.iter().enumerate() {
+        writeln!(out, r#"<section lang="{}">"#, Escape(&story.language))?;
+        chapter(&mut out, &html, i18n, first + index, chapter_)?;
+        writeln!(out, "</section>")?;
+    }
+
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+
+    Ok(out)
+}
+
+/// The story's name, authors, summary and details, with a link back to it.
+pub fn title_page(
+    out: &mut String,
+    i18n: &I18n,
+    config: &Config,
+    story: &Existing<Story>,
+    chapters: usize,
+) -> fmt::Result {
+    let lang = Escape(&story.language);
+
+    writeln!(out, r#"<h1 lang="{}">{}</h1>"#, lang, Escape(&story.name))?;
+    writeln!(
+        out,
+        "<p>{}</p>",
+        t!(i18n, "export-by", authors = super::names(&story.authors))
+    )?;
+
+    if !story.summary.is_empty() {
+        writeln!(out, r#"<div lang="{}">"#, lang)?;
+        Html { src: &|_| None }.text(out, &from_text(&story.summary))?;
+        writeln!(out, "</div>")?;
+    }
+
+    writeln!(out, "<dl>")?;
+
+    for (label, value) in super::details(i18n, story, chapters) {
+        writeln!(out, "<dt>{}</dt><dd>{}</dd>", Escape(label), Escape(value))?;
+    }
+
+    writeln!(out, "</dl>")?;
+    writeln!(
+        out,
+        r#"<p><a href="{}/stories/{}">{}</a></p>"#,
+        Escape(&config.url),
+        story.id.as_str(),
+        t!(i18n, "export-source", site = config.url.as_str())
+    )
+}
+
+/// The chapter's title and its parts, between the author's notes.
+pub fn chapter<F: Format>(
+    out: &mut String,
+    format: &F,
+    i18n: &I18n,
+    number: usize,
+    chapter: &Existing<Chapter>,
+) -> fmt::Result {
+    writeln!(
+        out,
+        "<h1>{}</h1>",
+        Escape(super::chapter_title(i18n, number, chapter))
+    )?;
+    notes(out, format, i18n, &chapter.prefix)?;
+    format.parts(out, &chapter.main)?;
+    notes(out, format, i18n, &chapter.suffix)
+}
+
+/// An author's note, skipped when the chapter doesn't have one.
+fn notes<F: Format>(
+    out: &mut String,
+    format: &F,
+    i18n: &I18n,
+    parts: &[Existing<Part>],
+) -> fmt::Result {
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "<aside>")?;
+    writeln!(out, "<h2>{}</h2>", t!(i18n, "chapter-notes"))?;
+    format.parts(out, parts)?;
+    writeln!(out, "</aside>")
+}
+
+#[cfg(test)]
+mod test {
+    use stry_common::media::LocalStore;
+
+    use super::*;
+    use crate::{
+        export::test::{chapter, config, story},
+        i18n::english,
+    };
+
+    #[test]
+    fn test_parts() {
+        let html = Html {
+            src: &|url| {
+                Some(format!("https://example.com{}", url)).filter(|_| url.starts_with('/'))
+            },
+        };
+
+        let mut out = String::new();
+
+        html.heading(&mut out, 1, "Cats & Dogs").unwrap();
+        html.heading(&mut out, 9, "Deep").unwrap();
+        html.image(&mut out, "/media/cat.png", "A cat").unwrap();
+        html.image(&mut out, "https://elsewhere.com/dog.png", "A dog")
+            .unwrap();
+        html.image(&mut out, "https://elsewhere.com/bird.png", "")
+            .unwrap();
+        html.text(&mut out, "").unwrap();
+
+        assert_eq!(
+            concat!(
+                "<h2>Cats &amp; Dogs</h2>\n",
+                "<h6>Deep</h6>\n",
+                "<figure><img src=\"https://example.com/media/cat.png\" alt=\"A cat\"/></figure>\n",
+                "<p>[A dog]</p>\n",
+            ),
+            out
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document() {
+        let i18n = english();
+        let storage = MediaStorage::new(LocalStore::new(std::env::temp_dir()));
+
+        let mut first = chapter("chapter1", Some("Beginning"), "<p>One</p>");
+
+        first.prefix = first.main.clone();
+
+        let chapters = [first, chapter("chapter2", None, "<p>Two</p>")];
+
+        let out = document(&i18n, &config(), &storage, &story(), &chapters, 1)
+            .await
+            .unwrap()
+            .replace(['\u{2068}', '\u{2069}'], "");
+
+        assert!(out.contains("<title>Cats &amp; Dogs</title>"));
+        assert!(out.contains(r#"<h1 lang="en">Cats &amp; Dogs</h1>"#));
+        assert!(out.contains("<p>by Author</p>"));
+        assert!(out.contains(r#"<a href="https://example.com/stories/story001">"#));
+        assert!(out.contains(
+            "<h1>Beginning</h1>\n<aside>\n<h2>Author&#39;s note</h2>\n<p>One</p>\n</aside>\n<p>One</p>\n"
+        ));
+        assert!(out.contains("<h1>Chapter 2</h1>\n<p>Two</p>\n"));
+    }
+}
@@ -0,0 +1,182 @@
+//! A story or chapter as Markdown (CommonMark), images link back to the site.
+
+use std::fmt::{self, Write as _};
+
+use stry_common::{
+    config::Config,
+    html::from_text,
+    markdown::{escape, from_html},
+    models::{
+        core::Part,
+        story::{Chapter, Story},
+        Existing,
+    },
+    prelude::*,
+};
+
+use crate::i18n::{t, I18n};
+
+use super::Format;
+
+pub const CONTENT_TYPE: &str = "text/markdown; charset=utf-8";
+
+/// Parts as Markdown blocks, each followed by a blank line.
+pub struct Markdown<'r> {
+    pub config: &'r Config,
+}
+
+impl<'r> Format for Markdown<'r> {
+    fn heading(&self, out: &mut String, level: u8, content: &str) -> fmt::Result {
+        let level = usize::from(level.clamp(1, 5)) + 1;
+
+        writeln!(out, "{} {}\n", "#".repeat(level), escape(content))
+    }
+
+    fn image(&self, out: &mut String, url: &str, alt: &str) -> fmt::Result {
+        writeln!(
+            out,
+            "![{}](<{}>)\n",
+            escape(alt),
+            super::absolute(self.config, url)
+                .replace('<', "%3C")
+                .replace('>', "%3E")
+        )
+    }
+
+    fn text(&self, out: &mut String, content: &str) -> fmt::Result {
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(out, "{}\n", from_html(content))
+    }
+}
+
+/// The story's details and the given chapters, `first` being the number of
+/// the first chapter.
+pub fn document(
+    i18n: &I18n,
+    config: &Config,
+    story: &Existing<Story>,
+    chapters: &[Existing<Chapter>],
+    first: usize,
+) -> Result<String, Error> {
+    let markdown = Markdown { config };
+
+    let mut out = String::new();
+
+    writeln!(out, "# {}\n", escape(&story.name))?;
+    writeln!(
+        out,
+        "{}\n",
+        escape(&t!(i18n, "export-by", authors = super::names(&story.authors)).0)
+    )?;
+    markdown.text(&mut out, &from_text(&story.summary))?;
+
+    for (label, value) in super::details(i18n, story, chapters.len()) {
+        writeln!(out, "- **{}:** {}", escape(&label), escape(&value))?;
+    }
+
+    writeln!(
+        out,
+        "\n[{}](<{}/stories/{}>)\n",
+        escape(&t!(i18n, "export-source", site = config.url.as_str()).0),
+        config.url,
+        story.id.as_str()
+    )?;
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        writeln!(out, "---\n")?;
+        writeln!(
+            out,
+            "# {}\n",
+            escape(&super::chapter_title(i18n, first + index, chapter))
+        )?;
+        notes(&mut out, &markdown, i18n, &chapter.prefix)?;
+        markdown.parts(&mut out, &chapter.main)?;
+        notes(&mut out, &markdown, i18n, &chapter.suffix)?;
+    }
+
+    Ok(out)
+}
+
+/// An author's note as a block quote, skipped when the chapter doesn't have one.
+fn notes(
+    out: &mut String,
+    markdown: &Markdown<'_>,
+    i18n: &I18n,
+    parts: &[Existing<Part>],
+) -> fmt::Result {
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    let mut note = format!("**{}**\n\n", escape(&t!(i18n, "chapter-notes").0));
+
+    markdown.parts(&mut note, parts)?;
+
+    for line in str::trim_end(&note).lines() {
+        if line.is_empty() {
+            writeln!(out, ">")?;
+        } else {
+            writeln!(out, "> {}", line)?;
+        }
+    }
+
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        export::test::{chapter, config, story},
+        i18n::english,
+    };
+
+    #[test]
+    fn test_parts() {
+        let config = config();
+        let markdown = Markdown { config: &config };
+
+        let mut out = String::new();
+
+        markdown.heading(&mut out, 1, "Cats & Dogs").unwrap();
+        markdown
+            .image(&mut out, "/media/cat.png", "A [cat]")
+            .unwrap();
+        markdown
+            .image(&mut out, "https://elsewhere.com/<dog>.png", "")
+            .unwrap();
+        markdown.text(&mut out, "").unwrap();
+
+        assert_eq!(
+            concat!(
+                "## Cats \\& Dogs\n\n",
+                "![A \\[cat\\]](<https://example.com/media/cat.png>)\n\n",
+                "![](<https://elsewhere.com/%3Cdog%3E.png>)\n\n",
+            ),
+            out
+        );
+    }
+
+    #[test]
+    fn test_document() {
+        let i18n = english();
+
+        let mut first = chapter("chapter1", Some("Beginning"), "<p>One</p>");
+
+        first.suffix = first.main.clone();
+
+        let chapters = [first, chapter("chapter2", None, "<p>Two</p>")];
+
+        let out = document(&i18n, &config(), &story(), &chapters, 1)
+            .unwrap()
+            .replace(['\u{2068}', '\u{2069}'], "");
+
+        assert!(out.starts_with("# Cats \\& Dogs\n\nby Author\n\nA summary\n\n"));
+        assert!(out.contains("(<https://example.com/stories/story001>)"));
+        assert!(out.contains("---\n\n# Beginning\n\nOne\n\n> **Author's note**\n>\n> One\n\n"));
+        assert!(out.ends_with("---\n\n# Chapter 2\n\nTwo\n\n"));
+    }
+}
@@ -0,0 +1,320 @@
+//! Stories and series as files for reading offline.
+//!
+//! Each format implements [`Format`] to write out a chapter's parts, the
+//! same renderers are used wherever parts leave the site (ie feeds).
+
+pub mod epub;
+pub mod html;
+pub mod markdown;
+pub mod text;
+
+use std::fmt;
+
+use stry_common::{
+    config::Config,
+    media::{self, MediaStorage},
+    models::{
+        core::{Part, User},
+        story::{Chapter, Rating, State, Story},
+        Existing,
+    },
+    prelude::*,
+};
+
+use crate::{
+    i18n::{format::number, t, I18n},
+    templates::partials,
+};
+
+/// How each kind of part is written out in a format.
+pub trait Format {
+    /// The chapter's title is always the only first level heading, so the
+    /// part's `level` should be pushed down one.
+    fn heading(&self, out: &mut String, level: u8, content: &str) -> fmt::Result;
+
+    fn image(&self, out: &mut String, url: &str, alt: &str) -> fmt::Result;
+
+    /// The `content` is sanitized HTML, plain text should be converted with
+    /// [`stry_common::html::from_text`] first.
+    fn text(&self, out: &mut String, content: &str) -> fmt::Result;
+
+    fn parts(&self, out: &mut String, parts: &[Existing<Part>]) -> fmt::Result {
+        for part in parts {
+            match &**part {
+                Part::Heading(heading) => self.heading(out, heading.level, &heading.content)?,
+                Part::Image(image) => {
+                    self.image(out, &image.url, image.alt.as_deref().unwrap_or_default())?
+                }
+                Part::Text(text) => self.text(out, &text.content)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An image read out of the media store.
+pub struct Image {
+    pub url: String,
+    pub extension: String,
+    pub media_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// The locally stored images of the exported chapters, for formats that embed
+/// them instead of linking back to the site.
+#[derive(Default)]
+pub struct Images {
+    images: Vec<Image>,
+}
+
+impl Images {
+    /// Reads every locally stored image in the chapters that hasn't been
+    /// read already.
+    pub async fn add(&mut self, storage: &MediaStorage, chapters: &[Existing<Chapter>]) {
+        for chapter in chapters {
+            let parts = chapter
+                .prefix
+                .iter()
+                .chain(&chapter.main)
+                .chain(&chapter.suffix);
+
+            for part in parts {
+                if let Part::Image(image) = &**part {
+                    if self.get(&image.url).is_none() {
+                        if let Some(image) = read(storage, &image.url).await {
+                            self.images.push(image);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The image and its position, which stays the same as images are added.
+    pub fn get(&self, url: &str) -> Option<(usize, &Image)> {
+        self.images
+            .iter()
+            .enumerate()
+            .find(|(_, image)| image.url == url)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Image> {
+        self.images.iter()
+    }
+}
+
+/// Returns `None` for images that are hosted elsewhere or can't be read.
+async fn read(storage: &MediaStorage, url: &str) -> Option<Image> {
+    let name = url.strip_prefix("/media/")?;
+
+    let media_type = media::media_type(name)?;
+    let extension = name.rsplit('.').next()?.to_ascii_lowercase();
+
+    match storage.get(name).await {
+        Ok(Some(data)) => Some(Image {
+            url: url.to_string(),
+            extension,
+            media_type,
+            data,
+        }),
+        Ok(None) => None,
+        Err(err) => {
+            warn!(url, error = %err, "unable to read image for export");
+
+            None
+        }
+    }
+}
+
+/// Makes urls relative to the site absolute, so they work once downloaded.
+pub fn absolute(config: &Config, url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{}{}", config.url, url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// The story's details as labels and values, the same as are shown on its page.
+fn details(i18n: &I18n, story: &Existing<Story>, chapters: usize) -> Vec<(String, String)> {
+    let mut details = vec![
+        (t!(i18n, "story-language").0, story.language.clone()),
+        (t!(i18n, "story-rating").0, t!(i18n, rating(story.rating)).0),
+        (t!(i18n, "story-state").0, t!(i18n, state(story.state)).0),
+        (
+            t!(i18n, "story-words-label").0,
+            number(i18n.lang(), story.words),
+        ),
+        (
+            t!(i18n, "story-chapters-label").0,
+            number(i18n.lang(), chapters),
+        ),
+        (
+            t!(i18n, "story-published").0,
+            story.created.date().to_string(),
+        ),
+        (
+            t!(i18n, "story-updated").0,
+            story.updated.date().to_string(),
+        ),
+    ];
+
+    if !story.translators.is_empty() {
+        details.push((t!(i18n, "story-translators").0, names(&story.translators)));
+    }
+
+    let tags = [
+        (
+            "export-origins",
+            story
+                .origins
+                .iter()
+                .map(|tag| tag.content.clone())
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "export-warnings",
+            story
+                .warnings
+                .iter()
+                .map(|tag| tag.content.clone())
+                .collect(),
+        ),
+        (
+            "export-pairings",
+            story
+                .pairings
+                .iter()
+                .map(|tag| partials::pairing_name(tag))
+                .collect(),
+        ),
+        (
+            "export-characters",
+            story
+                .characters
+                .iter()
+                .map(|tag| tag.content.clone())
+                .collect(),
+        ),
+        (
+            "export-tags",
+            story.tags.iter().map(|tag| tag.content.clone()).collect(),
+        ),
+    ];
+
+    for (label, tags) in tags {
+        if !tags.is_empty() {
+            details.push((t!(i18n, label).0, tags.join(", ")));
+        }
+    }
+
+    details
+}
+
+/// The chapter's name, falling back to `chapter-title` if it has none.
+fn chapter_title(i18n: &I18n, number: usize, chapter: &Existing<Chapter>) -> String {
+    match &chapter.name {
+        Some(name) => name.clone(),
+        None => t!(i18n, "chapter-title", number = number).0,
+    }
+}
+
+fn rating(rating: Rating) -> &'static str {
+    match rating {
+        Rating::Explicit => "rating-explicit",
+        Rating::Mature => "rating-mature",
+        Rating::Teen => "rating-teen",
+        Rating::General => "rating-general",
+    }
+}
+
+fn state(state: State) -> &'static str {
+    match state {
+        State::Completed => "state-completed",
+        State::InProgress => "state-in-progress",
+        State::Hiatus => "state-hiatus",
+        State::Abandoned => "state-abandoned",
+    }
+}
+
+fn names(users: &[Existing<User>]) -> String {
+    users
+        .iter()
+        .map(|user| user.account.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A story and its chapters for testing the formats.
+#[cfg(test)]
+pub(crate) mod test {
+    use stry_common::models::{
+        core::{Account, PartText},
+        Id,
+    };
+
+    use super::*;
+
+    pub fn config() -> Config {
+        serde_json::from_str(r#"{ "url": "https://example.com" }"#).unwrap()
+    }
+
+    pub fn existing<T>(id: &str, data: T) -> Existing<T> {
+        let time = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+
+        Existing::new(Id::try_from(id).unwrap(), data, time, time)
+    }
+
+    pub fn story() -> Existing<Story> {
+        let mut story = Story::new(
+            String::from("Cats & Dogs"),
+            String::from("A summary"),
+            String::from("en"),
+            Rating::General,
+            State::Completed,
+        );
+
+        story.authors = vec![existing(
+            "author01",
+            User::new(Account {
+                name: String::from("Author"),
+                email: None,
+                hash: None,
+                biography: None,
+            }),
+        )];
+
+        existing("story001", story)
+    }
+
+    /// A published chapter of a single text part.
+    pub fn chapter(id: &str, name: Option<&str>, content: &str) -> Existing<Chapter> {
+        let text = |content: &str| {
+            existing(
+                "part0001",
+                Part::Text(PartText {
+                    content: content.to_string(),
+                    words: 0,
+                    comments: Vec::new(),
+                }),
+            )
+        };
+
+        existing(
+            id,
+            Chapter {
+                name: name.map(String::from),
+                published: true,
+                scheduled: None,
+                published_at: None,
+                prefix: Vec::new(),
+                main: vec![text(content)],
+                suffix: Vec::new(),
+                comments: Vec::new(),
+                words: 0,
+                draft: None,
+            },
+        )
+    }
+}
@@ -0,0 +1,212 @@
+//! A story or chapter as plain text, wrapped to fit narrow screens and
+//! e-readers without reflowing.
+
+use std::fmt::{self, Write as _};
+
+use stry_common::{
+    config::Config,
+    html::{from_text, to_text},
+    models::{
+        core::Part,
+        story::{Chapter, Story},
+        Existing,
+    },
+    prelude::*,
+};
+
+use crate::i18n::{t, I18n};
+
+use super::Format;
+
+pub const CONTENT_TYPE: &str = "text/plain; charset=utf-8";
+
+/// The line length used for downloads.
+pub const WIDTH: usize = 72;
+
+/// Parts as wrapped paragraphs, each followed by a blank line.
+pub struct Text {
+    /// The most characters in a line, longer words are left whole.
+    pub width: usize,
+}
+
+impl Format for Text {
+    fn heading(&self, out: &mut String, _level: u8, content: &str) -> fmt::Result {
+        underline(out, content, '-')
+    }
+
+    fn image(&self, out: &mut String, _url: &str, alt: &str) -> fmt::Result {
+        if alt.is_empty() {
+            return Ok(());
+        }
+
+        wrap(out, &format!("[{}]", alt), self.width)?;
+        writeln!(out)
+    }
+
+    fn text(&self, out: &mut String, content: &str) -> fmt::Result {
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        for line in to_text(content).lines() {
+            wrap(out, line, self.width)?;
+        }
+
+        writeln!(out)
+    }
+}
+
+/// The story's details and the given chapters, `first` being the number of
+/// the first chapter.
+pub fn document(
+    i18n: &I18n,
+    config: &Config,
+    story: &Existing<Story>,
+    chapters: &[Existing<Chapter>],
+    first: usize,
+) -> Result<String, Error> {
+    let text = Text { width: WIDTH };
+
+    let mut out = String::new();
+
+    underline(&mut out, &story.name, '=')?;
+    text.text(
+        &mut out,
+        &from_text(&t!(i18n, "export-by", authors = super::names(&story.authors)).0),
+    )?;
+    text.text(&mut out, &from_text(&story.summary))?;
+
+    for (label, value) in super::details(i18n, story, chapters.len()) {
+        wrap(&mut out, &format!("{}: {}", label, value), WIDTH)?;
+    }
+
+    writeln!(out)?;
+    wrap(
+        &mut out,
+        &t!(i18n, "export-source", site = config.url.as_str()).0,
+        WIDTH,
+    )?;
+    writeln!(out, "{}/stories/{}\n", config.url, story.id.as_str())?;
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        writeln!(out)?;
+        underline(
+            &mut out,
+            &super::chapter_title(i18n, first + index, chapter),
+            '=',
+        )?;
+        notes(&mut out, &text, i18n, &chapter.prefix)?;
+        text.parts(&mut out, &chapter.main)?;
+        notes(&mut out, &text, i18n, &chapter.suffix)?;
+    }
+
+    Ok(out)
+}
+
+/// An author's note, skipped when the chapter doesn't have one.
+fn notes(out: &mut String, text: &Text, i18n: &I18n, parts: &[Existing<Part>]) -> fmt::Result {
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    underline(out, &t!(i18n, "chapter-notes").0, '-')?;
+    text.parts(out, parts)
+}
+
+/// The title over a line as long as it is.
+fn underline(out: &mut String, title: &str, line: char) -> fmt::Result {
+    writeln!(out, "{}", title)?;
+
+    // Fluent wraps its arguments in invisible isolation marks
+    for _ in title
+        .chars()
+        .filter(|c| !matches!(c, '\u{2068}' | '\u{2069}'))
+    {
+        out.push(line);
+    }
+
+    writeln!(out, "\n")
+}
+
+/// Breaks the line at spaces so no line is longer than `width` characters,
+/// runs of spaces are collapsed.
+pub fn wrap(out: &mut String, line: &str, width: usize) -> fmt::Result {
+    let mut length = 0;
+
+    for word in line.split(' ').filter(|word| !word.is_empty()) {
+        let chars = word.chars().count();
+
+        if length != 0 && length + 1 + chars > width {
+            out.push('\n');
+
+            length = 0;
+        } else if length != 0 {
+            out.push(' ');
+
+            length += 1;
+        }
+
+        out.push_str(word);
+
+        length += chars;
+    }
+
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        export::test::{chapter, config, story},
+        i18n::english,
+    };
+
+    #[test]
+    fn test_wrap() {
+        let mut out = String::new();
+
+        wrap(&mut out, "one  two three fourteen", 9).unwrap();
+        wrap(&mut out, "supercalifragilistic word", 9).unwrap();
+
+        assert_eq!(
+            "one two\nthree\nfourteen\nsupercalifragilistic\nword\n",
+            out
+        );
+    }
+
+    #[test]
+    fn test_parts() {
+        let text = Text { width: WIDTH };
+
+        let mut out = String::new();
+
+        text.heading(&mut out, 1, "Cats & Dogs").unwrap();
+        text.image(&mut out, "/media/cat.png", "A cat").unwrap();
+        text.image(&mut out, "/media/dog.png", "").unwrap();
+        text.text(&mut out, "").unwrap();
+
+        assert_eq!("Cats & Dogs\n-----------\n\n[A cat]\n\n", out);
+    }
+
+    #[test]
+    fn test_document() {
+        let i18n = english();
+
+        let mut first = chapter("chapter1", Some("Beginning"), "<p>One</p>");
+
+        first.prefix = first.main.clone();
+
+        let chapters = [first, chapter("chapter2", None, "<p>Two</p>")];
+
+        let out = document(&i18n, &config(), &story(), &chapters, 1)
+            .unwrap()
+            .replace(['\u{2068}', '\u{2069}'], "");
+
+        assert!(out.starts_with("Cats & Dogs\n===========\n\nby Author\n\nA summary\n\n"));
+        assert!(out.contains("https://example.com/stories/story001\n"));
+        assert!(out
+            .contains("\nBeginning\n=========\n\nAuthor's note\n-------------\n\nOne\n\nOne\n\n"));
+        assert!(out.ends_with("\nChapter 2\n=========\n\nTwo\n\n"));
+    }
+}
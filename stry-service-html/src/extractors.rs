@@ -0,0 +1,338 @@
+use std::net::{IpAddr, SocketAddr};
+
+use stry_common::{
+    auth,
+    backend::{ArcBackend, UserEntity},
+    config::ArcConfig,
+    error::{NotFound, Unauthorized},
+    http::{client_ip, random_token},
+    limiter::Duration,
+    models::{
+        core::{Appearance, Content},
+        Id,
+    },
+    settings::Settings,
+};
+
+use axum_language::AcceptLanguage;
+
+use axum::{
+    extract::{ConnectInfo, FromRequest, RequestParts},
+    headers::{Cookie, HeaderMapExt},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+};
+
+use crate::{
+    error::Error,
+    i18n::{FluentResources, I18n},
+};
+
+/// The name of the cookie holding the signed in user's session token.
+pub const SESSION_COOKIE: &str = "session";
+
+/// The name of the cookie holding the CSRF token, which every form echos back.
+pub const CSRF_COOKIE: &str = "csrf";
+
+/// Formats a `Set-Cookie` header value that is hidden from scripts.
+///
+/// Cookies without a `max_age` only last until the browser is closed.
+pub fn cookie(name: &str, value: &str, max_age: Option<Duration>, same_site: &str) -> HeaderValue {
+    let mut cookie = format!(
+        "{}={}; Path=/; HttpOnly; Secure; SameSite={}",
+        name, value, same_site
+    );
+
+    if let Some(max_age) = max_age {
+        cookie.push_str(&format!("; Max-Age={}", max_age.whole_seconds()));
+    }
+
+    HeaderValue::from_str(&cookie).expect("cookies are always valid header values")
+}
+
+/// The ip address of the client making the request, see [`client_ip`].
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for ClientIp
+where
+    B: Send,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .ok_or((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "missing connection information",
+            ))?;
+
+        Ok(Self(client_ip(peer, req.headers())))
+    }
+}
+
+/// The id of the signed in user, if the request carries a valid session cookie.
+///
+/// # Note
+///
+/// Expired or tampered tokens are treated like they were never sent.
+#[derive(Clone, Copy, Debug)]
+pub struct Session(pub Option<Id>);
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Session
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let config = req
+            .extensions()
+            .get::<ArcConfig>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::from_any(stry_common::prelude::err!("missing config extension"))
+            })?;
+
+        let id = req.headers().typed_get::<Cookie>().and_then(|cookie| {
+            cookie
+                .get(SESSION_COOKIE)
+                .and_then(|token| auth::verify_token(&config.secret, token).ok())
+        });
+
+        Ok(Self(id))
+    }
+}
+
+/// The signed in user's appearance and content preferences, or the defaults
+/// for anyone else, along with the site's name that every page shows.
+#[derive(Clone, Debug)]
+pub struct Preferences {
+    pub appearance: Appearance,
+    pub content: Content,
+    pub site: String,
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Preferences
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let settings = req.extensions().get::<Settings>().cloned().ok_or_else(|| {
+            Error::from_any(stry_common::prelude::err!("missing settings extension"))
+        })?;
+
+        let mut prefs = Self {
+            appearance: Appearance::default(),
+            content: Content::default(),
+            site: settings.site_name().await?,
+        };
+
+        let id = match Session::from_request(req).await? {
+            Session(Some(id)) => id,
+            Session(None) => return Ok(prefs),
+        };
+
+        let data = req
+            .extensions()
+            .get::<ArcBackend>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::from_any(stry_common::prelude::err!("missing backend extension"))
+            })?;
+
+        // The account may have been removed while the session was still valid
+        let user = match UserEntity::get(&data, id).await {
+            Ok(user) => user,
+            Err(err) if err.is::<NotFound>() => return Ok(prefs),
+            Err(err) => return Err(Error::from_any(err)),
+        };
+
+        prefs.appearance = user.appearance.clone();
+        prefs.content = user.content.clone();
+
+        Ok(prefs)
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for I18n
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let resources = req
+            .extensions()
+            .get::<FluentResources>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::from_any(stry_common::prelude::err!("missing localization extension"))
+            })?;
+
+        let accept = match AcceptLanguage::from_request(req).await {
+            Ok(accept) => accept,
+            Err(never) => match never {},
+        };
+
+        Ok(I18n(resources.negotiate(&accept.languages)))
+    }
+}
+
+/// A double submit CSRF token.
+///
+/// The token is stored in a `SameSite=Strict` cookie and has to be sent back
+/// in every form, a request from another site can't read the cookie so it
+/// can't fill in the form field.
+#[derive(Clone, Debug)]
+pub struct Csrf {
+    token: String,
+    fresh: bool,
+}
+
+impl Csrf {
+    /// The token to place in a form's hidden `csrf` field.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Errors with [`Unauthorized`] if the form's token doesn't match the cookie.
+    pub fn verify(&self, form: &str) -> Result<(), Error> {
+        if self.fresh || !constant_eq(self.token.as_bytes(), form.as_bytes()) {
+            return Err(Error::from_any(Unauthorized));
+        }
+
+        Ok(())
+    }
+
+    /// Sets the cookie on the `response` if the client didn't already have one.
+    pub fn apply(&self, mut response: Response) -> Response {
+        if self.fresh {
+            response.headers_mut().append(
+                header::SET_COOKIE,
+                cookie(CSRF_COOKIE, &self.token, None, "Strict"),
+            );
+        }
+
+        response
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Csrf
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let token = req
+            .headers()
+            .typed_get::<Cookie>()
+            .and_then(|cookie| cookie.get(CSRF_COOKIE).map(str::to_string))
+            .filter(|token| !token.is_empty());
+
+        Ok(match token {
+            Some(token) => Self {
+                token,
+                fresh: false,
+            },
+            None => Self {
+                token: random_token(),
+                fresh: true,
+            },
+        })
+    }
+}
+
+/// Compares the bytes without exiting early, so the time taken doesn't leak
+/// how much of the token was correct.
+fn constant_eq(left: &[u8], right: &[u8]) -> bool {
+    if left.len() != right.len() {
+        return false;
+    }
+
+    left.iter()
+        .zip(right)
+        .fold(0, |diff, (left, right)| diff | (left ^ right))
+        == 0
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        http::{Request, StatusCode},
+        response::IntoResponse,
+    };
+
+    use super::*;
+
+    async fn csrf(cookie: Option<&str>) -> Csrf {
+        let mut request = Request::builder();
+
+        if let Some(cookie) = cookie {
+            request = request.header(header::COOKIE, cookie);
+        }
+
+        let mut parts = RequestParts::new(request.body(()).unwrap());
+
+        Csrf::from_request(&mut parts).await.unwrap()
+    }
+
+    fn status(result: Result<(), Error>) -> StatusCode {
+        match result {
+            Ok(()) => StatusCode::OK,
+            Err(err) => err.into_response().status(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let csrf = csrf(Some("csrf=token")).await;
+
+        assert_eq!(csrf.token(), "token");
+        assert_eq!(status(csrf.verify("token")), StatusCode::OK);
+        assert_eq!(status(csrf.verify("other")), StatusCode::FORBIDDEN);
+        assert_eq!(status(csrf.verify("")), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_fresh() {
+        let csrf = csrf(None).await;
+
+        // Without a cookie the form couldn't have come from one of our pages,
+        // even if it somehow has the token that was just generated
+        assert_eq!(status(csrf.verify(csrf.token())), StatusCode::FORBIDDEN);
+
+        let response = csrf.apply(().into_response());
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+
+        assert!(cookie.starts_with(&format!("csrf={};", csrf.token())));
+        assert!(cookie.contains("SameSite=Strict"));
+    }
+
+    #[tokio::test]
+    async fn test_existing_cookie_is_kept() {
+        let csrf = csrf(Some("csrf=token")).await;
+
+        let response = csrf.apply(().into_response());
+
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+    }
+
+    #[test]
+    fn test_constant_eq() {
+        assert!(constant_eq(b"token", b"token"));
+        assert!(!constant_eq(b"token", b"tokem"));
+        assert!(!constant_eq(b"token", b"token!"));
+    }
+}
@@ -0,0 +1,353 @@
+use stry_common::{
+    auth,
+    backend::{ArcBackend, UserEntity},
+    config::ArcConfig,
+    error::{Conflict, FieldError, Invalid, RegistrationClosed, TooManyRequests},
+    limiter::{Duration, Limiter},
+    models::{
+        core::{
+            Appearance, Content, FontSize, NotificationPreference, Notifications, SiteTheme,
+            UserRegisterForm, UserSessionForm,
+        },
+        story::Rating,
+        Id,
+    },
+    prelude::*,
+    settings::Settings,
+};
+
+use axum::{
+    extract::{Form, Query},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Extension,
+};
+use windswept::Render as _;
+
+use crate::{
+    error::Error,
+    extractors::{cookie, ClientIp, Csrf, Preferences, Session, SESSION_COOKIE},
+    i18n::I18n,
+};
+
+#[derive(Debug, serde::Deserialize)]
+pub struct RegisterForm {
+    csrf: String,
+    #[serde(flatten)]
+    form: UserRegisterForm,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SignInForm {
+    csrf: String,
+    #[serde(flatten)]
+    form: UserSessionForm,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SettingsForm {
+    csrf: String,
+    theme: SiteTheme,
+    font_size: FontSize,
+    /// Checkboxes are only sent when checked.
+    #[serde(default)]
+    collapse_notes: bool,
+    comments: NotificationPreference,
+    #[serde(default, deserialize_with = "super::browse::empty_none")]
+    rating: Option<Rating>,
+    #[serde(default)]
+    warnings: String,
+    #[serde(default)]
+    tags: String,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct CsrfForm {
+    csrf: String,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct SettingsQuery {
+    #[serde(default)]
+    saved: bool,
+}
+
+pub async fn register(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(settings): Extension<Settings>,
+    csrf: Csrf,
+) -> Result<Response, Error> {
+    let message = (!settings.registration_open().await?).then_some("register-closed");
+
+    let page = crate::templates::page::register(&i18n, &prefs, csrf.token(), "", "", &[], message)
+        .render()?;
+
+    Ok(csrf.apply(Html(page).into_response()))
+}
+
+// Each argument is its own extractor
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(i18n, prefs, data, settings, limiter, csrf, form), err)]
+pub async fn register_post(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Extension(limiter): Extension<Limiter>,
+    ClientIp(ip): ClientIp,
+    csrf: Csrf,
+    Form(form): Form<RegisterForm>,
+) -> Result<Response, Error> {
+    csrf.verify(&form.csrf)?;
+
+    let (username, email) = (form.form.username.clone(), form.form.email.clone());
+
+    let err = match auth::register(&data, &settings, &limiter, ip, form.form).await {
+        Ok(_) => return Ok(Redirect::to("/sign-in").into_response()),
+        Err(err) => err,
+    };
+
+    let (status, errors, message) = rejected(err)?;
+
+    let page = crate::templates::page::register(
+        &i18n,
+        &prefs,
+        csrf.token(),
+        &username,
+        &email,
+        &errors,
+        message,
+    )
+    .render()?;
+
+    Ok((status, Html(page)).into_response())
+}
+
+pub async fn sign_in(i18n: I18n, prefs: Preferences, csrf: Csrf) -> Result<Response, Error> {
+    let page =
+        crate::templates::page::sign_in(&i18n, &prefs, csrf.token(), "", &[], None).render()?;
+
+    Ok(csrf.apply(Html(page).into_response()))
+}
+
+// Each argument is its own extractor
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(i18n, prefs, config, data, limiter, csrf, form), err)]
+pub async fn sign_in_post(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(config): Extension<ArcConfig>,
+    Extension(data): Extension<ArcBackend>,
+    Extension(limiter): Extension<Limiter>,
+    ClientIp(ip): ClientIp,
+    csrf: Csrf,
+    Form(form): Form<SignInForm>,
+) -> Result<Response, Error> {
+    csrf.verify(&form.csrf)?;
+
+    let email = form.form.email.clone();
+
+    let (status, errors, message) = match auth::sign_in(&data, &limiter, ip, form.form).await {
+        Ok(Some(user)) => {
+            let token = auth::issue_token(&config.secret, &user)?;
+
+            return Ok((
+                [(
+                    header::SET_COOKIE,
+                    cookie(SESSION_COOKIE, &token, Some(auth::SESSION_EXPIRY), "Lax"),
+                )],
+                Redirect::to("/"),
+            )
+                .into_response());
+        }
+        Ok(None) => (
+            StatusCode::UNAUTHORIZED,
+            Vec::new(),
+            Some("sign-in-incorrect"),
+        ),
+        Err(err) => rejected(err)?,
+    };
+
+    let page =
+        crate::templates::page::sign_in(&i18n, &prefs, csrf.token(), &email, &errors, message)
+            .render()?;
+
+    Ok((status, Html(page)).into_response())
+}
+
+pub async fn sign_out(csrf: Csrf, Form(form): Form<CsrfForm>) -> Result<Response, Error> {
+    csrf.verify(&form.csrf)?;
+
+    Ok((
+        [(
+            header::SET_COOKIE,
+            cookie(SESSION_COOKIE, "", Some(Duration::ZERO), "Lax"),
+        )],
+        Redirect::to("/"),
+    )
+        .into_response())
+}
+
+#[instrument(skip(i18n, prefs, data, csrf), err)]
+pub async fn settings(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    csrf: Csrf,
+    Query(query): Query<SettingsQuery>,
+) -> Result<Response, Error> {
+    let id = match session {
+        Some(id) => id,
+        None => return Ok(Redirect::to("/sign-in").into_response()),
+    };
+
+    let user = UserEntity::get(&data, id).await?;
+
+    let page =
+        crate::templates::page::settings(&i18n, &prefs, csrf.token(), &user, query.saved, &[])
+            .render()?;
+
+    Ok(csrf.apply(Html(page).into_response()))
+}
+
+#[instrument(skip(i18n, prefs, data, csrf, form), err)]
+pub async fn settings_post(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    csrf: Csrf,
+    Form(form): Form<SettingsForm>,
+) -> Result<Response, Error> {
+    csrf.verify(&form.csrf)?;
+
+    let id = match session {
+        Some(id) => id,
+        None => return Ok(Redirect::to("/sign-in").into_response()),
+    };
+
+    let (warnings, tags) = match (ids("warnings", &form.warnings), ids("tags", &form.tags)) {
+        (Ok(warnings), Ok(tags)) => (warnings, tags),
+        (warnings, tags) => {
+            let errors = [warnings.err(), tags.err()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+
+            let user = UserEntity::get(&data, id).await?;
+
+            let page = crate::templates::page::settings(
+                &i18n,
+                &prefs,
+                csrf.token(),
+                &user,
+                false,
+                &errors,
+            )
+            .render()?;
+
+            return Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(page)).into_response());
+        }
+    };
+
+    UserEntity::update_settings(
+        &data,
+        id,
+        Appearance {
+            theme: form.theme,
+            font_size: form.font_size,
+            collapse_notes: form.collapse_notes,
+        },
+        Notifications {
+            comments: form.comments,
+        },
+        Content {
+            rating: form.rating,
+            warnings,
+            tags,
+        },
+    )
+    .await?;
+
+    // Redirecting stops a refresh from submitting the form again
+    Ok(Redirect::to("/settings?saved=true").into_response())
+}
+
+/// Splits a list of ids separated by spaces or commas, the error is for the
+/// field with the `name`.
+fn ids(name: &str, value: &str) -> Result<Vec<Id>, FieldError> {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|id| !id.is_empty())
+        .map(|id| Id::try_from(id).map_err(|_| FieldError::new(name, "ids", Default::default())))
+        .collect()
+}
+
+/// Turns the errors a form can be fixed from into what's shown next to it,
+/// anything else is passed on.
+///
+/// The form wide error is returned as a message id.
+fn rejected(
+    err: stry_common::prelude::Error,
+) -> Result<(StatusCode, Vec<FieldError>, Option<&'static str>), Error> {
+    let err = match err.downcast::<Invalid>() {
+        Ok(invalid) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, invalid.fields, None)),
+        Err(err) => err,
+    };
+
+    let err = match err.downcast::<Conflict>() {
+        Ok(conflict) => return Ok((StatusCode::CONFLICT, conflict.fields, None)),
+        Err(err) => err,
+    };
+
+    if err.is::<RegistrationClosed>() {
+        return Ok((StatusCode::FORBIDDEN, Vec::new(), Some("register-closed")));
+    }
+
+    if err.is::<TooManyRequests>() {
+        return Ok((
+            StatusCode::TOO_MANY_REQUESTS,
+            Vec::new(),
+            Some("too-many-attempts"),
+        ));
+    }
+
+    Err(Error::from(err))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_out_checks_csrf() {
+        let router = Router::new().route("/sign-out", post(sign_out));
+
+        let request = |form: &'static str| {
+            Request::post("/sign-out")
+                .header(header::COOKIE, "csrf=token")
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(form))
+                .unwrap()
+        };
+
+        // A form posted from another site can't know the cookie's token
+        let response = router.clone().oneshot(request("csrf=other")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+
+        let response = router.oneshot(request("csrf=token")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert!(response.headers()[header::SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .starts_with("session=;"));
+    }
+}
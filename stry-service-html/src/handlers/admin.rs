@@ -0,0 +1,124 @@
+//! Pages for admins, users given access with `stry admin <user id>`.
+
+use std::collections::BTreeMap;
+
+use stry_common::{
+    backend::{ArcBackend, UserEntity},
+    error::{Invalid, Unauthorized},
+    models::{core::SettingValue, Id},
+    prelude::*,
+    settings::Settings,
+};
+
+use axum::{
+    extract::{Form, Query},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    Extension,
+};
+use windswept::Render as _;
+
+use crate::{
+    error::Error,
+    extractors::{Csrf, Preferences, Session},
+    i18n::I18n,
+};
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct SettingsQuery {
+    #[serde(default)]
+    saved: bool,
+}
+
+// Each argument is its own extractor
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(i18n, prefs, data, settings, csrf), err)]
+pub async fn settings(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Session(session): Session,
+    csrf: Csrf,
+    Query(query): Query<SettingsQuery>,
+) -> Result<Response, Error> {
+    let id = match session {
+        Some(id) => id,
+        None => return Ok(Redirect::to("/sign-in").into_response()),
+    };
+
+    ensure_admin(&data, id).await?;
+
+    let page = crate::templates::page::admin_settings(
+        &i18n,
+        &prefs,
+        csrf.token(),
+        &settings.all().await?,
+        &BTreeMap::new(),
+        query.saved,
+        &[],
+    )
+    .render()?;
+
+    Ok(csrf.apply(Html(page).into_response()))
+}
+
+// Each argument is its own extractor
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(i18n, prefs, data, settings, csrf, form), err)]
+pub async fn settings_post(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Session(session): Session,
+    csrf: Csrf,
+    Form(mut form): Form<BTreeMap<String, String>>,
+) -> Result<Response, Error> {
+    csrf.verify(form.remove("csrf").as_deref().unwrap_or_default())?;
+
+    let id = match session {
+        Some(id) => id,
+        None => return Ok(Redirect::to("/sign-in").into_response()),
+    };
+
+    ensure_admin(&data, id).await?;
+
+    // Every value is sent as text, they're parsed by the setting's kind
+    let values = form
+        .iter()
+        .map(|(key, value)| (key.clone(), SettingValue::Text(value.clone())))
+        .collect();
+
+    let invalid = match settings.set(values).await {
+        Ok(()) => {
+            info!(target: "audit", user = id.as_str(), keys = ?form.keys().collect::<Vec<_>>(), "changed settings");
+
+            // Redirecting stops a refresh from submitting the form again
+            return Ok(Redirect::to("/admin/settings?saved=true").into_response());
+        }
+        Err(err) => err.downcast::<Invalid>()?,
+    };
+
+    let page = crate::templates::page::admin_settings(
+        &i18n,
+        &prefs,
+        csrf.token(),
+        &settings.all().await?,
+        &form,
+        false,
+        &invalid.fields,
+    )
+    .render()?;
+
+    Ok((StatusCode::UNPROCESSABLE_ENTITY, Html(page)).into_response())
+}
+
+/// Errors with [`Unauthorized`] unless the user is an admin.
+async fn ensure_admin(data: &ArcBackend, id: Id) -> Result<(), Error> {
+    if !UserEntity::is_admin(data, id).await? {
+        return Err(Error::from_any(Unauthorized));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,330 @@
+//! Paginated indexes of every tag kind and author, and the stories carrying
+//! each of them.
+
+use axum_language::AcceptLanguage;
+use stry_common::{
+    backend::{
+        ArcBackend, CharacterEntity, OriginEntity, PairingEntity, PostEntity, StoryEntity,
+        TagEntity, UserEntity, WarningEntity,
+    },
+    http::Pagination,
+    models::{
+        core::Tag,
+        story::{Character, Origin, Pairing, Rating, State, StoryFilter, Warning},
+        Existing, Id,
+    },
+    prelude::*,
+};
+
+use axum::{
+    extract::{ContentLengthLimit, Path, Query},
+    response::{Html, IntoResponse},
+    Extension,
+};
+use serde::{de::IntoDeserializer as _, Deserialize, Deserializer};
+use windswept::Render as _;
+
+use crate::{error::Error, extractors::Preferences, i18n::I18n, templates::partials};
+
+/// The pagination and filters for a list of stories.
+#[derive(Debug, serde::Deserialize)]
+pub struct StoryQuery {
+    pub cursor: Option<Id>,
+    #[serde(default = "default_limit", deserialize_with = "clamp_limit")]
+    pub limit: i64,
+
+    #[serde(default, deserialize_with = "empty_none")]
+    pub rating: Option<Rating>,
+    #[serde(default, deserialize_with = "empty_none")]
+    pub state: Option<State>,
+
+    /// Is empty when the reader chose to see every language.
+    pub language: Option<String>,
+}
+
+impl StoryQuery {
+    /// Defaults the language to the reader's most preferred one if they
+    /// haven't picked one, and returns the filter for the query.
+    pub fn filter(&mut self, accept: &AcceptLanguage) -> StoryFilter {
+        let language = self.language.get_or_insert_with(|| {
+            accept
+                .languages
+                .first()
+                .map(|lang| lang.language.as_str().to_string())
+                .unwrap_or_default()
+        });
+
+        // Only language tag characters, so it can be put in links as is
+        language.retain(|c| c.is_ascii_alphanumeric() || c == '-');
+
+        StoryFilter {
+            rating: self.rating,
+            state: self.state,
+            language: Some(language.clone()).filter(|language| !language.is_empty()),
+            ..StoryFilter::default()
+        }
+    }
+
+    /// The link to the page after `entities`, keeping the current filters.
+    pub fn next<T>(&self, entities: &[Existing<T>]) -> Option<String> {
+        let mut href = next(entities, self.limit)?;
+
+        // The query values are the variant names, same as their debug output
+        if let Some(rating) = self.rating {
+            href.push_str(&format!("&rating={:?}", rating));
+        }
+
+        if let Some(state) = self.state {
+            href.push_str(&format!("&state={:?}", state));
+        }
+
+        if let Some(language) = &self.language {
+            href.push_str(&format!("&language={}", language));
+        }
+
+        Some(href)
+    }
+}
+
+fn default_limit() -> i64 {
+    10
+}
+
+/// Keeps the page size between 1 and 100 stories, the same range the JSON
+/// API's [`StorySearch`](stry_common::models::story::StorySearch) allows.
+fn clamp_limit<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(i64::deserialize(deserializer)?.clamp(1, 100))
+}
+
+/// Treats empty values (ie a select's 'any' option) as if they were missing.
+pub(super) fn empty_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => Ok(None),
+        Some(value) => T::deserialize(value.into_deserializer()).map(Some),
+    }
+}
+
+/// The link to the page after `entities`, if the current page is full.
+pub(super) fn next<T>(entities: &[Existing<T>], limit: i64) -> Option<String> {
+    if (entities.len() as i64) < limit {
+        return None;
+    }
+
+    let last = entities.last()?;
+
+    Some(format!("?cursor={}&limit={}", last.id.as_str(), limit))
+}
+
+/// How a tag is shown in the indexes and at the top of its page.
+trait Browse {
+    fn name(&self) -> String;
+    fn description(&self) -> &str;
+}
+
+macro_rules! impl_browse {
+    ($( $entity:ty ),* $(,)?) => {
+        $(
+            impl Browse for $entity {
+                fn name(&self) -> String {
+                    self.content.clone()
+                }
+
+                fn description(&self) -> &str {
+                    &self.description
+                }
+            }
+        )*
+    };
+}
+
+impl_browse![Origin, Warning, Character, Tag];
+
+impl Browse for Pairing {
+    fn name(&self) -> String {
+        partials::pairing_name(self)
+    }
+
+    fn description(&self) -> &str {
+        ""
+    }
+}
+
+macro_rules! browse {
+    ($( $entity:ident { field: $field:ident, url: $url:literal, title: $title:literal, all: $all:ident, get: $get:ident, feed: $feed:literal, } )+) => {
+        $(
+            #[instrument(skip(i18n, prefs, data, query), err)]
+            pub async fn $all(
+                i18n: I18n,
+                prefs: Preferences,
+                Extension(data): Extension<ArcBackend>,
+                ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+            ) -> Result<impl IntoResponse, Error> {
+                let entities = $entity::all(&data, query.cursor, query.limit).await?;
+
+                let links = entities
+                    .iter()
+                    .map(|entity| (format!(concat!($url, "/{}"), entity.id.as_str()), entity.name()))
+                    .collect::<Vec<_>>();
+
+                Ok(Html(
+                    crate::templates::page::browse::list(
+                        &i18n,
+                        &prefs,
+                        $title,
+                        &links,
+                        next(&entities, query.limit).as_deref(),
+                    )
+                    .render()?,
+                ))
+            }
+
+            #[instrument(skip(i18n, prefs, accept, data, query), err)]
+            pub async fn $get(
+                i18n: I18n,
+                prefs: Preferences,
+                accept: AcceptLanguage,
+                Extension(data): Extension<ArcBackend>,
+                Path(id): Path<Id>,
+                ContentLengthLimit(Query(mut query)): ContentLengthLimit<Query<StoryQuery>, { 1024 * 5000 }>,
+            ) -> Result<impl IntoResponse, Error> {
+                let entity = $entity::get(&data, id).await?;
+
+                let filter = StoryFilter {
+                    $field: Some(id),
+                    ..query.filter(&accept).hide(&prefs.content)
+                };
+
+                let stories = StoryEntity::filter(&data, filter, query.cursor, query.limit).await?;
+
+                let feed = $feed.then(|| format!(concat!($url, "/{}/feed.atom"), id.as_str()));
+
+                Ok(Html(
+                    crate::templates::page::browse::stories(
+                        &i18n,
+                        &prefs,
+                        &entity.name(),
+                        entity.description(),
+                        &stories,
+                        &query,
+                        query.next(&stories).as_deref(),
+                        feed.as_deref(),
+                    )
+                    .render()?,
+                ))
+            }
+        )+
+    };
+}
+
+browse! {
+    OriginEntity { field: origin, url: "/origins", title: "browse-origins", all: origins, get: origin, feed: false, }
+    WarningEntity { field: warning, url: "/warnings", title: "browse-warnings", all: warnings, get: warning, feed: false, }
+    PairingEntity { field: pairing, url: "/pairings", title: "browse-pairings", all: pairings, get: pairing, feed: false, }
+    CharacterEntity { field: character, url: "/characters", title: "browse-characters", all: characters, get: character, feed: false, }
+    TagEntity { field: tag, url: "/tags", title: "browse-tags", all: tags, get: tag, feed: true, }
+}
+
+#[instrument(skip(i18n, prefs, data, query), err)]
+pub async fn authors(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let users = UserEntity::all(&data, query.cursor, query.limit).await?;
+
+    let links = users
+        .iter()
+        .map(|user| {
+            (
+                format!("/authors/{}", user.id.as_str()),
+                user.account.name.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Html(
+        crate::templates::page::browse::list(
+            &i18n,
+            &prefs,
+            "browse-authors",
+            &links,
+            next(&users, query.limit).as_deref(),
+        )
+        .render()?,
+    ))
+}
+
+#[instrument(skip(i18n, prefs, accept, data, query), err)]
+pub async fn author(
+    i18n: I18n,
+    prefs: Preferences,
+    accept: AcceptLanguage,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+    ContentLengthLimit(Query(mut query)): ContentLengthLimit<Query<StoryQuery>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let user = UserEntity::get(&data, id).await?;
+
+    let filter = StoryFilter {
+        author: Some(id),
+        ..query.filter(&accept).hide(&prefs.content)
+    };
+
+    let stories = StoryEntity::filter(&data, filter, query.cursor, query.limit).await?;
+
+    // Only the newest posts, the stories are what's paginated
+    let posts = PostEntity::all(&data, id, None, 5).await?;
+
+    Ok(Html(
+        crate::templates::page::author(
+            &i18n,
+            &prefs,
+            &user,
+            &stories,
+            &posts,
+            &query,
+            query.next(&stories).as_deref(),
+        )
+        .render()?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        extract::{FromRequest as _, RequestParts},
+        http::Request,
+    };
+
+    use super::*;
+
+    async fn limit(query: &str) -> i64 {
+        let request = Request::builder()
+            .uri(format!("/?{}", query))
+            .body(())
+            .unwrap();
+
+        let Query(query) = Query::<StoryQuery>::from_request(&mut RequestParts::new(request))
+            .await
+            .unwrap();
+
+        query.limit
+    }
+
+    #[tokio::test]
+    async fn test_limit() {
+        assert_eq!(limit("").await, 10);
+        assert_eq!(limit("limit=25").await, 25);
+        assert_eq!(limit("limit=0").await, 1);
+        assert_eq!(limit("limit=-5").await, 1);
+        assert_eq!(limit("limit=100000").await, 100);
+    }
+}
@@ -0,0 +1,289 @@
+//! Stories and series as files for reading offline.
+
+use stry_common::{
+    backend::{ArcBackend, SeriesEntity, StoryEntity},
+    config::ArcConfig,
+    error::NotFound,
+    media::MediaStorage,
+    models::{
+        story::{Chapter, Story},
+        Existing, Id,
+    },
+    prelude::*,
+};
+
+use axum::{
+    extract::{FromRequest, Path, RequestParts},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::{
+    error::Error,
+    export::{epub, html, markdown, text},
+    i18n::I18n,
+};
+
+/// The download's format, from the extension of the requested path.
+#[derive(Clone, Copy, Debug)]
+pub enum Kind {
+    Epub,
+    Html,
+    Markdown,
+    Text,
+}
+
+impl Kind {
+    fn extension(self) -> &'static str {
+        match self {
+            Kind::Epub => "epub",
+            Kind::Html => "html",
+            Kind::Markdown => "md",
+            Kind::Text => "txt",
+        }
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Kind
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let path = req.uri().path();
+
+        if path.ends_with(".html") {
+            Ok(Kind::Html)
+        } else if path.ends_with(".md") {
+            Ok(Kind::Markdown)
+        } else if path.ends_with(".txt") {
+            Ok(Kind::Text)
+        } else {
+            Ok(Kind::Epub)
+        }
+    }
+}
+
+#[instrument(skip(i18n, config, data, storage), err)]
+pub async fn story(
+    i18n: I18n,
+    kind: Kind,
+    Extension(config): Extension<ArcConfig>,
+    Extension(data): Extension<ArcBackend>,
+    Extension(storage): Extension<MediaStorage>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let story = StoryEntity::get(&data, id).await?;
+    let chapters = super::story::chapters(&data, &story).await?;
+
+    let (content_type, body) = match kind {
+        Kind::Epub => (
+            epub::CONTENT_TYPE,
+            epub::story(&i18n, &config, &storage, &story, &chapters).await?,
+        ),
+        _ => document(&i18n, &config, &storage, kind, &story, &chapters, 1).await?,
+    };
+
+    Ok(attachment(content_type, &story.name, id, kind, body))
+}
+
+/// A single chapter, with the story's details at the top.
+#[instrument(skip(i18n, config, data, storage), err)]
+pub async fn chapter(
+    i18n: I18n,
+    kind: Kind,
+    Extension(config): Extension<ArcConfig>,
+    Extension(data): Extension<ArcBackend>,
+    Extension(storage): Extension<MediaStorage>,
+    Path((id, number)): Path<(Id, usize)>,
+) -> Result<Response, Error> {
+    let story = StoryEntity::get(&data, id).await?;
+    let chapters = super::story::chapters(&data, &story).await?;
+
+    // Chapter numbers start at one for readers
+    let chapter = number
+        .checked_sub(1)
+        .and_then(|index| chapters.get(index..=index))
+        .ok_or(NotFound)
+        .map_err(Error::from_any)?;
+
+    let (content_type, body) =
+        document(&i18n, &config, &storage, kind, &story, chapter, number).await?;
+
+    Ok(attachment(
+        content_type,
+        &format!("{} {}", story.name, number),
+        id,
+        kind,
+        body,
+    ))
+}
+
+#[instrument(skip(i18n, config, data, storage), err)]
+pub async fn series(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(data): Extension<ArcBackend>,
+    Extension(storage): Extension<MediaStorage>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let series = SeriesEntity::get(&data, id).await?;
+    let stories = super::story::stories(&data, &series).await?;
+
+    let chapters = super::story::works(&data, &stories).await?;
+    let works = stories.into_iter().zip(chapters).collect::<Vec<_>>();
+
+    let book = epub::series(&i18n, &config, &storage, &series, &works).await?;
+
+    Ok(attachment(
+        epub::CONTENT_TYPE,
+        &series.name,
+        id,
+        Kind::Epub,
+        book,
+    ))
+}
+
+/// Every format other than EPUB, which is only made for whole stories.
+async fn document(
+    i18n: &I18n,
+    config: &ArcConfig,
+    storage: &MediaStorage,
+    kind: Kind,
+    story: &Existing<Story>,
+    chapters: &[Existing<Chapter>],
+    first: usize,
+) -> Result<(&'static str, Vec<u8>), Error> {
+    let (content_type, document) = match kind {
+        Kind::Epub | Kind::Html => (
+            html::CONTENT_TYPE,
+            html::document(i18n, config, storage, story, chapters, first).await?,
+        ),
+        Kind::Markdown => (
+            markdown::CONTENT_TYPE,
+            markdown::document(i18n, config, story, chapters, first)?,
+        ),
+        Kind::Text => (
+            text::CONTENT_TYPE,
+            text::document(i18n, config, story, chapters, first)?,
+        ),
+    };
+
+    Ok((content_type, document.into_bytes()))
+}
+
+/// The characters RFC 5987 allows in an extended parameter value as is.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// The file named after the story or series.
+///
+/// Clients that understand RFC 5987 get the name as is in `filename*`, the
+/// `filename` fallback is reduced to ASCII letters and digits.
+fn attachment(
+    content_type: &'static str,
+    name: &str,
+    id: Id,
+    kind: Kind,
+    body: Vec<u8>,
+) -> Response {
+    let mut file = String::with_capacity(name.len());
+
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            file.push(c);
+        } else if !file.is_empty() && !file.ends_with('-') {
+            file.push('-');
+        }
+    }
+
+    let file = match str::trim_end_matches(&file, '-') {
+        "" => id.as_str().to_string(),
+        file => file.to_string(),
+    };
+
+    let name = match name.trim() {
+        "" => id.as_str(),
+        name => name,
+    };
+
+    let disposition = format!(
+        r#"attachment; filename="{}.{}"; filename*=UTF-8''{}.{}"#,
+        file,
+        kind.extension(),
+        utf8_percent_encode(name, ATTR_CHAR),
+        kind.extension()
+    );
+
+    (
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&disposition)
+                    .unwrap_or_else(|_| HeaderValue::from_static("attachment")),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn disposition(name: &str) -> String {
+        let response = attachment(
+            epub::CONTENT_TYPE,
+            name,
+            Id::try_from("story001").unwrap(),
+            Kind::Epub,
+            Vec::new(),
+        );
+
+        response.headers()[header::CONTENT_DISPOSITION]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_attachment() {
+        assert_eq!(
+            r#"attachment; filename="Cats-Dogs.epub"; filename*=UTF-8''Cats%20&%20Dogs.epub"#,
+            disposition("Cats & Dogs")
+        );
+        assert_eq!(
+            r#"attachment; filename="Caf-Cr-me.epub"; filename*=UTF-8''Caf%C3%A9%20Cr%C3%A8me.epub"#,
+            disposition("Café Crème")
+        );
+    }
+
+    #[test]
+    fn test_attachment_without_ascii() {
+        assert_eq!(
+            r#"attachment; filename="story001.epub"; filename*=UTF-8''%E7%8C%AB.epub"#,
+            disposition("猫")
+        );
+        assert_eq!(
+            r#"attachment; filename="story001.epub"; filename*=UTF-8''story001.epub"#,
+            disposition(" ")
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Atom and RSS feeds of new stories and newly published chapters, for the
+//! whole site, a tag, an author or a series.
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use stry_common::{
+    backend::{ArcBackend, ChapterEntity, SeriesEntity, StoryEntity, TagEntity, UserEntity},
+    config::ArcConfig,
+    models::{
+        story::{Chapter, Story, StoryFilter},
+        Existing, Id,
+    },
+    prelude::*,
+    settings::Settings,
+};
+
+use axum::{
+    extract::{FromRequest, Path, RequestParts},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+use crate::{
+    error::Error,
+    export::{self, html::Html, Format as _},
+    i18n::{t, I18n},
+    templates::feed::{Entry, Feed},
+};
+
+/// How many of the newest stories are turned into entries.
+const STORIES: i64 = 20;
+
+/// How many of the newest chapters are in the site's feed, and the most
+/// entries in any other feed.
+const CHAPTERS: i64 = 50;
+
+/// The feed's format, from the extension of the requested path.
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    Atom,
+    Rss,
+}
+
+impl Format {
+    fn respond(self, feed: &Feed) -> Result<Response, Error> {
+        let (content_type, body) = match self {
+            Format::Atom => ("application/atom+xml; charset=utf-8", feed.atom()?),
+            Format::Rss => ("application/rss+xml; charset=utf-8", feed.rss()?),
+        };
+
+        Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Format
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if req.uri().path().ends_with(".rss") {
+            Ok(Format::Rss)
+        } else {
+            Ok(Format::Atom)
+        }
+    }
+}
+
+#[instrument(skip(i18n, config, settings, data), err)]
+pub async fn stories(
+    i18n: I18n,
+    format: Format,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+) -> Result<Response, Error> {
+    let recent = ChapterEntity::recent(&data, CHAPTERS).await?;
+
+    let mut ids = recent.iter().map(|(story, _)| *story).collect::<Vec<_>>();
+
+    ids.sort_unstable_by(|a, b| a.as_str().cmp(b.as_str()));
+    ids.dedup();
+
+    let stories = StoryEntity::many(&data, ids).await?;
+
+    // The chapter's number is its place in the story's published chapters
+    let published = stories
+        .iter()
+        .zip(super::story::works(&data, &stories).await?)
+        .map(|(story, chapters)| {
+            (
+                story.id,
+                chapters
+                    .iter()
+                    .map(|chapter| chapter.id)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut entries = Vec::with_capacity(recent.len());
+
+    for (story, chapter) in recent {
+        let number = published
+            .get(&story)
+            .and_then(|chapters| chapters.iter().position(|id| *id == chapter.id));
+
+        let (story, index) = match (stories.iter().find(|s| s.id == story), number) {
+            (Some(story), Some(index)) => (story, index),
+            _ => continue,
+        };
+
+        entries.push(self::chapter(&i18n, &config, story, index, &chapter)?);
+    }
+
+    let feed = Feed {
+        site: settings.site_name().await?,
+        url: format!("{}/", config.url),
+        href: format!("{}/stories.{}", config.url, extension(format)),
+        title: t!(i18n, "feed-stories").0,
+        entries,
+    };
+
+    format.respond(&feed)
+}
+
+#[instrument(skip(i18n, config, settings, data), err)]
+pub async fn tag(
+    i18n: I18n,
+    format: Format,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let tag = TagEntity::get(&data, id).await?;
+
+    let filter = StoryFilter {
+        tag: Some(id),
+        ..StoryFilter::default()
+    };
+
+    let stories = StoryEntity::filter(&data, filter, None, STORIES).await?;
+
+    let feed = Feed {
+        site: settings.site_name().await?,
+        url: format!("{}/tags/{}", config.url, id.as_str()),
+        href: format!(
+            "{}/tags/{}/feed.{}",
+            config.url,
+            id.as_str(),
+            extension(format)
+        ),
+        title: t!(i18n, "feed-tag", name = tag.content.as_str()).0,
+        entries: entries(&i18n, &config, &data, stories).await?,
+    };
+
+    format.respond(&feed)
+}
+
+#[instrument(skip(i18n, config, settings, data), err)]
+pub async fn author(
+    i18n: I18n,
+    format: Format,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let user = UserEntity::get(&data, id).await?;
+
+    let filter = StoryFilter {
+        author: Some(id),
+        ..StoryFilter::default()
+    };
+
+    let stories = StoryEntity::filter(&data, filter, None, STORIES).await?;
+
+    let feed = Feed {
+        site: settings.site_name().await?,
+        url: format!("{}/authors/{}", config.url, id.as_str()),
+        href: format!(
+            "{}/authors/{}/feed.{}",
+            config.url,
+            id.as_str(),
+            extension(format)
+        ),
+        title: t!(i18n, "feed-author", name = user.account.name.as_str()).0,
+        entries: entries(&i18n, &config, &data, stories).await?,
+    };
+
+    format.respond(&feed)
+}
+
+#[instrument(skip(i18n, config, settings, data), err)]
+pub async fn series(
+    i18n: I18n,
+    format: Format,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let series = SeriesEntity::get(&data, id).await?;
+    let stories = super::story::stories(&data, &series).await?;
+
+    let feed = Feed {
+        site: settings.site_name().await?,
+        url: format!("{}/series/{}", config.url, id.as_str()),
+        href: format!(
+            "{}/series/{}/feed.{}",
+            config.url,
+            id.as_str(),
+            extension(format)
+        ),
+        title: t!(i18n, "feed-series", name = series.name.as_str()).0,
+        entries: entries(&i18n, &config, &data, stories).await?,
+    };
+
+    format.respond(&feed)
+}
+
+fn extension(format: Format) -> &'static str {
+    match format {
+        Format::Atom => "atom",
+        Format::Rss => "rss",
+    }
+}
+
+/// An entry for each story and each of its published chapters, newest first,
+/// up to [`CHAPTERS`] of them.
+async fn entries(
+    i18n: &I18n,
+    config: &ArcConfig,
+    data: &ArcBackend,
+    stories: Vec<Existing<Story>>,
+) -> Result<Vec<Entry>, Error> {
+    let mut entries = Vec::new();
+
+    let works = super::story::works(data, &stories).await?;
+
+    for (story, chapters) in stories.into_iter().zip(works) {
+        for (index, chapter) in chapters.iter().enumerate() {
+            entries.push(self::chapter(i18n, config, &story, index, chapter)?);
+        }
+
+        entries.push(Entry {
+            url: format!("{}/stories/{}", config.url, story.id.as_str()),
+            title: story.name.clone(),
+            summary: story.summary.clone(),
+            content: None,
+            authors: authors(&story),
+            published: story.created,
+            updated: story.updated,
+        });
+    }
+
+    entries.sort_by_key(|entry| Reverse(entry.published));
+
+    // Chapters carry their whole content, so only the newest are kept
+    entries.truncate(CHAPTERS as usize);
+
+    Ok(entries)
+}
+
+/// An entry for the story's chapter at `index` of its published chapters,
+/// dated from when it was first published.
+fn chapter(
+    i18n: &I18n,
+    config: &ArcConfig,
+    story: &Existing<Story>,
+    index: usize,
+    chapter: &Existing<Chapter>,
+) -> Result<Entry, Error> {
+    let mut content = String::new();
+
+    Html {
+        src: &|url| Some(export::absolute(config, url)),
+    }
+    .parts(&mut content, &chapter.main)?;
+
+    let name = match &chapter.name {
+        Some(name) => name.clone(),
+        None => t!(i18n, "chapter-title", number = index + 1).0,
+    };
+
+    Ok(Entry {
+        url: format!(
+            "{}/stories/{}/chapters/{}",
+            config.url,
+            story.id.as_str(),
+            index + 1
+        ),
+        title: t!(
+            i18n,
+            "feed-chapter",
+            story = story.name.as_str(),
+            chapter = name
+        )
+        .0,
+        summary: story.summary.clone(),
+        content: Some(content),
+        authors: authors(story),
+        published: chapter.published_at.unwrap_or(chapter.created),
+        updated: chapter.updated,
+    })
+}
+
+fn authors(story: &Existing<Story>) -> Vec<String> {
+    story
+        .authors
+        .iter()
+        .map(|author| author.account.name.clone())
+        .collect()
+}
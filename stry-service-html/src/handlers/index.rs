@@ -1,7 +1,6 @@
 use axum_language::AcceptLanguage;
 use stry_common::{
     backend::{ArcBackend, StoryEntity},
-    http::Pagination,
     prelude::*,
 };
 
@@ -12,15 +11,28 @@ use axum::{
 };
 use windswept::Render as _;
 
-use crate::error::Error;
+use crate::{error::Error, extractors::Preferences, handlers::StoryQuery, i18n::I18n};
 
-#[instrument(skip(lang, data, query), err)]
+#[instrument(skip(i18n, prefs, accept, data, query), err)]
 pub async fn get(
-    lang: AcceptLanguage,
+    i18n: I18n,
+    prefs: Preferences,
+    accept: AcceptLanguage,
     Extension(data): Extension<ArcBackend>,
-    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+    ContentLengthLimit(Query(mut query)): ContentLengthLimit<Query<StoryQuery>, { 1024 * 5000 }>,
 ) -> Result<impl IntoResponse, Error> {
-    let stories = StoryEntity::all(&data, query.cursor, query.limit).await?;
+    let filter = query.filter(&accept).hide(&prefs.content);
 
-    Ok(Html(crate::templates::page::index(&stories).render()?))
+    let stories = StoryEntity::filter(&data, filter, query.cursor, query.limit).await?;
+
+    Ok(Html(
+        crate::templates::page::index(
+            &i18n,
+            &prefs,
+            &stories,
+            &query,
+            query.next(&stories).as_deref(),
+        )
+        .render()?,
+    ))
 }
@@ -0,0 +1,57 @@
+//! Uploaded images, read out of the configured media store.
+
+use stry_common::{
+    error::NotFound,
+    media::{self, MediaStorage},
+};
+
+use axum::{
+    extract::Path,
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+use crate::error::Error;
+
+pub async fn get(
+    Extension(storage): Extension<MediaStorage>,
+    Path(name): Path<String>,
+) -> Result<Response, Error> {
+    // Wildcards keep the slash they start with
+    let name = name.strip_prefix('/').unwrap_or(&name);
+
+    let media_type = media::media_type(name)
+        .ok_or(NotFound)
+        .map_err(Error::from_any)?;
+
+    let data = storage
+        .get(name)
+        .await?
+        .ok_or(NotFound)
+        .map_err(Error::from_any)?;
+
+    // Uploads are named after the hash of their contents so they never change
+    let hashed = name
+        .split('.')
+        .next()
+        .is_some_and(|stem| stem.len() == 64 && stem.bytes().all(|b| b.is_ascii_hexdigit()));
+
+    let cache_control = if hashed {
+        "public, max-age=31536000, immutable"
+    } else {
+        "no-cache"
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(media_type)),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_static(cache_control),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
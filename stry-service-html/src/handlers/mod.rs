@@ -1,10 +1,90 @@
+mod account;
+mod admin;
+mod browse;
+mod download;
+mod feed;
 mod index;
+mod media;
+mod opds;
 mod resources;
+mod story;
 
-use axum::{routing::get, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+pub use self::browse::StoryQuery;
 
 pub fn routes() -> Router {
     Router::new()
         .route("/", get(index::get))
+        .route("/stories.atom", get(feed::stories))
+        .route("/stories.rss", get(feed::stories))
+        .route("/authors", get(browse::authors))
+        .route("/authors/:id", get(browse::author))
+        .route("/authors/:id/feed.atom", get(feed::author))
+        .route("/authors/:id/feed.rss", get(feed::author))
+        .route("/origins", get(browse::origins))
+        .route("/origins/:id", get(browse::origin))
+        .route("/warnings", get(browse::warnings))
+        .route("/warnings/:id", get(browse::warning))
+        .route("/pairings", get(browse::pairings))
+        .route("/pairings/:id", get(browse::pairing))
+        .route("/characters", get(browse::characters))
+        .route("/characters/:id", get(browse::character))
+        .route("/tags", get(browse::tags))
+        .route("/tags/:id", get(browse::tag))
+        .route("/tags/:id/feed.atom", get(feed::tag))
+        .route("/tags/:id/feed.rss", get(feed::tag))
+        .route("/series/:id", get(story::series))
+        .route("/series/:id/feed.atom", get(feed::series))
+        .route("/series/:id/feed.rss", get(feed::series))
+        .route("/series/:id/download.epub", get(download::series))
+        .route(
+            "/register",
+            get(account::register).post(account::register_post),
+        )
+        .route(
+            "/sign-in",
+            get(account::sign_in).post(account::sign_in_post),
+        )
+        .route("/sign-out", post(account::sign_out))
+        .route(
+            "/admin/settings",
+            get(admin::settings).post(admin::settings_post),
+        )
+        .route(
+            "/settings",
+            get(account::settings).post(account::settings_post),
+        )
+        .route("/opds", get(opds::root))
+        .route("/opds/recent", get(opds::recent))
+        .route("/opds/tags", get(opds::tags))
+        .route("/opds/tags/:id", get(opds::tag))
+        .route("/opds/authors", get(opds::authors))
+        .route("/opds/authors/:id", get(opds::author))
+        .route("/opds/series", get(opds::all_series))
+        .route("/opds/series/:id", get(opds::series))
+        .route("/stories/:id", get(story::get))
+        .route("/stories/:id/all", get(story::work))
+        .route("/stories/:id/download.epub", get(download::story))
+        .route("/stories/:id/download.html", get(download::story))
+        .route("/stories/:id/download.md", get(download::story))
+        .route("/stories/:id/download.txt", get(download::story))
+        .route("/stories/:id/chapters/:number", get(story::chapter))
+        .route(
+            "/stories/:id/chapters/:number/download.html",
+            get(download::chapter),
+        )
+        .route(
+            "/stories/:id/chapters/:number/download.md",
+            get(download::chapter),
+        )
+        .route(
+            "/stories/:id/chapters/:number/download.txt",
+            get(download::chapter),
+        )
+        .route("/media/*name", get(media::get))
         .nest("/assets", resources::routes())
 }
@@ -0,0 +1,596 @@
+//! The OPDS catalog, navigation feeds of tags, authors and series leading to
+//! acquisition feeds of their stories, with links to download each story.
+
+use stry_common::{
+    backend::{ArcBackend, SeriesEntity, StoryEntity, TagEntity, UserEntity},
+    config::ArcConfig,
+    http::Pagination,
+    models::{
+        story::{Story, StoryFilter},
+        Existing, Id,
+    },
+    prelude::*,
+    settings::Settings,
+};
+
+use axum::{
+    extract::{ContentLengthLimit, Path, Query},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+use crate::{
+    error::Error,
+    export::epub,
+    i18n::{t, I18n},
+    templates::opds::{
+        Author, Catalog, CatalogEntry, Link, ACQUISITION, NAVIGATION, REL_ACQUISITION,
+    },
+};
+
+#[instrument(skip(i18n, config, settings), err)]
+pub async fn root(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+) -> Result<Response, Error> {
+    let now = OffsetDateTime::now_utc();
+
+    let sections = [
+        ("recent", "feed-stories", ACQUISITION),
+        ("tags", "browse-tags", NAVIGATION),
+        ("authors", "browse-authors", NAVIGATION),
+        ("series", "browse-series", NAVIGATION),
+    ];
+
+    let entries = sections
+        .into_iter()
+        .map(|(path, title, kind)| {
+            let href = format!("{}/opds/{}", config.url, path);
+
+            CatalogEntry {
+                id: href.clone(),
+                title: t!(i18n, title).0,
+                updated: now,
+                authors: Vec::new(),
+                language: None,
+                summary: String::new(),
+                categories: Vec::new(),
+                links: vec![Link {
+                    rel: "subsection",
+                    kind,
+                    href,
+                }],
+            }
+        })
+        .collect();
+
+    respond(Catalog {
+        site: settings.site_name().await?,
+        href: format!("{}/opds", config.url),
+        start: format!("{}/opds", config.url),
+        kind: NAVIGATION,
+        title: t!(i18n, "opds-catalog").0,
+        updated: now,
+        next: None,
+        entries,
+    })
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn recent(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let stories = StoryEntity::all(&data, query.cursor, query.limit).await?;
+
+    respond(acquisition(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/recent", config.url),
+        t!(i18n, "feed-stories").0,
+        &stories,
+        query.limit,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn tags(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let tags = TagEntity::all(&data, query.cursor, query.limit).await?;
+
+    let entries = tags
+        .iter()
+        .map(|tag| {
+            navigation(
+                format!("{}/opds/tags/{}", config.url, tag.id.as_str()),
+                tag.content.clone(),
+                tag.description.clone(),
+                tag.updated,
+            )
+        })
+        .collect();
+
+    respond(navigation_catalog(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/tags", config.url),
+        t!(i18n, "browse-tags").0,
+        &tags,
+        query.limit,
+        entries,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn tag(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let tag = TagEntity::get(&data, id).await?;
+
+    let filter = StoryFilter {
+        tag: Some(id),
+        ..StoryFilter::default()
+    };
+
+    let stories = StoryEntity::filter(&data, filter, query.cursor, query.limit).await?;
+
+    respond(acquisition(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/tags/{}", config.url, id.as_str()),
+        t!(i18n, "feed-tag", name = tag.content.as_str()).0,
+        &stories,
+        query.limit,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn authors(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let users = UserEntity::all(&data, query.cursor, query.limit).await?;
+
+    let entries = users
+        .iter()
+        .map(|user| {
+            navigation(
+                format!("{}/opds/authors/{}", config.url, user.id.as_str()),
+                user.account.name.clone(),
+                String::new(),
+                user.updated,
+            )
+        })
+        .collect();
+
+    respond(navigation_catalog(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/authors", config.url),
+        t!(i18n, "browse-authors").0,
+        &users,
+        query.limit,
+        entries,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn author(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let user = UserEntity::get(&data, id).await?;
+
+    let filter = StoryFilter {
+        author: Some(id),
+        ..StoryFilter::default()
+    };
+
+    let stories = StoryEntity::filter(&data, filter, query.cursor, query.limit).await?;
+
+    respond(acquisition(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/authors/{}", config.url, id.as_str()),
+        t!(i18n, "feed-author", name = user.account.name.as_str()).0,
+        &stories,
+        query.limit,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data, query), err)]
+pub async fn all_series(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<Response, Error> {
+    let series = SeriesEntity::all(&data, query.cursor, query.limit).await?;
+
+    let entries = series
+        .iter()
+        .map(|series| {
+            navigation(
+                format!("{}/opds/series/{}", config.url, series.id.as_str()),
+                series.name.clone(),
+                series.summary.clone(),
+                series.updated,
+            )
+        })
+        .collect();
+
+    respond(navigation_catalog(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/series", config.url),
+        t!(i18n, "browse-series").0,
+        &series,
+        query.limit,
+        entries,
+    ))
+}
+
+#[instrument(skip(i18n, config, settings, data), err)]
+pub async fn series(
+    i18n: I18n,
+    Extension(config): Extension<ArcConfig>,
+    Extension(settings): Extension<Settings>,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<Response, Error> {
+    let series = SeriesEntity::get(&data, id).await?;
+    let stories = super::story::stories(&data, &series).await?;
+
+    // A series is shown whole, in reading order
+    respond(acquisition(
+        &config,
+        settings.site_name().await?,
+        format!("{}/opds/series/{}", config.url, id.as_str()),
+        t!(i18n, "feed-series", name = series.name.as_str()).0,
+        &stories,
+        i64::MAX,
+    ))
+}
+
+fn respond(catalog: Catalog) -> Result<Response, Error> {
+    Ok(([(header::CONTENT_TYPE, catalog.kind)], catalog.xml()?).into_response())
+}
+
+/// A link to a sub-catalog of stories.
+fn navigation(
+    href: String,
+    title: String,
+    summary: String,
+    updated: OffsetDateTime,
+) -> CatalogEntry {
+    CatalogEntry {
+        id: href.clone(),
+        title,
+        updated,
+        authors: Vec::new(),
+        language: None,
+        summary,
+        categories: Vec::new(),
+        links: vec![Link {
+            rel: "subsection",
+            kind: ACQUISITION,
+            href,
+        }],
+    }
+}
+
+fn navigation_catalog<T>(
+    config: &ArcConfig,
+    site: String,
+    href: String,
+    title: String,
+    entities: &[Existing<T>],
+    limit: i64,
+    entries: Vec<CatalogEntry>,
+) -> Catalog {
+    Catalog {
+        site,
+        next: super::browse::next(entities, limit).map(|query| format!("{}{}", href, query)),
+        href,
+        start: format!("{}/opds", config.url),
+        kind: NAVIGATION,
+        title,
+        updated: newest(&entries),
+        entries,
+    }
+}
+
+/// A page of stories, each with links to download them.
+fn acquisition(
+    config: &ArcConfig,
+    site: String,
+    href: String,
+    title: String,
+    stories: &[Existing<Story>],
+    limit: i64,
+) -> Catalog {
+    let entries = stories
+        .iter()
+        .map(|story| story_entry(config, story))
+        .collect::<Vec<_>>();
+
+    Catalog {
+        site,
+        next: super::browse::next(stories, limit).map(|query| format!("{}{}", href, query)),
+        href,
+        start: format!("{}/opds", config.url),
+        kind: ACQUISITION,
+        title,
+        updated: newest(&entries),
+        entries,
+    }
+}
+
+fn story_entry(config: &ArcConfig, story: &Existing<Story>) -> CatalogEntry {
+    let url = format!("{}/stories/{}", config.url, story.id.as_str());
+
+    CatalogEntry {
+        id: url.clone(),
+        title: story.name.clone(),
+        updated: story.updated,
+        authors: story
+            .authors
+            .iter()
+            .map(|author| Author {
+                name: author.account.name.clone(),
+                uri: format!("{}/opds/authors/{}", config.url, author.id.as_str()),
+            })
+            .collect(),
+        language: Some(story.language.clone()),
+        summary: story.summary.clone(),
+        categories: story
+            .tags
+            .iter()
+            .map(|tag| (tag.id.as_str().to_string(), tag.content.clone()))
+            .collect(),
+        links: vec![
+            Link {
+                rel: "alternate",
+                kind: "text/html",
+                href: url.clone(),
+            },
+            Link {
+                rel: REL_ACQUISITION,
+                kind: epub::CONTENT_TYPE,
+                href: format!("{}/download.epub", url),
+            },
+            Link {
+                rel: REL_ACQUISITION,
+                kind: "text/html",
+                href: format!("{}/all", url),
+            },
+        ],
+    }
+}
+
+/// Catalogs are as new as their newest entry, an empty catalog never changes.
+fn newest(entries: &[CatalogEntry]) -> OffsetDateTime {
+    entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use stry_common::models::core::Tag;
+
+    use super::*;
+    use crate::export::test::{config, existing, story};
+
+    /// The element's links as `(rel, type, href)`.
+    fn links<'d>(parent: roxmltree::Node<'d, 'd>) -> Vec<(&'d str, &'d str, &'d str)> {
+        parent
+            .children()
+            .filter(|node| node.tag_name().name() == "link")
+            .map(|node| {
+                (
+                    node.attribute("rel").unwrap(),
+                    node.attribute("type").unwrap(),
+                    node.attribute("href").unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_navigation() {
+        let config = Arc::new(config());
+
+        let tags = vec![existing(
+            "tag00001",
+            Tag {
+                content: String::from("Cats & <Dogs>"),
+                description: String::from("]]>"),
+            },
+        )];
+
+        let entries = tags
+            .iter()
+            .map(|tag| {
+                navigation(
+                    format!("{}/opds/tags/{}", config.url, tag.id.as_str()),
+                    tag.content.clone(),
+                    tag.description.clone(),
+                    tag.updated,
+                )
+            })
+            .collect();
+
+        // A full page has a next one
+        let xml = navigation_catalog(
+            &config,
+            String::from("Site & Co"),
+            format!("{}/opds/tags", config.url),
+            String::from("Tags"),
+            &tags,
+            1,
+            entries,
+        )
+        .xml()
+        .unwrap();
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let feed = document.root_element();
+
+        assert!(!xml.contains("]]>"));
+        assert_eq!(
+            links(feed),
+            [
+                ("self", NAVIGATION, "https://example.com/opds/tags"),
+                ("start", NAVIGATION, "https://example.com/opds"),
+                (
+                    "next",
+                    NAVIGATION,
+                    "https://example.com/opds/tags?cursor=tag00001&limit=1"
+                ),
+            ]
+        );
+
+        let entry = feed
+            .children()
+            .find(|node| node.tag_name().name() == "entry")
+            .unwrap();
+
+        assert_eq!(
+            links(entry),
+            [(
+                "subsection",
+                ACQUISITION,
+                "https://example.com/opds/tags/tag00001"
+            )]
+        );
+
+        let text = |name: &str| {
+            document
+                .descendants()
+                .filter(|node| node.tag_name().name() == name)
+                .map(|node| node.text().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(text("name"), ["Site & Co"]);
+        assert_eq!(text("title"), ["Tags", "Cats & <Dogs>"]);
+        assert_eq!(text("summary"), ["]]>"]);
+    }
+
+    #[test]
+    fn test_acquisition() {
+        let config = Arc::new(config());
+
+        let mut story = story();
+
+        story.summary = String::from("A <summary> ]]>");
+        story.tags = vec![existing(
+            "tag00001",
+            Tag {
+                content: String::from("\"Quoted\" & <tagged>"),
+                description: String::new(),
+            },
+        )];
+
+        let xml = acquisition(
+            &config,
+            String::from("stry"),
+            format!("{}/opds/recent", config.url),
+            String::from("Recent"),
+            &[story],
+            20,
+        )
+        .xml()
+        .unwrap();
+
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let feed = document.root_element();
+
+        assert!(!xml.contains("]]>"));
+
+        // Not a full page, so there's no next one
+        assert_eq!(
+            links(feed),
+            [
+                ("self", ACQUISITION, "https://example.com/opds/recent"),
+                ("start", NAVIGATION, "https://example.com/opds"),
+            ]
+        );
+
+        let entry = feed
+            .children()
+            .find(|node| node.tag_name().name() == "entry")
+            .unwrap();
+
+        assert_eq!(
+            links(entry),
+            [
+                (
+                    "alternate",
+                    "text/html",
+                    "https://example.com/stories/story001"
+                ),
+                (
+                    REL_ACQUISITION,
+                    "application/epub+zip",
+                    "https://example.com/stories/story001/download.epub"
+                ),
+                (
+                    REL_ACQUISITION,
+                    "text/html",
+                    "https://example.com/stories/story001/all"
+                ),
+            ]
+        );
+
+        let child = |name: &str| {
+            entry
+                .children()
+                .find(|node| node.tag_name().name() == name)
+                .unwrap()
+        };
+
+        assert_eq!(child("title").text(), Some("Cats & Dogs"));
+        assert_eq!(child("summary").text(), Some("A <summary> ]]>"));
+        assert_eq!(child("language").text(), Some("en"));
+        assert_eq!(
+            child("category").attribute("label"),
+            Some("\"Quoted\" & <tagged>")
+        );
+        assert_eq!(
+            child("author")
+                .children()
+                .map(|node| node.text().unwrap_or_default())
+                .collect::<Vec<_>>(),
+            ["Author", "https://example.com/opds/authors/author01"]
+        );
+    }
+}
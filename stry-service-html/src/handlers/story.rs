@@ -0,0 +1,189 @@
+use stry_common::{
+    backend::{ArcBackend, ChapterEntity, SeriesEntity, StoryEntity},
+    error::NotFound,
+    models::{
+        story::{Chapter, Series, Story},
+        Either, Existing, Id,
+    },
+    prelude::*,
+};
+
+use axum::{
+    extract::Path,
+    response::{Html, IntoResponse},
+    Extension,
+};
+use windswept::Render as _;
+
+use crate::{error::Error, extractors::Preferences, i18n::I18n};
+
+#[instrument(skip(i18n, prefs, data), err)]
+pub async fn get(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<impl IntoResponse, Error> {
+    let story = StoryEntity::get(&data, id).await?;
+    let chapters = chapters(&data, &story).await?;
+    let translations = StoryEntity::translations(&data, id).await?;
+
+    Ok(Html(
+        crate::templates::page::story(&i18n, &prefs, &story, &chapters, &translations).render()?,
+    ))
+}
+
+#[instrument(skip(i18n, prefs, data), err)]
+pub async fn chapter(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Path((id, number)): Path<(Id, usize)>,
+) -> Result<impl IntoResponse, Error> {
+    let story = StoryEntity::get(&data, id).await?;
+    let chapters = chapters(&data, &story).await?;
+
+    // Chapter numbers start at one for readers
+    let chapter = number
+        .checked_sub(1)
+        .and_then(|index| chapters.get(index))
+        .ok_or(NotFound)
+        .map_err(Error::from_any)?;
+
+    Ok(Html(
+        crate::templates::page::chapter(&i18n, &prefs, &story, chapters.len(), number, chapter)
+            .render()?,
+    ))
+}
+
+#[instrument(skip(i18n, prefs, data), err)]
+pub async fn work(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<impl IntoResponse, Error> {
+    let story = StoryEntity::get(&data, id).await?;
+    let chapters = chapters(&data, &story).await?;
+
+    Ok(Html(
+        crate::templates::page::work(&i18n, &prefs, &story, &chapters).render()?,
+    ))
+}
+
+#[instrument(skip(i18n, prefs, data), err)]
+pub async fn series(
+    i18n: I18n,
+    prefs: Preferences,
+    Extension(data): Extension<ArcBackend>,
+    Path(id): Path<Id>,
+) -> Result<impl IntoResponse, Error> {
+    let series = SeriesEntity::get(&data, id).await?;
+    let stories = stories(&data, &series).await?;
+
+    Ok(Html(
+        crate::templates::page::series(&i18n, &prefs, &series, &stories).render()?,
+    ))
+}
+
+/// Gets the series' stories in reading order.
+pub(super) async fn stories(
+    data: &ArcBackend,
+    series: &Existing<Series>,
+) -> Result<Vec<Existing<Story>>, Error> {
+    match &series.stories {
+        Either::Left(stories) => Ok(stories.clone()),
+        Either::Right(ids) => {
+            let mut stories = StoryEntity::many(data, ids.clone()).await?;
+
+            // Many doesn't keep the order the ids were given in
+            stories.sort_by_key(|story| ids.iter().position(|id| *id == story.id));
+
+            Ok(stories)
+        }
+    }
+}
+
+/// Gets the story's published chapters in reading order.
+pub(super) async fn chapters(
+    data: &ArcBackend,
+    story: &Existing<Story>,
+) -> Result<Vec<Existing<Chapter>>, Error> {
+    let mut chapters = works(data, std::slice::from_ref(story)).await?;
+
+    Ok(chapters.pop().unwrap_or_default())
+}
+
+/// Gets each of the stories' published chapters in reading order, loading
+/// them all at once.
+pub(super) async fn works(
+    data: &ArcBackend,
+    stories: &[Existing<Story>],
+) -> Result<Vec<Vec<Existing<Chapter>>>, Error> {
+    let ids = stories
+        .iter()
+        .filter_map(|story| match &story.chapters {
+            Some(Either::Right(ids)) => Some(ids.iter().copied()),
+            _ => None,
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    // Unpublished chapters are left out for readers
+    let loaded = ChapterEntity::many(data, ids, None).await?;
+
+    Ok(stories
+        .iter()
+        .map(|story| {
+            let chapters = match &story.chapters {
+                Some(Either::Left(chapters)) => chapters.clone(),
+                Some(Either::Right(ids)) => {
+                    let mut chapters = loaded
+                        .iter()
+                        .filter(|chapter| ids.contains(&chapter.id))
+                        .cloned()
+                        .collect::<Vec<_>>();
+
+                    // Many doesn't keep the order the ids were given in
+                    chapters.sort_by_key(|chapter| ids.iter().position(|id| *id == chapter.id));
+
+                    chapters
+                }
+                None => Vec::new(),
+            };
+
+            chapters
+                .into_iter()
+                .filter(|chapter| chapter.published)
+                .map(|mut chapter| {
+                    chapter.for_readers();
+
+                    chapter
+                })
+                .collect()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use stry_backend_postgres::testing::Drafts;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_readers_never_see_drafts() {
+        let drafts = Drafts::new().await;
+
+        let story = StoryEntity::get(&drafts.data, drafts.story).await.unwrap();
+        let chapters = chapters(&drafts.data, &story).await.unwrap();
+
+        drafts.remove().await;
+
+        // Only the published chapter is read, as it was published
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].name.as_deref(), Some("Published"));
+        assert!(chapters[0].draft.is_none());
+        assert!(chapters[0].scheduled.is_none());
+    }
+}
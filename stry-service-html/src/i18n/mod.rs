@@ -1 +1,51 @@
-pub mod resources;
+use std::ops::Deref;
+
+pub use stry_common::i18n::{format, FluentResources};
+
+/// The request's negotiated language and bundles, see
+/// [`stry_common::i18n::I18n`], wrapped so handlers can extract it.
+pub struct I18n(pub stry_common::i18n::I18n);
+
+impl Deref for I18n {
+    type Target = stry_common::i18n::I18n;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Looks up a message in the request's negotiated bundles, escaped so it can
+/// be used as both text and an attribute value inside `rsx!`.
+///
+/// Arguments are given as `name = value` pairs, numbers can be used by the
+/// message's plural selectors.
+///
+/// ```ignore
+/// t!(i18n, "story-chapters", count = chapters, chapters = number(i18n.lang(), chapters))
+/// ```
+macro_rules! t {
+    ($i18n:expr, $id:expr) => {
+        ::windswept::Escape($i18n.message($id, None))
+    };
+    ($i18n:expr, $id:expr, $( $name:ident = $value:expr ),+ $(,)?) => {{
+        let mut args = ::fluent::FluentArgs::new();
+
+        $( args.set(stringify!($name), $value); )+
+
+        ::windswept::Escape($i18n.message($id, Some(&args)))
+    }};
+}
+
+pub(crate) use t;
+
+/// The site's own `en-US` bundles, for rendering templates in tests.
+#[cfg(test)]
+pub(crate) fn english() -> I18n {
+    let resources = FluentResources::builder()
+        .add_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/localization"))
+        .expect("unable to load localization")
+        .build()
+        .expect("unable to build localization");
+
+    I18n(resources.negotiate(&[unic_langid::langid!("en-US")]))
+}
@@ -1,3 +1,5 @@
+mod export;
+mod extractors;
 mod handlers;
 mod i18n;
 mod templates;
@@ -6,7 +8,8 @@ mod error;
 
 use axum::Router;
 
+/// The HTML frontend, its translations come from the
+/// [`stry_common::i18n::FluentResources`] extension.
 pub fn routes() -> Router {
-    
-    Router::new().merge(handlers::routes())
+    handlers::routes()
 }
@@ -1,16 +1,28 @@
-use windswept::{rsx, Render};
+use stry_common::models::core::SiteTheme;
+use windswept::{rsx, Escape, Render};
 
 use super::partials;
+use crate::{extractors::Preferences, i18n::I18n};
+
+pub fn base<'r, R: Render + 'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    main: R,
+) -> impl Render + 'r {
+    // The stylesheet swaps its colors for the light theme, see `main.css`
+    let theme = match prefs.appearance.theme {
+        SiteTheme::Dark => "theme-dark",
+        SiteTheme::Light => "theme-light",
+    };
 
-pub fn base<R: Render>(main: R) -> impl Render {
     rsx! {
         <!DOCTYPE html>
-        <html lang="en">
+        <html lang={i18n.lang()} class={theme}>
         <head>
             <meta charset="UTF-8" />
             <meta http-equiv="X-UA-Compatible" content="IE=edge" />
             <meta name="viewport" content="width=device-width, initial-scale=1.0" />
-            <title>"Document"</title>
+            <title>{Escape(&prefs.site)}</title>
             <link rel="stylesheet" href="/assets/main.css" />
             <script defer src="/assets/alpinejs-collapse.js"></script>
             <script defer src="/assets/alpinejs.js"></script>
@@ -18,7 +30,7 @@ pub fn base<R: Render>(main: R) -> impl Render {
         </head>
         <body class="bg-zinc-900">
             <main class="container mx-auto my-2 max-w-3xl">
-                {partials::nav()}
+                {partials::nav(i18n, &prefs.site)}
 
                 {main}
             </main>
@@ -0,0 +1,261 @@
+//! Atom and RSS documents for following new stories and chapters.
+//!
+//! These are written by hand instead of with `rsx!` as it treats elements like
+//! `<link>` as empty HTML elements, which isn't valid XML.
+
+use std::fmt::Write as _;
+
+use stry_common::prelude::*;
+
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use windswept::Escape;
+
+pub struct Feed {
+    /// The site's name, shown as the feed's author.
+    pub site: String,
+    /// The absolute url of the page the feed follows.
+    pub url: String,
+    /// The absolute url of the feed itself, also used as its id.
+    pub href: String,
+    pub title: String,
+    /// Newest first.
+    pub entries: Vec<Entry>,
+}
+
+/// A new story or newly published chapter.
+pub struct Entry {
+    /// The absolute url of the story or chapter, also used as its id.
+    pub url: String,
+    pub title: String,
+    pub summary: String,
+    /// The chapter's text as HTML, stories only have a summary.
+    pub content: Option<String>,
+    pub authors: Vec<String>,
+    pub published: OffsetDateTime,
+    pub updated: OffsetDateTime,
+}
+
+impl Feed {
+    /// Feeds are as new as their newest entry, an empty feed never changes.
+    fn updated(&self) -> OffsetDateTime {
+        self.entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+    }
+
+    pub fn atom(&self) -> Result<String, Error> {
+        let mut out = String::new();
+
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(out, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+        writeln!(out, "<id>{}</id>", Escape(&self.href))?;
+        writeln!(out, "<title>{}</title>", Escape(&self.title))?;
+        writeln!(
+            out,
+            "<updated>{}</updated>",
+            self.updated().format(&Rfc3339)?
+        )?;
+        writeln!(out, "<author><name>{}</name></author>", Escape(&self.site))?;
+        writeln!(
+            out,
+            r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+            Escape(&self.url)
+        )?;
+        writeln!(out, r#"<link rel="self" href="{}"/>"#, Escape(&self.href))?;
+
+        for entry in &self.entries {
+            writeln!(out, "<entry>")?;
+            writeln!(out, "<id>{}</id>", Escape(&entry.url))?;
+            writeln!(out, "<title>{}</title>", Escape(&entry.title))?;
+            writeln!(
+                out,
+                r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+                Escape(&entry.url)
+            )?;
+            writeln!(
+                out,
+                "<published>{}</published>",
+                entry.published.format(&Rfc3339)?
+            )?;
+            writeln!(
+                out,
+                "<updated>{}</updated>",
+                entry.updated.format(&Rfc3339)?
+            )?;
+
+            for author in &entry.authors {
+                writeln!(out, "<author><name>{}</name></author>", Escape(author))?;
+            }
+
+            writeln!(out, "<summary>{}</summary>", Escape(&entry.summary))?;
+
+            if let Some(content) = &entry.content {
+                writeln!(out, r#"<content type="html">{}</content>"#, Escape(content))?;
+            }
+
+            writeln!(out, "</entry>")?;
+        }
+
+        writeln!(out, "</feed>")?;
+
+        Ok(out)
+    }
+
+    pub fn rss(&self) -> Result<String, Error> {
+        let mut out = String::new();
+
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:dc="http://purl.org/dc/elements/1.1/">"#
+        )?;
+        writeln!(out, "<channel>")?;
+        writeln!(out, "<title>{}</title>", Escape(&self.title))?;
+        writeln!(out, "<link>{}</link>", Escape(&self.url))?;
+        writeln!(out, "<description>{}</description>", Escape(&self.title))?;
+        writeln!(out, "<generator>{}</generator>", Escape(&self.site))?;
+        writeln!(
+            out,
+            "<lastBuildDate>{}</lastBuildDate>",
+            self.updated().format(&Rfc2822)?
+        )?;
+        writeln!(
+            out,
+            r#"<atom:link rel="self" type="application/rss+xml" href="{}"/>"#,
+            Escape(&self.href)
+        )?;
+
+        for entry in &self.entries {
+            writeln!(out, "<item>")?;
+            writeln!(out, "<title>{}</title>", Escape(&entry.title))?;
+            writeln!(out, "<link>{}</link>", Escape(&entry.url))?;
+            writeln!(
+                out,
+                r#"<guid isPermaLink="true">{}</guid>"#,
+                Escape(&entry.url)
+            )?;
+            writeln!(
+                out,
+                "<pubDate>{}</pubDate>",
+                entry.published.format(&Rfc2822)?
+            )?;
+
+            // RSS's own author element has to be an email address
+            for author in &entry.authors {
+                writeln!(out, "<dc:creator>{}</dc:creator>", Escape(author))?;
+            }
+
+            writeln!(out, "<description>{}</description>", Escape(&entry.summary))?;
+
+            if let Some(content) = &entry.content {
+                writeln!(
+                    out,
+                    "<content:encoded>{}</content:encoded>",
+                    Escape(content)
+                )?;
+            }
+
+            writeln!(out, "</item>")?;
+        }
+
+        writeln!(out, "</channel>")?;
+        writeln!(out, "</rss>")?;
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// User text that would break the document if it wasn't escaped.
+    const TEXT: &str = "Cats & <Dogs> ]]> \"quoted\"";
+
+    fn feed() -> Feed {
+        let time = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+
+        let entry = |url: &str, content: Option<&str>| Entry {
+            url: format!("https://example.com/{}?a=1&b=2", url),
+            title: TEXT.to_string(),
+            summary: TEXT.to_string(),
+            content: content.map(String::from),
+            authors: vec![TEXT.to_string()],
+            published: time,
+            updated: time,
+        };
+
+        Feed {
+            site: TEXT.to_string(),
+            url: String::from("https://example.com/"),
+            href: String::from("https://example.com/stories.atom"),
+            title: TEXT.to_string(),
+            entries: vec![
+                entry("stories/story001/chapters/1", Some("<p>One &amp; ]]></p>")),
+                entry("stories/story001", None),
+            ],
+        }
+    }
+
+    /// The text of every `name` element in the document.
+    fn texts(document: &roxmltree::Document<'_>, name: &str) -> Vec<String> {
+        document
+            .descendants()
+            .filter(|node| node.tag_name().name() == name)
+            .map(|node| node.text().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_atom() {
+        let xml = feed().atom().unwrap();
+        let document = roxmltree::Document::parse(&xml).unwrap();
+
+        assert_eq!(document.root_element().tag_name().name(), "feed");
+        assert!(!xml.contains("]]>"));
+
+        let entries = document
+            .root_element()
+            .children()
+            .filter(|node| node.tag_name().name() == "entry")
+            .collect::<Vec<_>>();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(texts(&document, "title"), [TEXT; 3]);
+        assert_eq!(texts(&document, "name"), [TEXT; 3]);
+        assert_eq!(texts(&document, "content"), ["<p>One &amp; ]]></p>"]);
+        assert_eq!(
+            texts(&document, "id")[1],
+            "https://example.com/stories/story001/chapters/1?a=1&b=2"
+        );
+    }
+
+    #[test]
+    fn test_rss() {
+        let xml = feed().rss().unwrap();
+        let document = roxmltree::Document::parse(&xml).unwrap();
+
+        assert_eq!(document.root_element().tag_name().name(), "rss");
+        assert!(!xml.contains("]]>"));
+
+        let channel = document.root_element().first_element_child().unwrap();
+
+        assert_eq!(channel.tag_name().name(), "channel");
+        assert_eq!(
+            channel
+                .children()
+                .filter(|node| node.tag_name().name() == "item")
+                .count(),
+            2
+        );
+        assert_eq!(texts(&document, "generator"), [TEXT]);
+        assert_eq!(texts(&document, "creator"), [TEXT; 2]);
+        assert_eq!(texts(&document, "encoded"), ["<p>One &amp; ]]></p>"]);
+        assert_eq!(
+            texts(&document, "guid")[1],
+            "https://example.com/stories/story001?a=1&b=2"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 pub mod page;
 
 mod base;
+pub mod feed;
+pub mod opds;
 pub mod partials;
 
 pub use self::base::base;
@@ -0,0 +1,141 @@
+//! OPDS 1.2 catalogs, the Atom feeds e-reader apps browse and download
+//! stories from.
+//!
+//! Written by hand for the same reason as [`super::feed`].
+
+use std::fmt::Write as _;
+
+use stry_common::prelude::*;
+
+use time::format_description::well_known::Rfc3339;
+use windswept::Escape;
+
+pub const NAVIGATION: &str = "application/atom+xml;profile=opds-catalog;kind=navigation";
+pub const ACQUISITION: &str = "application/atom+xml;profile=opds-catalog;kind=acquisition";
+
+/// The relation of a link to a story's file, ie an EPUB.
+pub const REL_ACQUISITION: &str = "http://opds-spec.org/acquisition";
+
+/// A navigation or acquisition feed.
+pub struct Catalog {
+    /// The site's name, shown as the catalog's author.
+    pub site: String,
+    /// The absolute url of the catalog itself, also used as its id.
+    pub href: String,
+    /// The absolute url of the root catalog.
+    pub start: String,
+    /// Either [`NAVIGATION`] or [`ACQUISITION`].
+    pub kind: &'static str,
+    pub title: String,
+    pub updated: OffsetDateTime,
+    /// The absolute url of the next page, if there is one.
+    pub next: Option<String>,
+    pub entries: Vec<CatalogEntry>,
+}
+
+/// A sub-catalog in a navigation feed, or a story in an acquisition feed.
+pub struct CatalogEntry {
+    /// An absolute url that is unique to the entry.
+    pub id: String,
+    pub title: String,
+    pub updated: OffsetDateTime,
+    pub authors: Vec<Author>,
+    pub language: Option<String>,
+    pub summary: String,
+    /// Tags as `(id, name)`.
+    pub categories: Vec<(String, String)>,
+    pub links: Vec<Link>,
+}
+
+pub struct Author {
+    pub name: String,
+    /// The absolute url of the author's catalog.
+    pub uri: String,
+}
+
+pub struct Link {
+    pub rel: &'static str,
+    pub kind: &'static str,
+    pub href: String,
+}
+
+impl Catalog {
+    pub fn xml(&self) -> Result<String, Error> {
+        let mut out = String::new();
+
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:dc="http://purl.org/dc/terms/" xmlns:opds="http://opds-spec.org/2010/catalog">"#
+        )?;
+        writeln!(out, "<id>{}</id>", Escape(&self.href))?;
+        writeln!(out, "<title>{}</title>", Escape(&self.title))?;
+        writeln!(out, "<updated>{}</updated>", self.updated.format(&Rfc3339)?)?;
+        writeln!(out, "<author><name>{}</name></author>", Escape(&self.site))?;
+        link(&mut out, "self", self.kind, &self.href)?;
+        link(&mut out, "start", NAVIGATION, &self.start)?;
+
+        if let Some(next) = &self.next {
+            link(&mut out, "next", self.kind, next)?;
+        }
+
+        for entry in &self.entries {
+            writeln!(out, "<entry>")?;
+            writeln!(out, "<id>{}</id>", Escape(&entry.id))?;
+            writeln!(out, "<title>{}</title>", Escape(&entry.title))?;
+            writeln!(
+                out,
+                "<updated>{}</updated>",
+                entry.updated.format(&Rfc3339)?
+            )?;
+
+            for author in &entry.authors {
+                writeln!(
+                    out,
+                    "<author><name>{}</name><uri>{}</uri></author>",
+                    Escape(&author.name),
+                    Escape(&author.uri)
+                )?;
+            }
+
+            if let Some(language) = &entry.language {
+                writeln!(out, "<dc:language>{}</dc:language>", Escape(language))?;
+            }
+
+            for (term, label) in &entry.categories {
+                writeln!(
+                    out,
+                    r#"<category term="{}" label="{}"/>"#,
+                    Escape(term),
+                    Escape(label)
+                )?;
+            }
+
+            if !entry.summary.is_empty() {
+                writeln!(out, "<summary>{}</summary>", Escape(&entry.summary))?;
+            }
+
+            for entry_link in &entry.links {
+                link(&mut out, entry_link.rel, entry_link.kind, &entry_link.href)?;
+            }
+
+            writeln!(out, "</entry>")?;
+        }
+
+        writeln!(out, "</feed>")?;
+
+        Ok(out)
+    }
+}
+
+fn link(out: &mut String, rel: &str, kind: &str, href: &str) -> Result<(), Error> {
+    writeln!(
+        out,
+        r#"<link rel="{}" type="{}" href="{}"/>"#,
+        Escape(rel),
+        Escape(kind),
+        Escape(href)
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use stry_common::{
+    error::FieldError,
+    models::{
+        core::{FontSize, NotificationPreference, Setting, SiteTheme, User},
+        story::Rating,
+        Existing, Id,
+    },
+    settings::{Definition, Kind},
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    i18n::{format::field_error, t, I18n},
+};
+
+macro_rules! input {
+    () => {
+        "block w-full mt-1 py-1 px-2 rounded bg-zinc-800 text-zinc-200 focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400"
+    };
+}
+
+macro_rules! button {
+    () => {
+        "py-1 px-3 rounded bg-zinc-700 text-zinc-200 transition-colors duration-75 ease-in-out hover:bg-zinc-600 focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400"
+    };
+}
+
+pub fn register<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    csrf: &'r str,
+    username: &'r str,
+    email: &'r str,
+    errors: &'r [FieldError],
+    message: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <h1 class="px-3 sm:px-6 lg:px-8 my-2 text-2xl font-bold text-zinc-200">{t!(i18n, "register-title")}</h1>
+
+            <form class="px-3 sm:px-6 lg:px-8 my-2" method="post" action="/register">
+                {alert(i18n, message)}
+                <input type="hidden" name="csrf" value={Escape(csrf)} />
+                {field(i18n, "field-username", "username", "text", "username", username, errors)}
+                {field(i18n, "field-email", "email", "email", "email", email, errors)}
+                {field(i18n, "field-password", "password", "password", "new-password", "", errors)}
+                <button class={button!()} type="submit">{t!(i18n, "register-submit")}</button>
+            </form>
+
+            <p class="px-3 sm:px-6 lg:px-8 my-2 text-sm text-zinc-400">{t!(i18n, "register-have-account")} " " <a class="text-zinc-300 hover:text-zinc-50" href="/sign-in">{t!(i18n, "register-sign-in")}</a></p>
+            </>
+        },
+    )
+}
+
+pub fn sign_in<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    csrf: &'r str,
+    email: &'r str,
+    errors: &'r [FieldError],
+    message: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <h1 class="px-3 sm:px-6 lg:px-8 my-2 text-2xl font-bold text-zinc-200">{t!(i18n, "sign-in-title")}</h1>
+
+            <form class="px-3 sm:px-6 lg:px-8 my-2" method="post" action="/sign-in">
+                {alert(i18n, message)}
+                <input type="hidden" name="csrf" value={Escape(csrf)} />
+                {field(i18n, "field-email", "email", "email", "email", email, errors)}
+                {field(i18n, "field-password", "password", "password", "current-password", "", errors)}
+                <button class={button!()} type="submit">{t!(i18n, "sign-in-submit")}</button>
+            </form>
+
+            <p class="px-3 sm:px-6 lg:px-8 my-2 text-sm text-zinc-400">{t!(i18n, "sign-in-no-account")} " " <a class="text-zinc-300 hover:text-zinc-50" href="/register">{t!(i18n, "sign-in-register")}</a></p>
+            </>
+        },
+    )
+}
+
+pub fn settings<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    csrf: &'r str,
+    user: &'r Existing<User>,
+    saved: bool,
+    errors: &'r [FieldError],
+) -> impl Render + 'r {
+    const THEMES: [(SiteTheme, &str); 2] = [
+        (SiteTheme::Dark, "theme-dark"),
+        (SiteTheme::Light, "theme-light"),
+    ];
+
+    const FONT_SIZES: [(FontSize, &str); 3] = [
+        (FontSize::Small, "font-size-small"),
+        (FontSize::Medium, "font-size-medium"),
+        (FontSize::Large, "font-size-large"),
+    ];
+
+    const RATINGS: [(Rating, &str); 3] = [
+        (Rating::Mature, "rating-mature"),
+        (Rating::Teen, "rating-teen"),
+        (Rating::General, "rating-general"),
+    ];
+
+    const COMMENTS: [(NotificationPreference, &str); 4] = [
+        (NotificationPreference::Both, "notifications-both"),
+        (NotificationPreference::Email, "notifications-email"),
+        (NotificationPreference::Web, "notifications-web"),
+        (NotificationPreference::Neither, "notifications-neither"),
+    ];
+
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <h1 class="px-3 sm:px-6 lg:px-8 my-2 text-2xl font-bold text-zinc-200">{t!(i18n, "settings-title")}</h1>
+
+            <form class="px-3 sm:px-6 lg:px-8 my-2" method="post" action="/settings">
+                {if saved {
+                    rsx! { <p class="my-2 text-sm text-green-400" role="status">{t!(i18n, "settings-saved")}</p> }
+                }}
+                <input type="hidden" name="csrf" value={Escape(csrf)} />
+                <label class="block my-3">
+                    <span class="block text-sm text-zinc-300">{t!(i18n, "settings-theme")}</span>
+                    <select class={input!()} name="theme">
+                        {for (theme, label) in THEMES {
+                            rsx! {
+                                {if user.appearance.theme == theme {
+                                    rsx! { <option value={format!("{:?}", theme)} selected>{t!(i18n, label)}</option> }
+                                } else {
+                                    rsx! { <option value={format!("{:?}", theme)}>{t!(i18n, label)}</option> }
+                                }}
+                            }
+                        }}
+                    </select>
+                </label>
+                <label class="block my-3">
+                    <span class="block text-sm text-zinc-300">{t!(i18n, "settings-font-size")}</span>
+                    <select class={input!()} name="font_size">
+                        {for (size, label) in FONT_SIZES {
+                            rsx! {
+                                {if user.appearance.font_size == size {
+                                    rsx! { <option value={format!("{:?}", size)} selected>{t!(i18n, label)}</option> }
+                                } else {
+                                    rsx! { <option value={format!("{:?}", size)}>{t!(i18n, label)}</option> }
+                                }}
+                            }
+                        }}
+                    </select>
+                </label>
+                <label class="block my-3 text-sm text-zinc-300">
+                    {if user.appearance.collapse_notes {
+                        rsx! { <input class="mr-2" type="checkbox" name="collapse_notes" value="true" checked /> }
+                    } else {
+                        rsx! { <input class="mr-2" type="checkbox" name="collapse_notes" value="true" /> }
+                    }}
+                    {t!(i18n, "settings-collapse-notes")}
+                </label>
+                <label class="block my-3">
+                    <span class="block text-sm text-zinc-300">{t!(i18n, "settings-comments")}</span>
+                    <select class={input!()} name="comments">
+                        {for (preference, label) in COMMENTS {
+                            rsx! {
+                                {if user.notifications.comments == preference {
+                                    rsx! { <option value={format!("{:?}", preference)} selected>{t!(i18n, label)}</option> }
+                                } else {
+                                    rsx! { <option value={format!("{:?}", preference)}>{t!(i18n, label)}</option> }
+                                }}
+                            }
+                        }}
+                    </select>
+                </label>
+
+                <h2 class="mt-6 text-lg font-bold text-zinc-200">{t!(i18n, "settings-content")}</h2>
+                <label class="block my-3">
+                    <span class="block text-sm text-zinc-300">{t!(i18n, "settings-rating")}</span>
+                    <select class={input!()} name="rating">
+                        <option value="">{t!(i18n, "filter-any-rating")}</option>
+                        {for (rating, label) in RATINGS {
+                            rsx! {
+                                {if user.content.rating == Some(rating) {
+                                    rsx! { <option value={format!("{:?}", rating)} selected>{t!(i18n, label)}</option> }
+                                } else {
+                                    rsx! { <option value={format!("{:?}", rating)}>{t!(i18n, label)}</option> }
+                                }}
+                            }
+                        }}
+                    </select>
+                </label>
+                {ids(i18n, "settings-hidden-warnings", "warnings", &user.content.warnings, errors)}
+                {ids(i18n, "settings-hidden-tags", "tags", &user.content.tags, errors)}
+                <button class={button!()} type="submit">{t!(i18n, "settings-save")}</button>
+            </form>
+
+            <form class="px-3 sm:px-6 lg:px-8 my-6" method="post" action="/sign-out">
+                <input type="hidden" name="csrf" value={Escape(csrf)} />
+                <button class={button!()} type="submit">{t!(i18n, "sign-out")}</button>
+            </form>
+            </>
+        },
+    )
+}
+
+/// The site-wide settings, `values` holds a submitted form that was turned
+/// down so it can be fixed.
+pub fn admin_settings<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    csrf: &'r str,
+    settings: &'r [Setting],
+    values: &'r BTreeMap<String, String>,
+    saved: bool,
+    errors: &'r [FieldError],
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <h1 class="px-3 sm:px-6 lg:px-8 my-2 text-2xl font-bold text-zinc-200">{t!(i18n, "admin-settings-title")}</h1>
+
+            <form class="px-3 sm:px-6 lg:px-8 my-2" method="post" action="/admin/settings">
+                {if saved {
+                    rsx! { <p class="my-2 text-sm text-green-400" role="status">{t!(i18n, "settings-saved")}</p> }
+                }}
+                <input type="hidden" name="csrf" value={Escape(csrf)} />
+                {for setting in settings {
+                    setting_field(i18n, setting, values.get(&setting.key), errors)
+                }}
+                <button class={button!()} type="submit">{t!(i18n, "settings-save")}</button>
+            </form>
+            </>
+        },
+    )
+}
+
+/// An input for the setting, fitting its kind, with its default and the errors
+/// for it below.
+fn setting_field<'r>(
+    i18n: &'r I18n,
+    setting: &'r Setting,
+    value: Option<&'r String>,
+    errors: &'r [FieldError],
+) -> impl Render + 'r {
+    let name = setting.key.as_str();
+    let value = value.cloned().unwrap_or_else(|| setting.value.to_string());
+    let kind = Definition::find(name).map(|definition| definition.kind);
+
+    rsx! {
+        <label class="block my-3">
+            <span class="block text-sm text-zinc-300">{t!(i18n, &format!("admin-setting-{}", name))}</span>
+            {match kind {
+                Some(Kind::Bool) => rsx! {
+                    <select class={input!()} name={name}>
+                        {if value == "true" {
+                            rsx! { <option value="true" selected>{t!(i18n, "admin-setting-on")}</option> }
+                        } else {
+                            rsx! { <option value="true">{t!(i18n, "admin-setting-on")}</option> }
+                        }}
+                        {if value == "false" {
+                            rsx! { <option value="false" selected>{t!(i18n, "admin-setting-off")}</option> }
+                        } else {
+                            rsx! { <option value="false">{t!(i18n, "admin-setting-off")}</option> }
+                        }}
+                    </select>
+                },
+                Some(Kind::Integer { min, max }) => rsx! {
+                    <input class={input!()} type="number" name={name} value={Escape(&value)} min={min.to_string()} max={max.to_string()} required />
+                },
+                Some(Kind::Text { max }) => rsx! {
+                    <input class={input!()} type="text" name={name} value={Escape(&value)} maxlength={max.to_string()} required />
+                },
+                None => rsx! {
+                    <input class={input!()} type="text" name={name} value={Escape(&value)} required />
+                },
+            }}
+            <span class="block mt-1 text-sm text-zinc-400">{t!(i18n, "admin-setting-default", value = setting.default.to_string())}</span>
+            {for error in errors.iter().filter(|error| error.path == name) {
+                rsx! { <span class="block mt-1 text-sm text-red-400">{Escape(field_error(i18n, error))}</span> }
+            }}
+        </label>
+    }
+}
+
+/// An input labeled with the `label` message, followed by the errors for the
+/// field with the same `name`.
+fn field<'r>(
+    i18n: &'r I18n,
+    label: &'r str,
+    name: &'r str,
+    kind: &'r str,
+    autocomplete: &'r str,
+    value: &'r str,
+    errors: &'r [FieldError],
+) -> impl Render + 'r {
+    rsx! {
+        <label class="block my-3">
+            <span class="block text-sm text-zinc-300">{t!(i18n, label)}</span>
+            <input class={input!()} type={kind} name={name} value={Escape(value)} autocomplete={autocomplete} required />
+            {for error in errors.iter().filter(|error| error.path == name) {
+                rsx! { <span class="block mt-1 text-sm text-red-400">{Escape(field_error(i18n, error))}</span> }
+            }}
+        </label>
+    }
+}
+
+/// An optional input of ids separated by spaces, followed by the errors for
+/// the field with the same `name`.
+fn ids<'r>(
+    i18n: &'r I18n,
+    label: &'r str,
+    name: &'r str,
+    ids: &'r [Id],
+    errors: &'r [FieldError],
+) -> impl Render + 'r {
+    let value = ids.iter().map(Id::as_str).collect::<Vec<_>>().join(" ");
+
+    rsx! {
+        <label class="block my-3">
+            <span class="block text-sm text-zinc-300">{t!(i18n, label)}</span>
+            <input class={input!()} type="text" name={name} value={Escape(value)} placeholder={t!(i18n, "settings-ids-hint")} />
+            {for error in errors.iter().filter(|error| error.path == name) {
+                rsx! { <span class="block mt-1 text-sm text-red-400">{Escape(field_error(i18n, error))}</span> }
+            }}
+        </label>
+    }
+}
+
+/// An error message for the form as a whole, ie incorrect credentials.
+fn alert<'r>(i18n: &'r I18n, message: Option<&'r str>) -> impl Render + 'r {
+    rsx! {
+        <>
+        {if let Some(message) = message {
+            rsx! { <p class="my-2 text-sm text-red-400" role="alert">{t!(i18n, message)}</p> }
+        }}
+        </>
+    }
+}
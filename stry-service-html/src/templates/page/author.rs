@@ -0,0 +1,66 @@
+use stry_common::models::{blog::Post, core::User, story::Story, Existing};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    handlers::StoryQuery,
+    i18n::{t, I18n},
+    templates::partials,
+};
+
+pub fn author<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    user: &'r Existing<User>,
+    stories: &'r [Existing<Story>],
+    posts: &'r [Existing<Post>],
+    query: &'r StoryQuery,
+    next: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <header class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <h1 class="text-2xl font-bold">{Escape(&user.account.name)}</h1>
+                <p class="text-sm text-zinc-400">{t!(i18n, "author-joined")} " " {partials::date(user.created)}</p>
+                {partials::feed(i18n, &format!("/authors/{}/feed.atom", user.id.as_str()))}
+            </header>
+
+            {if let Some(biography) = &user.account.biography {
+                rsx! {
+                    <section class="px-3 sm:px-6 lg:px-8 my-4 text-zinc-300">
+                        {partials::parts(biography)}
+                    </section>
+                }
+            }}
+
+            <h2 class="px-3 sm:px-6 lg:px-8 mt-6 text-xl font-bold text-zinc-200">{t!(i18n, "author-stories")}</h2>
+
+            {super::browse::filters(i18n, query)}
+
+            {partials::stories(i18n, stories)}
+
+            {partials::next(i18n, next)}
+
+            {if !posts.is_empty() {
+                rsx! {
+                    <>
+                    <h2 class="px-3 sm:px-6 lg:px-8 mt-6 text-xl font-bold text-zinc-200">{t!(i18n, "author-posts")}</h2>
+
+                    {for post in posts {
+                        rsx! {
+                            <article class="px-3 sm:px-6 lg:px-8 my-4 text-zinc-300">
+                                <p class="text-sm text-zinc-400">{partials::date(post.created)}</p>
+                                {partials::parts(&post.parts)}
+                            </article>
+                        }
+                    }}
+                    </>
+                }
+            }}
+            </>
+        },
+    )
+}
@@ -0,0 +1,133 @@
+use stry_common::models::{
+    story::{Rating, State, Story},
+    Existing,
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    handlers::StoryQuery,
+    i18n::{t, I18n},
+    templates::partials,
+};
+
+/// A paginated index of links, ie every origin, with the `title` message as its heading.
+pub fn list<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    title: &'r str,
+    links: &'r [(String, String)],
+    next: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <h1 class="px-3 sm:px-6 lg:px-8 my-2 text-2xl font-bold text-zinc-200">{t!(i18n, title)}</h1>
+
+            <ul class="px-3 sm:px-6 lg:px-8 my-2">
+                {for (href, name) in links {
+                    rsx! {
+                        <li class="py-1">
+                            <a class="text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={href}>{Escape(name)}</a>
+                        </li>
+                    }
+                }}
+            </ul>
+
+            {partials::next(i18n, next)}
+            </>
+        },
+    )
+}
+
+/// The stories carrying an entity, ie everything tagged with a warning.
+#[allow(clippy::too_many_arguments)]
+pub fn stories<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    name: &'r str,
+    description: &'r str,
+    stories: &'r [Existing<Story>],
+    query: &'r StoryQuery,
+    next: Option<&'r str>,
+    feed: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <header class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <h1 class="text-2xl font-bold">{Escape(name)}</h1>
+                <p class="my-2 text-sm text-zinc-400 whitespace-pre-line">{Escape(description)}</p>
+                {if let Some(feed) = feed {
+                    rsx! { {partials::feed(i18n, feed)} }
+                }}
+            </header>
+
+            {filters(i18n, query)}
+
+            {partials::stories(i18n, stories)}
+
+            {partials::next(i18n, next)}
+            </>
+        },
+    )
+}
+
+/// A form to narrow down a list of stories by rating and state.
+pub fn filters<'r>(i18n: &'r I18n, query: &'r StoryQuery) -> impl Render + 'r {
+    const RATINGS: [(Rating, &str); 4] = [
+        (Rating::General, "rating-general"),
+        (Rating::Teen, "rating-teen"),
+        (Rating::Mature, "rating-mature"),
+        (Rating::Explicit, "rating-explicit"),
+    ];
+
+    const STATES: [(State, &str); 4] = [
+        (State::Completed, "state-completed"),
+        (State::InProgress, "state-in-progress"),
+        (State::Hiatus, "state-hiatus"),
+        (State::Abandoned, "state-abandoned"),
+    ];
+
+    macro_rules! class {
+        () => {
+            "py-1 px-2 rounded bg-zinc-800 text-zinc-200 focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400"
+        };
+    }
+
+    rsx! {
+        <form class="flex flex-wrap gap-2 px-3 sm:px-6 lg:px-8 my-2 text-sm" method="get">
+            <select class={class!()} name="rating" aria-label={t!(i18n, "story-rating")}>
+                <option value="">{t!(i18n, "filter-any-rating")}</option>
+                {for (rating, label) in RATINGS {
+                    rsx! {
+                        {if query.rating == Some(rating) {
+                            rsx! { <option value={format!("{:?}", rating)} selected>{t!(i18n, label)}</option> }
+                        } else {
+                            rsx! { <option value={format!("{:?}", rating)}>{t!(i18n, label)}</option> }
+                        }}
+                    }
+                }}
+            </select>
+            <select class={class!()} name="state" aria-label={t!(i18n, "story-state")}>
+                <option value="">{t!(i18n, "filter-any-state")}</option>
+                {for (state, label) in STATES {
+                    rsx! {
+                        {if query.state == Some(state) {
+                            rsx! { <option value={format!("{:?}", state)} selected>{t!(i18n, label)}</option> }
+                        } else {
+                            rsx! { <option value={format!("{:?}", state)}>{t!(i18n, label)}</option> }
+                        }}
+                    }
+                }}
+            </select>
+            <input class={class!()} type="text" name="language" value={Escape(query.language.as_deref().unwrap_or_default())} size="8" placeholder={t!(i18n, "filter-any-language")} aria-label={t!(i18n, "story-language")} />
+            <input type="hidden" name="limit" value={query.limit} />
+            <button class={class!()} type="submit">{t!(i18n, "filter-submit")}</button>
+        </form>
+    }
+}
@@ -0,0 +1,104 @@
+use stry_common::models::{
+    core::{Appearance, FontSize, Part},
+    story::{Chapter, Story},
+    Existing,
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    i18n::{format, t, I18n},
+    templates::partials,
+};
+
+pub fn chapter<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    story: &'r Existing<Story>,
+    count: usize,
+    number: usize,
+    chapter: &'r Existing<Chapter>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <header class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <a class="text-sm text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/stories/{}", story.id.as_str())}>{Escape(&story.name)}</a>
+                <h1 class="text-2xl font-bold">{partials::chapter_title(i18n, number, chapter)}</h1>
+                <p class="text-sm text-zinc-400">
+                    {t!(i18n, "story-words", count = chapter.words, words = format::number(i18n.lang(), chapter.words))}
+                    " · "
+                    {partials::reading_time(i18n, chapter.reading_time())}
+                </p>
+                {partials::downloads(i18n, format!("/stories/{}/chapters/{}", story.id.as_str(), number), false)}
+            </header>
+
+            {partials::chapter_nav(i18n, story, count, number)}
+
+            {body(i18n, &prefs.appearance, chapter)}
+
+            {partials::chapter_nav(i18n, story, count, number)}
+            </>
+        },
+    )
+}
+
+/// The chapter's content surrounded by its author's notes, in the reader's
+/// chosen font size.
+pub(super) fn body<'r>(
+    i18n: &'r I18n,
+    appearance: &'r Appearance,
+    chapter: &'r Existing<Chapter>,
+) -> impl Render + 'r {
+    let size = match appearance.font_size {
+        FontSize::Small => "text-sm",
+        FontSize::Medium => "text-base",
+        FontSize::Large => "text-lg",
+    };
+
+    rsx! {
+        <>
+        {notes(i18n, appearance, &chapter.prefix)}
+
+        <article class={format!("px-3 sm:px-6 lg:px-8 my-4 text-zinc-200 {}", size)}>
+            {partials::parts(&chapter.main)}
+        </article>
+
+        {notes(i18n, appearance, &chapter.suffix)}
+        </>
+    }
+}
+
+/// An author's note that can be collapsed, starting closed if the reader
+/// prefers it.
+fn notes<'r>(
+    i18n: &'r I18n,
+    appearance: &'r Appearance,
+    parts: &'r [Existing<Part>],
+) -> impl Render + 'r {
+    rsx! {
+        <>
+        {if !parts.is_empty() {
+            rsx! {
+                {if appearance.collapse_notes {
+                    rsx! {
+                        <details class="px-3 sm:px-6 lg:px-8 my-4 text-sm text-zinc-400">
+                            <summary class="cursor-pointer">{t!(i18n, "chapter-notes")}</summary>
+                            {partials::parts(parts)}
+                        </details>
+                    }
+                } else {
+                    rsx! {
+                        <details class="px-3 sm:px-6 lg:px-8 my-4 text-sm text-zinc-400" open>
+                            <summary class="cursor-pointer">{t!(i18n, "chapter-notes")}</summary>
+                            {partials::parts(parts)}
+                        </details>
+                    }
+                }}
+            }
+        }}
+        </>
+    }
+}
@@ -1,18 +1,28 @@
 use stry_common::models::{story::Story, Existing};
 use windswept::{rsx, Render};
 
-pub fn index(stories: &[Existing<Story>]) -> impl Render + '_ {
-    crate::templates::base(rsx! {
-        <>
-        {for story in stories {
-            rsx! {
-                {crate::templates::partials::story(story)}
+use crate::{extractors::Preferences, handlers::StoryQuery, i18n::I18n, templates::partials};
 
-                <div class="hidden sm:block sm:px-6 lg:px-8 text-sm" aria-hidden="true">
-                    <div class="border-t border-gray-700"></div>
-                </div>
-            }
-        }}
-        </>
-    })
+pub fn index<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    stories: &'r [Existing<Story>],
+    query: &'r StoryQuery,
+    next: Option<&'r str>,
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <div class="px-3 sm:px-6 lg:px-8 my-2">{partials::feed(i18n, "/stories.atom")}</div>
+
+            {super::browse::filters(i18n, query)}
+
+            {partials::stories(i18n, stories)}
+
+            {partials::next(i18n, next)}
+            </>
+        },
+    )
 }
@@ -1,3 +1,19 @@
+pub mod browse;
+
+mod account;
+mod author;
+mod chapter;
 mod index;
+mod series;
+mod story;
+mod work;
 
-pub use self::index::index;
+pub use self::{
+    account::{admin_settings, register, settings, sign_in},
+    author::author,
+    chapter::chapter,
+    index::index,
+    series::series,
+    story::story,
+    work::work,
+};
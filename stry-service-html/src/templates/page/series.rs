@@ -0,0 +1,44 @@
+use stry_common::models::{
+    story::{Series, Story},
+    Existing,
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    i18n::{t, I18n},
+    templates::partials,
+};
+
+/// The series' stories in reading order, it isn't paginated as a series is
+/// read whole.
+pub fn series<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    series: &'r Existing<Series>,
+    stories: &'r [Existing<Story>],
+) -> impl Render + 'r {
+    let feed = format!("/series/{}/feed.atom", series.id.as_str());
+    let epub = format!("/series/{}/download.epub", series.id.as_str());
+
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <header class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <h1 class="text-2xl font-bold">{Escape(&series.name)}</h1>
+                <p class="my-2 text-sm text-zinc-400 whitespace-pre-line">{Escape(&series.summary)}</p>
+                {partials::feed(i18n, &feed)}
+                <p class="py-2 text-sm text-zinc-500">
+                    {t!(i18n, "download")}
+                    " "
+                    <a class="text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={epub}>{t!(i18n, "download-epub")}</a>
+                </p>
+            </header>
+
+            {partials::stories(i18n, stories)}
+            </>
+        },
+    )
+}
@@ -0,0 +1,119 @@
+use stry_common::models::{
+    story::{Chapter, Rating, State, Story},
+    Existing,
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{
+    extractors::Preferences,
+    i18n::{format::number, t, I18n},
+    templates::partials::{self, reading_time},
+};
+
+pub fn story<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    story: &'r Existing<Story>,
+    chapters: &'r [Existing<Chapter>],
+    translations: &'r [Existing<Story>],
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            {partials::story(i18n, story)}
+
+            <div class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <dl class="grid grid-cols-2 gap-x-4 my-2 text-sm text-zinc-400 sm:grid-cols-4">
+                    <dt>{t!(i18n, "story-language")}</dt>
+                    <dd>{Escape(&story.language)}</dd>
+                    <dt>{t!(i18n, "story-rating")}</dt>
+                    <dd>{t!(i18n, match story.rating {
+                        Rating::Explicit => "rating-explicit",
+                        Rating::Mature => "rating-mature",
+                        Rating::Teen => "rating-teen",
+                        Rating::General => "rating-general",
+                    })}</dd>
+                    <dt>{t!(i18n, "story-state")}</dt>
+                    <dd>{t!(i18n, match story.state {
+                        State::Completed => "state-completed",
+                        State::InProgress => "state-in-progress",
+                        State::Hiatus => "state-hiatus",
+                        State::Abandoned => "state-abandoned",
+                    })}</dd>
+                    <dt>{t!(i18n, "story-words-label")}</dt>
+                    <dd>{number(i18n.lang(), story.words)}</dd>
+                    <dt>{t!(i18n, "story-reading-time-label")}</dt>
+                    <dd>{reading_time(i18n, story.reading_time())}</dd>
+                    <dt>{t!(i18n, "story-chapters-label")}</dt>
+                    <dd>{number(i18n.lang(), chapters.len())}</dd>
+                    <dt>{t!(i18n, "story-published")}</dt>
+                    <dd>{partials::date(story.created)}</dd>
+                    <dt>{t!(i18n, "story-updated")}</dt>
+                    <dd>{partials::date(story.updated)}</dd>
+                    {if !story.translators.is_empty() {
+                        rsx! {
+                            <>
+                            <dt>{t!(i18n, "story-translators")}</dt>
+                            <dd>
+                                {for (index, translator) in story.translators.iter().enumerate() {
+                                    rsx! {
+                                        <>
+                                        {if index != 0 { rsx! { ", " } }}
+                                        <a class="text-zinc-300 hover:text-zinc-50" href={format!("/authors/{}", translator.id.as_str())}>{Escape(&translator.account.name)}</a>
+                                        </>
+                                    }
+                                }}
+                            </dd>
+                            </>
+                        }
+                    }}
+                </dl>
+            </div>
+
+            {if !translations.is_empty() {
+                rsx! {
+                    <div class="px-3 sm:px-6 lg:px-8 my-2 text-sm text-zinc-400">
+                        <h2 class="text-zinc-300">{t!(i18n, "story-translations")}</h2>
+                        <ul>
+                            {for translation in translations {
+                                rsx! {
+                                    <li class="py-1">
+                                        <span class="uppercase">{Escape(&translation.language)}</span>
+                                        " · "
+                                        <a class="text-zinc-300 hover:text-zinc-50" href={format!("/stories/{}", translation.id.as_str())} lang={Escape(&translation.language)}>{Escape(&translation.name)}</a>
+                                        {if translation.original.is_none() {
+                                            rsx! { <span class="text-zinc-500">" (" {t!(i18n, "story-original")} ")"</span> }
+                                        }}
+                                    </li>
+                                }
+                            }}
+                        </ul>
+                    </div>
+                }
+            }}
+
+            <div class="hidden sm:block sm:px-6 lg:px-8 text-sm" aria-hidden="true">
+                <div class="border-t border-gray-700"></div>
+            </div>
+
+            <div class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <ol class="list-decimal list-inside">
+                    {for (index, chapter) in chapters.iter().enumerate() {
+                        rsx! {
+                            <li class="py-1">
+                                <a class="text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/stories/{}/chapters/{}", story.id.as_str(), index + 1)}>
+                                    {partials::chapter_title(i18n, index + 1, chapter)}
+                                </a>
+                            </li>
+                        }
+                    }}
+                </ol>
+                <a class="inline-block py-2 text-sm text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/stories/{}/all", story.id.as_str())}>{t!(i18n, "entire-work")}</a>
+                {partials::downloads(i18n, format!("/stories/{}", story.id.as_str()), true)}
+            </div>
+            </>
+        },
+    )
+}
@@ -0,0 +1,40 @@
+use stry_common::models::{
+    story::{Chapter, Story},
+    Existing,
+};
+use windswept::{rsx, Escape, Render};
+
+use crate::{extractors::Preferences, i18n::I18n, templates::partials};
+
+pub fn work<'r>(
+    i18n: &'r I18n,
+    prefs: &'r Preferences,
+    story: &'r Existing<Story>,
+    chapters: &'r [Existing<Chapter>],
+) -> impl Render + 'r {
+    crate::templates::base(
+        i18n,
+        prefs,
+        rsx! {
+            <>
+            <header class="px-3 sm:px-6 lg:px-8 my-2 text-zinc-200">
+                <h1 class="text-2xl font-bold">
+                    <a class="transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/stories/{}", story.id.as_str())}>{Escape(&story.name)}</a>
+                </h1>
+            </header>
+
+            {for (index, chapter) in chapters.iter().enumerate() {
+                rsx! {
+                    <section id={format!("chapter-{}", index + 1)}>
+                        <h2 class="px-3 sm:px-6 lg:px-8 mt-6 text-xl font-bold text-zinc-200">
+                            {partials::chapter_title(i18n, index + 1, chapter)}
+                        </h2>
+
+                        {super::chapter::body(i18n, &prefs.appearance, chapter)}
+                    </section>
+                }
+            }}
+            </>
+        },
+    )
+}
@@ -1,9 +1,19 @@
-use stry_common::models::{
-    story::{Rating, State, Story, StoryTag, TagKind, TagLevel},
-    Existing,
+use stry_common::{
+    html,
+    models::{
+        core::Part,
+        story::{
+            Chapter, Pairing, Rating, Relationship, State, Story, StoryTag, TagKind, TagLevel,
+        },
+        Either, Existing,
+    },
+    prelude::OffsetDateTime,
 };
 
-use windswept::{rsx, Render};
+use time::Duration;
+use windswept::{rsx, Escape, Render};
+
+use crate::i18n::{format::number, t, I18n};
 
 pub fn media_object<L, T, S, M>(tile: L, title: T, sub: S, meta: M) -> impl Render
 where
@@ -61,27 +71,27 @@ where
     }
 }
 
-pub fn nav() -> impl Render {
+pub fn nav<'r>(i18n: &'r I18n, site: &'r str) -> impl Render + 'r {
     rsx! {
         <nav class="flex flex-wrap">
-            <a class="order-1 inline-block flex-1 py-3 px-2 font-mono font-bold text-zinc-200 md:flex-initial" href="#">"stry"</a>
+            <a class="order-1 inline-block flex-1 py-3 px-2 font-mono font-bold text-zinc-200 md:flex-initial" href="/">{Escape(site)}</a>
             <ul class="order-3 flex flex-1 basis-full overflow-x-auto md:order-2 md:basis-auto md:overflow-x-hidden">
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"authors"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"origins"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"warnings"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"pairings"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"characters"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"tags"</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/authors">{t!(i18n, "browse-authors")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/origins">{t!(i18n, "browse-origins")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/warnings">{t!(i18n, "browse-warnings")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/pairings">{t!(i18n, "browse-pairings")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/characters">{t!(i18n, "browse-characters")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/tags">{t!(i18n, "browse-tags")}</a></li>
             </ul>
             <ul class="order-2 flex md:order-3">
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"register"</a></li>
-                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="#">"sign-in"</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/register">{t!(i18n, "nav-register")}</a></li>
+                <li><a class="inline-block cursor-pointer py-3 px-2 text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href="/sign-in">{t!(i18n, "nav-sign-in")}</a></li>
             </ul>
         </nav>
     }
 }
 
-pub fn story_tile(rating: Rating, warning: bool, state: State) -> impl Render {
+pub fn story_tile(i18n: &I18n, rating: Rating, warning: bool, state: State) -> impl Render + '_ {
     rsx! {
         <a href="/" class="w-9 h-9 mt-1.5 mr-3 flex flex-col rounded focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400">
             <div class="flex">
@@ -91,13 +101,13 @@ pub fn story_tile(rating: Rating, warning: bool, state: State) -> impl Render {
                         Rating::Teen => "block w-4 h-4 mr-1 rounded bg-blue-400",
                         Rating::General => "block w-4 h-4 mr-1 rounded bg-gray-100",
                 }}>
-                    <span class="sr-only">"Rating"</span>
+                    <span class="sr-only">{t!(i18n, "tile-rating")}</span>
                 </div>
                 <div class={match warning {
                     true => "block w-4 h-4 rounded bg-red-400",
                     false => "block w-4 h-4 rounded bg-gray-100",
                 }}>
-                    <span class="sr-only">"Warning"</span>
+                    <span class="sr-only">{t!(i18n, "tile-warning")}</span>
                 </div>
             </div>
             <div class="flex justify-center">
@@ -107,57 +117,384 @@ pub fn story_tile(rating: Rating, warning: bool, state: State) -> impl Render {
                     State::Hiatus => "block w-4 h-4 mt-1 rounded bg-yellow-400",
                     State::Abandoned => "block w-4 h-4 mt-1 rounded bg-red-400",
                 }}>
-                    <span class="sr-only">"State"</span>
+                    <span class="sr-only">{t!(i18n, "tile-state")}</span>
                 </div>
             </div>
         </a>
     }
 }
 
-pub fn story(story: &Existing<Story>) -> impl Render + '_ {
+pub fn story<'r>(i18n: &'r I18n, story: &'r Existing<Story>) -> impl Render + 'r {
+    let chapters = match &story.chapters {
+        Some(Either::Left(chapters)) => chapters.len(),
+        Some(Either::Right(ids)) => ids.len(),
+        None => 0,
+    };
+
     rsx! {
         <div class="px-3 sm:px-6 lg:px-8 my-2">
             {media_object(
-                story_tile(story.rating, !story.warnings.is_empty(), story.state),
+                story_tile(i18n, story.rating, !story.warnings.is_empty(), story.state),
                 rsx! {
-                    <p class="text-base"></p>
+                    <p class="text-base" lang={Escape(&story.language)}>
+                        <a class="text-zinc-200 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/stories/{}", story.id.as_str())}>{Escape(&story.name)}</a>
+                    </p>
                 },
                 rsx! {
-                    <p class="text-sm"></p>
+                    <p class="text-sm text-zinc-400">
+                        {for (index, author) in story.authors.iter().enumerate() {
+                            rsx! {
+                                {if index != 0 {
+                                    rsx! { ", " }
+                                }}
+                                <a class="transition-colors duration-75 ease-in-out hover:text-zinc-50" href={format!("/authors/{}", author.id.as_str())}>{Escape(&author.account.name)}</a>
+                            }
+                        }}
+                    </p>
                 },
                 rsx! {
-                    <p class="text-sm text-opacity-60 text-white"></p>
+                    <p class="text-sm text-opacity-60 text-white">{date(story.updated)}</p>
                 },
             )}
-            <div class="pt-2 text-sm text-opacity-60 text-white"></div>
+            <div class="pt-2 text-sm text-opacity-60 text-white whitespace-pre-line" lang={Escape(&story.language)}>{Escape(&story.summary)}</div>
             <div class="text-sm">
                 <ul class="flex flex-wrap">
-                    {for warning in &story.warnings {
+                    {for warning in by_level(&story.warnings) {
                         rsx! {
-                            <li>{tag(warning, "", &warning.content)}</li>
+                            <li>{tag(warning, &format!("/warnings/{}", warning.id.as_str()), Escape(&warning.content))}</li>
                         }
                     }}
-                    {for pairing in &story.pairings {
+                    {for pairing in by_level(&story.pairings) {
                         rsx! {
-                            <li>{tag(pairing, "", "")}</li>
+                            <li>{tag(pairing, &format!("/pairings/{}", pairing.id.as_str()), Escape(pairing_name(pairing)))}</li>
                         }
                     }}
-                    {for character in &story.characters {
+                    {for character in by_level(&story.characters) {
                         rsx! {
-                            <li>{tag(character, "", &character.content)}</li>
+                            <li>{tag(character, &format!("/characters/{}", character.id.as_str()), Escape(&character.content))}</li>
                         }
                     }}
                     {for general in &story.tags {
                         rsx! {
-                            <li>{tag(general, "", &general.content)}</li>
+                            <li>{tag(general, &format!("/tags/{}", general.id.as_str()), Escape(&general.content))}</li>
                         }
                     }}
                 </ul>
             </div>
             <div class="text-sm text-opacity-60 text-white flex">
-                <p class="flex-grow"></p>
-                <p></p>
+                <p class="flex-grow">
+                    <span class="uppercase">{Escape(&story.language)}</span>
+                    " · "
+                    {t!(i18n, "story-words", count = story.words, words = number(i18n.lang(), story.words))}
+                    " · "
+                    {t!(i18n, "story-chapters", count = chapters, chapters = number(i18n.lang(), chapters))}
+                </p>
+                <p>{date(story.created)}</p>
             </div>
         </div>
     }
 }
+
+/// A list of stories with dividers between them.
+pub fn stories<'r>(i18n: &'r I18n, stories: &'r [Existing<Story>]) -> impl Render + 'r {
+    rsx! {
+        <>
+        {for item in stories {
+            rsx! {
+                {story(i18n, item)}
+
+                <div class="hidden sm:block sm:px-6 lg:px-8 text-sm" aria-hidden="true">
+                    <div class="border-t border-gray-700"></div>
+                </div>
+            }
+        }}
+        </>
+    }
+}
+
+/// A link to the next page of a list, if there is one.
+pub fn next<'r>(i18n: &'r I18n, next: Option<&'r str>) -> impl Render + 'r {
+    rsx! {
+        <>
+        {if let Some(href) = next {
+            rsx! {
+                <div class="flex justify-end px-3 sm:px-6 lg:px-8 my-2 text-sm">
+                    <a class="inline-block py-2 px-3 rounded text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50 focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400" href={Escape(href)} rel="next">{t!(i18n, "next-page")}</a>
+                </div>
+            }
+        }}
+        </>
+    }
+}
+
+/// Orders the tags so the major ones come before the minor ones.
+fn by_level<T: StoryTag>(tags: &[T]) -> impl Iterator<Item = &T> {
+    let major = tags.iter().filter(|tag| tag.level() == TagLevel::Major);
+    let minor = tags.iter().filter(|tag| tag.level() == TagLevel::Minor);
+
+    major.chain(minor)
+}
+
+/// A link to an Atom feed for following the page in a feed reader.
+pub fn feed<'r>(i18n: &'r I18n, href: &'r str) -> impl Render + 'r {
+    rsx! {
+        <a class="text-sm text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50" href={href} type="application/atom+xml">{t!(i18n, "feed")}</a>
+    }
+}
+
+/// Links to download the story or chapter at `href` in each format, only
+/// whole stories can be downloaded as an EPUB.
+pub fn downloads<'r>(i18n: &'r I18n, href: String, epub: bool) -> impl Render + 'r {
+    macro_rules! class {
+        () => {
+            "text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50"
+        };
+    }
+
+    let epub_href = format!("{}/download.epub", href);
+    let html_href = format!("{}/download.html", href);
+    let markdown_href = format!("{}/download.md", href);
+    let text_href = format!("{}/download.txt", href);
+
+    rsx! {
+        <p class="py-2 text-sm text-zinc-500">
+            {t!(i18n, "download")}
+            " "
+            {if epub {
+                rsx! {
+                    <>
+                    <a class={class!()} href={epub_href}>{t!(i18n, "download-epub")}</a>
+                    " · "
+                    </>
+                }
+            }}
+            <a class={class!()} href={html_href}>{t!(i18n, "download-html")}</a>
+            " · "
+            <a class={class!()} href={markdown_href}>{t!(i18n, "download-markdown")}</a>
+            " · "
+            <a class={class!()} href={text_href}>{t!(i18n, "download-text")}</a>
+        </p>
+    }
+}
+
+/// The pairing's characters joined by `/` for romantic relationships and
+/// `&` for everything else.
+pub fn pairing_name(pairing: &Pairing) -> String {
+    let separator = match pairing.relationship {
+        Relationship::Romantic => "/",
+        Relationship::Family | Relationship::Friends => " & ",
+    };
+
+    pairing
+        .characters
+        .iter()
+        .map(|character| character.content.as_str())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// A `<time>` element with the date in ISO 8601.
+pub fn date(time: OffsetDateTime) -> impl Render {
+    let date = time.date().to_string();
+
+    rsx! {
+        <time datetime={&date}>{&date}</time>
+    }
+}
+
+/// The estimated reading time, in minutes.
+pub fn reading_time(i18n: &I18n, time: Duration) -> impl Render {
+    let minutes = time.whole_minutes();
+
+    t!(
+        i18n,
+        "story-reading-time",
+        count = minutes,
+        minutes = number(i18n.lang(), minutes)
+    )
+}
+
+/// The chapter's title, falling back to `chapter-title` if it has none.
+pub fn chapter_title<'r>(
+    i18n: &'r I18n,
+    number: usize,
+    chapter: &'r Existing<Chapter>,
+) -> impl Render + 'r {
+    rsx! {
+        <>
+        {match &chapter.name {
+            Some(name) => Escape(name.clone()),
+            None => t!(i18n, "chapter-title", number = number),
+        }}
+        </>
+    }
+}
+
+/// Previous and next chapter links, with a link to the entire work between them.
+pub fn chapter_nav<'r>(
+    i18n: &'r I18n,
+    story: &'r Existing<Story>,
+    count: usize,
+    number: usize,
+) -> impl Render + 'r {
+    macro_rules! class {
+        () => {
+            "inline-block py-2 px-3 rounded text-zinc-400 transition-colors duration-75 ease-in-out hover:text-zinc-50 focus:ring-2 focus:ring-offset-2 focus:ring-offset-gray-900 focus:ring-blue-400"
+        };
+    }
+
+    rsx! {
+        <nav class="flex px-3 sm:px-6 lg:px-8 my-2 text-sm">
+            <div class="flex-1">
+                {if number > 1 {
+                    rsx! {
+                        <a class={class!()} href={format!("/stories/{}/chapters/{}", story.id.as_str(), number - 1)} rel="prev">{t!(i18n, "chapter-previous")}</a>
+                    }
+                }}
+            </div>
+            <a class={class!()} href={format!("/stories/{}/all", story.id.as_str())}>{t!(i18n, "entire-work")}</a>
+            <div class="flex-1 text-right">
+                {if number < count {
+                    rsx! {
+                        <a class={class!()} href={format!("/stories/{}/chapters/{}", story.id.as_str(), number + 1)} rel="next">{t!(i18n, "chapter-next")}</a>
+                    }
+                }}
+            </div>
+        </nav>
+    }
+}
+
+pub fn part(part: &Existing<Part>) -> impl Render + '_ {
+    rsx! {
+        <>
+        {match &**part {
+            Part::Heading(heading) => rsx! {
+                <div role="heading" aria-level={heading.level} class="mt-6 mb-2 text-lg font-bold text-zinc-100">{Escape(&heading.content)}</div>
+            },
+            Part::Image(image) => rsx! {
+                <figure class="my-4">
+                    <img class="mx-auto" src={Escape(&image.url)} alt={Escape(image.alt.as_deref().unwrap_or_default())} loading="lazy" />
+                </figure>
+            },
+            // The text is HTML so it isn't escaped, it's cleaned again in
+            // case the stored text wasn't sanitized (see `stry_common::html`)
+            Part::Text(text) => rsx! {
+                <div class="my-2 part-text">{html::clean(&text.content)}</div>
+            },
+        }}
+        </>
+    }
+}
+
+pub fn parts(parts: &[Existing<Part>]) -> impl Render + '_ {
+    rsx! {
+        <>
+        {for item in parts {
+            part(item)
+        }}
+        </>
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use stry_common::models::{
+        core::{Account, PartText, Tag, User},
+        story::Warning,
+        Id,
+    };
+
+    use super::*;
+    use crate::i18n::english;
+
+    fn existing<T>(id: &str, data: T) -> Existing<T> {
+        let time = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+
+        Existing::new(Id::try_from(id).unwrap(), data, time, time)
+    }
+
+    /// Renders without Fluent's bidi isolation marks, so placeables can be matched.
+    fn render(template: impl Render) -> String {
+        template
+            .render()
+            .unwrap()
+            .replace(['\u{2068}', '\u{2069}'], "")
+    }
+
+    #[test]
+    fn test_story() {
+        let i18n = english();
+
+        let mut data = Story::new(
+            String::from("Cats & Dogs"),
+            String::from("A <summary>"),
+            String::from("en"),
+            Rating::Teen,
+            State::InProgress,
+        );
+
+        data.authors = vec![existing(
+            "author01",
+            User::new(Account {
+                name: String::from("Author"),
+                email: None,
+                hash: None,
+                biography: None,
+            }),
+        )];
+        data.warnings = vec![existing(
+            "warning1",
+            Warning {
+                content: String::from("Violence"),
+                description: String::new(),
+                level: TagLevel::Major,
+            },
+        )];
+        data.tags = vec![existing(
+            "tagaaaa1",
+            Tag {
+                content: String::from("Fluff"),
+                description: String::new(),
+            },
+        )];
+        data.chapters = Some(Either::Right(vec![
+            Id::try_from("chapter1").unwrap(),
+            Id::try_from("chapter2").unwrap(),
+        ]));
+        data.words = 1234;
+
+        let html = render(story(&i18n, &existing("story001", data)));
+
+        assert!(html.contains(r#"href="/stories/story001">Cats &amp; Dogs</a>"#));
+        assert!(html.contains("A &lt;summary&gt;"));
+        assert!(html.contains(r#"href="/authors/author01">Author</a>"#));
+        assert!(html.contains(r#"href="/warnings/warning1""#));
+        assert!(html.contains(r#"href="/tags/tagaaaa1""#));
+        assert!(html.contains("1,234 words"));
+        assert!(html.contains("2 chapters"));
+        assert!(html.contains(r#"<time datetime="2020-09-13">"#));
+    }
+
+    #[test]
+    fn test_part_text_is_cleaned() {
+        let text = existing(
+            "part0001",
+            Part::Text(PartText {
+                content: String::from(concat!(
+                    r#"<p dir="rtl" onclick="steal()">Hello <em>there</em><script>steal()</script></p>"#,
+                    r#"<p><a href="javascript:steal()">link</a><iframe src="https://example.com"></iframe></p>"#,
+                )),
+                words: 0,
+                comments: Vec::new(),
+            }),
+        );
+
+        let html = render(part(&text));
+
+        assert!(html.contains(r#"<p dir="rtl">Hello <em>there</em></p>"#));
+        assert!(html.contains("link</a>"));
+        assert!(!html.contains("script"));
+        assert!(!html.contains("steal"));
+        assert!(!html.contains("iframe"));
+    }
+}
@@ -20,13 +20,26 @@ const map = (array, iteratee) => {
   return result
 }
 
+// Reads a color from the variables in `main.css`, which swaps them for the light theme
+const variable = (name) => `rgb(var(--color-${name}) / <alpha-value>)`
+
+const shades = (name) => Object.fromEntries(
+  [50, 100, 200, 300, 400, 500, 600, 700, 800, 900].map((shade) => [shade, variable(`${name}-${shade}`)])
+)
+
 /** @type {import('tailwindcss').Config} */
 module.exports = {
   content: [
     "src/templates/**/*.rs"
   ],
   theme: {
-    extend: {},
+    extend: {
+      colors: {
+        white: variable('white'),
+        gray: shades('gray'),
+        zinc: shades('zinc'),
+      },
+    },
   },
   plugins: [
     plugin(function ({ matchUtilities, theme }) {
@@ -1,9 +1,16 @@
 use stry_common::{
-    error::{ErrorResponse, StatusCodeErrorResponse},
+    error::{Conflict, ErrorResponse, Invalid, StatusCodeErrorResponse},
+    i18n::{format::field_error, FluentResources},
     prelude::*,
 };
 
-use axum::{http::StatusCode, Json};
+use axum::{
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_language::AcceptLanguage;
 
 #[derive(Debug)]
 pub struct Error(stry_common::prelude::Error);
@@ -35,25 +42,81 @@ impl std::error::Error for Error {
     }
 }
 
+/// A request that was rejected before reaching its handler, ie a body that
+/// was too large or was not JSON.
+#[derive(Debug)]
+pub struct Rejection {
+    pub status: StatusCode,
+    pub message: &'static str,
+}
+
+impl Rejection {
+    pub fn new(status: StatusCode, message: &'static str) -> Self {
+        Self { status, message }
+    }
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request rejected ({}): {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for Rejection {}
+
 impl axum::response::IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
         let err = self.0;
 
         error!(error = ?err, "error handling request");
 
-        let (status, message) = match err {
-            err if err.is::<stry_common::error::NotFound>() => {
-                (StatusCode::NOT_FOUND, "no resource found at this url")
-            }
-            err if err.is::<stry_common::error::Unauthenticated>() => (
-                StatusCode::UNAUTHORIZED,
-                "an account is required to access this resource",
-            ),
-            err if err.is::<stry_common::error::Unauthorized>() => (
-                StatusCode::FORBIDDEN,
-                "forbidden from accessing this resource",
-            ),
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal server error"),
+        let retry_after = err
+            .downcast_ref::<stry_common::error::TooManyRequests>()
+            .map(|err| err.retry_after);
+
+        let (status, message, fields) = if let Some(rejection) = err.downcast_ref::<Rejection>() {
+            (rejection.status, rejection.message, Vec::new())
+        } else if let Some(invalid) = err.downcast_ref::<Invalid>() {
+            (
+                StatusCode::BAD_REQUEST,
+                "request contains invalid fields",
+                invalid.fields.clone(),
+            )
+        } else if let Some(conflict) = err.downcast_ref::<Conflict>() {
+            (
+                StatusCode::CONFLICT,
+                "request conflicts with an existing resource",
+                conflict.fields.clone(),
+            )
+        } else {
+            let (status, message) = match err {
+                _ if retry_after.is_some() => (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "too many attempts, try again later",
+                ),
+                err if err.is::<stry_common::error::NotFound>() => {
+                    (StatusCode::NOT_FOUND, "no resource found at this url")
+                }
+                err if err.is::<stry_common::error::Unauthenticated>() => (
+                    StatusCode::UNAUTHORIZED,
+                    "an account is required to access this resource",
+                ),
+                err if err.is::<stry_common::error::Unauthorized>() => (
+                    StatusCode::FORBIDDEN,
+                    "forbidden from accessing this resource",
+                ),
+                err if err.is::<stry_common::error::RegistrationClosed>() => (
+                    StatusCode::FORBIDDEN,
+                    "registrations are closed",
+                ),
+                err if err.is::<stry_common::error::PreconditionFailed>() => (
+                    StatusCode::PRECONDITION_FAILED,
+                    "resource has changed since it was last fetched",
+                ),
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal server error"),
+            };
+
+            (status, message, Vec::new())
         };
 
         let body = ErrorResponse {
@@ -61,9 +124,125 @@ impl axum::response::IntoResponse for Error {
                 code: status.as_u16(),
                 status: status.canonical_reason(),
                 message,
+                fields,
             },
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(&body)).into_response();
+
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+
+        if !body.error.fields.is_empty() {
+            response.extensions_mut().insert(Fields(body));
+        }
+
+        response
+    }
+}
+
+/// The body of an error response with field errors, kept so [`localize`] can
+/// replace their messages.
+struct Fields(ErrorResponse<StatusCodeErrorResponse>);
+
+/// Replaces the messages of an error response's fields with ones in the
+/// client's `Accept-Language`, see [`field_error`].
+pub async fn localize<B>(req: Request<B>, next: Next<B>) -> Response {
+    let i18n = req
+        .extensions()
+        .get::<FluentResources>()
+        .map(|resources| resources.negotiate(&AcceptLanguage::from_headers(req.headers()).languages));
+
+    let mut response = next.run(req).await;
+
+    let (i18n, Fields(mut body)) = match (i18n, response.extensions_mut().remove::<Fields>()) {
+        (Some(i18n), Some(fields)) => (i18n, fields),
+        _ => return response,
+    };
+
+    for field in &mut body.error.fields {
+        field.message = field_error(&i18n, field);
+    }
+
+    let (parts, _) = response.into_parts();
+
+    (parts, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use stry_common::error::FieldError;
+
+    use axum::{
+        body::{Body, HttpBody as _},
+        http::header,
+        middleware,
+        routing::get,
+        Extension, Router,
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_localize_field_errors() {
+        let resources = FluentResources::builder()
+            .add_dir(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../stry-service-html/localization"
+            ))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async {
+                    let mut params = BTreeMap::new();
+
+                    params.insert(String::from("min"), serde_json::json!(8));
+                    params.insert(String::from("max"), serde_json::json!(1024));
+
+                    Error::from_any(Invalid {
+                        fields: vec![FieldError::new("password", "length", params)],
+                    })
+                }),
+            )
+            .layer(middleware::from_fn(localize))
+            .layer(Extension(resources));
+
+        let request = Request::builder()
+            .uri("/")
+            .header(header::ACCEPT_LANGUAGE, "en-US")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        // The default message doesn't group digits, the English bundle does
+        assert_eq!(
+            "must be between 8 and 1,024 characters long",
+            body["error"]["fields"][0]["message"]
+                .as_str()
+                .unwrap()
+                .replace(['\u{2068}', '\u{2069}'], "")
+        );
     }
 }
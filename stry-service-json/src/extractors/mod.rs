@@ -1 +1,404 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::SystemTime,
+};
 
+use stry_common::{
+    auth,
+    config::ArcConfig,
+    error::{FieldError, Invalid, PreconditionFailed},
+    http::client_ip,
+    models::{Existing, Id},
+    prelude::Validate,
+};
+
+use axum::{
+    body::{Bytes, HttpBody},
+    extract::{
+        rejection::ContentLengthLimitRejection, ConnectInfo, ContentLengthLimit, FromRequest,
+        RequestParts,
+    },
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError, Json,
+};
+use headers::{
+    authorization::Bearer, Authorization, ETag, HeaderMapExt, IfMatch, IfModifiedSince,
+    IfNoneMatch, IfUnmodifiedSince, LastModified,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{Error, Rejection};
+
+/// A JSON request body of at most `N` bytes, that has been deserialized and validated.
+///
+/// Unlike [`axum::Json`] every failure is returned in the standard error
+/// envelope, with the path to the field that caused it where possible.
+pub struct JsonBody<T, const N: u64>(pub T);
+
+#[axum::async_trait]
+impl<T, B, const N: u64> FromRequest<B> for JsonBody<T, N>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.split(';').next())
+            .map(|mime| {
+                let mime = mime.trim();
+
+                mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+            })
+            .unwrap_or(false);
+
+        if !is_json {
+            return Err(Error::from_any(Rejection::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "expected a request with `Content-Type: application/json`",
+            )));
+        }
+
+        let RawBody(bytes) = RawBody::<N>::from_request(req).await?;
+
+        let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
+
+        let value: T = serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            let path = err.path().to_string();
+            let inner = err.into_inner();
+
+            if !inner.is_data() {
+                return Error::from_any(Rejection::new(
+                    StatusCode::BAD_REQUEST,
+                    "request body is not valid json",
+                ));
+            }
+
+            Error::from_any(invalid_data(path, inner))
+        })?;
+
+        value
+            .validate()
+            .map_err(|err| Error::from_any(Invalid::from(err)))?;
+
+        Ok(Self(value))
+    }
+}
+
+/// A request body of at most `N` bytes, as is.
+///
+/// Like [`JsonBody`] failures are returned in the standard error envelope.
+pub struct RawBody<const N: u64>(pub Bytes);
+
+#[axum::async_trait]
+impl<B, const N: u64> FromRequest<B> for RawBody<N>
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let ContentLengthLimit(bytes) = ContentLengthLimit::<Bytes, N>::from_request(req)
+            .await
+            .map_err(|rejection| match rejection {
+                ContentLengthLimitRejection::PayloadTooLarge(_) => {
+                    Rejection::new(StatusCode::PAYLOAD_TOO_LARGE, "request body is too large")
+                }
+                ContentLengthLimitRejection::LengthRequired(_) => Rejection::new(
+                    StatusCode::LENGTH_REQUIRED,
+                    "request is missing a `Content-Length`",
+                ),
+                _ => Rejection::new(StatusCode::BAD_REQUEST, "unable to read request body"),
+            })
+            .map_err(Error::from_any)?;
+
+        Ok(Self(bytes))
+    }
+}
+
+/// Turns a serde data error into a field error.
+///
+/// Missing fields are reported by serde on their parent, so the name is moved
+/// into the path.
+fn invalid_data(path: String, err: serde_json::Error) -> Invalid {
+    let message = err.to_string();
+
+    let missing = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next());
+
+    let field = match missing {
+        Some(name) if path == "." => FieldError::new(name, "required", Default::default()),
+        Some(name) => FieldError::new(format!("{}.{}", path, name), "required", Default::default()),
+        None => FieldError::new(path, "type", Default::default()),
+    };
+
+    Invalid {
+        fields: vec![field],
+    }
+}
+
+/// The ip address of the client making the request, see [`client_ip`].
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for ClientIp
+where
+    B: Send,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .ok_or((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "missing connection information",
+            ))?;
+
+        Ok(Self(client_ip(peer, req.headers())))
+    }
+}
+
+/// The id of the user the `Authorization: Bearer` session token was issued to.
+///
+/// # Note
+///
+/// Is `None` when no token was sent, an invalid or expired token is rejected
+/// so clients know to sign in again.
+#[derive(Clone, Copy, Debug)]
+pub struct Session(pub Option<Id>);
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Session
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let authorization = match req.headers().typed_get::<Authorization<Bearer>>() {
+            Some(authorization) => authorization,
+            None => return Ok(Self(None)),
+        };
+
+        let config = req
+            .extensions()
+            .get::<ArcConfig>()
+            .cloned()
+            .ok_or_else(|| {
+                Error::from_any(stry_common::prelude::err!("missing config extension"))
+            })?;
+
+        let id =
+            auth::verify_token(&config.secret, authorization.token()).map_err(Error::from_any)?;
+
+        Ok(Self(Some(id)))
+    }
+}
+
+/// The conditional request headers, used to skip sending unchanged entities
+/// and to stop updates from overwriting changes the client hasn't seen.
+///
+/// # Note
+///
+/// Entity tags are made from the entity's id and `updated` time, so they
+/// change whenever the entity itself is updated.
+#[derive(Debug, Default)]
+pub struct Preconditions {
+    if_match: Option<IfMatch>,
+    if_unmodified_since: Option<IfUnmodifiedSince>,
+    if_none_match: Option<IfNoneMatch>,
+    if_modified_since: Option<IfModifiedSince>,
+}
+
+impl Preconditions {
+    /// Responds with the `entity` or, if the client's copy is still current,
+    /// an empty `304 Not Modified`.
+    pub fn respond<T: Serialize>(&self, entity: Existing<T>) -> Response {
+        let etag = etag(&entity);
+        let modified = SystemTime::from(entity.updated);
+
+        // `If-Modified-Since` is ignored when `If-None-Match` is present
+        let fresh = match (&self.if_none_match, &self.if_modified_since) {
+            (Some(if_none_match), _) => !if_none_match.precondition_passes(&etag),
+            (None, Some(if_modified_since)) => !if_modified_since.is_modified(modified),
+            (None, None) => false,
+        };
+
+        let mut response = if fresh {
+            StatusCode::NOT_MODIFIED.into_response()
+        } else {
+            Json(entity).into_response()
+        };
+
+        let headers = response.headers_mut();
+
+        headers.typed_insert(etag);
+        headers.typed_insert(LastModified::from(modified));
+
+        response
+    }
+
+    /// Errors with [`PreconditionFailed`] if the client sent an `If-Match`
+    /// that doesn't match the `entity`'s current version, or an
+    /// `If-Unmodified-Since` from before it was last updated.
+    pub fn check<T>(&self, entity: &Existing<T>) -> Result<(), Error> {
+        // `If-Unmodified-Since` is ignored when `If-Match` is present
+        let passes = match (&self.if_match, &self.if_unmodified_since) {
+            (Some(if_match), _) => if_match.precondition_passes(&etag(entity)),
+            (None, Some(if_unmodified_since)) => {
+                if_unmodified_since.precondition_passes(SystemTime::from(entity.updated))
+            }
+            (None, None) => true,
+        };
+
+        if passes {
+            Ok(())
+        } else {
+            Err(Error::from_any(PreconditionFailed))
+        }
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for Preconditions
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let headers = req.headers();
+
+        // Malformed headers are ignored like they were never sent
+        Ok(Self {
+            if_match: headers.typed_get(),
+            if_unmodified_since: headers.typed_get(),
+            if_none_match: headers.typed_get(),
+            if_modified_since: headers.typed_get(),
+        })
+    }
+}
+
+fn etag<T>(entity: &Existing<T>) -> ETag {
+    format!(
+        "\"{}-{:x}\"",
+        entity.id.as_str(),
+        entity.updated.unix_timestamp_nanos()
+    )
+    .parse()
+    .expect("entity tags are always valid")
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use stry_common::prelude::{OffsetDateTime, TryFrom};
+
+    use axum::http::Request;
+
+    use super::*;
+
+    fn entity() -> Existing<()> {
+        // Whole seconds so it survives being sent as an HTTP date
+        let updated = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+
+        Existing::new(Id::try_from("aaaaaaaa").unwrap(), (), updated, updated)
+    }
+
+    /// The value of the `header` as it's sent.
+    fn value<H: headers::Header>(header: H) -> String {
+        let mut headers = axum::http::HeaderMap::new();
+
+        headers.typed_insert(header);
+
+        headers
+            .values()
+            .next()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn preconditions(headers: &[(header::HeaderName, String)]) -> Preconditions {
+        let mut request = Request::builder();
+
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let mut parts = RequestParts::new(request.body(()).unwrap());
+
+        Preconditions::from_request(&mut parts).await.unwrap()
+    }
+
+    fn is_precondition_failed(result: Result<(), Error>) -> bool {
+        result.unwrap_err().into_response().status() == StatusCode::PRECONDITION_FAILED
+    }
+
+    #[tokio::test]
+    async fn test_if_none_match() {
+        let etag = value(etag(&entity()));
+
+        let response = preconditions(&[(header::IF_NONE_MATCH, etag)])
+            .await
+            .respond(entity());
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = preconditions(&[(header::IF_NONE_MATCH, String::from("\"stale\""))])
+            .await
+            .respond(entity());
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_if_match() {
+        let entity = entity();
+        let etag = value(etag(&entity));
+
+        let current = preconditions(&[(header::IF_MATCH, etag)]).await;
+
+        assert!(current.check(&entity).is_ok());
+
+        let stale = preconditions(&[(header::IF_MATCH, String::from("\"stale\""))]).await;
+
+        assert!(is_precondition_failed(stale.check(&entity)));
+    }
+
+    #[tokio::test]
+    async fn test_if_unmodified_since() {
+        let entity = entity();
+        let updated = SystemTime::from(entity.updated);
+
+        let date = |time: SystemTime| value(LastModified::from(time));
+
+        let current = preconditions(&[(header::IF_UNMODIFIED_SINCE, date(updated))]).await;
+
+        assert!(current.check(&entity).is_ok());
+
+        let stale = preconditions(&[(
+            header::IF_UNMODIFIED_SINCE,
+            date(updated - Duration::from_secs(60)),
+        )])
+        .await;
+
+        assert!(is_precondition_failed(stale.check(&entity)));
+    }
+}
@@ -0,0 +1,196 @@
+use stry_common::models::{self, Existing};
+
+use async_graphql::{Context, Object, Result, Union, ID};
+
+use super::{timestamp, Loaders};
+
+/// A user's public profile.
+///
+/// # Note
+///
+/// Account details (ie email) are never exposed here.
+pub struct User(pub Existing<models::core::User>);
+
+#[Object]
+impl User {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.account.name
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn biography(&self) -> Vec<Part> {
+        self.0
+            .account
+            .biography
+            .as_deref()
+            .map(Part::from_existing)
+            .unwrap_or_default()
+    }
+
+    async fn created(&self) -> Result<String> {
+        timestamp(self.0.created)
+    }
+}
+
+pub struct Tag(pub Existing<models::core::Tag>);
+
+#[Object]
+impl Tag {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+}
+
+pub struct Comment(pub Existing<models::core::Comment>);
+
+#[Object]
+impl Comment {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn author(&self, ctx: &Context<'_>) -> Result<User> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(User(loaders.user.load(self.0.author.id).await?))
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn main(&self) -> Vec<Part> {
+        Part::from_existing(&self.0.main)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn children(&self) -> Vec<Comment> {
+        self.0.children.iter().cloned().map(Comment).collect()
+    }
+
+    async fn created(&self) -> Result<String> {
+        timestamp(self.0.created)
+    }
+
+    async fn updated(&self) -> Result<String> {
+        timestamp(self.0.updated)
+    }
+}
+
+#[derive(Union)]
+pub enum Part {
+    Heading(PartHeading),
+    Image(PartImage),
+    Text(PartText),
+}
+
+impl Part {
+    pub fn from_existing(parts: &[Existing<models::core::Part>]) -> Vec<Self> {
+        parts
+            .iter()
+            .map(|part| match &**part {
+                models::core::Part::Heading(heading) => Part::Heading(PartHeading {
+                    id: part.id,
+                    inner: heading.clone(),
+                }),
+                models::core::Part::Image(image) => Part::Image(PartImage {
+                    id: part.id,
+                    inner: image.clone(),
+                }),
+                models::core::Part::Text(text) => Part::Text(PartText {
+                    id: part.id,
+                    inner: text.clone(),
+                }),
+            })
+            .collect()
+    }
+}
+
+pub struct PartHeading {
+    id: models::Id,
+    inner: models::core::PartHeading,
+}
+
+#[Object]
+impl PartHeading {
+    async fn id(&self) -> ID {
+        ID(self.id.as_str().to_string())
+    }
+
+    async fn level(&self) -> u8 {
+        self.inner.level
+    }
+
+    async fn content(&self) -> &str {
+        &self.inner.content
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn comments(&self) -> Vec<Comment> {
+        self.inner.comments.iter().cloned().map(Comment).collect()
+    }
+}
+
+pub struct PartImage {
+    id: models::Id,
+    inner: models::core::PartImage,
+}
+
+#[Object]
+impl PartImage {
+    async fn id(&self) -> ID {
+        ID(self.id.as_str().to_string())
+    }
+
+    /// The uploaded media the image is of, if it isn't hosted elsewhere.
+    async fn media(&self) -> Option<ID> {
+        self.inner.media.map(|id| ID(id.as_str().to_string()))
+    }
+
+    async fn url(&self) -> &str {
+        &self.inner.url
+    }
+
+    async fn alt(&self) -> Option<&str> {
+        self.inner.alt.as_deref()
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn comments(&self) -> Vec<Comment> {
+        self.inner.comments.iter().cloned().map(Comment).collect()
+    }
+}
+
+pub struct PartText {
+    id: models::Id,
+    inner: models::core::PartText,
+}
+
+#[Object]
+impl PartText {
+    async fn id(&self) -> ID {
+        ID(self.id.as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        &self.inner.content
+    }
+
+    async fn words(&self) -> i64 {
+        self.inner.words
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn comments(&self) -> Vec<Comment> {
+        self.inner.comments.iter().cloned().map(Comment).collect()
+    }
+}
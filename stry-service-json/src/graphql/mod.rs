@@ -0,0 +1,280 @@
+//! A read only GraphQL api over the stories and everything connected to them.
+//!
+//! Every nested entity is resolved through a set of per request [`Batcher`]s
+//! so fetching a story page (its authors, tags, chapters and comments) only
+//! costs one round-trip for the client and one lookup per entity.
+
+mod core;
+mod story;
+
+use stry_common::{
+    backend::{ArcBackend, StoryEntity},
+    dataloader::{Batcher, Fetcher, LoadError},
+    loader::{
+        core::{TagLoader, UserLoader},
+        story::{
+            ChapterLoader, CharacterLoader, OriginLoader, PairingLoader, SeriesLoader, StoryLoader,
+            WarningLoader,
+        },
+    },
+    models::{Existing, Id},
+    prelude::{OffsetDateTime, TryFrom},
+};
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, ID};
+use axum::{
+    extract::{ContentLengthLimit, Extension, Json},
+    response::IntoResponse,
+    routing::post,
+    Router,
+};
+use time::format_description::well_known::Rfc3339;
+
+use self::{
+    core::{Tag, User},
+    story::{Chapter, Series, Story},
+};
+
+/// The deepest a query can nest fields.
+const MAX_DEPTH: usize = 12;
+
+/// The most fields a query can resolve, lists count their items or
+/// [`LIST_ITEMS`] when nested.
+const MAX_COMPLEXITY: usize = 2048;
+
+/// How many items a nested list is counted as, only the top level lists know
+/// how many they return.
+const LIST_ITEMS: usize = 10;
+
+type Schema = async_graphql::Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/graphql", post(graphql))
+        .layer(Extension(schema()))
+}
+
+fn schema() -> Schema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+async fn graphql(
+    Extension(schema): Extension<Schema>,
+    Extension(data): Extension<ArcBackend>,
+    ContentLengthLimit(Json(request)): ContentLengthLimit<
+        Json<async_graphql::Request>,
+        { 1024 * 5000 },
+    >,
+) -> impl IntoResponse {
+    // The batchers cache indefinitely so they can't outlive the request
+    let loaders = Loaders::new(data.clone());
+
+    Json(schema.execute(request.data(data).data(loaders)).await)
+}
+
+/// The batchers used to resolve nested entities, one set per request.
+pub struct Loaders {
+    pub user: Batcher<UserLoader<ArcBackend>>,
+    pub tag: Batcher<TagLoader<ArcBackend>>,
+
+    pub origin: Batcher<OriginLoader<ArcBackend>>,
+    pub warning: Batcher<WarningLoader<ArcBackend>>,
+    pub pairing: Batcher<PairingLoader<ArcBackend>>,
+    pub character: Batcher<CharacterLoader<ArcBackend>>,
+
+    pub chapter: Batcher<ChapterLoader<ArcBackend>>,
+    pub story: Batcher<StoryLoader<ArcBackend>>,
+    pub series: Batcher<SeriesLoader<ArcBackend>>,
+}
+
+impl Loaders {
+    pub fn new(backend: ArcBackend) -> Self {
+        Self {
+            user: UserLoader::new(backend.clone()),
+            tag: TagLoader::new(backend.clone()),
+
+            origin: OriginLoader::new(backend.clone()),
+            warning: WarningLoader::new(backend.clone()),
+            pairing: PairingLoader::new(backend.clone()),
+            character: CharacterLoader::new(backend.clone()),
+
+            chapter: ChapterLoader::new(backend.clone()),
+            story: StoryLoader::new(backend.clone()),
+            series: SeriesLoader::new(backend),
+        }
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    async fn story(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Story>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(optional(loaders.story.load(parse_id(&id)?).await)?.map(Story))
+    }
+
+    /// A page of stories, newest first.
+    #[graphql(complexity = "list(limit.max(0) as usize, child_complexity)")]
+    async fn stories(
+        &self,
+        ctx: &Context<'_>,
+        cursor: Option<ID>,
+        #[graphql(default = 10, validator(minimum = 1, maximum = 100))] limit: i64,
+    ) -> Result<Vec<Story>> {
+        let data = ctx.data_unchecked::<ArcBackend>();
+
+        let cursor = cursor.as_ref().map(parse_id).transpose()?;
+
+        Ok(StoryEntity::all(data, cursor, limit)
+            .await?
+            .into_iter()
+            .map(Story)
+            .collect())
+    }
+
+    async fn chapter(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Chapter>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(optional(loaders.chapter.load(parse_id(&id)?).await)?.map(Chapter))
+    }
+
+    async fn series(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Series>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(optional(loaders.series.load(parse_id(&id)?).await)?.map(Series))
+    }
+
+    async fn tag(&self, ctx: &Context<'_>, id: ID) -> Result<Option<Tag>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(optional(loaders.tag.load(parse_id(&id)?).await)?.map(Tag))
+    }
+
+    async fn user(&self, ctx: &Context<'_>, id: ID) -> Result<Option<User>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        Ok(optional(loaders.user.load(parse_id(&id)?).await)?.map(User))
+    }
+}
+
+fn parse_id(id: &ID) -> Result<Id> {
+    Ok(Id::try_from(id.as_str())?)
+}
+
+/// Turns a batcher's not found error into `None`.
+fn optional<T>(result: Result<T, LoadError>) -> Result<Option<T>, LoadError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(LoadError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Loads the current version of every entity through the `batcher`.
+async fn load_many<F, T>(batcher: &Batcher<F>, entities: &[Existing<T>]) -> Result<Vec<F::Value>>
+where
+    F: Fetcher<Key = Id> + Send + Sync + 'static,
+{
+    let ids = entities.iter().map(|entity| entity.id).collect::<Vec<_>>();
+
+    Ok(batcher.load_many(&ids).await?)
+}
+
+fn timestamp(time: OffsetDateTime) -> Result<String> {
+    Ok(time.format(&Rfc3339)?)
+}
+
+/// The complexity of a list of `items`, capped just past the limit so deeply
+/// nested lists can't overflow it.
+fn list(items: usize, child_complexity: usize) -> usize {
+    items
+        .saturating_mul(child_complexity)
+        .min(MAX_COMPLEXITY + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use stry_backend_postgres::testing::Drafts;
+
+    use super::*;
+
+    /// Runs the query, returning its error messages.
+    ///
+    /// There isn't a backend so only queries that fail validation can be run.
+    async fn errors(query: String) -> Vec<String> {
+        let response = schema().execute(query).await;
+
+        response.errors.into_iter().map(|err| err.message).collect()
+    }
+
+    #[tokio::test]
+    async fn test_depth_limit() {
+        let mut fields = String::from("id");
+
+        // Lists would make it too complex first, so only single stories nest
+        for _ in 0..MAX_DEPTH {
+            fields = format!("original {{ {} }}", fields);
+        }
+
+        let errors = errors(format!("{{ story(id: \"aaaaaaaa\") {{ {} }} }}", fields)).await;
+
+        assert_eq!(errors, ["Query is nested too deep."]);
+    }
+
+    #[tokio::test]
+    async fn test_complexity_limit() {
+        // Each story costs 128 fields with its lists, so a full page is too much
+        let page = errors(String::from(
+            "{ stories(limit: 100) { id name summary rating state words created updated \
+                authors { id name created } commissioners { id name created } \
+                dedicatees { id name created } tags { id content description } } }",
+        ))
+        .await;
+
+        assert_eq!(page, ["Query is too complex."]);
+
+        // A single story is too, once its nested lists multiply
+        let nested = errors(String::from(
+            "{ story(id: \"aaaaaaaa\") { chapters { id comments { id children { id author { id name } } } } } }",
+        ))
+        .await;
+
+        assert_eq!(nested, ["Query is too complex."]);
+    }
+
+    #[tokio::test]
+    async fn test_readers_never_see_drafts() {
+        let drafts = Drafts::new().await;
+
+        let query = format!(
+            r#"{{ story(id: "{}") {{ chapters {{ id name }} }} unpublished: chapter(id: "{}") {{ id }} }}"#,
+            drafts.story.as_str(),
+            drafts.unpublished.as_str(),
+        );
+
+        let request = async_graphql::Request::new(query)
+            .data(drafts.data.clone())
+            .data(Loaders::new(drafts.data.clone()));
+
+        let response = schema().execute(request).await;
+        let published = drafts.published.as_str().to_string();
+
+        drafts.remove().await;
+
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().unwrap();
+
+        // The unpublished chapter is left out, the other is shown without its draft
+        assert_eq!(
+            data["story"]["chapters"],
+            serde_json::json!([ { "id": published, "name": "Published" } ])
+        );
+        assert!(data["unpublished"].is_null());
+    }
+}
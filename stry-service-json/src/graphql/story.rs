@@ -0,0 +1,438 @@
+use stry_common::{
+    backend::{ArcBackend, StoryEntity},
+    dataloader::LoadError,
+    models::{self, Either, Existing},
+};
+
+use async_graphql::{Context, Enum, Object, Result, ID};
+
+use super::{
+    core::{Comment, Part, Tag, User},
+    load_many, optional, timestamp, Loaders,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "models::story::Rating")]
+pub enum Rating {
+    Explicit,
+    Mature,
+    Teen,
+    General,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "models::story::State")]
+pub enum State {
+    Completed,
+    InProgress,
+    Hiatus,
+    Abandoned,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "models::story::TagLevel")]
+pub enum TagLevel {
+    Major,
+    Minor,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(remote = "models::story::Relationship")]
+pub enum Relationship {
+    Family,
+    Friends,
+    Romantic,
+}
+
+pub struct Story(pub Existing<models::story::Story>);
+
+#[Object]
+impl Story {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn summary(&self) -> &str {
+        &self.0.summary
+    }
+
+    /// A BCP-47 language tag, ie `en` or `pt-BR`.
+    async fn language(&self) -> &str {
+        &self.0.language
+    }
+
+    /// Is `null` for original works.
+    async fn original(&self, ctx: &Context<'_>) -> Result<Option<Story>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let original = match self.0.original {
+            Some(original) => original,
+            None => return Ok(None),
+        };
+
+        Ok(optional(loaders.story.load(original).await)?.map(Story))
+    }
+
+    /// The other language versions of this story, its original included.
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn translations(&self, ctx: &Context<'_>) -> Result<Vec<Story>> {
+        let data = ctx.data_unchecked::<ArcBackend>();
+
+        Ok(StoryEntity::translations(data, self.0.id)
+            .await?
+            .into_iter()
+            .map(Story)
+            .collect())
+    }
+
+    async fn rating(&self) -> Rating {
+        self.0.rating.into()
+    }
+
+    async fn state(&self) -> State {
+        self.0.state.into()
+    }
+
+    async fn words(&self) -> i32 {
+        self.0.words
+    }
+
+    /// The estimated reading time, in minutes.
+    async fn reading_time(&self) -> i64 {
+        self.0.reading_time().whole_minutes()
+    }
+
+    async fn created(&self) -> Result<String> {
+        timestamp(self.0.created)
+    }
+
+    async fn updated(&self) -> Result<String> {
+        timestamp(self.0.updated)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        users(ctx, &self.0.authors).await
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn commissioners(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        users(ctx, &self.0.commissioners).await
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn dedicatees(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        users(ctx, &self.0.dedicatees).await
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn translators(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        users(ctx, &self.0.translators).await
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn origins(&self, ctx: &Context<'_>) -> Result<Vec<Origin>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let mut origins = load_many(&loaders.origin, &self.0.origins).await?;
+
+        // The level is specific to this story, not the origin itself
+        for (origin, story) in origins.iter_mut().zip(&self.0.origins) {
+            origin.level = story.level;
+        }
+
+        Ok(origins.into_iter().map(Origin).collect())
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn warnings(&self, ctx: &Context<'_>) -> Result<Vec<Warning>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let mut warnings = load_many(&loaders.warning, &self.0.warnings).await?;
+
+        for (warning, story) in warnings.iter_mut().zip(&self.0.warnings) {
+            warning.level = story.level;
+        }
+
+        Ok(warnings.into_iter().map(Warning).collect())
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn pairings(&self, ctx: &Context<'_>) -> Result<Vec<Pairing>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let mut pairings = load_many(&loaders.pairing, &self.0.pairings).await?;
+
+        for (pairing, story) in pairings.iter_mut().zip(&self.0.pairings) {
+            pairing.level = story.level;
+        }
+
+        Ok(pairings.into_iter().map(Pairing).collect())
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn characters(&self, ctx: &Context<'_>) -> Result<Vec<Character>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let mut characters = load_many(&loaders.character, &self.0.characters).await?;
+
+        for (character, story) in characters.iter_mut().zip(&self.0.characters) {
+            character.level = story.level;
+        }
+
+        Ok(characters.into_iter().map(Character).collect())
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let tags = load_many(&loaders.tag, &self.0.tags).await?;
+
+        Ok(tags.into_iter().map(Tag).collect())
+    }
+
+    async fn series(&self, ctx: &Context<'_>) -> Result<Option<Series>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let series = match &self.0.series {
+            Some(series) => series,
+            None => return Ok(None),
+        };
+
+        Ok(optional(loaders.series.load(series.id).await)?.map(Series))
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn chapters(&self, ctx: &Context<'_>) -> Result<Vec<Chapter>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let ids = match &self.0.chapters {
+            Some(Either::Left(chapters)) => chapters.iter().map(|chapter| chapter.id).collect(),
+            Some(Either::Right(ids)) => ids.clone(),
+            None => Vec::new(),
+        };
+
+        // Every chapter is looked up at once, the ones readers can't see are
+        // cached as not found so they're left out without another lookup
+        match loaders.chapter.load_many(&ids).await {
+            Ok(chapters) => return Ok(chapters.into_iter().map(Chapter).collect()),
+            Err(LoadError::NotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        let mut chapters = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            if let Some(chapter) = optional(loaders.chapter.load(id).await)? {
+                chapters.push(Chapter(chapter));
+            }
+        }
+
+        Ok(chapters)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn comments(&self) -> Vec<Comment> {
+        self.0.comments.iter().cloned().map(Comment).collect()
+    }
+}
+
+async fn users(ctx: &Context<'_>, users: &[Existing<models::core::User>]) -> Result<Vec<User>> {
+    let loaders = ctx.data_unchecked::<Loaders>();
+
+    let users = load_many(&loaders.user, users).await?;
+
+    Ok(users.into_iter().map(User).collect())
+}
+
+pub struct Chapter(pub Existing<models::story::Chapter>);
+
+#[Object]
+impl Chapter {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    /// Is `null` when the chapter should use its default title.
+    async fn name(&self) -> Option<&str> {
+        self.0.name.as_deref()
+    }
+
+    async fn published(&self) -> bool {
+        self.0.published
+    }
+
+    async fn words(&self) -> i64 {
+        self.0.words
+    }
+
+    /// The estimated reading time, in minutes.
+    async fn reading_time(&self) -> i64 {
+        self.0.reading_time().whole_minutes()
+    }
+
+    async fn created(&self) -> Result<String> {
+        timestamp(self.0.created)
+    }
+
+    async fn updated(&self) -> Result<String> {
+        timestamp(self.0.updated)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn prefix(&self) -> Vec<Part> {
+        Part::from_existing(&self.0.prefix)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn main(&self) -> Vec<Part> {
+        Part::from_existing(&self.0.main)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn suffix(&self) -> Vec<Part> {
+        Part::from_existing(&self.0.suffix)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn comments(&self) -> Vec<Comment> {
+        self.0.comments.iter().cloned().map(Comment).collect()
+    }
+}
+
+pub struct Series(pub Existing<models::story::Series>);
+
+#[Object]
+impl Series {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn summary(&self) -> &str {
+        &self.0.summary
+    }
+
+    async fn state(&self) -> State {
+        self.0.state.into()
+    }
+
+    async fn created(&self) -> Result<String> {
+        timestamp(self.0.created)
+    }
+
+    async fn updated(&self) -> Result<String> {
+        timestamp(self.0.updated)
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn stories(&self, ctx: &Context<'_>) -> Result<Vec<Story>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let ids = match &self.0.stories {
+            Either::Left(stories) => stories.iter().map(|story| story.id).collect(),
+            Either::Right(ids) => ids.clone(),
+        };
+
+        let stories = loaders.story.load_many(&ids).await?;
+
+        Ok(stories.into_iter().map(Story).collect())
+    }
+}
+
+pub struct Origin(pub Existing<models::story::Origin>);
+
+#[Object]
+impl Origin {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn level(&self) -> TagLevel {
+        self.0.level.into()
+    }
+}
+
+pub struct Warning(pub Existing<models::story::Warning>);
+
+#[Object]
+impl Warning {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn level(&self) -> TagLevel {
+        self.0.level.into()
+    }
+}
+
+pub struct Character(pub Existing<models::story::Character>);
+
+#[Object]
+impl Character {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+
+    async fn description(&self) -> &str {
+        &self.0.description
+    }
+
+    async fn level(&self) -> TagLevel {
+        self.0.level.into()
+    }
+}
+
+pub struct Pairing(pub Existing<models::story::Pairing>);
+
+#[Object]
+impl Pairing {
+    async fn id(&self) -> ID {
+        ID(self.0.id.as_str().to_string())
+    }
+
+    async fn relationship(&self) -> Relationship {
+        self.0.relationship.into()
+    }
+
+    async fn level(&self) -> TagLevel {
+        self.0.level.into()
+    }
+
+    #[graphql(complexity = "super::list(super::LIST_ITEMS, child_complexity)")]
+    async fn characters(&self, ctx: &Context<'_>) -> Result<Vec<Character>> {
+        let loaders = ctx.data_unchecked::<Loaders>();
+
+        let characters = load_many(&loaders.character, &self.0.characters).await?;
+
+        Ok(characters.into_iter().map(Character).collect())
+    }
+}
@@ -1,13 +1,19 @@
-mod extractors;
-mod layers;
-mod provider;
-mod v1;
-
-mod error;
-mod utils;
-
-use axum::Router;
-
-pub fn routes() -> Router {
-    Router::new().nest("/v1", v1::router())
-}
+mod extractors;
+mod graphql;
+mod layers;
+mod provider;
+mod v1;
+
+mod error;
+mod utils;
+
+use axum::{middleware, Router};
+
+/// The JSON and GraphQL apis, field errors are translated with the
+/// [`stry_common::i18n::FluentResources`] extension.
+pub fn routes() -> Router {
+    Router::new()
+        .nest("/v1", v1::router())
+        .merge(graphql::router())
+        .layer(middleware::from_fn(error::localize))
+}
@@ -1,43 +1,385 @@
 use stry_common::{
-    backend::{ArcBackend, ChapterEntity},
-    config::ArcConfig,
-    models::{story::Chapter, Id, New},
+    backend::{ArcBackend, ChapterEntity, StoryEntity},
+    error::{Unauthenticated, Unauthorized},
+    html,
+    http::{DraftQuery, Format, FormatQuery, StoryQuery},
+    markdown, media,
+    models::{
+        story::{Chapter, ChapterDraft, ChapterMarkdown, ChapterOrder},
+        Existing, Id, New,
+    },
 };
 
 use axum::{
-    extract::{ContentLengthLimit, Extension, Json, Path, TypedHeader},
-    response::IntoResponse,
+    body::HttpBody,
+    extract::{rejection::QueryRejection, Extension, FromRequest, Json, Path, Query, RequestParts},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    BoxError,
 };
-use biscuit::{jwa::SignatureAlgorithm, jws::Secret, ValidationOptions, JWT};
-use headers::{authorization::Bearer, Authorization};
 
-use crate::error::Error;
+use crate::{
+    error::{Error, Rejection},
+    extractors::{JsonBody, Preconditions, Session},
+};
+
+/// A chapter's body, as its parts or as Markdown depending on the `format`
+/// query parameter.
+pub enum ChapterBody {
+    Parts(New<Chapter>),
+    Markdown(ChapterMarkdown),
+}
+
+impl ChapterBody {
+    /// Converts a Markdown body into parts, keeping the comments of any part
+    /// of the `current` chapter that hasn't changed.
+    ///
+    /// The text of parts is sanitized either way, see [`html::sanitize`], image
+    /// parts still need to be resolved with [`media::resolve`].
+    ///
+    /// Markdown can't hold a draft so the `current` chapter's is kept.
+    fn into_chapter(self, current: Option<&Chapter>) -> Result<New<Chapter>, Error> {
+        match self {
+            ChapterBody::Parts(mut chapter) => {
+                html::sanitize_chapter(&mut chapter);
+
+                Ok(chapter)
+            }
+            ChapterBody::Markdown(markdown) => {
+                let mut chapter =
+                    markdown::to_chapter(markdown, current).map_err(Error::from_any)?;
+
+                chapter.draft = current.and_then(|current| current.draft.clone());
 
+                Ok(New::from(chapter))
+            }
+        }
+    }
+}
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for ChapterBody
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        match ChapterFormat::from_request(req).await?.0 {
+            Format::Parts => {
+                let JsonBody(chapter) =
+                    JsonBody::<New<Chapter>, { 1024 * 5000 }>::from_request(req).await?;
+
+                Ok(ChapterBody::Parts(chapter))
+            }
+            Format::Markdown => {
+                let JsonBody(chapter) =
+                    JsonBody::<ChapterMarkdown, { 1024 * 5000 }>::from_request(req).await?;
+
+                Ok(ChapterBody::Markdown(chapter))
+            }
+        }
+    }
+}
+
+/// The `format` query parameter, defaulting to [`Format::Parts`].
+#[derive(Clone, Copy, Debug)]
+pub struct ChapterFormat(pub Format);
+
+#[axum::async_trait]
+impl<B> FromRequest<B> for ChapterFormat
+where
+    B: Send,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<FormatQuery>::from_request(req).await.map_err(|_| {
+            Error::from_any(Rejection::new(
+                StatusCode::BAD_REQUEST,
+                "`format` must be either `parts` or `markdown`",
+            ))
+        })?;
+
+        Ok(Self(query.format))
+    }
+}
+
+/// Gets a chapter, with `draft` its draft replaces it if the signed in user
+/// is one of the story's authors.
 pub async fn get(
     Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
     Path(id): Path<Id>,
+    ChapterFormat(format): ChapterFormat,
+    Query(query): Query<DraftQuery>,
 ) -> Result<impl IntoResponse, Error> {
-    Ok(Json(ChapterEntity::get(&data, id).await?))
+    let mut chapter = ChapterEntity::get(&data, id, session).await?;
+
+    // Only authors are given the draft, for anyone else there's nothing to apply
+    if query.draft {
+        chapter.apply_draft();
+    }
+
+    Ok(respond(&preconditions, format, chapter))
 }
 
+/// Adds a chapter to the end of a story.
 pub async fn create(
-    Extension(config): Extension<ArcConfig>,
     Extension(data): Extension<ArcBackend>,
-    TypedHeader(authorization): TypedHeader<Authorization<Bearer>>,
-    ContentLengthLimit(Json(chapter)): ContentLengthLimit<Json<New<Chapter>>, { 1024 * 5000 }>,
+    Session(session): Session,
+    Path(story): Path<Id>,
+    body: ChapterBody,
 ) -> Result<impl IntoResponse, Error> {
-    let token = JWT::<biscuit::Empty, biscuit::Empty>::new_encoded(authorization.0.token())
-        .into_decoded(
-            &Secret::bytes_from_str(&config.secret),
-            SignatureAlgorithm::HS256,
-        )
-        .map_err(Error::from_any)?;
+    let user = authorize(&data, story, session).await?;
+
+    let mut chapter = body.into_chapter(None)?;
+
+    media::resolve(&data, &mut chapter).await?;
+
+    Ok(Json(
+        ChapterEntity::create(&data, story, chapter, user).await?,
+    ))
+}
+
+/// Adds a chapter to the end of the story given by the `story` query
+/// parameter.
+///
+/// # Note
+///
+/// Deprecated in favour of [`create`], chapters used to be created on their
+/// own but now always belong to a story.
+pub async fn create_deprecated(
+    data: Extension<ArcBackend>,
+    session: Session,
+    query: Result<Query<StoryQuery>, QueryRejection>,
+    body: ChapterBody,
+) -> Result<impl IntoResponse, Error> {
+    let Query(query) = query.map_err(|_| {
+        Error::from_any(Rejection::new(
+            StatusCode::BAD_REQUEST,
+            "`story` is required, use `POST /stories/{id}/chapters` instead",
+        ))
+    })?;
+
+    create(data, session, Path(query.story), body).await
+}
+
+/// Replaces a chapter, with `draft` the body is kept as the chapter's draft
+/// (along with its schedule) instead.
+pub async fn update(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
+    Path(id): Path<Id>,
+    ChapterFormat(format): ChapterFormat,
+    Query(query): Query<DraftQuery>,
+    body: ChapterBody,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+    let user = authorize(&data, story, session).await?;
+
+    let current = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    preconditions.check(&current)?;
+
+    let mut chapter = body.into_chapter(Some(&current))?;
+
+    media::resolve(&data, &mut chapter).await?;
+
+    if query.draft {
+        let mut drafted = Chapter::clone(&current);
+
+        drafted.scheduled = chapter.scheduled;
+        drafted.draft = Some(ChapterDraft::from(Chapter::clone(&chapter)));
+
+        chapter = New::from(drafted);
+    }
+
+    ChapterEntity::update(&data, id, chapter, current.updated, user).await?;
+
+    let chapter = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    Ok(respond(&Preconditions::default(), format, chapter))
+}
+
+/// Publishes a chapter now, along with its draft.
+pub async fn publish(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
+    Path(id): Path<Id>,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+    let user = authorize(&data, story, session).await?;
+
+    let current = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    preconditions.check(&current)?;
+
+    let mut chapter = Chapter::clone(&current);
+
+    chapter.publish();
+
+    ChapterEntity::update(&data, id, New::from(chapter), current.updated, user).await?;
+
+    let chapter = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    Ok(Preconditions::default().respond(chapter))
+}
+
+/// Puts a story's chapters in the order given.
+pub async fn reorder(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path(story): Path<Id>,
+    JsonBody(order): JsonBody<ChapterOrder, { 1024 * 64 }>,
+) -> Result<impl IntoResponse, Error> {
+    authorize(&data, story, session).await?;
+
+    ChapterEntity::reorder(&data, story, order.chapters).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Checks that the signed in user is one of the story's authors, returning
+/// their id.
+pub async fn authorize(data: &ArcBackend, story: Id, session: Option<Id>) -> Result<Id, Error> {
+    let user = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
+
+    let story = StoryEntity::get(data, story).await?;
+
+    if story.authors.iter().any(|author| author.id == user) {
+        Ok(user)
+    } else {
+        Err(Error::from_any(Unauthorized))
+    }
+}
+
+/// The chapter in the requested format.
+fn respond(preconditions: &Preconditions, format: Format, chapter: Existing<Chapter>) -> Response {
+    match format {
+        Format::Parts => preconditions.respond(chapter),
+        Format::Markdown => preconditions.respond(Existing::new(
+            chapter.id,
+            markdown::from_chapter(&chapter),
+            chapter.created,
+            chapter.updated,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use stry_backend_postgres::testing::Drafts;
+    use stry_common::{
+        auth,
+        config::Config,
+        models::core::{Account, User},
+        prelude::OffsetDateTime,
+        utils::nanoid,
+    };
+
+    use axum::{
+        body::{Body, HttpBody as _},
+        http::{header, Request},
+        Router,
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    /// Gets the chapter as the holder of the `token`, returning its status
+    /// and body.
+    async fn fetch(
+        app: &Router,
+        uri: String,
+        token: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().uri(uri);
+
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_readers_never_see_drafts() {
+        let drafts = Drafts::new().await;
+
+        let config: Config = serde_json::from_str(r#"{ "secret": "secret" }"#).unwrap();
+
+        let app = super::super::router()
+            .layer(Extension(drafts.data.clone()))
+            .layer(Extension(Arc::new(config.clone())));
+
+        let reader = Existing::new(
+            nanoid::new_id().unwrap(),
+            User::new(Account {
+                name: String::from("Reader"),
+                email: None,
+                hash: None,
+                biography: None,
+            }),
+            OffsetDateTime::now_utc(),
+            OffsetDateTime::now_utc(),
+        );
+
+        let author = auth::issue_token(&config.secret, &drafts.author).unwrap();
+        let reader = auth::issue_token(&config.secret, &reader).unwrap();
+
+        let published = format!("/chapters/{}?draft=true", drafts.published.as_str());
+        let unpublished = format!("/chapters/{}", drafts.unpublished.as_str());
+
+        let mut responses = Vec::new();
+
+        for token in [None, Some(reader.as_str()), Some(author.as_str())] {
+            responses.push((
+                fetch(&app, published.clone(), token).await,
+                fetch(&app, unpublished.clone(), token).await,
+            ));
+        }
+
+        drafts.remove().await;
+
+        // Signed out and signed in readers see the same thing
+        for ((status, chapter), (unpublished, _)) in &responses[..2] {
+            assert_eq!(*status, StatusCode::OK);
+            assert_eq!(chapter["name"], "Published");
+            assert!(chapter["scheduled"].is_null());
+            assert!(chapter["draft"].is_null());
+
+            assert_eq!(*unpublished, StatusCode::NOT_FOUND);
+        }
 
-    token
-        .validate(ValidationOptions::default())
-        .map_err(Error::from_any)?;
+        let ((status, chapter), (unpublished, _)) = &responses[2];
 
-    // TODO(txuritan): validate that the token is in the database and to retrieve the user id
+        assert_eq!(*status, StatusCode::OK);
+        assert_eq!(chapter["name"], "Draft");
+        assert!(!chapter["scheduled"].is_null());
 
-    Ok(Json(ChapterEntity::create(&data, chapter).await?))
+        assert_eq!(*unpublished, StatusCode::OK);
+    }
 }
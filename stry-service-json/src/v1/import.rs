@@ -0,0 +1,40 @@
+use stry_common::{backend::ArcBackend, error::Unauthenticated, import};
+
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    error::{Error, Rejection},
+    extractors::{RawBody, Session},
+};
+
+/// Imports an AO3 HTML or EPUB download as a new story by the signed in user.
+pub async fn create(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    RawBody(body): RawBody<{ 1024 * 1024 * 20 }>,
+) -> Result<impl IntoResponse, Error> {
+    let author = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
+
+    let work = tokio::task::spawn_blocking(move || import::read(&body))
+        .await
+        .map_err(Error::from_any)?
+        .map_err(|err| {
+            stry_common::prelude::debug!(error = ?err, "unable to read import");
+
+            Error::from_any(Rejection::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "unable to read the work, expected an AO3 HTML or EPUB download",
+            ))
+        })?;
+
+    let import = import::resolve(&data, work, author).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(import::save(&data, import).await?),
+    ))
+}
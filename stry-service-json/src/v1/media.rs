@@ -0,0 +1,65 @@
+use stry_common::{
+    backend::ArcBackend,
+    error::Unauthenticated,
+    media::{self, MediaStorage, Rejected, Uploaded},
+    settings::Settings,
+};
+
+use axum::{
+    extract::{Extension, Json},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::{
+    error::{Error, Rejection},
+    extractors::{RawBody, Session},
+};
+
+/// Uploads an image for the signed in user, to be used in image parts.
+///
+/// # Note
+///
+/// The body is limited to [`media::MAX_SIZE`], the site's own limit is
+/// checked after it has been read.
+pub async fn create(
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Extension(storage): Extension<MediaStorage>,
+    Session(session): Session,
+    RawBody(body): RawBody<{ media::MAX_SIZE as u64 }>,
+) -> Result<impl IntoResponse, Error> {
+    let uploader = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
+
+    let uploaded = if body.len() > settings.max_upload_size().await? {
+        Err(Rejected::TooLarge.into())
+    } else {
+        storage.upload(&data, uploader, body.to_vec()).await
+    };
+
+    let media = uploaded.map_err(|err| match err.downcast_ref::<Rejected>() {
+        Some(rejected) => {
+            stry_common::prelude::debug!(error = ?rejected, "unable to process upload");
+
+            let (status, message) = match rejected {
+                Rejected::TooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "the image is too large"),
+                Rejected::Unsupported => (
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "expected a GIF, JPEG, PNG or WebP image",
+                ),
+                Rejected::Dimensions => (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "the image is wider or taller than 8192 pixels",
+                ),
+                Rejected::Corrupt(_) => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "unable to read the image")
+                }
+            };
+
+            Error::from_any(Rejection::new(status, message))
+        }
+        None => Error::from(err),
+    })?;
+
+    Ok((StatusCode::CREATED, Json(Uploaded::from(&media))))
+}
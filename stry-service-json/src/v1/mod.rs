@@ -1,69 +1,149 @@
 mod chapter;
+mod import;
+mod media;
+mod notification;
+mod openapi;
+mod revision;
+mod settings;
 mod story;
 
 use stry_common::{
-    backend::{ArcBackend, UserEntity},
-    error::ErrorResponse,
-    models::{
-        core::{Account, User, UserRegisterForm},
-        New,
-    },
-    prelude::{err, Validate as _},
+    auth,
+    backend::ArcBackend,
+    config::ArcConfig,
+    error::{ErrorResponse, StatusCodeErrorResponse},
+    limiter::Limiter,
+    models::core::{UserRegisterForm, UserSessionForm},
+    settings::Settings,
 };
 
 use axum::{
-    extract::{ContentLengthLimit, Extension, Json},
+    body::Bytes,
+    extract::{Extension, Json},
     handler::Handler,
-    http::StatusCode,
+    http::{header, StatusCode},
     response::IntoResponse,
-    routing::{get, post},
+    routing::{get, post, MethodRouter},
     Router,
 };
 use tower::limit::ConcurrencyLimitLayer;
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    extractors::{ClientIp, JsonBody},
+};
 
 pub fn router() -> Router {
-    Router::new()
+    routes()
+        .into_iter()
+        .fold(Router::new(), |router, (path, route)| {
+            router.route(path, route)
+        })
+}
+
+/// Every route in the api, along with the methods it handles.
+///
+/// # Note
+///
+/// These are kept separate from the [`Router`] so they can be checked
+/// against the OpenAPI document, see [`openapi::document`].
+fn routes() -> Vec<(&'static str, MethodRouter)> {
+    // The document never changes so it is only serialized once
+    let document = Bytes::from(
+        serde_json::to_vec(&openapi::document()).expect("failed to serialize openapi document"),
+    );
+
+    vec![
         // These have a limiter on them due to the global router allowing someone to flood these handlers causing the server to run out of memory
-        .route(
+        (
             "/register",
             post(Handler::layer(register, ConcurrencyLimitLayer::new(32))),
-        )
-        .route(
+        ),
+        (
             "/session",
             post(Handler::layer(session, ConcurrencyLimitLayer::new(128))),
-        )
+        ),
         //
-        .route("/search", post(empty))
+        ("/search", post(story::search)),
         //
-        .route("/chapters", post(chapter::create))
-        .route("/chapters/:id", get(chapter::get))
-        .route("/stories", get(story::all).post(story::create))
-        .route("/stories/:id", get(story::get))
+        ("/import", post(import::create)),
+        ("/media", post(media::create)),
+        ("/notifications", get(notification::all)),
+        ("/settings", get(settings::all).put(settings::update)),
+        ("/chapters", post(chapter::create_deprecated)),
+        ("/chapters/:id", get(chapter::get).put(chapter::update)),
+        ("/chapters/:id/publish", post(chapter::publish)),
+        ("/chapters/:id/diff", get(revision::chapter_diff)),
+        ("/chapters/:id/revisions", get(revision::chapter_all)),
+        (
+            "/chapters/:id/revisions/:revision",
+            get(revision::chapter_get),
+        ),
+        (
+            "/chapters/:id/revisions/:revision/restore",
+            post(revision::chapter_restore),
+        ),
+        ("/stories", get(story::all).post(story::create)),
+        ("/stories/:id", get(story::get).put(story::update)),
+        (
+            "/stories/:id/chapters",
+            post(chapter::create).put(chapter::reorder),
+        ),
+        ("/stories/:id/diff", get(revision::story_diff)),
+        ("/stories/:id/revisions", get(revision::story_all)),
+        ("/stories/:id/revisions/:revision", get(revision::story_get)),
+        (
+            "/stories/:id/revisions/:revision/restore",
+            post(revision::story_restore),
+        ),
+        //
+        (
+            "/openapi.json",
+            get(move || async move { ([(header::CONTENT_TYPE, "application/json")], document) }),
+        ),
+    ]
 }
 
 async fn register(
     Extension(data): Extension<ArcBackend>,
-    ContentLengthLimit(Json(form)): ContentLengthLimit<Json<UserRegisterForm>, { 1024 * 5000 }>,
+    Extension(settings): Extension<Settings>,
+    Extension(limiter): Extension<Limiter>,
+    ClientIp(ip): ClientIp,
+    JsonBody(form): JsonBody<UserRegisterForm, { 1024 * 5000 }>,
 ) -> Result<impl IntoResponse, Error> {
-    if let Err(err) = form.validate() {
-        // TODO(txuritan): turn the `err` into a standard format rather than being based off the structure itself
-        return Ok((StatusCode::BAD_REQUEST, Json(ErrorResponse { error: err })).into_response());
-    }
-
-    let account =
-        tokio::task::spawn_blocking(move || Account::new(form.username, form.email, form.password))
-            .await
-            .map_err(|err| err!(err))??;
-
-    UserEntity::create(&data, New::from(User::new(account))).await?;
+    auth::register(&data, &settings, &limiter, ip, form).await?;
 
     Ok((StatusCode::CREATED, Json(serde_json::json!({}))).into_response())
 }
 
-async fn session(Extension(data): Extension<ArcBackend>) -> Result<impl IntoResponse, Error> {
-    Ok((StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({}))).into_response())
-}
+async fn session(
+    Extension(config): Extension<ArcConfig>,
+    Extension(data): Extension<ArcBackend>,
+    Extension(limiter): Extension<Limiter>,
+    ClientIp(ip): ClientIp,
+    JsonBody(form): JsonBody<UserSessionForm, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let user = match auth::sign_in(&data, &limiter, ip, form).await? {
+        Some(user) => user,
+        None => {
+            let body = ErrorResponse {
+                error: StatusCodeErrorResponse {
+                    code: StatusCode::UNAUTHORIZED.as_u16(),
+                    status: StatusCode::UNAUTHORIZED.canonical_reason(),
+                    message: "incorrect email or password",
+                    fields: Vec::new(),
+                },
+            };
 
-async fn empty() {}
+            return Ok((StatusCode::UNAUTHORIZED, Json(body)).into_response());
+        }
+    };
+
+    let token = auth::issue_token(&config.secret, &user)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "token": token })),
+    )
+        .into_response())
+}
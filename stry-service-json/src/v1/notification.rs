@@ -0,0 +1,25 @@
+use stry_common::{
+    backend::{ArcBackend, NotificationEntity},
+    error::Unauthenticated,
+    http::Pagination,
+};
+
+use axum::{
+    extract::{ContentLengthLimit, Extension, Json, Query},
+    response::IntoResponse,
+};
+
+use crate::{error::Error, extractors::Session};
+
+/// The signed in user's notifications, newest first.
+pub async fn all(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let user = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
+
+    Ok(Json(
+        NotificationEntity::all(&data, user, query.cursor, query.limit).await?,
+    ))
+}
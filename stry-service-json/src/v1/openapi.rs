@@ -0,0 +1,646 @@
+//! The OpenAPI 3 description of the v1 api, served at `/v1/openapi.json`.
+//!
+//! Schemas are generated from the `stry-common` models, while the operations
+//! are listed by hand, a test checks they match the methods each of the
+//! [`super::routes`] handles.
+
+use stry_common::{
+    diff::RevisionDiff,
+    error::{ErrorResponse, StatusCodeErrorResponse},
+    http::{DraftQuery, FormatQuery, Pagination, RevisionDiffQuery, StoryQuery},
+    import::Imported,
+    media::Uploaded,
+    models::{
+        core::{Notification, Setting, SettingsForm, UserRegisterForm, UserSessionForm},
+        story::{
+            Chapter, ChapterMarkdown, ChapterOrder, ChapterRevision, Revision, Story,
+            StoryRevision, StorySearch,
+        },
+        Existing, Id, New,
+    },
+};
+
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde_json::{json, Map, Value};
+
+/// Builds the OpenAPI document for every route in [`super::router`].
+pub fn document() -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+
+    let error = schema::<ErrorResponse<StatusCodeErrorResponse>>(&mut gen);
+    let id = schema::<Id>(&mut gen);
+    let revision = json!({
+        "name": "revision",
+        "in": "path",
+        "required": true,
+        "schema": id,
+    });
+
+    // Chapters are sent as either their parts or Markdown, see `format`
+    let chapter_body = body(json!({
+        "oneOf": [
+            schema::<New<Chapter>>(&mut gen),
+            schema::<ChapterMarkdown>(&mut gen),
+        ],
+    }));
+    let chapter = json!({
+        "oneOf": [
+            schema::<Existing<Chapter>>(&mut gen),
+            schema::<Existing<ChapterMarkdown>>(&mut gen),
+        ],
+    });
+
+    let paths = json!({
+        "/register": {
+            "post": {
+                "summary": "Create a new user account",
+                "operationId": "register",
+                "requestBody": body(schema::<UserRegisterForm>(&mut gen)),
+                "responses": {
+                    "201": content("The account was created", json!({ "type": "object" })),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "403": content("Registrations are closed", error.clone()),
+                    "409": content("The email is already in use", error.clone()),
+                    "429": content("Too many registrations, see `Retry-After`", error.clone()),
+                },
+            },
+        },
+        "/session": {
+            "post": {
+                "summary": "Sign in, creating a session token",
+                "operationId": "session",
+                "requestBody": body(schema::<UserSessionForm>(&mut gen)),
+                "responses": {
+                    "201": content("The session was created", json!({
+                        "type": "object",
+                        "required": [ "token" ],
+                        "properties": { "token": { "type": "string" } },
+                    })),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Incorrect email or password", error.clone()),
+                    "429": content("Too many attempts or the account is locked, see `Retry-After`", error.clone()),
+                },
+            },
+        },
+        "/search": {
+            "post": {
+                "summary": "Search for stories, hiding any the signed in user has chosen not to see",
+                "operationId": "search",
+                "security": [ {}, { "bearer": [] } ],
+                "requestBody": body(schema::<StorySearch>(&mut gen)),
+                "responses": {
+                    "200": content("A page of matching stories", schema::<Vec<Existing<Story>>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Invalid session token", error.clone()),
+                },
+            },
+        },
+        "/import": {
+            "post": {
+                "summary": "Import an AO3 HTML or EPUB download as a new story",
+                "description": "Tags that don't match an existing one are queued to be created and returned as `requests`.",
+                "operationId": "importStory",
+                "security": [ { "bearer": [] } ],
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "text/html": { "schema": { "type": "string" } },
+                        "application/epub+zip": { "schema": { "type": "string", "format": "binary" } },
+                    },
+                },
+                "responses": {
+                    "201": content("The new story's id and its tag requests", schema::<Imported>(&mut gen)),
+                    "400": content("The work contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "413": content("The download is too large", error.clone()),
+                    "422": content("The download isn't a work", error.clone()),
+                },
+            },
+        },
+        "/media": {
+            "post": {
+                "summary": "Upload an image",
+                "description": "The image is re-encoded without its metadata and given a thumbnail, its `id` can be used as an image part's `media`.",
+                "operationId": "uploadMedia",
+                "security": [ { "bearer": [] } ],
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "image/*": { "schema": { "type": "string", "format": "binary" } },
+                    },
+                },
+                "responses": {
+                    "201": content("The uploaded image and its thumbnail", schema::<Uploaded>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "413": content("The image is too large", error.clone()),
+                    "415": content("The upload isn't a GIF, JPEG, PNG or WebP image", error.clone()),
+                    "422": content("The image can't be read or is too wide or tall", error.clone()),
+                },
+            },
+        },
+        "/notifications": {
+            "get": {
+                "summary": "List the signed in user's notifications, newest first",
+                "operationId": "listNotifications",
+                "security": [ { "bearer": [] } ],
+                "parameters": query::<Pagination>(),
+                "responses": {
+                    "200": content("A page of notifications", schema::<Vec<Existing<Notification>>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                },
+            },
+        },
+        "/settings": {
+            "get": {
+                "summary": "List the site-wide settings with their values and defaults",
+                "operationId": "listSettings",
+                "security": [ { "bearer": [] } ],
+                "responses": {
+                    "200": content("Every setting", schema::<Vec<Setting>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("The user isn't an admin", error.clone()),
+                },
+            },
+            "put": {
+                "summary": "Change site-wide settings",
+                "description": "The body maps setting keys to their new values, nothing is changed if any of them are invalid.",
+                "operationId": "updateSettings",
+                "security": [ { "bearer": [] } ],
+                "requestBody": body(schema::<SettingsForm>(&mut gen)),
+                "responses": {
+                    "200": content("Every setting, after the change", schema::<Vec<Setting>>(&mut gen)),
+                    "400": content("The request contains unknown settings or invalid values", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("The user isn't an admin", error.clone()),
+                },
+            },
+        },
+        "/chapters": {
+            "post": {
+                "summary": "Add a chapter to the end of a story",
+                "description": "Use `POST /stories/{id}/chapters` instead, the story is given with the `story` query parameter.",
+                "operationId": "createChapterDeprecated",
+                "deprecated": true,
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(query::<StoryQuery>(), query::<FormatQuery>()),
+                "requestBody": chapter_body.clone(),
+                "responses": {
+                    "200": content("The id of the new chapter", id.clone()),
+                    "400": content("Missing `story`, or the request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story exists with the id", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}": {
+            "get": {
+                "summary": "Get a chapter",
+                "description": "Unpublished chapters, drafts and schedules can only be seen by the story's authors, with `draft` the draft replaces the chapter.",
+                "operationId": "getChapter",
+                "security": [ {}, { "bearer": [] } ],
+                "parameters": extend(
+                    extend(
+                        json!([ path_id(&id), header("If-None-Match"), header("If-Modified-Since") ]),
+                        query::<FormatQuery>(),
+                    ),
+                    query::<DraftQuery>(),
+                ),
+                "responses": {
+                    "200": content("The chapter", chapter.clone()),
+                    "304": { "description": "The chapter has not changed" },
+                    "401": content("Invalid session token", error.clone()),
+                    "404": content("No chapter exists with the id", error.clone()),
+                },
+            },
+            "put": {
+                "summary": "Replace a chapter",
+                "description": "With `draft` the body is kept as the chapter's draft, along with its `scheduled` time, leaving the chapter itself as is.",
+                "operationId": "updateChapter",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(
+                    extend(json!([ path_id(&id), header("If-Match"), header("If-Unmodified-Since") ]), query::<FormatQuery>()),
+                    query::<DraftQuery>(),
+                ),
+                "requestBody": chapter_body.clone(),
+                "responses": {
+                    "200": content("The updated chapter", chapter),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter exists with the id", error.clone()),
+                    "412": content("The chapter has changed since it was fetched", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}/publish": {
+            "post": {
+                "summary": "Publish a chapter and its draft now",
+                "operationId": "publishChapter",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), header("If-Match"), header("If-Unmodified-Since") ],
+                "responses": {
+                    "200": content("The published chapter", schema::<Existing<Chapter>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter exists with the id", error.clone()),
+                    "412": content("The chapter has changed since it was fetched", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}/diff": {
+            "get": {
+                "summary": "Compare two of a chapter's revisions word by word",
+                "operationId": "diffChapterRevisions",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(json!([ path_id(&id) ]), query::<RevisionDiffQuery>()),
+                "responses": {
+                    "200": content("The changed fields", schema::<RevisionDiff>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter or revision exists with the id", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}/revisions": {
+            "get": {
+                "summary": "List a chapter's revisions, newest first",
+                "operationId": "listChapterRevisions",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(json!([ path_id(&id) ]), query::<Pagination>()),
+                "responses": {
+                    "200": content("A page of revisions", schema::<Vec<Existing<Revision>>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter exists with the id", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}/revisions/{revision}": {
+            "get": {
+                "summary": "Get a past version of a chapter",
+                "operationId": "getChapterRevision",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), revision.clone() ],
+                "responses": {
+                    "200": content("The revision", schema::<Existing<ChapterRevision>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter or revision exists with the id", error.clone()),
+                },
+            },
+        },
+        "/chapters/{id}/revisions/{revision}/restore": {
+            "post": {
+                "summary": "Restore a chapter to a past version, as a new revision",
+                "operationId": "restoreChapterRevision",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), revision.clone(), header("If-Match"), header("If-Unmodified-Since") ],
+                "responses": {
+                    "200": content("The restored chapter", schema::<Existing<Chapter>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No chapter or revision exists with the id", error.clone()),
+                    "412": content("The chapter has changed since it was fetched", error.clone()),
+                },
+            },
+        },
+        "/stories": {
+            "get": {
+                "summary": "List stories, hiding any the signed in user has chosen not to see",
+                "operationId": "listStories",
+                "security": [ {}, { "bearer": [] } ],
+                "parameters": query::<Pagination>(),
+                "responses": {
+                    "200": content("A page of stories", schema::<Vec<Existing<Story>>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Invalid session token", error.clone()),
+                },
+            },
+            "post": {
+                "summary": "Create a new story",
+                "operationId": "createStory",
+                "security": [ { "bearer": [] } ],
+                "requestBody": body(schema::<New<Story>>(&mut gen)),
+                "responses": {
+                    "200": content("The id of the new story", id.clone()),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}": {
+            "get": {
+                "summary": "Get a story",
+                "operationId": "getStory",
+                "parameters": [ path_id(&id), header("If-None-Match"), header("If-Modified-Since") ],
+                "responses": {
+                    "200": content("The story", schema::<Existing<Story>>(&mut gen)),
+                    "304": { "description": "The story has not changed" },
+                    "404": content("No story exists with the id", error.clone()),
+                },
+            },
+            "put": {
+                "summary": "Replace a story",
+                "description": "Replaces its metadata, credited users, tags and series, its chapters are left as they are.",
+                "operationId": "updateStory",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), header("If-Match"), header("If-Unmodified-Since") ],
+                "requestBody": body(schema::<New<Story>>(&mut gen)),
+                "responses": {
+                    "200": content("The updated story", schema::<Existing<Story>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story exists with the id", error.clone()),
+                    "412": content("The story has changed since it was fetched", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}/chapters": {
+            "post": {
+                "summary": "Add a chapter to the end of a story",
+                "operationId": "createChapter",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(json!([ path_id(&id) ]), query::<FormatQuery>()),
+                "requestBody": chapter_body,
+                "responses": {
+                    "200": content("The id of the new chapter", id.clone()),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story exists with the id", error.clone()),
+                },
+            },
+            "put": {
+                "summary": "Reorder a story's chapters",
+                "operationId": "reorderChapters",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id) ],
+                "requestBody": body(schema::<ChapterOrder>(&mut gen)),
+                "responses": {
+                    "204": { "description": "The chapters were reordered" },
+                    "400": content("The ids aren't exactly the story's chapters", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story exists with the id", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}/diff": {
+            "get": {
+                "summary": "Compare two of a story's revisions word by word",
+                "operationId": "diffStoryRevisions",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(json!([ path_id(&id) ]), query::<RevisionDiffQuery>()),
+                "responses": {
+                    "200": content("The changed fields", schema::<RevisionDiff>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story or revision exists with the id", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}/revisions": {
+            "get": {
+                "summary": "List a story's revisions, newest first",
+                "operationId": "listStoryRevisions",
+                "security": [ { "bearer": [] } ],
+                "parameters": extend(json!([ path_id(&id) ]), query::<Pagination>()),
+                "responses": {
+                    "200": content("A page of revisions", schema::<Vec<Existing<Revision>>>(&mut gen)),
+                    "400": content("The request contains invalid fields", error.clone()),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story exists with the id", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}/revisions/{revision}": {
+            "get": {
+                "summary": "Get a past version of a story",
+                "operationId": "getStoryRevision",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), revision.clone() ],
+                "responses": {
+                    "200": content("The revision", schema::<Existing<StoryRevision>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story or revision exists with the id", error.clone()),
+                },
+            },
+        },
+        "/stories/{id}/revisions/{revision}/restore": {
+            "post": {
+                "summary": "Restore a story to a past version, as a new revision",
+                "operationId": "restoreStoryRevision",
+                "security": [ { "bearer": [] } ],
+                "parameters": [ path_id(&id), revision.clone(), header("If-Match"), header("If-Unmodified-Since") ],
+                "responses": {
+                    "200": content("The restored story", schema::<Existing<Story>>(&mut gen)),
+                    "401": content("Missing or invalid session token", error.clone()),
+                    "403": content("Not one of the story's authors", error.clone()),
+                    "404": content("No story or revision exists with the id", error.clone()),
+                    "412": content("The story has changed since it was fetched", error.clone()),
+                },
+            },
+        },
+        "/openapi.json": {
+            "get": {
+                "summary": "This document",
+                "operationId": "openapi",
+                "responses": {
+                    "200": content("The OpenAPI document", json!({ "type": "object" })),
+                },
+            },
+        },
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "stry",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [ { "url": "/v1" } ],
+        "paths": paths,
+        "components": {
+            "schemas": gen.take_definitions(),
+            "securitySchemes": {
+                "bearer": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "bearerFormat": "JWT",
+                },
+            },
+        },
+    })
+}
+
+/// Returns a reference to `T`'s schema, adding it to the components.
+fn schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    to_value(gen.subschema_for::<T>())
+}
+
+fn body(schema: Value) -> Value {
+    json!({
+        "required": true,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn content(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn path_id(id: &Value) -> Value {
+    json!({
+        "name": "id",
+        "in": "path",
+        "required": true,
+        "schema": id,
+    })
+}
+
+fn header(name: &str) -> Value {
+    json!({
+        "name": name,
+        "in": "header",
+        "required": false,
+        "schema": { "type": "string" },
+    })
+}
+
+/// Turns the properties of `T` into query parameters.
+///
+/// # Note
+///
+/// The schemas are inlined so each parameter is self contained.
+fn query<T: JsonSchema>() -> Value {
+    let root = SchemaSettings::openapi3()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+
+    let object = match root.schema.object {
+        Some(object) => *object,
+        None => return Value::Array(Vec::new()),
+    };
+    let required = object.required;
+
+    let parameters = object
+        .properties
+        .into_iter()
+        .map(|(name, schema)| {
+            let mut parameter = Map::new();
+
+            parameter.insert("required".into(), required.contains(&name).into());
+            parameter.insert("in".into(), "query".into());
+            parameter.insert("schema".into(), to_value(schema));
+            parameter.insert("name".into(), name.into());
+
+            Value::Object(parameter)
+        })
+        .collect();
+
+    Value::Array(parameters)
+}
+
+/// Appends the parameters in `more` to `parameters`.
+fn extend(mut parameters: Value, more: Value) -> Value {
+    if let (Value::Array(parameters), Value::Array(more)) = (&mut parameters, more) {
+        parameters.extend(more);
+    }
+
+    parameters
+}
+
+fn to_value(schema: Schema) -> Value {
+    // Schemas only contain maps with string keys, serializing can't fail
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use tower::ServiceExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_document_matches_routes() {
+        let document = document();
+        let paths = document["paths"].as_object().unwrap();
+
+        let routes = super::super::routes();
+
+        assert_eq!(paths.len(), routes.len(), "a documented path isn't routed");
+
+        for (path, route) in routes {
+            // `/stories/:id` is documented as `/stories/{id}`
+            let documented = path
+                .split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(name) => format!("{{{}}}", name),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let operations = paths
+                .get(&documented)
+                .unwrap_or_else(|| panic!("`{}` is not documented", path));
+
+            let uri = path
+                .split('/')
+                .map(|segment| {
+                    if segment.starts_with(':') {
+                        "abcdefgh"
+                    } else {
+                        segment
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            for method in [
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+            ] {
+                let request = Request::builder()
+                    .method(method.clone())
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap();
+
+                // Without a backend the handlers error, but never with a `405`
+                let response = route.clone().oneshot(request).await.unwrap();
+
+                assert_eq!(
+                    operations.get(method.as_str().to_lowercase()).is_some(),
+                    response.status() != StatusCode::METHOD_NOT_ALLOWED,
+                    "`{} {}` is routed but not documented, or the other way around",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+}
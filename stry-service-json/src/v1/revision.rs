@@ -0,0 +1,181 @@
+//! The revision history of chapters and stories, only their authors can see
+//! or restore it.
+
+use stry_common::{
+    backend::{ArcBackend, ChapterEntity, StoryEntity},
+    diff::{self, RevisionDiff},
+    http::{Pagination, RevisionDiffQuery},
+    models::{
+        core::User,
+        story::{Chapter, Revision, Story},
+        Existing, Id, New,
+    },
+};
+
+use axum::{
+    extract::{ContentLengthLimit, Extension, Json, Path, Query},
+    response::IntoResponse,
+};
+
+use crate::{
+    error::Error,
+    extractors::{Preconditions, Session},
+    v1::chapter::authorize,
+};
+
+/// Lists a chapter's revisions, newest first.
+pub async fn chapter_all(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path(id): Path<Id>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+
+    authorize(&data, story, session).await?;
+
+    Ok(Json(
+        ChapterEntity::revisions(&data, id, query.cursor, query.limit).await?,
+    ))
+}
+
+/// Gets a past version of a chapter.
+pub async fn chapter_get(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path((id, revision)): Path<(Id, Id)>,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+
+    authorize(&data, story, session).await?;
+
+    Ok(Json(ChapterEntity::revision(&data, id, revision).await?))
+}
+
+/// The word-level changes between two of a chapter's revisions.
+pub async fn chapter_diff(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path(id): Path<Id>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+
+    authorize(&data, story, session).await?;
+
+    let from = ChapterEntity::revision(&data, id, query.from).await?;
+    let to = ChapterEntity::revision(&data, id, query.to).await?;
+
+    Ok(Json(RevisionDiff {
+        fields: diff::chapter(&from, &to),
+        from: entry(&from, from.editor.clone()),
+        to: entry(&to, to.editor.clone()),
+    }))
+}
+
+/// Puts a chapter's name and parts back to how they were at a revision,
+/// recording it as a new revision.
+pub async fn chapter_restore(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
+    Path((id, revision)): Path<(Id, Id)>,
+) -> Result<impl IntoResponse, Error> {
+    let story = ChapterEntity::story(&data, id).await?;
+    let user = authorize(&data, story, session).await?;
+
+    let current = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    preconditions.check(&current)?;
+
+    let revision = ChapterEntity::revision(&data, id, revision).await?;
+
+    let mut chapter = Chapter::clone(&current);
+
+    revision.restore(&mut chapter);
+
+    ChapterEntity::update(&data, id, New::from(chapter), current.updated, user).await?;
+
+    let chapter = ChapterEntity::get(&data, id, Some(user)).await?;
+
+    Ok(Preconditions::default().respond(chapter))
+}
+
+/// Lists the revisions of a story's metadata, newest first.
+pub async fn story_all(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path(id): Path<Id>,
+    ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    authorize(&data, id, session).await?;
+
+    Ok(Json(
+        StoryEntity::revisions(&data, id, query.cursor, query.limit).await?,
+    ))
+}
+
+/// Gets a past version of a story's metadata.
+pub async fn story_get(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path((id, revision)): Path<(Id, Id)>,
+) -> Result<impl IntoResponse, Error> {
+    authorize(&data, id, session).await?;
+
+    Ok(Json(StoryEntity::revision(&data, id, revision).await?))
+}
+
+/// The word-level changes between two revisions of a story's metadata.
+pub async fn story_diff(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    Path(id): Path<Id>,
+    Query(query): Query<RevisionDiffQuery>,
+) -> Result<impl IntoResponse, Error> {
+    authorize(&data, id, session).await?;
+
+    let from = StoryEntity::revision(&data, id, query.from).await?;
+    let to = StoryEntity::revision(&data, id, query.to).await?;
+
+    Ok(Json(RevisionDiff {
+        fields: diff::story(&from, &to),
+        from: entry(&from, from.editor.clone()),
+        to: entry(&to, to.editor.clone()),
+    }))
+}
+
+/// Puts a story's metadata back to how it was at a revision, recording it
+/// as a new revision.
+pub async fn story_restore(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
+    Path((id, revision)): Path<(Id, Id)>,
+) -> Result<impl IntoResponse, Error> {
+    let user = authorize(&data, id, session).await?;
+
+    let current = StoryEntity::get(&data, id).await?;
+
+    preconditions.check(&current)?;
+
+    let revision = StoryEntity::revision(&data, id, revision).await?;
+
+    let mut story = Story::clone(&current);
+
+    revision.restore(&mut story);
+
+    StoryEntity::update(&data, id, New::from(story), current.updated, user).await?;
+
+    Ok(Preconditions::default().respond(StoryEntity::get(&data, id).await?))
+}
+
+/// A revision as it is listed, without its content.
+fn entry<T>(revision: &Existing<T>, editor: Option<Existing<User>>) -> Existing<Revision> {
+    Existing::new(
+        revision.id,
+        Revision { editor },
+        revision.created,
+        revision.updated,
+    )
+}
@@ -0,0 +1,57 @@
+use stry_common::{
+    backend::{ArcBackend, UserEntity},
+    error::{Unauthenticated, Unauthorized},
+    models::{core::SettingsForm, Id},
+    settings::Settings,
+};
+
+use axum::{
+    extract::{Extension, Json},
+    response::IntoResponse,
+};
+
+use crate::{
+    error::Error,
+    extractors::{JsonBody, Session},
+};
+
+/// Every site-wide setting with its current value, admins only.
+pub async fn all(
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Session(session): Session,
+) -> Result<impl IntoResponse, Error> {
+    admin(&data, session).await?;
+
+    Ok(Json(settings.all().await?))
+}
+
+/// Changes any number of settings, admins only.
+///
+/// Nothing is changed if any key or value is invalid.
+pub async fn update(
+    Extension(data): Extension<ArcBackend>,
+    Extension(settings): Extension<Settings>,
+    Session(session): Session,
+    JsonBody(form): JsonBody<SettingsForm, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let user = admin(&data, session).await?;
+
+    let keys = form.values.keys().cloned().collect::<Vec<_>>();
+
+    settings.set(form.values).await?;
+
+    stry_common::prelude::info!(target: "audit", user = user.as_str(), ?keys, "changed settings");
+
+    Ok(Json(settings.all().await?))
+}
+
+async fn admin(data: &ArcBackend, session: Option<Id>) -> Result<Id, Error> {
+    let user = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
+
+    if !UserEntity::is_admin(data, user).await? {
+        return Err(Error::from_any(Unauthorized));
+    }
+
+    Ok(user)
+}
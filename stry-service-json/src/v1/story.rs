@@ -1,53 +1,97 @@
 use stry_common::{
-    backend::{ArcBackend, StoryEntity},
-    config::ArcConfig,
+    backend::{ArcBackend, StoryEntity, UserEntity},
+    error::Unauthenticated,
     http::Pagination,
-    models::{story::Story, Id, New},
+    models::{
+        core::Content,
+        story::{Story, StoryFilter, StorySearch},
+        Id, New,
+    },
 };
 
 use axum::{
-    extract::{ContentLengthLimit, Extension, Json, Path, Query, TypedHeader},
+    extract::{ContentLengthLimit, Extension, Json, Path, Query},
     response::IntoResponse,
 };
-use biscuit::{jwa::SignatureAlgorithm, jws::Secret, ValidationOptions, JWT};
-use headers::{authorization::Bearer, Authorization};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    extractors::{JsonBody, Preconditions, Session},
+    v1::chapter::authorize,
+};
 
 pub async fn get(
     Extension(data): Extension<ArcBackend>,
+    preconditions: Preconditions,
     Path(id): Path<Id>,
 ) -> Result<impl IntoResponse, Error> {
-    Ok(Json(StoryEntity::get(&data, id).await?))
+    Ok(preconditions.respond(StoryEntity::get(&data, id).await?))
 }
 
 pub async fn all(
     Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
     ContentLengthLimit(Query(query)): ContentLengthLimit<Query<Pagination>, { 1024 * 5000 }>,
 ) -> Result<impl IntoResponse, Error> {
+    let stories = match session {
+        Some(id) => {
+            let filter = StoryFilter::default().hide(&content(&data, id).await?);
+
+            StoryEntity::filter(&data, filter, query.cursor, query.limit).await?
+        }
+        None => StoryEntity::all(&data, query.cursor, query.limit).await?,
+    };
+
+    Ok(Json(stories))
+}
+
+pub async fn search(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    JsonBody(search): JsonBody<StorySearch, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let filter = match session {
+        Some(id) => search.filter.hide(&content(&data, id).await?),
+        None => search.filter,
+    };
+
     Ok(Json(
-        StoryEntity::all(&data, query.cursor, query.limit).await?,
+        StoryEntity::filter(&data, filter, search.cursor, search.limit).await?,
     ))
 }
 
+/// The stories the signed in user has chosen to hide.
+async fn content(data: &ArcBackend, id: Id) -> Result<Content, Error> {
+    Ok(UserEntity::get(data, id).await?.content.clone())
+}
+
 pub async fn create(
-    Extension(config): Extension<ArcConfig>,
     Extension(data): Extension<ArcBackend>,
-    TypedHeader(authorization): TypedHeader<Authorization<Bearer>>,
-    ContentLengthLimit(Json(story)): ContentLengthLimit<Json<New<Story>>, { 1024 * 5000 }>,
+    Session(session): Session,
+    JsonBody(mut story): JsonBody<New<Story>, { 1024 * 5000 }>,
 ) -> Result<impl IntoResponse, Error> {
-    let token = JWT::<biscuit::Empty, biscuit::Empty>::new_encoded(authorization.0.token())
-        .into_decoded(
-            &Secret::bytes_from_str(&config.secret),
-            SignatureAlgorithm::HS256,
-        )
-        .map_err(Error::from_any)?;
+    let author = session.ok_or_else(|| Error::from_any(Unauthenticated))?;
 
-    token
-        .validate(ValidationOptions::default())
-        .map_err(Error::from_any)?;
-
-    // TODO(txuritan): validate that the token is in the database and to retrieve the user id
+    // Only the signed in user can be credited, not anyone they name
+    story.authors = vec![UserEntity::get(&data, author).await?];
 
     Ok(Json(StoryEntity::create(&data, story).await?))
 }
+
+pub async fn update(
+    Extension(data): Extension<ArcBackend>,
+    Session(session): Session,
+    preconditions: Preconditions,
+    Path(id): Path<Id>,
+    JsonBody(story): JsonBody<New<Story>, { 1024 * 5000 }>,
+) -> Result<impl IntoResponse, Error> {
+    let user = authorize(&data, id, session).await?;
+
+    let current = StoryEntity::get(&data, id).await?;
+
+    preconditions.check(&current)?;
+
+    StoryEntity::update(&data, id, story, current.updated, user).await?;
+
+    Ok(Preconditions::default().respond(StoryEntity::get(&data, id).await?))
+}
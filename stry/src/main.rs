@@ -2,10 +2,15 @@ use std::{net::SocketAddr, time::Duration};
 
 use stry_backend_postgres::PostgresBackend;
 use stry_common::{
-    backend::ArcBackend,
+    backend::{ArcBackend, UserEntity},
     config::{Config, DEFAULT_SECRET},
-    futures::utils::TryFutureExt as _,
+    i18n::FluentResources,
+    import, jobs,
+    limiter::{Limiter, MemoryStore},
+    media::MediaStorage,
+    models::Id,
     prelude::*,
+    settings::Settings,
     uri::Uri,
 };
 
@@ -20,7 +25,7 @@ async fn main() -> Result<(), Error> {
     if std::env::var_os("RUST_LOG").is_none() {
         std::env::set_var(
             "RUST_LOG",
-            "stry=debug,stry_service_html=debug,stry_service_json=debug,sqlx=info",
+            "stry=debug,stry_service_html=debug,stry_service_json=debug,sqlx=info,audit=info",
         )
     }
 
@@ -40,13 +45,50 @@ async fn main() -> Result<(), Error> {
 
     let uri = Uri::parse(&config.database).context("unable to parse database connection uri")?;
 
-    let backend = match uri.scheme.as_str() {
-        "postgres" => PostgresBackend::new(uri).map_ok(ArcBackend::new).await?,
+    let (backend, limiter) = match uri.scheme.as_str() {
+        "postgres" => {
+            let backend = PostgresBackend::new(uri).await?;
+
+            let limiter = match config.limiter.as_str() {
+                "memory" => Limiter::new(MemoryStore::new()),
+                "database" => Limiter::new(backend.clone()),
+                limiter => bail!("`{}` is not a supported limiter store", limiter),
+            };
+
+            (ArcBackend::new(backend), limiter)
+        }
         schema => bail!("`{}` is not a supported database", schema),
     };
 
     backend.migrate().await?;
 
+    let storage = MediaStorage::open(&config).context("unable to open media store")?;
+
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("import") => return import(&backend, args).await,
+        Some("admin") => return admin(&backend, args).await,
+        Some(command) => bail!(
+            "`{}` is not a command, expected `import` or `admin`",
+            command
+        ),
+        None => {}
+    }
+
+    let settings = Settings::new(backend.clone());
+
+    let jobs = tokio::spawn(jobs::run(
+        backend.clone(),
+        settings.clone(),
+        stry_common::http::shutdown_signal(),
+    ));
+
+    // Both services translate with the same bundles
+    let resources = FluentResources::builder()
+        .add_dir(&config.localization)?
+        .build()?;
+
     let app = Router::new()
         .merge(stry_service_html::routes())
         .merge(stry_service_json::routes())
@@ -66,6 +108,10 @@ async fn main() -> Result<(), Error> {
                 .layer(HelmetLayer::with_defaults())
                 .layer(Extension(config.clone()))
                 .layer(Extension(backend))
+                .layer(Extension(limiter))
+                .layer(Extension(storage))
+                .layer(Extension(settings))
+                .layer(Extension(resources))
                 .layer(TraceLayer::new_for_http().make_span_with(stry_common::http::make_span))
                 .into_inner(),
         );
@@ -75,9 +121,69 @@ async fn main() -> Result<(), Error> {
     info!("listening on {}", addr);
 
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(stry_common::http::shutdown_signal())
         .await?;
 
+    // The jobs get the same signal, let them finish what they're doing
+    jobs.await?;
+
+    Ok(())
+}
+
+/// `stry import <author id> <file>...`, imports AO3 HTML or EPUB downloads as
+/// stories by the author.
+async fn import(backend: &ArcBackend, mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    const USAGE: &str = "usage: stry import <author id> <file>...";
+
+    let author = args.next().context(USAGE)?;
+    let author = Id::try_from(author.as_str()).context("author is not a valid id")?;
+
+    let files = args.collect::<Vec<_>>();
+
+    ensure!(!files.is_empty(), USAGE);
+
+    for path in files {
+        let bytes = std::fs::read(&path).with_context(|| format!("unable to open `{}`", path))?;
+        let work = import::read(&bytes).with_context(|| format!("unable to read `{}`", path))?;
+
+        let imported = import::save(backend, import::resolve(backend, work, author).await?).await?;
+
+        println!("{}: created story {}", path, imported.id.as_str());
+
+        for request in imported.requests {
+            println!("  requested {:?} tag `{}`", request.kind, request.content);
+        }
+    }
+
+    Ok(())
+}
+
+/// `stry admin <user id> [--remove]`, lets the user change the site's settings,
+/// or takes that away.
+async fn admin(backend: &ArcBackend, mut args: impl Iterator<Item = String>) -> Result<(), Error> {
+    const USAGE: &str = "usage: stry admin <user id> [--remove]";
+
+    let user = args.next().context(USAGE)?;
+    let user = Id::try_from(user.as_str()).context("user is not a valid id")?;
+
+    let admin = match args.next().as_deref() {
+        Some("--remove") => false,
+        Some(_) => bail!(USAGE),
+        None => true,
+    };
+
+    UserEntity::set_admin(backend, user, admin)
+        .await
+        .context("unable to update the user")?;
+
+    info!(target: "audit", user = user.as_str(), admin, "changed admin");
+
+    if admin {
+        println!("{} is now an admin", user.as_str());
+    } else {
+        println!("{} is no longer an admin", user.as_str());
+    }
+
     Ok(())
 }
@@ -56,6 +56,50 @@ impl<'r> Render for &'r String {
     }
 }
 
+/// Text that has its HTML special characters escaped when rendered.
+///
+/// Use this for any user supplied text, in both element content and
+/// attribute values (it also implements [`Display`](std::fmt::Display)).
+pub struct Escape<S>(pub S);
+
+impl<S: AsRef<str>> std::fmt::Display for Escape<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = self.0.as_ref();
+
+        let mut last = 0;
+
+        for (i, c) in text.char_indices() {
+            let entity = match c {
+                '&' => "&amp;",
+                '<' => "&lt;",
+                '>' => "&gt;",
+                '"' => "&quot;",
+                '\'' => "&#39;",
+                _ => continue,
+            };
+
+            f.write_str(&text[last..i])?;
+            f.write_str(entity)?;
+
+            last = i + 1;
+        }
+
+        f.write_str(&text[last..])
+    }
+}
+
+impl<S: AsRef<str>> Render for Escape<S> {
+    #[inline]
+    fn render_into(self, writer: &mut dyn Write) -> Result<(), std::fmt::Error> {
+        write!(writer, "{}", self)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        self.0.as_ref().len()
+    }
+}
+
 /// This is hidden as its an internal implementation and should mot be relied on
 #[doc(hidden)]
 impl<F> Render for (F, usize)
@@ -1,4 +1,4 @@
-use windswept::{rsx, Render};
+use windswept::{rsx, Escape, Render};
 
 fn button(text: &str) -> impl Render + '_ {
     rsx! {
@@ -153,3 +153,22 @@ fn test_match() {
 
     assert_eq!("<p>Hello, John!</p>", render(Some("John")).as_str());
 }
+
+#[test]
+fn test_escape() {
+    let name = "<script>alert('\"hi\" & bye')</script>";
+
+    let frag = rsx! {
+        <p title={Escape(name)}>{Escape(name)}</p>
+    }
+    .render()
+    .unwrap();
+
+    let expected = concat!(
+        r#"<p title="&lt;script&gt;alert(&#39;&quot;hi&quot; &amp; bye&#39;)&lt;/script&gt;">"#,
+        "&lt;script&gt;alert(&#39;&quot;hi&quot; &amp; bye&#39;)&lt;/script&gt;",
+        "</p>",
+    );
+
+    assert_eq!(expected, frag.as_str());
+}